@@ -0,0 +1,218 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Cache bundles for seeding air-gapped machines.
+//!
+//! A bundle carries the cache database, which also holds the local nixpkgs
+//! index (see [`LocalIndex`](crate::search::index::LocalIndex)), and the
+//! downloaded NUR index.
+
+use crate::core::config::Config;
+use crate::core::error::{CacheError, Result};
+use rusqlite::Connection;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// SQLite cache database file name
+pub const CACHE_DB: &str = "cache.db";
+
+/// Downloaded NUR index file name
+pub const NUR_INDEX: &str = "nur-packages.json";
+
+/// Fetch time and upstream commit of the NUR index
+pub const NUR_INDEX_META: &str = "nur-packages.meta.json";
+
+/// Files (relative to the cache directory) that make up a bundle
+pub const BUNDLE_FILES: &[&str] = &[CACHE_DB, NUR_INDEX, NUR_INDEX_META];
+
+/// zstd compression level used for bundles
+const COMPRESSION_LEVEL: i32 = 19;
+
+/// Exports and imports `.tar.zst` bundles of the cache directory
+pub struct CacheBundle {
+    cache_dir: PathBuf,
+}
+
+impl CacheBundle {
+    /// Create a bundle handler for the default cache directory
+    pub fn new() -> Self {
+        Self::with_dir(Config::cache_dir())
+    }
+
+    /// Create a bundle handler for a specific cache directory
+    pub fn with_dir(cache_dir: PathBuf) -> Self {
+        Self { cache_dir }
+    }
+
+    /// Write all present cache files into a zstd-compressed tarball.
+    ///
+    /// Returns the names of the files that were bundled.
+    pub fn export(&self, bundle: &Path) -> Result<Vec<String>> {
+        info!("Exporting cache bundle to {:?}", bundle);
+
+        let file = File::create(bundle)
+            .map_err(|e| CacheError::WriteError(format!("Failed to create {:?}: {}", bundle, e)))?;
+        let encoder = zstd::Encoder::new(file, COMPRESSION_LEVEL)
+            .map_err(|e| CacheError::WriteError(e.to_string()))?;
+        let mut archive = tar::Builder::new(encoder);
+
+        let snapshot = self.cache_dir.join(format!(".export-{}", CACHE_DB));
+        let mut included = Vec::new();
+        for name in BUNDLE_FILES {
+            let source = self.cache_dir.join(name);
+            if !source.exists() {
+                debug!("Skipping missing cache file {}", name);
+                continue;
+            }
+
+            // The database may be mid-write in WAL mode; take a consistent snapshot
+            let path = if *name == CACHE_DB {
+                let _ = std::fs::remove_file(&snapshot);
+                snapshot_database(&source, &snapshot)?;
+                snapshot.clone()
+            } else {
+                source
+            };
+
+            archive.append_path_with_name(&path, name)
+                .map_err(|e| CacheError::WriteError(format!("Failed to add {}: {}", name, e)))?;
+            included.push(name.to_string());
+        }
+
+        archive.into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(|e| CacheError::WriteError(format!("Failed to finish bundle: {}", e)))?;
+        let _ = std::fs::remove_file(&snapshot);
+
+        info!("Exported {} cache file(s)", included.len());
+        Ok(included)
+    }
+
+    /// Unpack a bundle into the cache directory, replacing existing files.
+    ///
+    /// Each file is extracted next to its destination and renamed over it,
+    /// so a failed import never leaves a half-written database behind. Only
+    /// known cache files are extracted; anything else in the archive is
+    /// ignored. Returns the names of the files that were imported.
+    pub fn import(&self, bundle: &Path) -> Result<Vec<String>> {
+        info!("Importing cache bundle from {:?}", bundle);
+
+        std::fs::create_dir_all(&self.cache_dir)
+            .map_err(|e| CacheError::WriteError(e.to_string()))?;
+
+        let file = File::open(bundle)
+            .map_err(|e| CacheError::ReadError(format!("Failed to open {:?}: {}", bundle, e)))?;
+        let decoder = zstd::Decoder::new(file)
+            .map_err(|e| CacheError::ReadError(e.to_string()))?;
+        let mut archive = tar::Archive::new(decoder);
+
+        let entries = archive.entries()
+            .map_err(|e| CacheError::Corrupted(e.to_string()))?;
+
+        let mut imported = Vec::new();
+        for entry in entries {
+            let mut entry = entry.map_err(|e| CacheError::Corrupted(e.to_string()))?;
+            let name = entry.path()
+                .map_err(|e| CacheError::Corrupted(e.to_string()))?
+                .to_string_lossy()
+                .to_string();
+
+            let Some(known) = BUNDLE_FILES.iter().find(|f| **f == name) else {
+                debug!("Ignoring unknown bundle entry {}", name);
+                continue;
+            };
+
+            let partial = self.cache_dir.join(format!(".import-{}", known));
+            if let Err(e) = entry.unpack(&partial) {
+                let _ = std::fs::remove_file(&partial);
+                return Err(CacheError::WriteError(format!("Failed to extract {}: {}", name, e)).into());
+            }
+
+            if *known == CACHE_DB {
+                // Stale WAL files would be replayed over the imported database
+                for suffix in ["-wal", "-shm"] {
+                    let _ = std::fs::remove_file(self.cache_dir.join(format!("{}{}", CACHE_DB, suffix)));
+                }
+            }
+
+            std::fs::rename(&partial, self.cache_dir.join(known))
+                .map_err(|e| CacheError::WriteError(format!("Failed to replace {}: {}", name, e)))?;
+            imported.push(name);
+        }
+
+        if imported.is_empty() {
+            return Err(CacheError::Corrupted("bundle contains no cache files".to_string()).into());
+        }
+
+        info!("Imported {} cache file(s)", imported.len());
+        Ok(imported)
+    }
+}
+
+impl Default for CacheBundle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Copy a live SQLite database into a standalone file
+fn snapshot_database(source: &Path, dest: &Path) -> Result<()> {
+    let conn = Connection::open(source)
+        .map_err(|e| CacheError::ReadError(e.to_string()))?;
+    conn.execute("VACUUM INTO ?1", [dest.to_string_lossy()])
+        .map_err(|e| CacheError::ReadError(format!("Failed to snapshot cache database: {}", e)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::DiskCache;
+    use crate::core::types::Package;
+    use crate::search::index::LocalIndex;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let source = TempDir::new().unwrap();
+        {
+            let cache = DiskCache::with_path(source.path().join(CACHE_DB)).unwrap();
+            cache.set("search:firefox", r#""cached""#, 3600).unwrap();
+            let index = LocalIndex::open(&source.path().join(CACHE_DB)).unwrap();
+            index.replace(&[Package::from_nixpkgs("ripgrep", "14.1.0", "Fast grep")], None).unwrap();
+        }
+        std::fs::write(source.path().join(NUR_INDEX), "{}").unwrap();
+
+        let out = TempDir::new().unwrap();
+        let bundle = out.path().join("bundle.tar.zst");
+        let exported = CacheBundle::with_dir(source.path().to_path_buf()).export(&bundle).unwrap();
+        assert_eq!(exported, vec![CACHE_DB.to_string(), NUR_INDEX.to_string()]);
+
+        let target = TempDir::new().unwrap();
+        let imported = CacheBundle::with_dir(target.path().to_path_buf()).import(&bundle).unwrap();
+        assert_eq!(imported.len(), 2);
+
+        let cache = DiskCache::with_path(target.path().join(CACHE_DB)).unwrap();
+        let value: Option<String> = cache.get("search:firefox").unwrap();
+        assert_eq!(value, Some("cached".to_string()));
+        assert!(target.path().join(NUR_INDEX).exists());
+        assert!(!target.path().join(format!(".import-{}", CACHE_DB)).exists());
+
+        let index = LocalIndex::open(&target.path().join(CACHE_DB)).unwrap();
+        assert_eq!(index.candidates("ripgrep", 10).unwrap().len(), 1);
+    }
+}
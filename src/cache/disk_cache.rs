@@ -18,23 +18,68 @@
 
 use crate::core::config::Config;
 use crate::core::error::{CacheError, Result};
+use rusqlite::types::ValueRef;
 use rusqlite::{Connection, params};
+use serde::Serialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{debug, info, warn};
 
+/// Number of buffered stat updates before they are flushed to disk
+const STATS_FLUSH_THRESHOLD: u64 = 64;
+
+/// Values shorter than this are stored as plain text even with compression on
+const COMPRESSION_THRESHOLD: usize = 1024;
+
+/// zstd level for cached values; favours speed since the cache is read often
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Eviction shrinks the cache to this fraction of the limit, so the next few
+/// writes do not each trigger another eviction
+const EVICTION_TARGET: f64 = 0.9;
+
 /// Persistent SQLite-based disk cache
 pub struct DiskCache {
     conn: Mutex<Connection>,
     path: PathBuf,
+    /// Hit/miss counters and access stats not yet written to the database
+    pending: Mutex<PendingStats>,
+    /// zstd-compress large values on write
+    compression: bool,
+    /// Evict least recently accessed entries above this many bytes of values
+    max_size: Option<u64>,
+}
+
+/// Write-behind buffer for cache statistics
+#[derive(Debug, Default)]
+struct PendingStats {
+    hits: u64,
+    misses: u64,
+    /// Per-key access count delta and last access timestamp
+    accesses: HashMap<String, (u64, u64)>,
+}
+
+impl PendingStats {
+    fn ops(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    fn is_empty(&self) -> bool {
+        self.hits == 0 && self.misses == 0 && self.accesses.is_empty()
+    }
 }
 
 impl DiskCache {
-    /// Create a new disk cache
+    /// Create a new disk cache, compressing values per `[cache] compression`
+    /// and bounded by `[cache] max_size_mb`
     pub fn new() -> Result<Self> {
-        let path = Config::cache_dir().join("cache.db");
-        Self::with_path(path)
+        let path = Config::cache_dir().join(super::bundle::CACHE_DB);
+        let cache = Config::try_get().map(|c| &c.cache);
+        let compression = cache.map(|c| c.compression).unwrap_or(true);
+        let max_size = cache.map(|c| c.max_size_mb).filter(|&mb| mb > 0).map(|mb| mb * 1024 * 1024);
+        Ok(Self::with_path(path)?.compression(compression).max_size(max_size))
     }
 
     /// Create a disk cache at a specific path
@@ -58,7 +103,9 @@ impl DiskCache {
                 created_at INTEGER NOT NULL,
                 expires_at INTEGER NOT NULL,
                 access_count INTEGER DEFAULT 0,
-                last_accessed INTEGER
+                last_accessed INTEGER,
+                compressed INTEGER NOT NULL DEFAULT 0,
+                size INTEGER NOT NULL DEFAULT 0
             );
             
             CREATE INDEX IF NOT EXISTS idx_expires ON cache(expires_at);
@@ -76,6 +123,10 @@ impl DiskCache {
             "
         ).map_err(|e| CacheError::InitFailed(e.to_string()))?;
 
+        // Columns added after the first release
+        add_column(&conn, "compressed", "INTEGER NOT NULL DEFAULT 0", None)?;
+        add_column(&conn, "size", "INTEGER NOT NULL DEFAULT 0", Some("UPDATE cache SET size = length(CAST(value AS BLOB))"))?;
+
         // Enable WAL mode for better performance
         conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")
             .map_err(|e| CacheError::InitFailed(e.to_string()))?;
@@ -85,45 +136,78 @@ impl DiskCache {
         Ok(Self {
             conn: Mutex::new(conn),
             path,
+            pending: Mutex::new(PendingStats::default()),
+            compression: false,
+            max_size: None,
         })
     }
 
+    /// Keep the stored values under `bytes`, evicting the least recently
+    /// accessed entries after writes. `None` disables the limit.
+    pub fn max_size(mut self, bytes: Option<u64>) -> Self {
+        self.max_size = bytes;
+        self
+    }
+
+    /// Store values of at least [`COMPRESSION_THRESHOLD`] bytes zstd-compressed.
+    /// Reads handle both forms regardless of this setting.
+    pub fn compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
     /// Get a value from the cache
+    ///
+    /// Reads are a single SELECT; hit/miss counters and access stats are
+    /// buffered and written in batches (see [`DiskCache::flush_stats`]).
     pub fn get<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        Ok(self.get_with_expiry(key)?.map(|(value, _)| value))
+    }
+
+    /// Get a value together with its expiry time (Unix seconds)
+    pub fn get_with_expiry<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<(T, u64)>> {
         let conn = self.conn.lock().map_err(|e| CacheError::ReadError(e.to_string()))?;
         let now = current_timestamp();
 
         // Try to get the value
-        let result: rusqlite::Result<(String, i64)> = conn.query_row(
-            "SELECT value, expires_at FROM cache WHERE key = ?1",
+        let result: rusqlite::Result<(Vec<u8>, i64, bool)> = conn.query_row(
+            "SELECT value, expires_at, compressed FROM cache WHERE key = ?1",
             params![key],
-            |row| Ok((row.get(0)?, row.get(1)?)),
+            |row| {
+                let value = match row.get_ref(0)? {
+                    ValueRef::Text(bytes) | ValueRef::Blob(bytes) => bytes.to_vec(),
+                    _ => Vec::new(),
+                };
+                Ok((value, row.get(1)?, row.get(2)?))
+            },
         );
 
         match result {
-            Ok((value, expires_at)) => {
+            Ok((value, expires_at, compressed)) => {
                 if expires_at < now as i64 {
                     // Expired, delete it
                     debug!("Cache entry expired: {}", key);
                     let _ = conn.execute("DELETE FROM cache WHERE key = ?1", params![key]);
-                    self.increment_misses(&conn)?;
+                    self.record_miss(&conn);
                     return Ok(None);
                 }
 
-                // Update access stats
-                let _ = conn.execute(
-                    "UPDATE cache SET access_count = access_count + 1, last_accessed = ?2 WHERE key = ?1",
-                    params![key, now],
-                );
-                self.increment_hits(&conn)?;
+                self.record_hit(&conn, key, now);
+
+                let value = if compressed {
+                    zstd::decode_all(value.as_slice())
+                        .map_err(|e| CacheError::ReadError(format!("Decompress error: {}", e)))?
+                } else {
+                    value
+                };
 
                 // Deserialize
-                let parsed: T = serde_json::from_str(&value)
+                let parsed: T = serde_json::from_slice(&value)
                     .map_err(|e| CacheError::ReadError(format!("Deserialize error: {}", e)))?;
-                Ok(Some(parsed))
+                Ok(Some((parsed, expires_at as u64)))
             }
             Err(rusqlite::Error::QueryReturnedNoRows) => {
-                self.increment_misses(&conn)?;
+                self.record_miss(&conn);
                 Ok(None)
             }
             Err(e) => Err(CacheError::ReadError(e.to_string()).into()),
@@ -136,13 +220,32 @@ impl DiskCache {
         let now = current_timestamp();
         let expires_at = now + ttl_secs;
 
-        conn.execute(
-            "INSERT OR REPLACE INTO cache (key, value, created_at, expires_at, access_count, last_accessed)
-             VALUES (?1, ?2, ?3, ?4, 0, ?3)",
-            params![key, value, now, expires_at],
-        ).map_err(|e| CacheError::WriteError(e.to_string()))?;
+        let compressed = if self.compression && value.len() >= COMPRESSION_THRESHOLD {
+            zstd::encode_all(value.as_bytes(), COMPRESSION_LEVEL).ok()
+        } else {
+            None
+        };
+
+        let size = compressed.as_ref().map_or(value.len(), Vec::len);
+        let sql = "INSERT OR REPLACE INTO cache (key, value, created_at, expires_at, access_count, last_accessed, compressed, size)
+             VALUES (?1, ?2, ?3, ?4, 0, ?3, ?5, ?6)";
+        match compressed {
+            Some(ref bytes) => conn.execute(sql, params![key, bytes, now, expires_at, true, size]),
+            None => conn.execute(sql, params![key, value, now, expires_at, false, size]),
+        }
+        .map_err(|e| CacheError::WriteError(e.to_string()))?;
 
-        debug!("Cached key: {} (ttl: {}s)", key, ttl_secs);
+        if let Some(limit) = self.max_size {
+            self.evict(&conn, limit, key)?;
+        }
+
+        debug!(
+            "Cached key: {} (ttl: {}s, {} bytes{})",
+            key,
+            ttl_secs,
+            size,
+            if compressed.is_some() { ", compressed" } else { "" }
+        );
         Ok(())
     }
 
@@ -169,7 +272,10 @@ impl DiskCache {
         conn.execute("DELETE FROM cache", [])
             .map_err(|e| CacheError::WriteError(e.to_string()))?;
         
-        // Reset counters
+        // Reset counters, including any not yet flushed
+        if let Ok(mut pending) = self.pending.lock() {
+            *pending = PendingStats::default();
+        }
         conn.execute("UPDATE metadata SET value = '0' WHERE key IN ('hits', 'misses')", [])
             .map_err(|e| CacheError::WriteError(e.to_string()))?;
         
@@ -191,6 +297,109 @@ impl DiskCache {
         Ok(affected)
     }
 
+    /// Total bytes of stored values
+    pub fn data_size(&self) -> Result<u64> {
+        let conn = self.conn.lock().map_err(|e| CacheError::ReadError(e.to_string()))?;
+        conn.query_row("SELECT COALESCE(SUM(size), 0) FROM cache", [], |row| row.get(0))
+            .map_err(|e| CacheError::ReadError(e.to_string()).into())
+    }
+
+    /// Evict least recently accessed entries (fewest accesses first on ties)
+    /// until the values fit in [`EVICTION_TARGET`] of `limit`. `keep` is the
+    /// key just written and is never evicted.
+    fn evict(&self, conn: &Connection, limit: u64, keep: &str) -> Result<usize> {
+        let read = |e: rusqlite::Error| CacheError::ReadError(e.to_string());
+        let total: u64 = conn.query_row("SELECT COALESCE(SUM(size), 0) FROM cache", [], |row| row.get(0))
+            .map_err(read)?;
+        if total <= limit {
+            return Ok(0);
+        }
+
+        // Recency must include accesses still sitting in the write-behind buffer
+        self.flush_pending(conn)?;
+
+        let target = (limit as f64 * EVICTION_TARGET) as u64;
+        let mut stmt = conn.prepare(
+            "SELECT key, size FROM cache WHERE key != ?1
+             ORDER BY COALESCE(last_accessed, created_at) ASC, access_count ASC"
+        ).map_err(read)?;
+        let candidates = stmt
+            .query_map(params![keep], |row| Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?)))
+            .map_err(read)?;
+
+        let mut remaining = total;
+        let mut victims = Vec::new();
+        for candidate in candidates {
+            if remaining <= target {
+                break;
+            }
+            let (key, size) = candidate.map_err(read)?;
+            remaining = remaining.saturating_sub(size);
+            victims.push(key);
+        }
+        drop(stmt);
+
+        let write = |e: rusqlite::Error| CacheError::WriteError(e.to_string());
+        conn.execute_batch("BEGIN").map_err(write)?;
+        for key in &victims {
+            if let Err(e) = conn.execute("DELETE FROM cache WHERE key = ?1", params![key]) {
+                let _ = conn.execute_batch("ROLLBACK");
+                return Err(write(e).into());
+            }
+        }
+        conn.execute_batch("COMMIT").map_err(write)?;
+
+        info!("Evicted {} cache entries ({} -> {} bytes)", victims.len(), total, remaining);
+        Ok(victims.len())
+    }
+
+    /// Check the database with `PRAGMA integrity_check` and make sure every
+    /// value decodes to JSON, deleting the entries that do not
+    pub fn verify(&self) -> Result<CacheVerifyReport> {
+        let conn = self.conn.lock().map_err(|e| CacheError::ReadError(e.to_string()))?;
+        let read = |e: rusqlite::Error| CacheError::ReadError(e.to_string());
+
+        let mut stmt = conn.prepare("PRAGMA integrity_check").map_err(read)?;
+        let integrity_errors: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(read)?
+            .filter_map(|message| message.ok())
+            .filter(|message| message != "ok")
+            .collect();
+        drop(stmt);
+
+        let mut stmt = conn.prepare("SELECT key, value, compressed FROM cache").map_err(read)?;
+        let rows = stmt
+            .query_map([], |row| {
+                let valid = match row.get_ref(1)? {
+                    ValueRef::Text(bytes) | ValueRef::Blob(bytes) => is_valid_value(bytes, row.get(2)?),
+                    _ => false,
+                };
+                Ok((row.get::<_, String>(0)?, valid))
+            })
+            .map_err(read)?;
+
+        let mut checked = 0;
+        let mut corrupted = Vec::new();
+        for row in rows {
+            let (key, valid) = row.map_err(read)?;
+            checked += 1;
+            if !valid {
+                corrupted.push(key);
+            }
+        }
+        drop(stmt);
+
+        let mut removed = 0;
+        for key in &corrupted {
+            warn!("Removing corrupted cache entry: {}", key);
+            removed += conn.execute("DELETE FROM cache WHERE key = ?1", params![key])
+                .map_err(|e| CacheError::WriteError(e.to_string()))?;
+        }
+
+        Ok(CacheVerifyReport { integrity_errors, checked, corrupted, removed })
+    }
+
     /// Vacuum the database to reclaim space
     pub fn vacuum(&self) -> Result<()> {
         let conn = self.conn.lock().map_err(|e| CacheError::WriteError(e.to_string()))?;
@@ -203,6 +412,7 @@ impl DiskCache {
     /// Get cache statistics
     pub fn stats(&self) -> Result<DiskCacheStats> {
         let conn = self.conn.lock().map_err(|e| CacheError::ReadError(e.to_string()))?;
+        self.flush_pending(&conn)?;
 
         let entries: usize = conn.query_row(
             "SELECT COUNT(*) FROM cache",
@@ -255,20 +465,100 @@ impl DiskCache {
         }
     }
 
-    fn increment_hits(&self, conn: &Connection) -> Result<()> {
-        conn.execute(
-            "UPDATE metadata SET value = CAST(CAST(value AS INTEGER) + 1 AS TEXT) WHERE key = 'hits'",
-            [],
-        ).map_err(|e| CacheError::WriteError(e.to_string()))?;
-        Ok(())
+    /// Write buffered hit/miss counters and access stats to the database
+    pub fn flush_stats(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| CacheError::WriteError(e.to_string()))?;
+        self.flush_pending(&conn)
     }
 
-    fn increment_misses(&self, conn: &Connection) -> Result<()> {
-        conn.execute(
-            "UPDATE metadata SET value = CAST(CAST(value AS INTEGER) + 1 AS TEXT) WHERE key = 'misses'",
-            [],
-        ).map_err(|e| CacheError::WriteError(e.to_string()))?;
-        Ok(())
+    fn record_hit(&self, conn: &Connection, key: &str, now: u64) {
+        let should_flush = match self.pending.lock() {
+            Ok(mut pending) => {
+                pending.hits += 1;
+                let entry = pending.accesses.entry(key.to_string()).or_insert((0, now));
+                entry.0 += 1;
+                entry.1 = now;
+                pending.ops() >= STATS_FLUSH_THRESHOLD
+            }
+            Err(_) => false,
+        };
+
+        if should_flush {
+            if let Err(e) = self.flush_pending(conn) {
+                warn!("Failed to flush cache stats: {}", e);
+            }
+        }
+    }
+
+    fn record_miss(&self, conn: &Connection) {
+        let should_flush = match self.pending.lock() {
+            Ok(mut pending) => {
+                pending.misses += 1;
+                pending.ops() >= STATS_FLUSH_THRESHOLD
+            }
+            Err(_) => false,
+        };
+
+        if should_flush {
+            if let Err(e) = self.flush_pending(conn) {
+                warn!("Failed to flush cache stats: {}", e);
+            }
+        }
+    }
+
+    /// Drain the write-behind buffer into a single transaction
+    fn flush_pending(&self, conn: &Connection) -> Result<()> {
+        let pending = match self.pending.lock() {
+            Ok(mut pending) if !pending.is_empty() => std::mem::take(&mut *pending),
+            _ => return Ok(()),
+        };
+
+        let map_err = |e: rusqlite::Error| CacheError::WriteError(e.to_string());
+
+        conn.execute_batch("BEGIN").map_err(map_err)?;
+        let result = (|| -> rusqlite::Result<()> {
+            conn.execute(
+                "UPDATE metadata SET value = CAST(CAST(value AS INTEGER) + ?1 AS TEXT) WHERE key = 'hits'",
+                params![pending.hits],
+            )?;
+            conn.execute(
+                "UPDATE metadata SET value = CAST(CAST(value AS INTEGER) + ?1 AS TEXT) WHERE key = 'misses'",
+                params![pending.misses],
+            )?;
+
+            let mut stmt = conn.prepare_cached(
+                "UPDATE cache SET access_count = access_count + ?2, last_accessed = ?3 WHERE key = ?1",
+            )?;
+            for (key, (count, last_accessed)) in &pending.accesses {
+                stmt.execute(params![key, count, last_accessed])?;
+            }
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => {
+                conn.execute_batch("COMMIT").map_err(map_err)?;
+                debug!(
+                    "Flushed cache stats ({} hits, {} misses, {} keys)",
+                    pending.hits,
+                    pending.misses,
+                    pending.accesses.len()
+                );
+                Ok(())
+            }
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK");
+                Err(map_err(e).into())
+            }
+        }
+    }
+}
+
+impl Drop for DiskCache {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush_stats() {
+            warn!("Failed to flush cache stats on drop: {}", e);
+        }
     }
 }
 
@@ -282,6 +572,52 @@ pub struct DiskCacheStats {
     pub expired: usize,
 }
 
+/// Outcome of [`DiskCache::verify`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheVerifyReport {
+    /// Problems reported by `PRAGMA integrity_check`; empty when it passed
+    pub integrity_errors: Vec<String>,
+    /// Entries read
+    pub checked: usize,
+    /// Keys whose value did not decompress or parse as JSON
+    pub corrupted: Vec<String>,
+    /// Corrupted entries deleted
+    pub removed: usize,
+}
+
+impl CacheVerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.integrity_errors.is_empty() && self.corrupted.is_empty()
+    }
+}
+
+/// Whether a stored value decodes to well-formed JSON
+fn is_valid_value(value: &[u8], compressed: bool) -> bool {
+    let decoded = if compressed {
+        match zstd::decode_all(value) {
+            Ok(decoded) => decoded,
+            Err(_) => return false,
+        }
+    } else {
+        value.to_vec()
+    };
+    serde_json::from_slice::<serde::de::IgnoredAny>(&decoded).is_ok()
+}
+
+/// Add `column` to the cache table if an older database lacks it, then run
+/// `backfill` to populate existing rows
+fn add_column(conn: &Connection, column: &str, definition: &str, backfill: Option<&str>) -> Result<()> {
+    if conn.prepare(&format!("SELECT {} FROM cache LIMIT 0", column)).is_ok() {
+        return Ok(());
+    }
+    let init = |e: rusqlite::Error| CacheError::InitFailed(e.to_string());
+    conn.execute_batch(&format!("ALTER TABLE cache ADD COLUMN {} {}", column, definition)).map_err(init)?;
+    if let Some(sql) = backfill {
+        conn.execute_batch(sql).map_err(init)?;
+    }
+    Ok(())
+}
+
 fn current_timestamp() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -363,6 +699,112 @@ mod tests {
         assert_eq!(stats.entries, 2);
     }
 
+    #[test]
+    fn test_batched_hit_counters() {
+        let (cache, _tmp) = create_test_cache();
+
+        cache.set("key1", r#""value1""#, 3600).unwrap();
+        let _: Option<String> = cache.get("key1").unwrap();
+        let _: Option<String> = cache.get("key1").unwrap();
+        let _: Option<String> = cache.get("missing").unwrap();
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+
+        let conn = cache.conn.lock().unwrap();
+        let access_count: u64 = conn.query_row(
+            "SELECT access_count FROM cache WHERE key = 'key1'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(access_count, 2);
+    }
+
+    #[test]
+    fn test_compression() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("test_cache.db");
+        let large = serde_json::to_string(&vec!["nur.repos.alice.hello"; 500]).unwrap();
+
+        let cache = DiskCache::with_path(path.clone()).unwrap().compression(true);
+        cache.set("nur:index", &large, 3600).unwrap();
+        cache.set("small", r#""value""#, 3600).unwrap();
+
+        let read: Vec<String> = cache.get("nur:index").unwrap().unwrap();
+        assert_eq!(read.len(), 500);
+        assert_eq!(cache.get::<String>("small").unwrap().as_deref(), Some("value"));
+
+        {
+            let conn = cache.conn.lock().unwrap();
+            let (stored, compressed): (usize, bool) = conn.query_row(
+                "SELECT length(value), compressed FROM cache WHERE key = 'nur:index'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            ).unwrap();
+            assert!(compressed);
+            assert!(stored < large.len() / 10);
+        }
+
+        // Compressed entries stay readable with compression turned off
+        drop(cache);
+        let cache = DiskCache::with_path(path).unwrap();
+        let read: Vec<String> = cache.get("nur:index").unwrap().unwrap();
+        assert_eq!(read.len(), 500);
+    }
+
+    #[test]
+    fn test_size_limit_evicts_least_recently_used() {
+        let tmp = TempDir::new().unwrap();
+        let cache = DiskCache::with_path(tmp.path().join("test_cache.db")).unwrap().max_size(Some(1000));
+        let value = format!("\"{}\"", "x".repeat(298));
+
+        cache.set("old", &value, 3600).unwrap();
+        cache.set("used", &value, 3600).unwrap();
+        cache.set("new", &value, 3600).unwrap();
+        {
+            // Make recency unambiguous within the same second
+            let conn = cache.conn.lock().unwrap();
+            conn.execute("UPDATE cache SET last_accessed = last_accessed - 10 WHERE key IN ('old', 'used')", []).unwrap();
+        }
+        let _: Option<String> = cache.get("used").unwrap();
+        assert_eq!(cache.data_size().unwrap(), 900);
+
+        // Going over 1000 bytes evicts down to 900, oldest first
+        cache.set("newest", &value, 3600).unwrap();
+        assert!(!cache.contains("old"));
+        assert!(cache.contains("used"));
+        assert!(cache.contains("new"));
+        assert!(cache.contains("newest"));
+        assert_eq!(cache.data_size().unwrap(), 900);
+    }
+
+    #[test]
+    fn test_verify_removes_corrupted_entries() {
+        let (cache, _tmp) = create_test_cache();
+        cache.set("good", r#"{"a": 1}"#, 3600).unwrap();
+        {
+            let conn = cache.conn.lock().unwrap();
+            conn.execute(
+                "INSERT INTO cache (key, value, created_at, expires_at, compressed) VALUES
+                 ('truncated', '{\"a\": ', 0, 9999999999, 0),
+                 ('bad-zstd', 'not zstd', 0, 9999999999, 1)",
+                [],
+            ).unwrap();
+        }
+
+        let report = cache.verify().unwrap();
+        assert!(report.integrity_errors.is_empty());
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.corrupted.len(), 2);
+        assert_eq!(report.removed, 2);
+        assert!(!report.is_ok());
+
+        let report = cache.verify().unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.checked, 1);
+    }
+
     #[test]
     fn test_clear() {
         let (cache, _tmp) = create_test_cache();
@@ -0,0 +1,118 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Cache for `nix eval` results, keyed by expression and nixpkgs revision.
+
+use crate::cache::CacheManager;
+use crate::cache::invalidation::{CacheKey, TTL};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Revision placeholder used when the nixpkgs revision cannot be determined
+pub const UNKNOWN_REVISION: &str = "unknown";
+
+/// Outcome of a single `nix eval` invocation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalOutcome {
+    /// Whether the evaluation succeeded
+    pub success: bool,
+    /// Raw stdout of the evaluation
+    pub stdout: String,
+}
+
+/// Evaluation cache bound to a specific nixpkgs revision
+pub struct EvalCache {
+    cache: Arc<CacheManager>,
+    revision: String,
+}
+
+impl EvalCache {
+    /// Create an eval cache for the given nixpkgs revision
+    pub fn new(cache: Arc<CacheManager>, revision: impl Into<String>) -> Self {
+        Self {
+            cache,
+            revision: revision.into(),
+        }
+    }
+
+    /// Whether results can be cached safely.
+    ///
+    /// Without a known revision we cannot tell when nixpkgs moves, so
+    /// nothing is cached.
+    pub fn is_pinned(&self) -> bool {
+        self.revision != UNKNOWN_REVISION
+    }
+
+    /// Look up a cached evaluation
+    pub fn get(&self, expr: &str) -> Option<EvalOutcome> {
+        if !self.is_pinned() {
+            return None;
+        }
+
+        let outcome = self.cache.get::<EvalOutcome>(&CacheKey::eval(&self.revision, expr));
+        if outcome.is_some() {
+            debug!("Eval cache hit for '{}'", expr);
+        }
+        outcome
+    }
+
+    /// Store an evaluation result
+    pub fn set(&self, expr: &str, outcome: &EvalOutcome) {
+        if !self.is_pinned() {
+            return;
+        }
+
+        if let Err(e) = self.cache.set(&CacheKey::eval(&self.revision, expr), outcome, TTL::EVAL) {
+            warn!("Failed to cache eval result: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::DiskCache;
+    use tempfile::TempDir;
+
+    fn create_test_manager() -> (Arc<CacheManager>, TempDir) {
+        let tmp = TempDir::new().unwrap();
+        let manager = CacheManager::with_disk(DiskCache::with_path(tmp.path().join("cache.db")).unwrap(), 10);
+        (Arc::new(manager), tmp)
+    }
+
+    #[test]
+    fn test_keyed_by_revision() {
+        let (manager, _tmp) = create_test_manager();
+        let outcome = EvalOutcome { success: true, stdout: "hello-2.12".to_string() };
+
+        EvalCache::new(manager.clone(), "abc123").set("nixpkgs#hello.meta.name", &outcome);
+
+        let same_rev = EvalCache::new(manager.clone(), "abc123");
+        assert_eq!(same_rev.get("nixpkgs#hello.meta.name").unwrap().stdout, "hello-2.12");
+
+        let new_rev = EvalCache::new(manager, "def456");
+        assert!(new_rev.get("nixpkgs#hello.meta.name").is_none());
+    }
+
+    #[test]
+    fn test_unknown_revision_not_cached() {
+        let (manager, _tmp) = create_test_manager();
+        let cache = EvalCache::new(manager, UNKNOWN_REVISION);
+        cache.set("expr", &EvalOutcome { success: true, stdout: String::new() });
+        assert!(cache.get("expr").is_none());
+    }
+}
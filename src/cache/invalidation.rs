@@ -16,6 +16,9 @@
 
 //! Cache invalidation strategies for NixBoost.
 
+use crate::core::config::Config;
+use parking_lot::Mutex;
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::debug;
@@ -24,6 +27,44 @@ use tracing::debug;
 pub struct CacheInvalidator {
     /// Last global invalidation time
     last_invalidation: AtomicU64,
+    /// Last invalidation time of each namespace
+    namespaces: Mutex<HashMap<String, u64>>,
+}
+
+/// Something nixboost did that makes cached entries stale
+#[derive(Debug, Clone, Copy)]
+pub enum Invalidation<'a> {
+    /// Packages, by attribute name, were installed into or removed from the profile
+    ProfileChanged(&'a [String]),
+    /// nixpkgs was fetched at, or found to point to, a different revision
+    ChannelUpdated,
+}
+
+impl Invalidation<'_> {
+    /// Key patterns (see [`matches`]) the event invalidates
+    pub fn patterns(&self) -> Vec<String> {
+        match self {
+            Invalidation::ProfileChanged(names) => {
+                let mut patterns = vec![CacheKey::installed(), "gc:".to_string()];
+                patterns.extend(names.iter().map(|name| CacheKey::package(name)));
+                patterns
+            }
+            Invalidation::ChannelUpdated => vec!["search:".to_string(), "pkg:".to_string(), "size:".to_string()],
+        }
+    }
+}
+
+/// Whether `key` matches an invalidation pattern. A pattern ending in `:` or
+/// `*` matches keys starting with the rest; any other pattern matches that
+/// key and the keys nested under it (`pkg:hello` matches `pkg:hello:meta`).
+pub fn matches(key: &str, pattern: &str) -> bool {
+    if let Some(prefix) = pattern.strip_suffix('*') {
+        return key.starts_with(prefix);
+    }
+    if pattern.ends_with(':') {
+        return key.starts_with(pattern);
+    }
+    key == pattern || key.strip_prefix(pattern).is_some_and(|rest| rest.starts_with(':'))
 }
 
 impl CacheInvalidator {
@@ -31,9 +72,24 @@ impl CacheInvalidator {
     pub fn new() -> Self {
         Self {
             last_invalidation: AtomicU64::new(0),
+            namespaces: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Record an invalidation of the namespace `pattern` falls in
+    pub fn invalidate(&self, pattern: &str) {
+        let namespace = pattern.trim_end_matches('*').split(':').next().unwrap_or_default();
+        self.namespaces.lock().insert(namespace.to_string(), current_epoch_ms());
+        debug!("Cache invalidation triggered for {}", pattern);
+    }
+
+    /// Check if an entry of `namespace` cached at `cached_at_ms` outlived
+    /// both global and namespace invalidations
+    pub fn is_valid_in(&self, namespace: &str, cached_at_ms: u64) -> bool {
+        let last = self.namespaces.lock().get(namespace).copied().unwrap_or(0);
+        self.is_valid(cached_at_ms) && cached_at_ms > last
+    }
+
     /// Trigger a global cache invalidation
     pub fn invalidate_all(&self) {
         let now = current_epoch_ms();
@@ -65,6 +121,27 @@ impl Default for CacheInvalidator {
 pub struct CacheKey;
 
 impl CacheKey {
+    /// Key namespaces (the part before the first `:`) and what they hold
+    pub const NAMESPACES: &'static [(&'static str, &'static str)] = &[
+        ("search", "search results"),
+        ("pkg", "package metadata"),
+        ("nur", "NUR index and packages"),
+        ("flake", "private flake indexes"),
+        ("deps", "dependency trees"),
+        ("installed", "installed packages"),
+        ("generations", "generation closure sizes"),
+        ("eval", "nix eval results"),
+        ("nixpkgs", "nixpkgs revision"),
+        ("hints", "post-install setup hints"),
+        ("gc", "GC preview dead paths"),
+        ("size", "install closure sizes"),
+    ];
+
+    /// Whether `key` belongs to `namespace`
+    pub fn in_namespace(key: &str, namespace: &str) -> bool {
+        key.split(':').next() == Some(namespace)
+    }
+
     /// Create a search results cache key
     pub fn search(query: &str) -> String {
         format!("search:{}", query.to_lowercase())
@@ -85,6 +162,11 @@ impl CacheKey {
         format!("nur:pkg:{}", name)
     }
 
+    /// Create a private flake package index cache key
+    pub fn flake_index(url: &str) -> String {
+        format!("flake:index:{}", url)
+    }
+
     /// Create a dependency tree cache key
     pub fn dependencies(package: &str) -> String {
         format!("deps:{}", package)
@@ -99,6 +181,46 @@ impl CacheKey {
     pub fn generations() -> String {
         "generations".to_string()
     }
+
+    /// Create a key for the closure size of a generation's store path
+    pub fn generation_size(store_path: &str) -> String {
+        format!("generations:size:{}", store_path)
+    }
+
+    /// Create a `nix eval` result cache key
+    pub fn eval(revision: &str, expr: &str) -> String {
+        format!("eval:{}:{}", revision, expr)
+    }
+
+    /// Create the detected system architecture cache key
+    pub fn current_system() -> String {
+        "eval:currentSystem".to_string()
+    }
+
+    /// Create the current nixpkgs revision cache key
+    pub fn nixpkgs_revision() -> String {
+        "nixpkgs:revision".to_string()
+    }
+
+    /// Create the key of the nixpkgs revision the cached searches belong to
+    pub fn seen_revision() -> String {
+        "nixpkgs:seen".to_string()
+    }
+
+    /// Create the community install hints cache key
+    pub fn install_hints() -> String {
+        "hints:install".to_string()
+    }
+
+    /// Create a key for the closure and download size of installing a package
+    pub fn size(package: &str) -> String {
+        format!("size:{}", package)
+    }
+
+    /// Create a key for the dead store paths seen at store marker `marker`
+    pub fn gc_dead_paths(marker: u64) -> String {
+        format!("gc:dead:{}", marker)
+    }
 }
 
 /// TTL (Time-To-Live) constants
@@ -126,11 +248,41 @@ impl TTL {
     /// Dependencies TTL (1 hour)
     pub const DEPENDENCIES: u64 = 3600;
     
+    /// Evaluation results TTL (1 week - keyed by nixpkgs revision)
+    pub const EVAL: u64 = 604800;
+
+    /// Update check TTL (24 hours)
+    pub const UPDATE_CHECK: u64 = 86400;
+
+    /// nixpkgs revision lookup TTL (10 minutes)
+    pub const REVISION: u64 = 600;
+
+    /// Install hints TTL (24 hours)
+    pub const HINTS: u64 = 86400;
+
+    /// GC preview TTL (1 day - keyed by the store's modification time)
+    pub const GC_PREVIEW: u64 = 86400;
+
     /// Short TTL for temporary data (30 seconds)
     pub const SHORT: u64 = 30;
     
     /// Long TTL for stable data (1 week)
     pub const LONG: u64 = 604800;
+
+    /// Search results TTL from `[cache] search_ttl_secs`
+    pub fn search() -> u64 {
+        Config::try_get().map(|c| c.cache.search_ttl_secs).unwrap_or(Self::SEARCH)
+    }
+
+    /// Package metadata TTL from `[cache] package_ttl_secs`
+    pub fn package() -> u64 {
+        Config::try_get().map(|c| c.cache.package_ttl_secs).unwrap_or(Self::PACKAGE)
+    }
+
+    /// NUR index TTL from `[cache] nur_ttl_secs`
+    pub fn nur_index() -> u64 {
+        Config::try_get().map(|c| c.cache.nur_ttl_secs).unwrap_or(Self::NUR_INDEX)
+    }
 }
 
 fn current_epoch_ms() -> u64 {
@@ -156,6 +308,27 @@ mod tests {
         assert_eq!(key, "pkg:firefox");
     }
 
+    #[test]
+    fn test_cache_key_eval() {
+        let key = CacheKey::eval("abc123", "nixpkgs#hello.meta.name");
+        assert_eq!(key, "eval:abc123:nixpkgs#hello.meta.name");
+    }
+
+    #[test]
+    fn test_cache_key_namespaces() {
+        let keys = [
+            CacheKey::search("x"), CacheKey::package("x"), CacheKey::nur_index(), CacheKey::nur_package("x"),
+            CacheKey::flake_index("x"), CacheKey::dependencies("x"), CacheKey::installed(), CacheKey::generations(),
+            CacheKey::generation_size("/nix/store/x"), CacheKey::eval("r", "x"), CacheKey::current_system(),
+            CacheKey::nixpkgs_revision(), CacheKey::install_hints(), CacheKey::gc_dead_paths(1), CacheKey::seen_revision(),
+            CacheKey::size("x"),
+        ];
+        for key in keys {
+            assert!(CacheKey::NAMESPACES.iter().any(|(ns, _)| CacheKey::in_namespace(&key, ns)), "{}", key);
+        }
+        assert!(!CacheKey::in_namespace("nixpkgs:revision", "nix"));
+    }
+
     #[test]
     fn test_invalidator() {
         let invalidator = CacheInvalidator::new();
@@ -175,4 +348,28 @@ mod tests {
         let new_cached_at = current_epoch_ms();
         assert!(invalidator.is_valid(new_cached_at));
     }
+
+    #[test]
+    fn test_namespace_invalidation() {
+        let invalidator = CacheInvalidator::new();
+        let cached_at = current_epoch_ms();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        invalidator.invalidate("search:*");
+        assert!(!invalidator.is_valid_in("search", cached_at));
+        assert!(invalidator.is_valid_in("pkg", cached_at));
+
+        assert!(matches("search:firefox", "search:"));
+        assert!(matches("search:firefox", "search:*"));
+        assert!(matches("pkg:hello", "pkg:hello"));
+        assert!(!matches("pkg:hello-wayland", "pkg:hello"));
+        assert!(matches("installed", "installed"));
+        assert!(matches("nur:pkg:x", "nur"));
+
+        let names = vec!["hello".to_string()];
+        assert_eq!(
+            Invalidation::ProfileChanged(&names).patterns(),
+            vec!["installed".to_string(), "gc:".to_string(), "pkg:hello".to_string()]
+        );
+    }
 }
@@ -16,66 +16,83 @@
 
 //! LRU in-memory cache for NixBoost.
 
+use crate::utils::time::unix_now;
 use lru::LruCache;
+use parking_lot::Mutex;
 use std::num::NonZeroUsize;
 use std::sync::atomic::{AtomicU64, Ordering};
 
-/// LRU in-memory cache for hot data
+/// LRU in-memory cache for hot data.
+///
+/// Lookups reorder the LRU list, so the map sits behind its own lock and
+/// every method takes `&self`.
 pub struct MemoryCache {
-    cache: LruCache<String, String>,
+    cache: Mutex<LruCache<String, MemoryEntry>>,
     hits: AtomicU64,
     misses: AtomicU64,
 }
 
+/// A serialized value and when it expires (Unix seconds, `None` for never)
+#[derive(Debug, Clone)]
+struct MemoryEntry {
+    value: String,
+    expires_at: Option<u64>,
+}
+
+impl MemoryEntry {
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|at| at < now)
+    }
+}
+
 impl MemoryCache {
     /// Create a new memory cache with the specified capacity
     pub fn new(capacity: usize) -> Self {
         let cap = NonZeroUsize::new(capacity.max(1)).unwrap();
         Self {
-            cache: LruCache::new(cap),
+            cache: Mutex::new(LruCache::new(cap)),
             hits: AtomicU64::new(0),
             misses: AtomicU64::new(0),
         }
     }
 
-    /// Get a value from the cache
+    /// Get and deserialize a value
     pub fn get<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
-        // Note: This requires mutable access to update LRU order
-        // The caller should hold a write lock
-        None // Will be properly implemented by the caller with lock
+        self.get_raw(key).and_then(|value| serde_json::from_str(&value).ok())
     }
 
-    /// Get a raw string value (for internal use with lock)
-    pub fn get_raw(&mut self, key: &str) -> Option<String> {
-        if let Some(value) = self.cache.get(key) {
-            self.hits.fetch_add(1, Ordering::Relaxed);
-            Some(value.clone())
-        } else {
-            self.misses.fetch_add(1, Ordering::Relaxed);
-            None
-        }
+    /// Get a raw string value, dropping it if it has expired
+    pub fn get_raw(&self, key: &str) -> Option<String> {
+        let mut cache = self.cache.lock();
+        let value = match cache.get(key) {
+            Some(entry) if entry.is_expired(unix_now()) => {
+                cache.pop(key);
+                None
+            }
+            Some(entry) => Some(entry.value.clone()),
+            None => None,
+        };
+
+        let counter = if value.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
     }
 
-    /// Get and deserialize (requires mutable self for LRU update)
-    pub fn get_mut<T: serde::de::DeserializeOwned>(&mut self, key: &str) -> Option<T> {
-        if let Some(value) = self.cache.get(key) {
-            self.hits.fetch_add(1, Ordering::Relaxed);
-            serde_json::from_str(value).ok()
-        } else {
-            self.misses.fetch_add(1, Ordering::Relaxed);
-            None
-        }
+    /// Set a value that never expires
+    pub fn set(&self, key: impl Into<String>, value: impl Into<String>) {
+        self.cache.lock().put(key.into(), MemoryEntry { value: value.into(), expires_at: None });
     }
 
-    /// Set a value in the cache
-    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
-        self.cache.put(key.into(), value.into());
+    /// Set a value that expires at `expires_at` (Unix seconds), like the
+    /// disk entry it mirrors
+    pub fn set_until(&self, key: impl Into<String>, value: impl Into<String>, expires_at: u64) {
+        self.cache.lock().put(key.into(), MemoryEntry { value: value.into(), expires_at: Some(expires_at) });
     }
 
     /// Set a serializable value
-    pub fn set_value<T: serde::Serialize>(&mut self, key: impl Into<String>, value: &T) -> bool {
+    pub fn set_value<T: serde::Serialize>(&self, key: impl Into<String>, value: &T) -> bool {
         if let Ok(serialized) = serde_json::to_string(value) {
-            self.cache.put(key.into(), serialized);
+            self.set(key, serialized);
             true
         } else {
             false
@@ -83,56 +100,57 @@ impl MemoryCache {
     }
 
     /// Remove a value from the cache
-    pub fn remove(&mut self, key: &str) -> Option<String> {
-        self.cache.pop(key)
+    pub fn remove(&self, key: &str) -> Option<String> {
+        self.cache.lock().pop(key).map(|entry| entry.value)
     }
 
     /// Check if a key exists
     pub fn contains(&self, key: &str) -> bool {
-        self.cache.contains(key)
+        self.cache.lock().contains(key)
     }
 
     /// Clear the cache
-    pub fn clear(&mut self) {
-        self.cache.clear();
+    pub fn clear(&self) {
+        self.cache.lock().clear();
         self.hits.store(0, Ordering::Relaxed);
         self.misses.store(0, Ordering::Relaxed);
     }
 
     /// Get the number of entries
     pub fn len(&self) -> usize {
-        self.cache.len()
+        self.cache.lock().len()
     }
 
     /// Check if the cache is empty
     pub fn is_empty(&self) -> bool {
-        self.cache.is_empty()
+        self.cache.lock().is_empty()
     }
 
     /// Get cache statistics
     pub fn stats(&self) -> MemoryCacheStats {
+        let cache = self.cache.lock();
         MemoryCacheStats {
-            entries: self.cache.len(),
-            capacity: self.cache.cap().get(),
+            entries: cache.len(),
+            capacity: cache.cap().get(),
             hits: self.hits.load(Ordering::Relaxed),
             misses: self.misses.load(Ordering::Relaxed),
         }
     }
 
     /// Resize the cache
-    pub fn resize(&mut self, new_capacity: usize) {
+    pub fn resize(&self, new_capacity: usize) {
         let cap = NonZeroUsize::new(new_capacity.max(1)).unwrap();
-        self.cache.resize(cap);
+        self.cache.lock().resize(cap);
     }
 
     /// Peek at a value without updating LRU order
-    pub fn peek(&self, key: &str) -> Option<&String> {
-        self.cache.peek(key)
+    pub fn peek(&self, key: &str) -> Option<String> {
+        self.cache.lock().peek(key).map(|entry| entry.value.clone())
     }
 
     /// Get all keys
-    pub fn keys(&self) -> impl Iterator<Item = &String> {
-        self.cache.iter().map(|(k, _)| k)
+    pub fn keys(&self) -> Vec<String> {
+        self.cache.lock().iter().map(|(k, _)| k.clone()).collect()
     }
 }
 
@@ -170,7 +188,7 @@ mod tests {
 
     #[test]
     fn test_set_and_get() {
-        let mut cache = MemoryCache::new(100);
+        let cache = MemoryCache::new(100);
         cache.set("key1", "value1");
         
         let value = cache.get_raw("key1");
@@ -179,7 +197,7 @@ mod tests {
 
     #[test]
     fn test_lru_eviction() {
-        let mut cache = MemoryCache::new(2);
+        let cache = MemoryCache::new(2);
         
         cache.set("key1", "value1");
         cache.set("key2", "value2");
@@ -192,7 +210,7 @@ mod tests {
 
     #[test]
     fn test_stats() {
-        let mut cache = MemoryCache::new(100);
+        let cache = MemoryCache::new(100);
         cache.set("key1", "value1");
         
         let _ = cache.get_raw("key1"); // Hit
@@ -207,7 +225,7 @@ mod tests {
 
     #[test]
     fn test_clear() {
-        let mut cache = MemoryCache::new(100);
+        let cache = MemoryCache::new(100);
         cache.set("key1", "value1");
         cache.set("key2", "value2");
         
@@ -217,9 +235,20 @@ mod tests {
         assert_eq!(cache.stats().hits, 0);
     }
 
+    #[test]
+    fn test_expired_entry_is_a_miss() {
+        let cache = MemoryCache::new(100);
+        cache.set_until("old", "1", unix_now() - 10);
+        cache.set_until("fresh", "2", unix_now() + 3600);
+
+        assert_eq!(cache.get::<u32>("old"), None);
+        assert_eq!(cache.get::<u32>("fresh"), Some(2));
+        assert!(!cache.contains("old"));
+    }
+
     #[test]
     fn test_serializable_value() {
-        let mut cache = MemoryCache::new(100);
+        let cache = MemoryCache::new(100);
         
         #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
         struct TestData {
@@ -230,7 +259,7 @@ mod tests {
         let data = TestData { name: "test".to_string(), count: 42 };
         cache.set_value("test_data", &data);
         
-        let retrieved: Option<TestData> = cache.get_mut("test_data");
+        let retrieved: Option<TestData> = cache.get("test_data");
         assert_eq!(retrieved, Some(data));
     }
 }
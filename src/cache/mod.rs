@@ -19,19 +19,24 @@
 pub mod disk_cache;
 pub mod memory_cache;
 pub mod invalidation;
+pub mod bundle;
+pub mod eval_cache;
 
-pub use disk_cache::DiskCache;
+pub use disk_cache::{CacheVerifyReport, DiskCache};
 pub use memory_cache::MemoryCache;
-pub use invalidation::CacheInvalidator;
+pub use invalidation::{CacheInvalidator, Invalidation};
+pub use bundle::CacheBundle;
+pub use eval_cache::EvalCache;
 
 use crate::core::error::Result;
+use crate::utils::time::unix_now;
+use tracing::warn;
 use std::sync::Arc;
-use parking_lot::RwLock;
 
 /// Combined cache manager with memory and disk caching
 pub struct CacheManager {
     /// In-memory LRU cache for hot data
-    pub memory: Arc<RwLock<MemoryCache>>,
+    pub memory: Arc<MemoryCache>,
     /// Persistent SQLite cache
     pub disk: Arc<DiskCache>,
     /// Cache invalidator
@@ -41,29 +46,31 @@ pub struct CacheManager {
 impl CacheManager {
     /// Create a new cache manager
     pub fn new(memory_size: usize) -> Result<Self> {
-        let memory = Arc::new(RwLock::new(MemoryCache::new(memory_size)));
-        let disk = Arc::new(DiskCache::new()?);
-        let invalidator = Arc::new(CacheInvalidator::new());
+        Ok(Self::with_disk(DiskCache::new()?, memory_size))
+    }
 
-        Ok(Self {
-            memory,
-            disk,
-            invalidator,
-        })
+    /// Create a cache manager over an already opened disk cache
+    pub fn with_disk(disk: DiskCache, memory_size: usize) -> Self {
+        Self {
+            memory: Arc::new(MemoryCache::new(memory_size)),
+            disk: Arc::new(disk),
+            invalidator: Arc::new(CacheInvalidator::new()),
+        }
     }
 
-    /// Get a value, checking memory first, then disk
+    /// Get a value, checking memory first, then disk. Disk hits are
+    /// promoted to memory with the disk entry's expiry.
     pub fn get<T: serde::de::DeserializeOwned + serde::Serialize + Clone>(&self, key: &str) -> Option<T> {
         // Try memory cache first
-        if let Some(value) = self.memory.read().get::<T>(key) {
+        if let Some(value) = self.memory.get::<T>(key) {
             return Some(value);
         }
 
         // Try disk cache
-        if let Ok(Some(value)) = self.disk.get::<T>(key) {
+        if let Ok(Some((value, expires_at))) = self.disk.get_with_expiry::<T>(key) {
             // Promote to memory cache
             if let Ok(serialized) = serde_json::to_string(&value) {
-                self.memory.write().set(key, serialized);
+                self.memory.set_until(key, serialized, expires_at);
             }
             return Some(value);
         }
@@ -77,7 +84,7 @@ impl CacheManager {
             .map_err(|e| crate::core::error::CacheError::WriteError(e.to_string()))?;
 
         // Store in memory
-        self.memory.write().set(key, serialized.clone());
+        self.memory.set_until(key, serialized.clone(), unix_now() + ttl_secs);
 
         // Store on disk
         self.disk.set(key, &serialized, ttl_secs)?;
@@ -85,16 +92,55 @@ impl CacheManager {
         Ok(())
     }
 
+    /// Delete a value from both caches
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        self.memory.remove(key);
+        self.disk.delete(key)
+    }
+
     /// Clear all caches
     pub fn clear(&self) -> Result<()> {
-        self.memory.write().clear();
+        self.memory.clear();
         self.disk.clear()?;
         Ok(())
     }
 
+    /// Clear the entries of one key namespace (see `CacheKey::NAMESPACES`),
+    /// returning how many were removed from disk
+    pub fn clear_namespace(&self, namespace: &str) -> Result<usize> {
+        self.invalidate(namespace)
+    }
+
+    /// Remove the entries matching `pattern` (see [`invalidation::matches`])
+    /// from memory and disk, returning how many were removed from disk
+    pub fn invalidate(&self, pattern: &str) -> Result<usize> {
+        for key in self.memory.keys().into_iter().filter(|k| invalidation::matches(k, pattern)) {
+            self.memory.remove(&key);
+        }
+        self.invalidator.invalidate(pattern);
+
+        match pattern.strip_suffix('*') {
+            Some(prefix) => self.disk.delete_prefix(prefix),
+            None if pattern.ends_with(':') => self.disk.delete_prefix(pattern),
+            None => {
+                let exact = usize::from(self.disk.delete(pattern)?);
+                Ok(exact + self.disk.delete_prefix(&format!("{}:", pattern))?)
+            }
+        }
+    }
+
+    /// Invalidate what `event` makes stale
+    pub fn on(&self, event: Invalidation) {
+        for pattern in event.patterns() {
+            if let Err(e) = self.invalidate(&pattern) {
+                warn!("Failed to invalidate {}: {}", pattern, e);
+            }
+        }
+    }
+
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
-        let memory_stats = self.memory.read().stats();
+        let memory_stats = self.memory.stats();
         let disk_stats = self.disk.stats().unwrap_or_default();
 
         CacheStats {
@@ -148,3 +194,48 @@ impl CacheStats {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_manager() -> (CacheManager, TempDir) {
+        let tmp = TempDir::new().unwrap();
+        let disk = DiskCache::with_path(tmp.path().join("cache.db")).unwrap();
+        (CacheManager::with_disk(disk, 10), tmp)
+    }
+
+    #[test]
+    fn test_disk_hit_is_promoted_to_memory() {
+        let (cache, _tmp) = create_test_manager();
+        cache.disk.set("pkg:hello", r#""2.12""#, 3600).unwrap();
+
+        // Memory miss, disk hit
+        assert_eq!(cache.get::<String>("pkg:hello").as_deref(), Some("2.12"));
+        assert!(cache.memory.contains("pkg:hello"));
+
+        // Served from memory; the disk sees no further reads
+        assert_eq!(cache.get::<String>("pkg:hello").as_deref(), Some("2.12"));
+        assert_eq!(cache.get::<String>("pkg:hello").as_deref(), Some("2.12"));
+
+        let stats = cache.stats();
+        assert_eq!((stats.memory_hits, stats.memory_misses), (2, 1));
+        assert_eq!(stats.disk_hits, 1);
+        assert!((cache.memory.stats().hit_rate() - 2.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_delete_and_clear_namespace_reach_memory() {
+        let (cache, _tmp) = create_test_manager();
+        cache.set("installed", &vec!["hello"], 60).unwrap();
+        cache.set("gc:dead:1", &Vec::<String>::new(), 60).unwrap();
+
+        cache.delete("installed").unwrap();
+        assert_eq!(cache.get::<Vec<String>>("installed"), None);
+
+        cache.clear_namespace("gc").unwrap();
+        assert!(!cache.memory.contains("gc:dead:1"));
+        assert_eq!(cache.get::<Vec<String>>("gc:dead:1"), None);
+    }
+}
@@ -16,7 +16,12 @@
 
 //! CLI argument definitions for NixBoost.
 
+use crate::core::config::{MaintainStep, OperationMode};
+use crate::system::generations::Profile;
 use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::engine::ArgValueCandidates;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -33,6 +38,10 @@ pub struct Cli {
     #[arg(short = 'S', long)]
     pub sync: bool,
 
+    /// Upgrade installed packages (with -S: -Su upgrades all, or only TARGETS)
+    #[arg(short = 'u', long)]
+    pub sysupgrade: bool,
+
     /// Remove packages (like pacman -R)
     #[arg(short = 'R', long)]
     pub remove: bool,
@@ -53,10 +62,18 @@ pub struct Cli {
     #[arg(long)]
     pub history: bool,
 
-    /// Run garbage collection
-    #[arg(long)]
+    /// Run garbage collection (with -S: -Sc)
+    #[arg(short = 'c', long)]
     pub clean: bool,
 
+    /// With --clean: delete profile generations older than this (e.g. 30d, 2w)
+    #[arg(long, value_name = "AGE", value_parser = crate::system::garbage_collector::parse_age)]
+    pub older_than: Option<std::time::Duration>,
+
+    /// With --clean: delete old generations until about this much can be freed (e.g. 5G)
+    #[arg(long, value_name = "SIZE", value_parser = crate::system::garbage_collector::parse_size)]
+    pub free_at_least: Option<u64>,
+
     /// Fetch NixOS news
     #[arg(long)]
     pub news: bool,
@@ -65,6 +82,14 @@ pub struct Cli {
     #[arg(long)]
     pub health: bool,
 
+    /// Quick health check (default): skip hashing store contents
+    #[arg(long, conflicts_with = "full")]
+    pub quick: bool,
+
+    /// Full health check, including `nix-store --verify --check-contents`
+    #[arg(long)]
+    pub full: bool,
+
     /// Show this package's info
     #[arg(short = 'i', long)]
     pub info: bool,
@@ -77,10 +102,31 @@ pub struct Cli {
     #[arg(short = 'y', long)]
     pub yes: bool,
 
+    /// Install all targets or none: offer to roll back the profile if one fails
+    #[arg(long)]
+    pub atomic: bool,
+
+    /// With -S/-Su: download the closures into the store without installing,
+    /// so a later install needs no network
+    #[arg(long, conflicts_with = "atomic")]
+    pub download_only: bool,
+
+    /// Browse, mark and install packages in the interactive terminal UI
+    #[arg(long)]
+    pub interactive: bool,
+
     /// Be verbose (show debug info)
     #[arg(short = 'v', long)]
     pub verbose: bool,
 
+    /// Print every nix command before running it
+    #[arg(long)]
+    pub show_commands: bool,
+
+    /// Pass a Nix setting to every nix command, e.g. `--option sandbox relaxed` (repeatable)
+    #[arg(long = "option", num_args = 2, value_names = ["NAME", "VALUE"], action = clap::ArgAction::Append)]
+    pub nix_options: Vec<String>,
+
     /// Be quiet (minimal output)
     #[arg(short = 'q', long)]
     pub quiet: bool,
@@ -89,13 +135,53 @@ pub struct Cli {
     #[arg(long)]
     pub no_update_check: bool,
 
+    /// Don't print setup hints after installing packages
+    #[arg(long)]
+    pub no_hints: bool,
+
     /// Use specific config file
     #[arg(long, value_name = "FILE")]
     pub config: Option<String>,
 
-    /// Maximum number of results to show
-    #[arg(long, default_value = "50")]
-    pub max_results: usize,
+    /// Maximum number of results to show [default: search.max_results]
+    #[arg(long, value_name = "N")]
+    pub max_results: Option<usize>,
+
+    /// Minimum fuzzy match score for a result [default: search.min_score]
+    #[arg(long, value_name = "SCORE")]
+    pub min_score: Option<i64>,
+
+    /// Restrict search to an attribute namespace (e.g. python, nodePackages)
+    #[arg(long, value_name = "NAMESPACE")]
+    pub scope: Option<String>,
+
+    /// Only show search results under this license (e.g. MIT, gpl)
+    #[arg(long, value_name = "LICENSE")]
+    pub license: Option<String>,
+
+    /// Only show search results maintained by this handle
+    #[arg(long, value_name = "HANDLE")]
+    pub maintainer: Option<String>,
+
+    /// Only show search results supporting this platform (e.g. aarch64-linux)
+    #[arg(long, value_name = "SYSTEM")]
+    pub platform: Option<String>,
+
+    /// Leave search results matching this glob out, e.g. '*-unwrapped' (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Include nixpkgs alias attributes and wrapper variants in search results
+    #[arg(long)]
+    pub show_aliases: bool,
+
+    /// Search result layout
+    #[arg(long, value_enum, value_name = "LAYOUT", default_value = "list")]
+    pub format: SearchFormat,
+
+    /// Columns for `--format table`, comma-separated (implies --format table)
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "COLUMNS")]
+    pub columns: Vec<SearchColumn>,
 
     /// Disable cache
     #[arg(long)]
@@ -113,6 +199,27 @@ pub struct Cli {
     #[arg(long, value_enum, default_value = "human")]
     pub output: OutputFormat,
 
+    /// Stable, versioned output for scripts and editors (tab-separated, or JSON with --output json)
+    #[arg(long, value_enum, value_name = "VERSION")]
+    pub porcelain: Option<PorcelainVersion>,
+
+    /// Act on the NixOS system instead of the user profile (overrides `general.mode`)
+    #[arg(long)]
+    pub system: bool,
+
+    /// Evaluate search, info and dry-run checks for another system, e.g. aarch64-linux
+    #[arg(long, value_name = "SYSTEM", value_parser = crate::system::capabilities::parse_system)]
+    pub for_system: Option<String>,
+
+    /// Profile installs, removals, listings and generation commands act on:
+    /// user, system, home-manager, a `nixboost profile` name or a profile path
+    #[arg(long, value_name = "PROFILE", conflicts_with = "system", value_parser = crate::system::generations::parse_profile, add = ArgValueCandidates::new(crate::cli::complete::profiles))]
+    pub profile: Option<Profile>,
+
+    /// Only install from this source (overrides `general.nur_fallback`)
+    #[arg(long, value_enum, value_name = "SOURCE")]
+    pub source: Option<InstallSource>,
+
     /// Target packages or search queries
     #[arg(value_name = "TARGETS")]
     pub targets: Vec<String>,
@@ -122,6 +229,67 @@ pub struct Cli {
     pub command: Option<Commands>,
 }
 
+/// Porcelain output contract versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PorcelainVersion {
+    V1,
+}
+
+/// Where packages may be installed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InstallSource {
+    /// nixpkgs only, never NUR
+    Nixpkgs,
+    /// NUR only
+    Nur,
+}
+
+/// Package environments that can be compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Environment {
+    /// The user's `nix profile`
+    Profile,
+    /// The NixOS system environment (`environment.systemPackages`)
+    System,
+}
+
+/// Search result layouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SearchFormat {
+    /// Two lines per package: source/name version, then the description
+    List,
+    /// One row per package
+    Table,
+}
+
+/// Columns available to `--format table`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SearchColumn {
+    Name,
+    Version,
+    Source,
+    Description,
+    License,
+    Homepage,
+}
+
+impl SearchColumn {
+    /// Columns shown when `--columns` is not given
+    pub const DEFAULT: [SearchColumn; 4] = [Self::Name, Self::Version, Self::Source, Self::Description];
+
+    /// Table header for the column
+    pub fn header(self) -> &'static str {
+        match self {
+            Self::Name => "Name",
+            Self::Version => "Version",
+            Self::Source => "Source",
+            Self::Description => "Description",
+            Self::License => "License",
+            Self::Homepage => "Homepage",
+        }
+    }
+}
+
 /// Output format options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum OutputFormat {
@@ -129,6 +297,8 @@ pub enum OutputFormat {
     Human,
     /// JSON output for scripting
     Json,
+    /// Newline-delimited JSON, one record per line
+    Ndjson,
     /// Plain text (no colors, simple format)
     Plain,
 }
@@ -138,8 +308,9 @@ pub enum OutputFormat {
 pub enum Commands {
     /// Show package information
     Info {
-        /// Package name
-        package: String,
+        /// Package names
+        #[arg(required = true)]
+        packages: Vec<String>,
     },
 
     /// Manage generations
@@ -148,12 +319,50 @@ pub enum Commands {
         action: GenerationAction,
     },
 
+    /// Create, list and switch named profiles (use one with `--profile <name>`)
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+
     /// Cache management
     Cache {
         #[command(subcommand)]
         action: CacheAction,
     },
 
+    /// Compare two package environments, e.g. `diff profile system` to find packages installed both ways
+    Diff {
+        #[arg(value_enum)]
+        from: Environment,
+        #[arg(value_enum)]
+        to: Environment,
+    },
+
+    /// Garbage collection analysis
+    Gc {
+        #[command(subcommand)]
+        action: GcAction,
+    },
+
+    /// Local nixpkgs search index
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
+    },
+
+    /// Operation history
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+
+    /// NUR index management
+    Nur {
+        #[command(subcommand)]
+        action: NurAction,
+    },
+
     /// Configuration management
     Config {
         #[command(subcommand)]
@@ -166,7 +375,193 @@ pub enum Commands {
         action: SystemAction,
     },
 
-    /// Generate shell completions
+    /// Prune the cache, delete old generations, collect garbage, optimise
+    /// the store and refresh the index, as set in [maintain]
+    Maintain {
+        /// Steps to leave out this time (comma-separated)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        skip: Vec<MaintainStep>,
+        /// Show the steps that would run
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+
+    /// Write nixboost.lock capturing every installed package's locked flake ref
+    Lock {
+        /// Lockfile to write
+        #[arg(short, long, default_value = "nixboost.lock")]
+        file: PathBuf,
+    },
+
+    /// Print installed packages and their sources as JSON: `export > packages.json`
+    Export {
+        /// Include locked references pinning each package's exact revision
+        #[arg(long)]
+        locked: bool,
+    },
+
+    /// Install the packages of an `export` manifest that are missing, after showing the difference
+    Import {
+        /// Manifest written by `nixboost export`
+        file: PathBuf,
+        /// Only show what would be installed
+        #[arg(short, long)]
+        dry_run: bool,
+    },
+
+    /// Install declared packages from config, or reconcile with a lockfile
+    Sync {
+        /// Make the profile match the lockfile exactly
+        #[arg(long)]
+        frozen: bool,
+        /// Also remove installed packages that are not declared
+        #[arg(long, conflicts_with = "frozen")]
+        prune: bool,
+        /// Lockfile to read
+        #[arg(short, long, default_value = "nixboost.lock")]
+        file: PathBuf,
+    },
+
+    /// Edit the package list in a NixOS or home-manager file and rebuild
+    Declare {
+        /// Nix file with the package list (default: `packages.file` in config)
+        #[arg(short, long, global = true)]
+        file: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: DeclareAction,
+    },
+
+    /// Queue installs (-S) and removals (-R) to apply later as one transaction
+    #[command(args_conflicts_with_subcommands = true)]
+    Stage {
+        #[command(subcommand)]
+        action: Option<StageAction>,
+
+        /// Packages to install
+        #[arg(short = 'S', long = "install", num_args = 1.., value_name = "PKG")]
+        install: Vec<String>,
+
+        /// Installed packages to remove
+        #[arg(short = 'R', long = "remove", num_args = 1.., value_name = "PKG")]
+        remove: Vec<String>,
+    },
+
+    /// Install a package from a nixpkgs commit and hold it there on upgrades
+    #[command(args_conflicts_with_subcommands = true)]
+    Pin {
+        #[command(subcommand)]
+        action: Option<PinAction>,
+
+        /// nixpkgs attribute to pin
+        package: Option<String>,
+
+        /// nixpkgs commit to install it from
+        #[arg(long, requires = "package")]
+        rev: Option<String>,
+    },
+
+    /// Let pinned packages be upgraded again
+    Unpin {
+        #[arg(required = true)]
+        packages: Vec<String>,
+    },
+
+    /// Show a package's runtime dependency tree
+    Deps {
+        /// Installed package or nixpkgs attribute
+        package: String,
+
+        /// Levels of the tree to print (all by default)
+        #[arg(long)]
+        depth: Option<usize>,
+
+        /// Explain why the package depends on this one (`nix why-depends`)
+        #[arg(long, value_name = "DEPENDENCY")]
+        why: Option<String>,
+    },
+
+    /// Find the package that provides a command or file, e.g. `provides rg` or `provides include/zlib.h`
+    Provides {
+        /// Command name, or a path (anything containing `/`)
+        query: String,
+        /// Download the nix-index database again before searching
+        #[arg(long)]
+        update: bool,
+    },
+
+    /// Show the store paths a package resolves to, whether it is installed and its size
+    Which {
+        /// Installed package, nixpkgs attribute or flake reference
+        package: String,
+    },
+
+    /// Remove and reinstall packages from the flake they were installed from
+    Reinstall {
+        /// Installed package names
+        #[arg(required = true)]
+        packages: Vec<String>,
+    },
+
+    /// Show what upgrading to the latest nixpkgs revision would change, without installing
+    #[command(alias = "outdated")]
+    PreviewUpgrade,
+
+    /// Install the latest GitHub release binary for this platform (for tools not in nixpkgs)
+    FetchBin {
+        /// GitHub repository as owner/repo
+        #[arg(value_name = "OWNER/REPO")]
+        repo: String,
+    },
+
+    /// Open `nix repl` with nixpkgs and the installed packages (as `installed.<name>`) in scope
+    Repl,
+
+    /// Start a temporary shell with packages available, without installing them
+    Shell {
+        /// Packages from nixpkgs (or NUR when nixpkgs does not have them)
+        #[arg(required = true)]
+        packages: Vec<String>,
+    },
+
+    /// Run a package's main program without installing it: `run cowsay -- hello`
+    Run {
+        /// nixpkgs attribute or flake reference
+        package: String,
+
+        /// Arguments for the program
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+
+    /// Show a package's or derivation's build log, highlighted and paged
+    Logs {
+        /// nixpkgs attribute, flake reference or store path
+        target: String,
+
+        /// Only show the last N (matching) lines
+        #[arg(short = 'n', long)]
+        tail: Option<usize>,
+
+        /// Only show lines containing this text (case-insensitive)
+        #[arg(short, long)]
+        grep: Option<String>,
+
+        /// Print instead of opening $PAGER
+        #[arg(long)]
+        no_pager: bool,
+    },
+
+    /// Interactive package browser: search, inspect and mark packages to install or remove
+    Tui,
+
+    /// Helpers for packaging software that is missing from nixpkgs
+    Contrib {
+        #[command(subcommand)]
+        action: ContribAction,
+    },
+
+    /// Generate shell completions (config keys, generations and cache namespaces complete dynamically)
     Completions {
         /// Shell to generate completions for
         #[arg(value_enum)]
@@ -174,6 +569,21 @@ pub enum Commands {
     },
 }
 
+/// Named profile subcommands
+#[derive(Subcommand, Debug)]
+pub enum ProfileAction {
+    /// Create a named profile; it is populated with `--profile <name> <packages>`
+    Create {
+        name: String,
+    },
+    /// List named profiles
+    List,
+    /// Make a named profile the active one (`default` returns to the original)
+    Switch {
+        name: String,
+    },
+}
+
 /// Generation subcommands
 #[derive(Subcommand, Debug)]
 pub enum GenerationAction {
@@ -193,13 +603,19 @@ pub enum GenerationAction {
     /// Rollback to a specific generation
     Rollback {
         /// Generation number (omit for previous)
+        #[arg(add = ArgValueCandidates::new(crate::cli::complete::generations))]
         generation: Option<u64>,
     },
     /// Delete old generations
     Delete {
-        /// Keep last N generations
-        #[arg(short, long, default_value = "5")]
-        keep: usize,
+        /// Keep the newest N generations instead of applying [generations]
+        #[arg(short, long)]
+        keep: Option<usize>,
+    },
+    /// Restore a deleted generation (omit the number to list restorable ones)
+    Restore {
+        /// Generation number
+        generation: Option<u64>,
     },
 }
 
@@ -208,12 +624,142 @@ pub enum GenerationAction {
 pub enum CacheAction {
     /// Show cache statistics
     Stats,
-    /// Clear all cache
-    Clear,
-    /// Verify cache integrity
+    /// Clear all cache, or one namespace of it
+    Clear {
+        /// Only clear keys in this namespace (e.g. search, nur, eval)
+        #[arg(long, value_parser = crate::cli::complete::parse_namespace, add = ArgValueCandidates::new(crate::cli::complete::cache_namespaces))]
+        namespace: Option<String>,
+    },
+    /// Remove entries by key or prefix (`installed`, `pkg:hello`, `search:*`)
+    Invalidate {
+        /// Key, namespace, or prefix ending in `:` or `*`
+        pattern: String,
+    },
+    /// Check cache integrity and remove corrupted entries
     Verify,
     /// Prune expired entries
     Prune,
+    /// Export the cache, local package index and NUR index to a .tar.zst bundle
+    Export {
+        /// Bundle file to write
+        path: PathBuf,
+    },
+    /// Seed the cache from a bundle created with `cache export`
+    Import {
+        /// Bundle file to read
+        path: PathBuf,
+    },
+    /// Time a narinfo lookup on each configured substituter
+    Substituters,
+}
+
+/// Contrib subcommands
+#[derive(Subcommand, Debug)]
+pub enum ContribAction {
+    /// Write a derivation skeleton for a new package
+    Init {
+        /// Package name
+        pname: String,
+        /// GitHub repository (owner/repo) to prefill src and meta from
+        #[arg(long, value_name = "OWNER/REPO")]
+        repo: Option<String>,
+        /// File to write (default: <pname>/package.nix)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Overwrite an existing file
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+/// GC subcommands
+#[derive(Subcommand, Debug)]
+pub enum GcAction {
+    /// How much space removing a package and collecting garbage would reclaim
+    Impact {
+        /// Installed package name
+        package: String,
+    },
+}
+
+/// Index subcommands
+#[derive(Subcommand, Debug)]
+pub enum IndexAction {
+    /// Rebuild the index from a full `nix search nixpkgs ^` dump
+    Update,
+    /// Show the index size, age and nixpkgs revision
+    Status,
+}
+
+/// Declare subcommands
+#[derive(Subcommand, Debug)]
+pub enum DeclareAction {
+    /// Add packages to the list
+    Add {
+        #[arg(required = true)]
+        packages: Vec<String>,
+    },
+    /// Remove packages from the list
+    Remove {
+        #[arg(required = true)]
+        packages: Vec<String>,
+    },
+    /// Show the packages in the list
+    List,
+}
+
+/// Pin subcommands
+#[derive(Subcommand, Debug)]
+pub enum PinAction {
+    /// Show pinned packages
+    List,
+}
+
+/// Stage subcommands
+#[derive(Subcommand, Debug)]
+pub enum StageAction {
+    /// Review the staged changes
+    Show,
+    /// Apply the staged changes as one transaction
+    Apply,
+    /// Discard the staged changes
+    Clear,
+}
+
+/// History subcommands
+#[derive(Subcommand, Debug)]
+pub enum HistoryAction {
+    /// Show the nixpkgs revision each install resolved to, with refs that reproduce it
+    Revisions {
+        /// Number of most recent installs to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+    },
+    /// Browse recorded installs, removals, garbage collections and rollbacks
+    Ops {
+        /// Only this operation: install, remove, gc or rollback
+        #[arg(long = "type", value_name = "OPERATION")]
+        operation: Option<String>,
+        /// Only failed operations
+        #[arg(long)]
+        failed: bool,
+        /// Only operations involving this package
+        #[arg(long)]
+        package: Option<String>,
+        /// Only operations within this span, e.g. 12h, 7d, 2w
+        #[arg(long, value_name = "SPAN")]
+        since: Option<String>,
+        /// Number of most recent operations to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+    },
+}
+
+/// NUR subcommands
+#[derive(Subcommand, Debug)]
+pub enum NurAction {
+    /// Show when the NUR index was fetched and from which commit
+    Status,
 }
 
 /// Config subcommands
@@ -231,6 +777,14 @@ pub enum ConfigAction {
     Edit,
     /// Validate config file
     Validate,
+    /// Change one setting, e.g. `config set search.max_results 100`
+    Set {
+        /// Dotted setting name
+        #[arg(add = ArgValueCandidates::new(crate::cli::complete::config_keys))]
+        key: String,
+        /// New value
+        value: String,
+    },
     /// Show config file path
     Path,
 }
@@ -239,22 +793,82 @@ pub enum ConfigAction {
 #[derive(Subcommand, Debug)]
 pub enum SystemAction {
     /// Run health check
-    Health,
+    Health {
+        /// Also verify store contents (slow)
+        #[arg(long, conflicts_with = "watch")]
+        full: bool,
+        /// Keep running the quick checks and report state changes
+        #[arg(long)]
+        watch: bool,
+        /// Time between checks in watch mode (e.g. 30s, 5m, 1h)
+        #[arg(long, default_value = "5m", value_parser = crate::system::monitor::parse_interval)]
+        interval: std::time::Duration,
+    },
     /// Run garbage collection
     Gc {
-        /// Keep minimum generations
-        #[arg(short, long, default_value = "3")]
-        keep_generations: usize,
+        /// Keep the newest N generations instead of applying [generations]
+        #[arg(short, long)]
+        keep_generations: Option<usize>,
+        /// Also delete generations older than this (e.g. 30d, 2w)
+        #[arg(long, value_name = "AGE", value_parser = crate::system::garbage_collector::parse_age)]
+        older_than: Option<std::time::Duration>,
+        /// Delete old generations until about this much can be freed (e.g. 5G)
+        #[arg(long, value_name = "SIZE", value_parser = crate::system::garbage_collector::parse_size)]
+        free_at_least: Option<u64>,
         /// Dry run (show what would be deleted)
         #[arg(short, long)]
         dry_run: bool,
     },
     /// Verify Nix store
     Verify,
+    /// Categorize store verification issues and repair them interactively
+    Triage,
     /// Optimize Nix store
     Optimize,
     /// Show disk usage
     DiskUsage,
+    /// List remote builders and check that they are reachable
+    Builders,
+    /// Rebuild the NixOS configuration, showing what changes before switching
+    Rebuild {
+        /// Update channels before building
+        #[arg(long)]
+        upgrade: bool,
+        /// Switch without prompting even if the kernel, bootloader or display driver changes
+        #[arg(long)]
+        allow_critical: bool,
+        /// Roll back automatically unless the switch is confirmed in time
+        #[arg(long)]
+        watchdog: bool,
+        /// How long to wait for confirmation in watchdog mode (e.g. 90s, 5m)
+        #[arg(long, default_value = "5m", value_parser = crate::system::monitor::parse_interval)]
+        confirm_within: std::time::Duration,
+    },
+    /// Confirm the running system is healthy and cancel a pending auto-rollback
+    Confirm,
+    /// Inspect or restart nix-daemon
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonAction,
+    },
+}
+
+/// nix-daemon subcommands
+#[derive(Subcommand, Debug)]
+pub enum DaemonAction {
+    /// Show daemon status
+    Status,
+    /// Restart the daemon (uses sudo when not root)
+    Restart,
+    /// Show daemon logs
+    Logs {
+        /// Number of lines to show
+        #[arg(short = 'n', long, default_value = "50")]
+        lines: usize,
+        /// Follow new log output
+        #[arg(short, long)]
+        follow: bool,
+    },
 }
 
 /// Shell types for completion generation
@@ -270,9 +884,9 @@ pub enum Shell {
 impl Cli {
     /// Check if any operation is requested
     pub fn has_operation(&self) -> bool {
-        self.sync || self.remove || self.search || self.nur || self.list || 
+        self.sync || self.sysupgrade || self.remove || self.search || self.nur || self.list || 
         self.history || self.clean || self.news || self.health || self.info ||
-        self.cache_stats || self.command.is_some()
+        self.cache_stats || self.interactive || self.command.is_some()
     }
 
     /// Check if this is a read-only operation
@@ -281,6 +895,31 @@ impl Cli {
         self.info || self.cache_stats || self.dry_run
     }
 
+    /// Profile operations act on, from `--profile`, `--system` or `general.mode`
+    pub fn target_profile(&self, mode: OperationMode) -> Profile {
+        if let Some(profile) = &self.profile {
+            profile.clone()
+        } else if self.system || mode == OperationMode::System {
+            Profile::System
+        } else {
+            Profile::User
+        }
+    }
+
+    /// Profile `nix profile` commands act on; `None` for the user's default
+    pub fn package_profile(&self, mode: OperationMode) -> Option<PathBuf> {
+        self.target_profile(mode).nix_profile()
+    }
+
+    /// `[nix.options]` with `--option` settings applied over them
+    pub fn nix_options(&self, config: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        let mut options = config.clone();
+        for pair in self.nix_options.chunks_exact(2) {
+            options.insert(pair[0].clone(), pair[1].clone());
+        }
+        options
+    }
+
     /// Check if confirmation should be skipped
     pub fn skip_confirm(&self) -> bool {
         self.yes || self.dry_run
@@ -351,6 +990,32 @@ mod tests {
         assert_eq!(cli_quiet.verbosity(), Verbosity::Quiet);
     }
 
+    #[test]
+    fn test_cache_export_parsing() {
+        let cli = Cli::parse_from(["nixboost", "cache", "export", "bundle.tar.zst"]);
+        match cli.command {
+            Some(Commands::Cache { action: CacheAction::Export { path } }) => {
+                assert_eq!(path, PathBuf::from("bundle.tar.zst"));
+            }
+            _ => panic!("expected cache export"),
+        }
+    }
+
+    #[test]
+    fn test_stage_parsing() {
+        let cli = Cli::parse_from(["nixboost", "stage", "-S", "ripgrep", "fd", "-R", "htop"]);
+        match cli.command {
+            Some(Commands::Stage { action: None, install, remove }) => {
+                assert_eq!(install, vec!["ripgrep", "fd"]);
+                assert_eq!(remove, vec!["htop"]);
+            }
+            _ => panic!("expected stage"),
+        }
+
+        let cli = Cli::parse_from(["nixboost", "stage", "apply"]);
+        assert!(matches!(cli.command, Some(Commands::Stage { action: Some(StageAction::Apply), .. })));
+    }
+
     #[test]
     fn test_dry_run() {
         let cli = Cli::parse_from(["nixboost", "-S", "--dry-run", "pkg"]);
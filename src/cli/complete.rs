@@ -0,0 +1,84 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Dynamic shell completion candidates for values that depend on the
+//! system: config keys, generation numbers, cache namespaces and profiles.
+
+use crate::cache::invalidation::CacheKey;
+use crate::core::config::{Config, OperationMode};
+use crate::package::profiles::NamedProfiles;
+use crate::system::generations::{GenerationManager, Profile};
+use crate::utils::time::{format_age, unix_now};
+use clap_complete::engine::CompletionCandidate;
+use std::time::UNIX_EPOCH;
+
+/// Settings accepted by `config set`
+pub fn config_keys() -> Vec<CompletionCandidate> {
+    Config::keys().into_iter().map(CompletionCandidate::new).collect()
+}
+
+/// Generations of the profile `general.mode` selects, newest first
+pub fn generations() -> Vec<CompletionCandidate> {
+    let profile = match Config::load().map(|c| c.general.mode) {
+        Ok(OperationMode::System) => Profile::System,
+        _ => Profile::User,
+    };
+    let now = unix_now();
+
+    GenerationManager::new(profile)
+        .list(50)
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(order, gen)| {
+            let created = gen.created_at.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(now);
+            let age = format!("{} ago", format_age(std::time::Duration::from_secs(now.saturating_sub(created))));
+            let help = if gen.is_current { format!("current, {}", age) } else { age };
+            CompletionCandidate::new(gen.number.to_string())
+                .help(Some(help.into()))
+                .display_order(Some(order))
+        })
+        .collect()
+}
+
+/// Values accepted by `--profile`: the built-in profiles and named ones
+pub fn profiles() -> Vec<CompletionCandidate> {
+    let builtin = ["user", "system", "home-manager"].into_iter().map(CompletionCandidate::new);
+    let named = NamedProfiles::load()
+        .unwrap_or_default()
+        .profiles
+        .into_iter()
+        .map(|p| CompletionCandidate::new(p.name).help(Some("named profile".into())));
+    builtin.chain(named).collect()
+}
+
+/// Namespaces accepted by `cache clear --namespace`
+pub fn cache_namespaces() -> Vec<CompletionCandidate> {
+    CacheKey::NAMESPACES
+        .iter()
+        .map(|(name, about)| CompletionCandidate::new(*name).help(Some((*about).into())))
+        .collect()
+}
+
+/// Value parser for `--namespace`
+pub fn parse_namespace(s: &str) -> Result<String, String> {
+    if CacheKey::NAMESPACES.iter().any(|(name, _)| *name == s) {
+        Ok(s.to_string())
+    } else {
+        let names: Vec<&str> = CacheKey::NAMESPACES.iter().map(|(name, _)| *name).collect();
+        Err(format!("unknown namespace '{}', expected one of: {}", s, names.join(", ")))
+    }
+}
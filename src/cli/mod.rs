@@ -17,5 +17,6 @@
 //! CLI module for NixBoost - argument parsing and command definitions.
 
 pub mod args;
+pub mod complete;
 
 pub use args::{Cli, Commands, VERSION};
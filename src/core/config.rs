@@ -18,6 +18,7 @@
 
 use crate::core::error::{NixBoostError, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::OnceLock;
 use tracing::{debug, info, warn};
@@ -39,6 +40,20 @@ pub struct Config {
     pub network: NetworkConfig,
     /// UI preferences
     pub ui: UiConfig,
+    /// Declared package list
+    pub packages: PackagesConfig,
+    /// Additional package sources
+    pub sources: SourcesConfig,
+    /// Credentials for private substituters
+    pub credentials: CredentialsConfig,
+    /// Generation retention policy
+    pub generations: GenerationsConfig,
+    /// Package groups, installed or removed together as `@name`
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// Settings passed to Nix
+    pub nix: NixConfig,
+    /// `nixboost maintain` pipeline
+    pub maintain: MaintainConfig,
 }
 
 impl Default for Config {
@@ -49,6 +64,13 @@ impl Default for Config {
             cache: CacheConfig::default(),
             network: NetworkConfig::default(),
             ui: UiConfig::default(),
+            packages: PackagesConfig::default(),
+            sources: SourcesConfig::default(),
+            credentials: CredentialsConfig::default(),
+            generations: GenerationsConfig::default(),
+            groups: BTreeMap::new(),
+            nix: NixConfig::default(),
+            maintain: MaintainConfig::default(),
         }
     }
 }
@@ -66,7 +88,42 @@ pub struct GeneralConfig {
     /// Check for updates on startup
     pub check_updates: bool,
     /// Default operation mode: "user" or "system"
-    pub mode: String,
+    pub mode: OperationMode,
+    /// Check free disk space before installs that build locally
+    pub disk_guard: bool,
+    /// Space to reserve per locally built derivation, in MB
+    pub build_space_mb: u64,
+    /// Warn when local builds are likely to run out of memory and offer to
+    /// build with fewer jobs and cores
+    pub memory_guard: bool,
+    /// Whether packages missing from nixpkgs may be installed from NUR
+    pub nur_fallback: NurFallback,
+    /// Flake reference nixpkgs is taken from (e.g. `github:NixOS/nixpkgs/nixos-24.05`)
+    pub channel: String,
+}
+
+/// What installs, removals and generation commands act on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationMode {
+    /// The user's `nix profile`
+    #[default]
+    User,
+    /// The NixOS configuration, applied with `nixos-rebuild`
+    System,
+}
+
+/// Behaviour when an install target is not found in nixpkgs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NurFallback {
+    /// Ask before installing each package from NUR
+    #[default]
+    Ask,
+    /// Never fall back to NUR
+    Never,
+    /// Install from NUR without asking
+    Auto,
 }
 
 impl Default for GeneralConfig {
@@ -76,7 +133,12 @@ impl Default for GeneralConfig {
             debug: false,
             log_file: Some("nixboost.log".to_string()),
             check_updates: true,
-            mode: "user".to_string(),
+            mode: OperationMode::User,
+            disk_guard: true,
+            build_space_mb: 1024,
+            memory_guard: true,
+            nur_fallback: NurFallback::Ask,
+            channel: "nixpkgs".to_string(),
         }
     }
 }
@@ -87,12 +149,18 @@ impl Default for GeneralConfig {
 pub struct SearchConfig {
     /// Maximum number of search results to display
     pub max_results: usize,
+    /// Minimum fuzzy match score for a result to be shown
+    pub min_score: i64,
     /// Enable fuzzy matching
     pub fuzzy: bool,
     /// Fuzzy match threshold (0.0 - 1.0)
     pub fuzzy_threshold: f64,
     /// Include NUR in searches by default
     pub include_nur: bool,
+    /// Rank packages from the install history higher
+    pub history_boost: bool,
+    /// Glob patterns of attribute names always left out of results
+    pub default_excludes: Vec<String>,
     /// Parallel search threads
     pub parallel_threads: usize,
 }
@@ -101,9 +169,12 @@ impl Default for SearchConfig {
     fn default() -> Self {
         Self {
             max_results: 50,
+            min_score: 30,
             fuzzy: true,
             fuzzy_threshold: 0.6,
             include_nur: false,
+            history_boost: true,
+            default_excludes: Vec::new(),
             parallel_threads: 4,
         }
     }
@@ -117,7 +188,7 @@ pub struct CacheConfig {
     pub enabled: bool,
     /// Cache directory (relative to XDG cache dir)
     pub directory: String,
-    /// Maximum cache size in MB
+    /// Maximum size of cached values in MB; 0 disables eviction
     pub max_size_mb: u64,
     /// TTL for package metadata in seconds (default: 1 hour)
     pub package_ttl_secs: u64,
@@ -125,7 +196,7 @@ pub struct CacheConfig {
     pub search_ttl_secs: u64,
     /// TTL for NUR index in seconds (default: 24 hours)
     pub nur_ttl_secs: u64,
-    /// Enable compression (zstd)
+    /// zstd-compress large cache values (e.g. the NUR index) on disk
     pub compression: bool,
     /// In-memory LRU cache size
     pub memory_cache_size: usize,
@@ -194,6 +265,8 @@ pub struct UiConfig {
     pub table_style: String,
     /// Progress bar refresh rate in milliseconds
     pub progress_refresh_ms: u64,
+    /// Colors and symbols for diffs
+    pub theme: ThemeConfig,
 }
 
 impl Default for UiConfig {
@@ -204,10 +277,195 @@ impl Default for UiConfig {
             unicode: true,
             table_style: "unicode".to_string(),
             progress_refresh_ms: 100,
+            theme: ThemeConfig::default(),
+        }
+    }
+}
+
+/// Theme settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Palette: "default" (red/green), "colorblind" (blue/orange) or "monochrome"
+    pub palette: String,
+    /// Prefix for added entries
+    pub added_symbol: String,
+    /// Prefix for removed entries
+    pub removed_symbol: String,
+    /// Prefix for changed entries
+    pub changed_symbol: String,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            palette: "default".to_string(),
+            added_symbol: "+".to_string(),
+            removed_symbol: "-".to_string(),
+            changed_symbol: "~".to_string(),
+        }
+    }
+}
+
+/// Declared package list for `nixboost sync`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackagesConfig {
+    /// Packages that should always be installed
+    pub declared: Vec<String>,
+    /// Remove installed packages that are not declared when syncing
+    pub remove_undeclared: bool,
+    /// Nix file holding the package list edited by `nixboost declare`
+    pub file: Option<PathBuf>,
+    /// Configuration that `file` belongs to
+    pub target: DeclareTarget,
+}
+
+/// Configuration rebuilt after `nixboost declare` edits the package list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeclareTarget {
+    /// `environment.systemPackages`, applied with `nixos-rebuild`
+    #[default]
+    Nixos,
+    /// `home.packages`, applied with `home-manager switch`
+    HomeManager,
+}
+
+impl PackagesConfig {
+    /// Declared packages that are not in `installed`
+    pub fn missing(&self, installed: &[String]) -> Vec<String> {
+        self.declared
+            .iter()
+            .filter(|p| !installed.contains(p))
+            .cloned()
+            .collect()
+    }
+
+    /// Installed packages that are not declared
+    pub fn undeclared(&self, installed: &[String]) -> Vec<String> {
+        installed
+            .iter()
+            .filter(|p| !self.declared.contains(p))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Additional package sources searched alongside nixpkgs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourcesConfig {
+    /// Private flakes by alias, e.g. `work = "git+ssh://git@git.corp/nix/flake"`
+    pub flakes: BTreeMap<String, String>,
+}
+
+impl SourcesConfig {
+    /// Expand an `alias#attr` target to a full flake reference
+    pub fn flake_ref(&self, target: &str) -> Option<String> {
+        let (alias, attr) = target.split_once('#')?;
+        self.flakes.get(alias).map(|url| format!("{}#{}", url, attr))
+    }
+}
+
+/// Settings passed to every Nix command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NixConfig {
+    /// nix.conf settings, e.g. `sandbox = "relaxed"`; `--option` overrides them
+    pub options: BTreeMap<String, toml::Value>,
+}
+
+impl NixConfig {
+    /// `options` as nix.conf values: lists are space-separated
+    pub fn settings(&self) -> BTreeMap<String, String> {
+        fn setting(value: &toml::Value) -> String {
+            match value {
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Array(items) => items.iter().map(setting).collect::<Vec<_>>().join(" "),
+                other => other.to_string(),
+            }
         }
+        self.options.iter().map(|(key, value)| (key.clone(), setting(value))).collect()
     }
 }
 
+/// Credentials for private substituters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CredentialsConfig {
+    /// netrc file (default: Nix's `netrc-file` setting, then `~/.netrc`)
+    pub netrc_file: Option<PathBuf>,
+    /// Bearer tokens by host; `env:NAME` reads the token from `$NAME`
+    pub tokens: BTreeMap<String, String>,
+}
+
+/// Which generations `generation delete`, `system gc` and `maintain` keep.
+/// A generation is kept if any rule retains it; the current one always is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationsConfig {
+    /// Keep the newest N generations
+    pub keep_last: usize,
+    /// Keep generations created in the last N days
+    pub keep_days: u64,
+    /// Keep the newest generation of each of the last N months
+    pub keep_monthly: usize,
+    /// Days deleted generations stay restorable with `generation restore` (0 disables)
+    pub quarantine_days: u64,
+}
+
+impl Default for GenerationsConfig {
+    fn default() -> Self {
+        Self { keep_last: 10, keep_days: 30, keep_monthly: 3, quarantine_days: 7 }
+    }
+}
+
+impl GenerationsConfig {
+    /// Keep only the newest `n` generations (`--keep`)
+    pub fn keep_last(n: usize) -> Self {
+        Self { keep_last: n, keep_days: 0, keep_monthly: 0, ..Self::default() }
+    }
+}
+
+/// Steps run by `nixboost maintain`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintainConfig {
+    /// Steps to run, in order
+    pub steps: Vec<MaintainStep>,
+}
+
+impl Default for MaintainConfig {
+    fn default() -> Self {
+        Self {
+            steps: vec![
+                MaintainStep::CachePrune,
+                MaintainStep::Generations,
+                MaintainStep::Gc,
+                MaintainStep::Optimise,
+                MaintainStep::Index,
+            ],
+        }
+    }
+}
+
+/// One step of the `nixboost maintain` pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum MaintainStep {
+    /// Remove expired cache entries
+    CachePrune,
+    /// Delete generations the `[generations]` policy does not keep
+    Generations,
+    /// Collect unreferenced store paths
+    Gc,
+    /// Hard-link identical files in the store
+    Optimise,
+    /// Rebuild the local nixpkgs index
+    Index,
+}
+
 impl Config {
     /// Get the configuration directory path
     pub fn config_dir() -> PathBuf {
@@ -223,9 +481,10 @@ impl Config {
 
     /// Get the cache directory path
     pub fn cache_dir() -> PathBuf {
+        let directory = Self::try_get().map(|c| c.cache.directory.as_str()).unwrap_or("nixboost");
         dirs::cache_dir()
             .unwrap_or_else(|| PathBuf::from(".cache"))
-            .join("nixboost")
+            .join(directory)
     }
 
     /// Get the data directory path
@@ -279,10 +538,62 @@ impl Config {
         Ok(())
     }
 
+    /// Dotted names of the settings, e.g. `search.max_results`
+    pub fn keys() -> Vec<String> {
+        let mut keys = Vec::new();
+        if let Ok(toml::Value::Table(table)) = toml::Value::try_from(Config::default()) {
+            collect_keys(&table, "", &mut keys);
+        }
+        keys
+    }
+
+    /// Set the dotted `key` to `value`, read as TOML unless the setting is a
+    /// string (so `nixboost config set general.channel nixpkgs` needs no quotes)
+    pub fn set_key(&mut self, key: &str, value: &str) -> Result<()> {
+        let unknown = || NixBoostError::Config(format!("Unknown setting '{}'", key));
+        let invalid = |e: &dyn std::fmt::Display| NixBoostError::Config(format!("Invalid value for {}: {}", key, e));
+
+        let mut doc = toml::Value::try_from(&*self).map_err(|e| invalid(&e))?;
+        let (parents, field) = match key.rsplit_once('.') {
+            Some((parents, field)) => (parents, field),
+            None => return Err(unknown()),
+        };
+        let mut table = doc.as_table_mut().ok_or_else(unknown)?;
+        for part in parents.split('.') {
+            table = table.get_mut(part).and_then(|v| v.as_table_mut()).ok_or_else(unknown)?;
+        }
+
+        let parsed = match table.get(field) {
+            Some(toml::Value::String(_)) => toml::Value::String(value.to_string()),
+            _ => toml::from_str::<toml::Table>(&format!("v = {}", value))
+                .ok()
+                .and_then(|mut t| t.remove("v"))
+                .unwrap_or_else(|| toml::Value::String(value.to_string())),
+        };
+        table.insert(field.to_string(), parsed);
+
+        let updated: Config = doc.try_into().map_err(|e| invalid(&e))?;
+        // Unknown fields are dropped by serde(default), so they do not survive a round trip
+        let kept = toml::Value::try_from(&updated).ok().and_then(|v| {
+            key.split('.').try_fold(v, |v, part| v.get(part).cloned())
+        });
+        if kept.is_none() {
+            return Err(unknown());
+        }
+
+        *self = updated;
+        Ok(())
+    }
+
     /// Initialize global configuration
     pub fn init() -> Result<&'static Config> {
-        let config = Self::load()?;
-        Ok(CONFIG.get_or_init(|| config))
+        Ok(Self::init_with(Self::load()?))
+    }
+
+    /// Make `config` the global configuration. Only the first call takes
+    /// effect; later ones return the configuration already set.
+    pub fn init_with(config: Config) -> &'static Config {
+        CONFIG.get_or_init(|| config)
     }
 
     /// Get global configuration (panics if not initialized)
@@ -295,6 +606,37 @@ impl Config {
         CONFIG.get()
     }
 
+    /// Replace `@name` targets with the members of group `name`, in order
+    /// and without duplicates. Groups may include other groups.
+    pub fn expand_groups(&self, targets: &[String]) -> Result<Vec<String>> {
+        fn expand(config: &Config, target: &str, stack: &mut Vec<String>, out: &mut Vec<String>) -> Result<()> {
+            let Some(name) = target.strip_prefix('@') else {
+                if !out.iter().any(|t| t == target) {
+                    out.push(target.to_string());
+                }
+                return Ok(());
+            };
+            if stack.iter().any(|g| g == name) {
+                return Err(NixBoostError::Config(format!("group '@{}' includes itself", name)));
+            }
+            let members = config.groups.get(name).ok_or_else(|| {
+                NixBoostError::Config(format!("Unknown group '@{}'; define it under [groups] in config.toml", name))
+            })?;
+            stack.push(name.to_string());
+            for member in members {
+                expand(config, member, stack, out)?;
+            }
+            stack.pop();
+            Ok(())
+        }
+
+        let mut out = Vec::new();
+        for target in targets {
+            expand(self, target, &mut Vec::new(), &mut out)?;
+        }
+        Ok(out)
+    }
+
     /// Apply environment variable overrides
     pub fn with_env_overrides(mut self) -> Self {
         // NIXBOOST_VERBOSE
@@ -339,6 +681,16 @@ pub fn generate_default_config() -> String {
     toml::to_string_pretty(&config).unwrap_or_else(|_| String::from("# Failed to generate config"))
 }
 
+fn collect_keys(table: &toml::Table, prefix: &str, keys: &mut Vec<String>) {
+    for (name, value) in table {
+        let key = format!("{}{}", prefix, name);
+        match value {
+            toml::Value::Table(inner) if !inner.is_empty() => collect_keys(inner, &format!("{}.", key), keys),
+            _ => keys.push(key),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,6 +720,100 @@ mod tests {
         assert!(cache_dir.to_string_lossy().contains("nixboost"));
     }
 
+    #[test]
+    fn test_declared_packages() {
+        let config: Config = toml::from_str(r#"
+            [packages]
+            declared = ["git", "ripgrep"]
+        "#).unwrap();
+        assert!(!config.packages.remove_undeclared);
+
+        let installed = vec!["git".to_string(), "htop".to_string()];
+        assert_eq!(config.packages.missing(&installed), vec!["ripgrep"]);
+        assert_eq!(config.packages.undeclared(&installed), vec!["htop"]);
+    }
+
+    #[test]
+    fn test_private_flakes() {
+        let config: Config = toml::from_str(r#"
+            [sources.flakes]
+            work = "git+ssh://git@git.corp/nix/flake"
+        "#).unwrap();
+        assert_eq!(
+            config.sources.flake_ref("work#deploy-tool").as_deref(),
+            Some("git+ssh://git@git.corp/nix/flake#deploy-tool")
+        );
+        assert_eq!(config.sources.flake_ref("nixpkgs#hello"), None);
+        assert_eq!(config.sources.flake_ref("work"), None);
+    }
+
+    #[test]
+    fn test_nix_options() {
+        let config: Config = toml::from_str(r#"
+            [nix.options]
+            max-jobs = 4
+            sandbox = "relaxed"
+            extra-experimental-features = ["nix-command", "flakes"]
+        "#).unwrap();
+        let settings = config.nix.settings();
+        assert_eq!(settings["max-jobs"], "4");
+        assert_eq!(settings["sandbox"], "relaxed");
+        assert_eq!(settings["extra-experimental-features"], "nix-command flakes");
+    }
+
+    #[test]
+    fn test_expand_groups() {
+        let config: Config = toml::from_str(r#"
+            [groups]
+            dev = ["git", "ripgrep", "fd"]
+            full = ["@dev", "htop", "git"]
+            loop = ["@loop"]
+        "#).unwrap();
+        let targets = |t: &[&str]| t.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(config.expand_groups(&targets(&["@full", "jq"])).unwrap(), targets(&["git", "ripgrep", "fd", "htop", "jq"]));
+        assert_eq!(config.expand_groups(&targets(&["hello"])).unwrap(), targets(&["hello"]));
+        assert!(config.expand_groups(&targets(&["@missing"])).is_err());
+        assert!(config.expand_groups(&targets(&["@loop"])).is_err());
+    }
+
+    #[test]
+    fn test_nur_fallback() {
+        assert_eq!(Config::default().general.nur_fallback, NurFallback::Ask);
+
+        let config: Config = toml::from_str(r#"
+            [general]
+            nur_fallback = "never"
+        "#).unwrap();
+        assert_eq!(config.general.nur_fallback, NurFallback::Never);
+    }
+
+    #[test]
+    fn test_operation_mode() {
+        assert_eq!(Config::default().general.mode, OperationMode::User);
+
+        let config: Config = toml::from_str(r#"
+            [general]
+            mode = "system"
+        "#).unwrap();
+        assert_eq!(config.general.mode, OperationMode::System);
+    }
+
+    #[test]
+    fn test_set_key() {
+        let mut config = Config::default();
+        config.set_key("search.max_results", "25").unwrap();
+        config.set_key("general.channel", "github:NixOS/nixpkgs/nixos-24.05").unwrap();
+        config.set_key("general.mode", "system").unwrap();
+        assert_eq!(config.search.max_results, 25);
+        assert_eq!(config.general.channel, "github:NixOS/nixpkgs/nixos-24.05");
+        assert_eq!(config.general.mode, OperationMode::System);
+
+        assert!(config.set_key("search.max_results", "many").is_err());
+        assert!(config.set_key("search.typo", "1").is_err());
+        assert!(Config::keys().contains(&"cache.memory_cache_size".to_string()));
+    }
+
     #[test]
     fn test_generate_default_config() {
         let content = generate_default_config();
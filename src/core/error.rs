@@ -95,6 +95,12 @@ pub enum PackageError {
 
     #[error("Transaction failed: {0}")]
     TransactionFailed(String),
+
+    #[error("'{query}' matches several installed packages: {candidates}")]
+    Ambiguous { query: String, candidates: String },
+
+    #[error("Profile '{name}': {reason}")]
+    Profile { name: String, reason: String },
 }
 
 /// Network-related errors
@@ -168,6 +174,9 @@ pub enum SystemError {
     #[error("Generation not found: {generation}")]
     GenerationNotFound { generation: u64 },
 
+    #[error("Generation {generation} was garbage collected and cannot be restored")]
+    GenerationCollected { generation: u64 },
+
     #[error("Rollback failed: {0}")]
     RollbackFailed(String),
 
@@ -177,8 +186,14 @@ pub enum SystemError {
     #[error("Garbage collection failed: {0}")]
     GarbageCollectionFailed(String),
 
+    #[error("Maintenance steps failed: {0}")]
+    MaintenanceFailed(String),
+
     #[error("Architecture detection failed")]
     ArchDetectionFailed,
+
+    #[error("Insufficient disk space on {path}: need {required}, {available} available")]
+    InsufficientDiskSpace { path: String, required: String, available: String },
 }
 
 /// Search-related errors
@@ -217,6 +232,12 @@ pub enum NurError {
 
     #[error("NUR index update failed: {0}")]
     IndexUpdateFailed(String),
+
+    #[error("NUR index is {age} old and could not be refreshed: {reason}")]
+    IndexStale { age: String, reason: String },
+
+    #[error("NUR index download cancelled")]
+    DownloadCancelled,
 }
 
 impl NixBoostError {
@@ -248,6 +269,12 @@ impl NixBoostError {
             NixBoostError::Package(PackageError::AlreadyInstalled { .. }) => {
                 Some("The package is already installed. Use 'nixboost -l' to list installed packages")
             }
+            NixBoostError::Package(PackageError::VersionMismatch(_)) => {
+                Some("Relax the constraint or list versions with 'nixboost -Ss <name>'")
+            }
+            NixBoostError::Package(PackageError::Ambiguous { .. }) => {
+                Some("Use the full element name or attribute path shown by 'nixboost -l'")
+            }
             NixBoostError::Network(NetworkError::Timeout { .. }) => {
                 Some("Check your internet connection or increase timeout in config")
             }
@@ -263,13 +290,94 @@ impl NixBoostError {
             NixBoostError::System(SystemError::PermissionDenied(_)) => {
                 Some("Try running with sudo or check file permissions")
             }
+            NixBoostError::System(SystemError::InsufficientDiskSpace { .. }) => {
+                Some("Free space with 'nixboost --clean', or point TMPDIR at a larger disk")
+            }
+            NixBoostError::Search(SearchError::IndexNotAvailable) => {
+                Some("Build the local package index with 'nixboost index update'")
+            }
             NixBoostError::Nur(NurError::PackageNotFound { .. }) => {
                 Some("Search NUR packages with 'nixboost -A <query>'")
             }
+            NixBoostError::Nur(NurError::IndexStale { .. }) => {
+                Some("Check your connection; 'nixboost nur status' shows when the index was last fetched")
+            }
             _ => None,
         }
     }
 
+    /// Process exit code for this error's category, so scripts can tell
+    /// failures apart without parsing messages:
+    ///
+    /// | code | meaning |
+    /// |------|---------|
+    /// | 1  | other error |
+    /// | 2  | invalid command line (from clap) |
+    /// | 3  | configuration error |
+    /// | 4  | package operation failed |
+    /// | 5  | package not found or not installed |
+    /// | 6  | network failure |
+    /// | 7  | permission denied |
+    /// | 8  | Nix command or system failure |
+    /// | 9  | cache error |
+    /// | 10 | search error |
+    /// | 11 | NUR error |
+    /// | 12 | I/O or serialization error |
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            NixBoostError::Config(_) => 3,
+            NixBoostError::Package(PackageError::NotFound { .. } | PackageError::NotInstalled { .. })
+            | NixBoostError::Nur(NurError::PackageNotFound { .. }) => 5,
+            NixBoostError::Package(_) => 4,
+            NixBoostError::Network(_) | NixBoostError::Nur(NurError::IndexUpdateFailed(_)) => 6,
+            NixBoostError::System(SystemError::PermissionDenied(_)) => 7,
+            NixBoostError::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied => 7,
+            NixBoostError::System(_) => 8,
+            NixBoostError::Cache(_) => 9,
+            NixBoostError::Search(_) => 10,
+            NixBoostError::Nur(_) => 11,
+            NixBoostError::Io(_) | NixBoostError::Serialization(_) => 12,
+            NixBoostError::Other(_) => 1,
+        }
+    }
+
+    /// Recover the typed error from an `anyhow::Error`, whether it was built
+    /// from a `NixBoostError` or from one of the category enums directly
+    pub fn from_anyhow(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<NixBoostError>() {
+            Ok(e) => return e,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<PackageError>() {
+            Ok(e) => return e.into(),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<NetworkError>() {
+            Ok(e) => return e.into(),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<SystemError>() {
+            Ok(e) => return e.into(),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<CacheError>() {
+            Ok(e) => return e.into(),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<SearchError>() {
+            Ok(e) => return e.into(),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<NurError>() {
+            Ok(e) => return e.into(),
+            Err(err) => err,
+        };
+        match err.downcast::<std::io::Error>() {
+            Ok(e) => e.into(),
+            Err(err) => NixBoostError::Other(err),
+        }
+    }
+
     /// Check if this error is retryable
     pub fn is_retryable(&self) -> bool {
         matches!(
@@ -356,6 +464,20 @@ mod tests {
         assert!(!not_found_err.is_retryable());
     }
 
+    #[test]
+    fn test_exit_codes() {
+        let not_found: anyhow::Error = PackageError::NotFound { name: "test".to_string() }.into();
+        assert_eq!(NixBoostError::from_anyhow(not_found).exit_code(), 5);
+
+        let network: anyhow::Error = NixBoostError::Network(NetworkError::AllMirrorsFailed).into();
+        assert_eq!(NixBoostError::from_anyhow(network).exit_code(), 6);
+
+        let denied: anyhow::Error = std::io::Error::from(std::io::ErrorKind::PermissionDenied).into();
+        assert_eq!(NixBoostError::from_anyhow(denied).exit_code(), 7);
+
+        assert_eq!(NixBoostError::from_anyhow(anyhow::anyhow!("boom")).exit_code(), 1);
+    }
+
     #[test]
     fn test_error_display() {
         let err = PackageError::NotFound { name: "firefox".to_string() };
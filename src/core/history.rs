@@ -0,0 +1,240 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Operation history (`nixboost history ops`).
+//!
+//! Every install, remove, garbage collection and rollback is recorded as an
+//! [`OperationResult`] in `history.db` in the data directory. It is kept out
+//! of the cache database so clearing the cache does not lose it.
+
+use crate::core::config::Config;
+use crate::core::error::{CacheError, Result};
+use crate::core::types::{OperationResult, OperationStatus, OperationType};
+use crate::utils::time::unix_now;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+use tracing::warn;
+
+/// A recorded operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationRecord {
+    pub id: i64,
+    /// Unix timestamp of completion
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub result: OperationResult,
+}
+
+/// Which records to return, newest first
+#[derive(Debug, Clone, Default)]
+pub struct OperationFilter {
+    pub operation: Option<OperationType>,
+    pub status: Option<OperationStatus>,
+    /// Records involving this package, by name or as the attribute of an
+    /// installable (`nixpkgs#git`)
+    pub package: Option<String>,
+    /// Unix timestamp; older records are skipped
+    pub since: Option<u64>,
+    pub limit: usize,
+}
+
+/// SQLite-backed operation log
+pub struct OperationLog {
+    conn: Connection,
+}
+
+impl OperationLog {
+    /// Log in the data directory
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Config::data_dir().join("history.db"))
+    }
+
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| CacheError::InitFailed(e.to_string()))?;
+        }
+
+        let conn = Connection::open(path).map_err(|e| CacheError::InitFailed(e.to_string()))?;
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS operations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                operation TEXT NOT NULL,
+                packages TEXT NOT NULL,
+                status TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                error TEXT,
+                message TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_operations_timestamp ON operations(timestamp);
+            "
+        ).map_err(|e| CacheError::InitFailed(e.to_string()))?;
+
+        Ok(Self { conn })
+    }
+
+    pub fn append(&self, result: &OperationResult) -> Result<()> {
+        let packages = serde_json::to_string(&result.packages).unwrap_or_else(|_| "[]".to_string());
+        self.conn.execute(
+            "INSERT INTO operations (timestamp, operation, packages, status, duration_ms, error, message)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                unix_now() as i64,
+                result.operation.to_string(),
+                packages,
+                result.status.to_string(),
+                result.duration_ms as i64,
+                result.error,
+                result.message,
+            ],
+        ).map_err(|e| CacheError::WriteError(e.to_string()))?;
+        Ok(())
+    }
+
+    pub fn query(&self, filter: &OperationFilter) -> Result<Vec<OperationRecord>> {
+        let read = |e: rusqlite::Error| CacheError::ReadError(e.to_string());
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, operation, packages, status, duration_ms, error, message
+             FROM operations
+             WHERE (?1 IS NULL OR operation = ?1)
+               AND (?2 IS NULL OR status = ?2)
+               AND (?3 IS NULL OR EXISTS (
+                   SELECT 1 FROM json_each(packages) WHERE value = ?3 OR value LIKE '%#' || ?3
+               ))
+               AND (?4 IS NULL OR timestamp >= ?4)
+             ORDER BY timestamp DESC, id DESC
+             LIMIT ?5"
+        ).map_err(read)?;
+
+        let rows = stmt.query_map(
+            params![
+                filter.operation.map(|o| o.to_string()),
+                filter.status.map(|s| s.to_string()),
+                filter.package,
+                filter.since.map(|t| t as i64),
+                filter.limit as i64,
+            ],
+            |row| {
+                let operation: String = row.get(2)?;
+                let packages: String = row.get(3)?;
+                let status: String = row.get(4)?;
+                Ok(OperationRecord {
+                    id: row.get(0)?,
+                    timestamp: row.get::<_, i64>(1)? as u64,
+                    result: OperationResult {
+                        operation: operation.parse().unwrap_or(OperationType::Install),
+                        packages: serde_json::from_str(&packages).unwrap_or_default(),
+                        status: status.parse().unwrap_or(OperationStatus::Failed),
+                        duration_ms: row.get::<_, i64>(5)? as u64,
+                        error: row.get(6)?,
+                        message: row.get(7)?,
+                        rollback_to: None,
+                    },
+                })
+            },
+        ).map_err(read)?;
+
+        let records = rows.collect::<rusqlite::Result<Vec<_>>>().map_err(read)?;
+        Ok(records)
+    }
+
+    /// How often each package was successfully installed, keyed by
+    /// lowercase name
+    pub fn install_counts(&self) -> Result<HashMap<String, u32>> {
+        let read = |e: rusqlite::Error| CacheError::ReadError(e.to_string());
+
+        let mut stmt = self.conn.prepare(
+            "SELECT packages FROM operations WHERE operation = ?1 AND status = ?2"
+        ).map_err(read)?;
+        let rows = stmt.query_map(
+            params![OperationType::Install.to_string(), OperationStatus::Success.to_string()],
+            |row| row.get::<_, String>(0),
+        ).map_err(read)?;
+
+        let mut counts = HashMap::new();
+        for packages in rows {
+            let packages: Vec<String> = serde_json::from_str(&packages.map_err(read)?).unwrap_or_default();
+            for pkg in packages {
+                *counts.entry(pkg.to_lowercase()).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+}
+
+/// Record the outcome of an operation started at `start`. A log that cannot
+/// be written only produces a warning.
+pub fn record<T>(operation: OperationType, packages: &[String], start: Instant, outcome: &Result<T>) {
+    let result = OperationResult::from_outcome(operation, packages.to_vec(), start, outcome);
+    if let Err(e) = OperationLog::open_default().and_then(|log| log.append(&result)) {
+        warn!("Failed to record {} in operation history: {}", operation, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_log_filter() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = OperationLog::open(&dir.path().join("history.db")).unwrap();
+
+        let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        log.append(&OperationResult::success(OperationType::Install, names(&["ripgrep", "fd"]), 1200)).unwrap();
+        log.append(&OperationResult::failure(OperationType::Install, names(&["broken"]), "build failed")).unwrap();
+        log.append(&OperationResult::success(OperationType::GarbageCollect, vec![], 5000)).unwrap();
+
+        let all = log.query(&OperationFilter { limit: 10, ..Default::default() }).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].result.operation, OperationType::GarbageCollect);
+
+        let failed = log.query(&OperationFilter {
+            status: Some(OperationStatus::Failed),
+            limit: 10,
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].result.error.as_deref(), Some("build failed"));
+
+        let fd = log.query(&OperationFilter {
+            operation: Some(OperationType::Install),
+            package: Some("fd".to_string()),
+            limit: 10,
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(fd[0].result.packages, names(&["ripgrep", "fd"]));
+
+        // Whole names only: `f` must not match `fd`
+        let f = log.query(&OperationFilter { package: Some("f".to_string()), limit: 10, ..Default::default() }).unwrap();
+        assert!(f.is_empty());
+        log.append(&OperationResult::success(OperationType::Install, names(&["nixpkgs#ripgrep"]), 900)).unwrap();
+        let rg = log.query(&OperationFilter { package: Some("ripgrep".to_string()), limit: 10, ..Default::default() }).unwrap();
+        assert_eq!(rg.len(), 2);
+
+        log.append(&OperationResult::success(OperationType::Install, names(&["fd"]), 800)).unwrap();
+        let counts = log.install_counts().unwrap();
+        assert_eq!(counts.get("fd"), Some(&2));
+        assert_eq!(counts.get("ripgrep"), Some(&1));
+        assert!(!counts.contains_key("broken"));
+    }
+}
@@ -18,6 +18,7 @@
 
 pub mod config;
 pub mod error;
+pub mod history;
 pub mod types;
 
 pub use config::Config;
@@ -49,6 +49,9 @@ pub struct Package {
     /// Platforms supported
     #[serde(default)]
     pub platforms: Vec<String>,
+    /// nixpkgs alias attribute (absent with `config.allowAliases = false`)
+    #[serde(default)]
+    pub alias: bool,
 }
 
 impl Package {
@@ -64,6 +67,7 @@ impl Package {
             license: None,
             maintainers: Vec::new(),
             platforms: Vec::new(),
+            alias: false,
         }
     }
 
@@ -87,16 +91,24 @@ impl Package {
             PackageSource::Nixpkgs => format!("nix profile install nixpkgs#{}", self.name),
             PackageSource::Nur { repo } => format!("nix profile install github:nix-community/NUR#repos.{}.{}", repo, self.name),
             PackageSource::Flake { url } => format!("nix profile install {}#{}", url, self.name),
+            PackageSource::Private { url, .. } => format!("nix profile install {}#{}", url, self.name),
             PackageSource::Unknown => format!("nix profile install {}", self.name),
         }
     }
 
+    /// Top-level attribute set this package lives in (e.g. `python3Packages`),
+    /// or `None` for top-level attributes
+    pub fn namespace(&self) -> Option<&str> {
+        self.name.split_once('.').map(|(ns, _)| ns)
+    }
+
     /// Get display name with source prefix
     pub fn display_name(&self) -> String {
         match &self.source {
             PackageSource::Nixpkgs => format!("nixpkgs/{}", self.name),
             PackageSource::Nur { repo } => format!("nur/{}/{}", repo, self.name),
             PackageSource::Flake { url } => format!("{}#{}", url, self.name),
+            PackageSource::Private { name, .. } => format!("{}#{}", name, self.name),
             PackageSource::Unknown => self.name.clone(),
         }
     }
@@ -134,6 +146,8 @@ pub enum PackageSource {
     Nur { repo: String },
     /// A flake
     Flake { url: String },
+    /// A private flake configured under `[sources.flakes]`
+    Private { name: String, url: String },
     /// Unknown source
     Unknown,
 }
@@ -144,6 +158,7 @@ impl fmt::Display for PackageSource {
             PackageSource::Nixpkgs => write!(f, "nixpkgs"),
             PackageSource::Nur { repo } => write!(f, "nur:{}", repo),
             PackageSource::Flake { url } => write!(f, "flake:{}", url),
+            PackageSource::Private { name, .. } => write!(f, "{}", name),
             PackageSource::Unknown => write!(f, "unknown"),
         }
     }
@@ -171,11 +186,13 @@ pub struct SearchResult {
     pub score: f64,
     /// Match type
     pub match_type: MatchType,
+    /// Other sources providing a package of the same name
+    pub alternatives: Vec<PackageSource>,
 }
 
 impl SearchResult {
     pub fn new(package: Package, score: f64, match_type: MatchType) -> Self {
-        Self { package, score, match_type }
+        Self { package, score, match_type, alternatives: Vec::new() }
     }
 }
 
@@ -229,7 +246,8 @@ impl MatchType {
 }
 
 /// Operation status for progress tracking
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OperationStatus {
     Pending,
     Running,
@@ -252,13 +270,31 @@ impl fmt::Display for OperationStatus {
     }
 }
 
+impl std::str::FromStr for OperationStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(OperationStatus::Pending),
+            "running" => Ok(OperationStatus::Running),
+            "success" => Ok(OperationStatus::Success),
+            "failed" => Ok(OperationStatus::Failed),
+            "skipped" => Ok(OperationStatus::Skipped),
+            "cancelled" => Ok(OperationStatus::Cancelled),
+            _ => Err(format!("unknown status '{}'", s)),
+        }
+    }
+}
+
 /// Operation type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OperationType {
     Install,
     Remove,
     Update,
     Search,
+    #[serde(rename = "gc")]
     GarbageCollect,
     Rollback,
 }
@@ -276,8 +312,24 @@ impl fmt::Display for OperationType {
     }
 }
 
+impl std::str::FromStr for OperationType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "install" => Ok(OperationType::Install),
+            "remove" => Ok(OperationType::Remove),
+            "update" => Ok(OperationType::Update),
+            "search" => Ok(OperationType::Search),
+            "gc" => Ok(OperationType::GarbageCollect),
+            "rollback" => Ok(OperationType::Rollback),
+            _ => Err(format!("unknown operation '{}'", s)),
+        }
+    }
+}
+
 /// Result of a package operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OperationResult {
     /// Operation type
     pub operation: OperationType,
@@ -288,9 +340,14 @@ pub struct OperationResult {
     /// Duration in milliseconds
     pub duration_ms: u64,
     /// Error message if failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     /// Detailed message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// Profile generation to restore after a partially applied transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollback_to: Option<u64>,
 }
 
 impl OperationResult {
@@ -302,6 +359,7 @@ impl OperationResult {
             duration_ms,
             error: None,
             message: None,
+            rollback_to: None,
         }
     }
 
@@ -313,8 +371,24 @@ impl OperationResult {
             duration_ms: 0,
             error: Some(error.into()),
             message: None,
+            rollback_to: None,
         }
     }
+
+    /// Result of an operation started at `start` that ended with `outcome`
+    pub fn from_outcome<T, E: fmt::Display>(
+        operation: OperationType,
+        packages: Vec<String>,
+        start: std::time::Instant,
+        outcome: &std::result::Result<T, E>,
+    ) -> Self {
+        let mut result = match outcome {
+            Ok(_) => Self::success(operation, packages, 0),
+            Err(e) => Self::failure(operation, packages, e.to_string()),
+        };
+        result.duration_ms = start.elapsed().as_millis() as u64;
+        result
+    }
 }
 
 /// Nix generation info
@@ -328,10 +402,16 @@ pub struct Generation {
     pub is_current: bool,
     /// Path to the generation
     pub path: String,
+    /// Closure size in bytes, when computed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closure_size: Option<u64>,
+    /// Closure size change from the previous generation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_delta: Option<i64>,
 }
 
 /// Garbage collection preview
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GCPreview {
     /// Paths that would be deleted
     pub paths: Vec<String>,
@@ -339,6 +419,15 @@ pub struct GCPreview {
     pub size_bytes: u64,
     /// Generations that would be affected
     pub affected_generations: Vec<u64>,
+    /// Largest paths with their NAR size, largest first
+    #[serde(default)]
+    pub largest: Vec<(String, u64)>,
+    /// Change in `size_bytes` since the previous preview
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_bytes: Option<i64>,
+    /// Unix timestamp of the previous preview
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_at: Option<u64>,
 }
 
 impl GCPreview {
@@ -392,10 +481,17 @@ mod tests {
             paths: vec![],
             size_bytes: 1024 * 1024 * 512, // 512 MB
             affected_generations: vec![],
+            ..Default::default()
         };
         assert!(preview.size_human().contains("MB"));
     }
 
+    #[test]
+    fn test_package_namespace() {
+        assert_eq!(Package::new("python3Packages.requests", "2.31", "").namespace(), Some("python3Packages"));
+        assert_eq!(Package::new("firefox", "120.0", "").namespace(), None);
+    }
+
     #[test]
     fn test_package_display_name() {
         let pkg = Package::from_nixpkgs("firefox", "120.0", "Browser");
@@ -404,4 +500,11 @@ mod tests {
         let nur_pkg = Package::from_nur("pkg", "1.0", "Desc", "user");
         assert_eq!(nur_pkg.display_name(), "nur/user/pkg");
     }
+
+    #[test]
+    fn test_operation_type_names_match() {
+        let gc = OperationType::GarbageCollect;
+        assert_eq!(serde_json::to_string(&gc).unwrap(), format!("\"{}\"", gc));
+        assert_eq!("gc".parse::<OperationType>(), Ok(gc));
+    }
 }
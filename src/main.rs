@@ -28,7 +28,9 @@ use anyhow::Result;
 use clap::Parser;
 use console::style;
 use dialoguer::{Confirm, theme::ColorfulTheme};
-use std::time::Duration;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, warn, Level};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
@@ -45,106 +47,228 @@ mod ui;
 mod utils;
 
 use cli::{Cli, Commands, VERSION};
-use cli::args::OutputFormat;
-use core::config::Config;
+use cli::args::{InstallSource, OutputFormat, SearchFormat};
+use core::config::{Config, GenerationsConfig, MaintainStep, NurFallback};
+use core::types::{OperationResult, OperationStatus, OperationType};
 use package::PackageManager;
+use package::profile::{resolve_element, ElementMatch, ProfileElement};
 use nur::NurClient;
-use system::{HealthChecker, GarbageCollector, GenerationManager};
+use system::{HealthChecker, GarbageCollector, GenerationManager, NixDaemon};
+use system::garbage_collector::GcOptions;
+use system::generations::Profile;
+use utils::command::Echo;
+use system::health::HealthMode;
 use ui::output::Output;
-use ui::progress;
+use ui::ProgressManager;
 use utils::{check_for_updates, perform_update, fetch_nixos_news};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
+    if let Err(e) = try_main().await {
+        let err = core::error::NixBoostError::from_anyhow(e);
+        let code = format!("error[{}]:", err.code());
+        eprintln!("{} {}", style(code).red().bold().for_stderr(), err);
+        if let Some(suggestion) = err.suggestion() {
+            eprintln!("  {} {}", style("hint:").cyan().for_stderr(), suggestion);
+        }
+        std::process::exit(err.exit_code());
+    }
+}
+
+async fn try_main() -> Result<()> {
+    // Answers completion requests from the scripts `completions` installs
+    clap_complete::CompleteEnv::with_factory(<Cli as clap::CommandFactory>::command).complete();
+
     // Parse CLI arguments
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
 
     // Initialize logging
     init_logging(&cli);
+    utils::command::set_show_commands(cli.show_commands);
 
-    // Initialize configuration
-    let config = match Config::load() {
+    // Initialize configuration; modules read their settings from the global
+    let config = Config::init_with(match Config::load() {
         Ok(c) => c.with_env_overrides(),
         Err(e) => {
             warn!("Failed to load config, using defaults: {}", e);
             Config::default()
         }
-    };
+    });
+
+    utils::command::set_nix_options(&cli.nix_options(&config.nix.settings()));
 
     // Initialize output formatter
     let output = Output::new(cli.output)
-        .no_colors(!config.ui.colors || cli.output == OutputFormat::Plain);
+        .no_colors(!config.ui.colors || cli.output == OutputFormat::Plain)
+        .theme(ui::theme::DiffTheme::from_config(&config.ui.theme))
+        .porcelain(cli.porcelain)
+        .with_progress(ProgressManager::from_config(&config.ui).enabled(!cli.quiet));
+
+    // `@name` install and remove targets stand for the packages of a group
+    if cli.command.is_none() && !cli.search && cli.targets.iter().any(|t| t.starts_with('@')) {
+        cli.targets = expand_groups(&cli, config, &output)?;
+    }
+
+    // Checked in the background; a newer release is reported once the
+    // command has finished
+    let update_check = (config.general.check_updates && !cli.no_update_check && !cli.quiet).then(|| {
+        let http = network::HttpClient::from_config(config).quiet(true);
+        tokio::spawn(async move { check_for_updates(&http, VERSION).await })
+    });
+
+    let result = run(&cli, config, &output).await;
+
+    if let Some(check) = update_check {
+        // A slow check must not hold up exit; the next run tries again
+        if let Ok(Ok(info)) = tokio::time::timeout(Duration::from_millis(500), check).await {
+            // The command's own outcome is what the caller needs to see
+            if let Err(e) = prompt_update(&cli, info) {
+                warn!("Update prompt failed: {}", e);
+            }
+        }
+    }
+
+    result
+}
+
+/// Warn about environment that changes how packages resolve; with
+/// `--verbose`, also show what the system and `nixpkgs` resolve to
+async fn show_resolution_context(manager: &PackageManager, cli: &Cli, output: &Output) {
+    use package::resolution;
+
+    if cli.quiet {
+        return;
+    }
+    for impurity in resolution::detect() {
+        output.warn(&format!("{} {}", impurity.source, impurity.effect));
+    }
+    if !cli.verbose {
+        return;
+    }
+
+    let nixpkgs = package::revision::lock_flake("nixpkgs").await;
+    let registry = resolution::user_registry()
+        .and_then(|path| resolution::registry_override(&path))
+        .or_else(|| resolution::registry_override(std::path::Path::new(resolution::SYSTEM_REGISTRY)));
+    let nix_config = utils::command::nix_config_env()
+        .map(String::from)
+        .or_else(|| std::env::var("NIX_CONFIG").ok())
+        .map(|config| config.lines().collect::<Vec<_>>().join("; "));
+
+    output.info("Resolution context:");
+    output.info(&format!("  system:     {}", manager.query_system()));
+    output.info(&format!("  nixpkgs:    {}", nixpkgs.map(|n| n.url).unwrap_or_else(|| "unresolved".to_string())));
+    output.info(&format!(
+        "  registry:   {}",
+        registry.map(|r| format!("{} ({})", r.target, r.registry.display())).unwrap_or_else(|| "global".to_string())
+    ));
+    output.info(&format!("  NIX_PATH:   {}", std::env::var("NIX_PATH").unwrap_or_else(|_| "(unset)".to_string())));
+    output.info(&format!("  NIX_CONFIG: {}", nix_config.unwrap_or_else(|| "(unset)".to_string())));
+}
 
-    // Check for updates (unless skipped)
-    if config.general.check_updates && !cli.no_update_check && !cli.quiet {
-        check_and_prompt_update(&cli)?;
+/// Expand `@group` targets; a dry run also shows what each group stands for
+fn expand_groups(cli: &Cli, config: &Config, output: &Output) -> Result<Vec<String>> {
+    if cli.dry_run {
+        for group in cli.targets.iter().filter_map(|t| t.strip_prefix('@')) {
+            if let Some(members) = config.groups.get(group) {
+                output.info(&format!("@{} → {}", group, members.join(", ")));
+            }
+        }
     }
+    Ok(config.expand_groups(&cli.targets)?)
+}
+
+/// Run the requested operation
+async fn run(cli: &Cli, config: &Config, output: &Output) -> Result<()> {
+    // Outside of search, the system is changed through its configuration
+    let profile = cli.target_profile(config.general.mode);
+    let system = profile == Profile::System && !cli.search;
+
+    // Cache initialization and arch detection overlap with the update check
+    let needs_manager = cli.command.is_none()
+        && !cli.interactive
+        && !system
+        && !(cli.cache_stats || cli.news || cli.history || cli.health || cli.clean);
+    let manager_init = needs_manager.then(|| {
+        let use_cache = !cli.no_cache && config.cache.enabled;
+        let memory_cache_size = config.cache.memory_cache_size;
+        let profile = profile.nix_profile();
+        tokio::task::spawn_blocking(move || init_manager(use_cache, memory_cache_size, profile))
+    });
 
     // Handle subcommands first
     if let Some(ref cmd) = cli.command {
-        return handle_subcommand(cmd, &output).await;
+        return handle_subcommand(cli, cmd, output).await;
+    }
+
+    if cli.interactive {
+        return browse_packages(cli, output).await;
     }
 
     // Handle utility flags
     if cli.cache_stats {
-        return show_cache_stats(&output);
+        return show_cache_stats(output);
     }
 
     if cli.news {
-        return fetch_nixos_news().await;
+        return fetch_nixos_news(&network::HttpClient::from_config(config), output.progress()).await;
     }
 
     if cli.history {
-        return show_history(&output);
+        return show_history(profile, output);
     }
 
     if cli.health {
-        return run_health_check(&output);
+        return run_health_check(cli.full, output);
     }
 
     if cli.clean {
-        return run_garbage_collection(&cli, &output);
+        let start = Instant::now();
+        let cache = (!cli.no_cache && config.cache.enabled)
+            .then(|| cache::CacheManager::new(config.cache.memory_cache_size).ok())
+            .flatten();
+        let outcome = run_garbage_collection(cli, cache.as_ref(), output);
+        return report_operation(OperationType::GarbageCollect, start, outcome, cli, output);
     }
 
-    // Initialize cache manager
-    let cache_manager = if !cli.no_cache && config.cache.enabled {
-        match cache::CacheManager::new(config.cache.memory_cache_size) {
-            Ok(cm) => {
-                if cli.clear_cache {
-                    let _ = cm.clear();
-                    output.info("Cache cleared");
-                }
-                Some(std::sync::Arc::new(cm))
-            }
-            Err(e) => {
-                warn!("Failed to initialize cache: {}", e);
-                None
-            }
-        }
-    } else {
-        None
-    };
+    if system {
+        return run_system(cli, output);
+    }
 
-    // Initialize package manager
-    let manager = if let Some(ref cache) = cache_manager {
-        PackageManager::with_cache(cache.clone())?
-    } else {
-        PackageManager::new()?
+    // Started at the top of run()
+    let Some(manager_init) = manager_init else {
+        return Ok(());
     };
+    let (cache_manager, manager) = manager_init.await?;
+    let manager = manager?.with_system(cli.for_system.clone());
+    show_resolution_context(&manager, cli, output).await;
+    if cli.clear_cache {
+        if let Some(ref cm) = cache_manager {
+            let _ = cm.clear();
+            output.info("Cache cleared");
+        }
+    }
 
     // Handle list command
     if cli.list {
-        return list_installed(&manager, &output).await;
+        return list_installed(&manager, output).await;
     }
 
     // Handle NUR operations
     if cli.nur {
-        return handle_nur(&cli, cache_manager.clone(), &output).await;
+        return handle_nur(&manager, cli, cache_manager.clone(), output).await;
+    }
+
+    // Handle upgrade
+    if cli.sync && cli.sysupgrade {
+        return upgrade_packages(&manager, cli, output).await;
     }
 
     // Handle search
     if cli.sync && cli.search {
-        return search_packages(&manager, &cli, &output).await;
+        let start = Instant::now();
+        let outcome = search_packages(&manager, cli, config, cache_manager.clone(), output).await;
+        return report_operation(OperationType::Search, start, outcome, cli, output);
     }
 
     // Handle install/remove
@@ -156,23 +280,135 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if cli.sync && cli.download_only {
+        let targets = manager.resolve_targets(&cli.targets).await?;
+        return download_packages(&manager, &targets, false, cli, output).await;
+    }
+
     if cli.sync {
-        return install_packages(&manager, &cli, cache_manager.clone(), &output).await;
+        let start = Instant::now();
+        let outcome = install_packages(&manager, cli, config, cache_manager.clone(), output).await;
+        return report_operation(OperationType::Install, start, outcome, cli, output);
     }
 
     if cli.remove {
-        return remove_packages(&manager, &cli, &output).await;
+        let start = Instant::now();
+        let outcome = remove_packages(&manager, cli, output).await;
+        return report_operation(OperationType::Remove, start, outcome, cli, output);
     }
 
     output.success("Operation finished");
     Ok(())
 }
 
+/// `-l`, `-S`, `-R` and `-Su` against the NixOS configuration: packages
+/// are edited in `packages.file` and applied with `nixos-rebuild`
+fn run_system(cli: &Cli, output: &Output) -> Result<()> {
+    use cli::args::DeclareAction;
+    use core::config::DeclareTarget;
+
+    if cli.list {
+        let installed: Vec<String> = system::duplicates::system_packages()?
+            .iter()
+            .map(|path| {
+                let (name, version) = package::upgrade::parse_store_name(path);
+                if version.is_empty() { name } else { format!("{}-{}", name, version) }
+            })
+            .collect();
+        output.print_installed(&installed);
+        return Ok(());
+    }
+
+    if cli.sync && cli.sysupgrade {
+        return rebuild_system(cli, true, false, None, output);
+    }
+
+    if cli.targets.is_empty() || !(cli.sync || cli.remove) {
+        return Ok(());
+    }
+    if Config::get().packages.target != DeclareTarget::Nixos {
+        output.error("System mode edits environment.systemPackages; set [packages] target = \"nixos\" in config.toml.");
+        return Ok(());
+    }
+
+    let start = Instant::now();
+    let packages = cli.targets.clone();
+    let (operation, action) = if cli.sync {
+        (OperationType::Install, DeclareAction::Add { packages })
+    } else {
+        (OperationType::Remove, DeclareAction::Remove { packages })
+    };
+    let outcome = declare_packages(cli, None, &action, output);
+    report_operation(operation, start, outcome, cli, output)
+}
+
+/// Delete the generations `options` selects and collect garbage, after
+/// showing the plan
+fn collect_with_plan(options: &GcOptions, dry_run: bool, skip_confirm: bool, output: &Output) -> Result<()> {
+    let pb = output.progress().spinner("Planning garbage collection...");
+    let plan = GarbageCollector::plan(options);
+    pb.finish_and_clear();
+    let plan = plan?;
+
+    if dry_run {
+        output.print_gc_plan(&plan);
+        return Ok(());
+    }
+    if !output.is_structured() {
+        output.print_gc_plan(&plan);
+    }
+
+    if !plan.generations.is_empty() && !skip_confirm && !Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Delete {} generation(s)? They can no longer be rolled back to", plan.generations.len()))
+        .default(true)
+        .interact()?
+    {
+        return Ok(());
+    }
+
+    let pb = output.progress().spinner("Collecting garbage...");
+    let result = GarbageCollector::run_plan(&plan);
+    pb.finish_and_clear();
+    output.print_gc_result(&result?);
+    Ok(())
+}
+
+/// Print the closing summary of an operation started at `start` and pass
+/// its outcome through. Failures are left to the error report in human mode.
+fn report_operation(
+    operation: OperationType,
+    start: Instant,
+    outcome: Result<()>,
+    cli: &Cli,
+    output: &Output,
+) -> Result<()> {
+    let mut result = OperationResult::from_outcome(operation, cli.targets.clone(), start, &outcome);
+    if cli.dry_run && outcome.is_ok() {
+        result.status = OperationStatus::Skipped;
+        result.message = Some("dry run".to_string());
+    }
+
+    // The other operations are recorded where they run
+    if operation == OperationType::Search {
+        if let Err(e) = core::history::OperationLog::open_default().and_then(|log| log.append(&result)) {
+            debug!("Failed to record search in operation history: {}", e);
+        }
+    }
+
+    // Structured search and gc output is their own result
+    let own_record = matches!(operation, OperationType::Search | OperationType::GarbageCollect);
+    if !(output.is_structured() && own_record) && (outcome.is_ok() || output.is_structured()) {
+        output.print_operation_result(&result);
+    }
+    outcome
+}
+
 /// Initialize logging based on CLI flags
 fn init_logging(cli: &Cli) {
     let level = if cli.verbose {
         Level::DEBUG
-    } else if cli.quiet {
+    } else if cli.quiet || cli.interactive || matches!(cli.command, Some(Commands::Tui)) {
+        // Log lines would draw over the terminal UI
         Level::ERROR
     } else {
         Level::INFO
@@ -188,12 +424,9 @@ fn init_logging(cli: &Cli) {
         .init();
 }
 
-/// Check for updates and prompt user
-fn check_and_prompt_update(cli: &Cli) -> Result<()> {
-    let pb = progress::spinner("checking for updates...");
-
-    if let Some(info) = check_for_updates(VERSION) {
-        pb.finish_and_clear();
+/// Offer to install a newer release found by the update check
+fn prompt_update(cli: &Cli, info: Option<utils::updater::UpdateInfo>) -> Result<()> {
+    if let Some(info) = info {
         println!(
             "{} a new version is available: {} -> {}",
             style("::").bold().cyan(),
@@ -215,68 +448,305 @@ fn check_and_prompt_update(cli: &Cli) -> Result<()> {
                 }
             }
         }
-    } else {
-        pb.finish_and_clear();
     }
 
     Ok(())
 }
 
+/// Open the cache and create the package manager. Runs on a blocking
+/// thread: both touch the disk and arch detection spawns `nix eval`.
+fn init_manager(
+    use_cache: bool,
+    memory_cache_size: usize,
+    profile: Option<PathBuf>,
+) -> (Option<std::sync::Arc<cache::CacheManager>>, core::error::Result<PackageManager>) {
+    let cache_manager = if use_cache {
+        match cache::CacheManager::new(memory_cache_size) {
+            Ok(cm) => Some(std::sync::Arc::new(cm)),
+            Err(e) => {
+                warn!("Failed to initialize cache: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let manager = match cache_manager {
+        Some(ref cache) => PackageManager::with_cache(cache.clone()),
+        None => PackageManager::new(),
+    };
+    (cache_manager, manager.map(|m| m.with_profile(profile)))
+}
+
 /// Handle subcommands
-async fn handle_subcommand(cmd: &Commands, output: &Output) -> Result<()> {
+async fn handle_subcommand(cli: &Cli, cmd: &Commands, output: &Output) -> Result<()> {
     match cmd {
-        Commands::Info { package } => {
-            let manager = PackageManager::new()?;
-            if let Some(pkg) = manager.package_info(package).await? {
-                output.print_packages(&[pkg]);
-            } else {
-                output.error(&format!("Package '{}' not found", package));
+        Commands::Info { packages } => {
+            let manager = match cache::CacheManager::new(Config::get().cache.memory_cache_size) {
+                Ok(cache) => PackageManager::with_cache(std::sync::Arc::new(cache))?,
+                Err(_) => PackageManager::new()?,
+            }
+            .with_system(cli.for_system.clone());
+            let mut found = Vec::new();
+            for (name, pkg) in manager.package_info(packages).await? {
+                match pkg {
+                    Some(pkg) => found.push(pkg),
+                    None => output.error(&format!("Package '{}' not found", name)),
+                }
+            }
+            if !found.is_empty() {
+                output.print_packages(&found);
             }
         }
         Commands::Generation { action } => {
             use cli::args::GenerationAction;
+            let generations = GenerationManager::new(cli.target_profile(Config::get().general.mode));
             match action {
                 GenerationAction::List { limit } => {
-                    let generations = GenerationManager::list(*limit)?;
-                    GenerationManager::print_list(&generations);
+                    // One more than shown, for the oldest generation's change
+                    let mut generations = generations.list(limit.saturating_add(1))?;
+                    let cache = cache::CacheManager::new(Config::get().cache.memory_cache_size).ok();
+                    let pb = output.progress().spinner("Computing closure sizes...");
+                    let sized = GenerationManager::with_sizes(&mut generations, cache.as_ref());
+                    pb.finish_and_clear();
+                    if let Err(e) = sized {
+                        output.warn(&format!("Closure sizes unavailable: {}", e));
+                    }
+                    generations.truncate(*limit);
+                    output.print_generations(&generations);
                 }
                 GenerationAction::Diff { from, to } => {
-                    let diff = GenerationManager::diff(*from, *to)?;
-                    diff.print();
+                    let diff = generations.diff(*from, *to)?;
+                    output.print_generation_diff(&diff);
                 }
                 GenerationAction::Rollback { generation } => {
-                    if let Some(gen) = generation {
-                        GenerationManager::rollback_to(*gen)?;
-                    } else {
-                        GenerationManager::rollback()?;
+                    let start = Instant::now();
+                    let outcome = match generation {
+                        Some(gen) => generations.rollback_to(*gen),
+                        None => generations.rollback(),
+                    };
+                    if outcome.is_ok() {
+                        output.success("Rollback completed");
                     }
-                    output.success("Rollback completed");
+                    report_operation(OperationType::Rollback, start, outcome.map_err(Into::into), cli, output)?;
                 }
                 GenerationAction::Delete { keep } => {
-                    let deleted = GenerationManager::delete_old(*keep)?;
+                    let policy = keep.map(GenerationsConfig::keep_last).unwrap_or_else(|| Config::get().generations.clone());
+                    let deleted = generations.delete_old(&policy)?;
+                    // Deleted generations no longer root their paths
+                    if let Ok(cache) = cache::CacheManager::new(Config::get().cache.memory_cache_size) {
+                        let _ = cache.clear_namespace("gc");
+                    }
                     output.success(&format!("Deleted {} generations", deleted));
                 }
+                GenerationAction::Restore { generation } => match generation {
+                    Some(gen) => {
+                        generations.restore(*gen)?;
+                        // The restored generation roots its paths again
+                        if let Ok(cache) = cache::CacheManager::new(Config::get().cache.memory_cache_size) {
+                            let _ = cache.clear_namespace("gc");
+                        }
+                        output.success(&format!("Restored generation {}", gen));
+                    }
+                    None => output.print_quarantined(&generations.quarantined()?),
+                },
+            }
+        }
+        Commands::Profile { action } => {
+            use cli::args::ProfileAction;
+            use package::profiles::{self, NamedProfiles, DEFAULT_PROFILE};
+
+            let mut named = NamedProfiles::load()?;
+            match action {
+                ProfileAction::Create { name } => {
+                    named.create(name)?;
+                    named.save()?;
+                    output.success(&format!("Created profile {}; install into it with --profile {}", name, name));
+                }
+                ProfileAction::List => output.print_profiles(&named.status()),
+                ProfileAction::Switch { name } => {
+                    let path = if name == DEFAULT_PROFILE {
+                        profiles::default_profile()
+                    } else if named.contains(name) {
+                        NamedProfiles::path(name)
+                    } else {
+                        return Err(core::error::PackageError::Profile {
+                            name: name.clone(),
+                            reason: "no such profile; see 'nixboost profile list'".to_string(),
+                        }.into());
+                    };
+                    if cli.dry_run {
+                        output.info(&format!("Dry run - would switch to {}", path.display()));
+                        return Ok(());
+                    }
+                    profiles::switch(&path)?;
+                    output.success(&format!("Switched to profile {}", name));
+                }
             }
         }
         Commands::Cache { action } => {
             use cli::args::CacheAction;
             match action {
                 CacheAction::Stats => show_cache_stats(output)?,
-                CacheAction::Clear => {
-                    if let Ok(cache) = cache::CacheManager::new(100) {
-                        cache.clear()?;
-                        output.success("Cache cleared");
+                CacheAction::Clear { namespace } => {
+                    if let Ok(cache) = cache::CacheManager::new(Config::get().cache.memory_cache_size) {
+                        match namespace {
+                            Some(namespace) => {
+                                let cleared = cache.clear_namespace(namespace)?;
+                                output.success(&format!("Cleared {} {} entries", cleared, namespace));
+                            }
+                            None => {
+                                cache.clear()?;
+                                output.success("Cache cleared");
+                            }
+                        }
                     }
                 }
+                CacheAction::Invalidate { pattern } => {
+                    let cache = cache::CacheManager::new(Config::get().cache.memory_cache_size)?;
+                    let removed = cache.invalidate(pattern)?;
+                    output.success(&format!("Invalidated {} entries matching {}", removed, pattern));
+                }
                 CacheAction::Verify => {
-                    output.info("Cache verification not yet implemented");
+                    let cache = cache::CacheManager::new(Config::get().cache.memory_cache_size)?;
+                    let report = cache.disk.verify()?;
+                    output.print_cache_verify(&report);
                 }
                 CacheAction::Prune => {
-                    if let Ok(cache) = cache::CacheManager::new(100) {
+                    if let Ok(cache) = cache::CacheManager::new(Config::get().cache.memory_cache_size) {
                         let pruned = cache.disk.prune()?;
                         output.success(&format!("Pruned {} expired entries", pruned));
                     }
                 }
+                CacheAction::Export { path } => {
+                    let files = cache::CacheBundle::new().export(path)?;
+                    output.success(&format!(
+                        "Exported {} ({}) to {}",
+                        files.len(),
+                        files.join(", "),
+                        path.display()
+                    ));
+                }
+                CacheAction::Import { path } => {
+                    let files = cache::CacheBundle::new().import(path)?;
+                    output.success(&format!("Imported {} ({})", files.len(), files.join(", ")));
+                }
+                CacheAction::Substituters => check_substituters(output).await?,
+            }
+        }
+        Commands::Diff { from, to } => {
+            if from == to {
+                output.error("Compare two different environments, e.g. 'nixboost diff profile system'");
+                return Ok(());
+            }
+
+            let manager = PackageManager::new()?;
+            let profile: Vec<String> = manager
+                .profile_elements()
+                .await?
+                .into_iter()
+                .flat_map(|el| el.store_paths)
+                .collect();
+            let system = system::duplicates::system_packages()?;
+            output.print_duplicates(&system::duplicates::find_duplicates(&profile, &system));
+        }
+        Commands::Gc { action } => {
+            use cli::args::GcAction;
+            match action {
+                GcAction::Impact { package } => {
+                    let manager = PackageManager::new()?;
+                    let elements = manager.profile_elements().await?;
+                    let target = match resolve_element(&elements, package) {
+                        ElementMatch::One(el) => el,
+                        ElementMatch::None => {
+                            return Err(core::error::PackageError::NotInstalled { name: package.clone() }.into());
+                        }
+                        ElementMatch::Ambiguous(candidates) => {
+                            return Err(core::error::PackageError::Ambiguous {
+                                query: package.clone(),
+                                candidates: candidates.iter().map(|el| el.name.as_str()).collect::<Vec<_>>().join(", "),
+                            }.into());
+                        }
+                    };
+                    let kept: Vec<String> = elements
+                        .iter()
+                        .filter(|el| el.name != target.name)
+                        .flat_map(|el| el.store_paths.iter().cloned())
+                        .collect();
+
+                    let pb = output.progress().spinner("computing closures...");
+                    let impact = GarbageCollector::impact(&target.name, &target.store_paths, &kept);
+                    pb.finish_and_clear();
+                    output.print_gc_impact(&impact?);
+                }
+            }
+        }
+        Commands::Index { action } => {
+            use cli::args::IndexAction;
+            let index = search::index::LocalIndex::open_default()?;
+            match action {
+                IndexAction::Update => {
+                    let count = refresh_index(&index, output).await?;
+                    output.success(&format!("Indexed {} packages", count));
+                }
+                IndexAction::Status => output.print_index_status(&index.status()?),
+            }
+        }
+        Commands::History { action } => {
+            use cli::args::HistoryAction;
+            match action {
+                HistoryAction::Revisions { limit } => {
+                    let entries = package::journal::Journal::new().entries()?;
+                    let recent = &entries[entries.len().saturating_sub(*limit)..];
+                    if recent.is_empty() && !output.is_structured() {
+                        output.info("No installs recorded yet");
+                    } else {
+                        output.print_journal(recent);
+                    }
+                }
+                HistoryAction::Ops { operation, failed, package, since, limit } => {
+                    use core::history::{OperationFilter, OperationLog};
+
+                    let operation = match operation.as_deref().map(str::parse::<core::types::OperationType>) {
+                        Some(Err(e)) => {
+                            output.error(&e);
+                            return Ok(());
+                        }
+                        Some(Ok(op)) => Some(op),
+                        None => None,
+                    };
+                    let since = match since.as_deref().map(utils::time::parse_duration) {
+                        Some(None) => {
+                            output.error("--since takes a span such as 12h, 7d or 2w");
+                            return Ok(());
+                        }
+                        Some(Some(span)) => Some(utils::time::unix_now().saturating_sub(span.as_secs())),
+                        None => None,
+                    };
+
+                    let records = OperationLog::open_default()?.query(&OperationFilter {
+                        operation,
+                        status: failed.then_some(core::types::OperationStatus::Failed),
+                        package: package.clone(),
+                        since,
+                        limit: *limit,
+                    })?;
+                    if records.is_empty() && !output.is_structured() {
+                        output.info("No matching operations recorded");
+                    } else {
+                        output.print_operations(&records);
+                    }
+                }
+            }
+        }
+        Commands::Nur { action } => {
+            use cli::args::NurAction;
+            match action {
+                NurAction::Status => {
+                    let status = NurClient::new().status();
+                    output.print_nur_status(&status);
+                }
             }
         }
         Commands::Config { action } => {
@@ -307,6 +777,12 @@ async fn handle_subcommand(cmd: &Commands, output: &Output) -> Result<()> {
                         Err(e) => output.error(&format!("Config is invalid: {}", e)),
                     }
                 }
+                ConfigAction::Set { key, value } => {
+                    let mut config = Config::load()?;
+                    config.set_key(key, value)?;
+                    config.save()?;
+                    output.success(&format!("Set {} = {}", key, value));
+                }
                 ConfigAction::Path => {
                     println!("{}", Config::config_path().display());
                 }
@@ -315,193 +791,1558 @@ async fn handle_subcommand(cmd: &Commands, output: &Output) -> Result<()> {
         Commands::System { action } => {
             use cli::args::SystemAction;
             match action {
-                SystemAction::Health => run_health_check(output)?,
-                SystemAction::Gc { keep_generations, dry_run } => {
-                    if *dry_run {
-                        let preview = GarbageCollector::preview()?;
-                        output.info(&format!(
-                            "Would delete {} paths, freeing {}",
-                            preview.paths.len(),
-                            preview.size_human()
-                        ));
+                SystemAction::Health { full, watch, interval } => {
+                    if *watch {
+                        system::monitor::HealthMonitor::new(*interval).watch()?;
                     } else {
-                        let result = GarbageCollector::run_with_options(*keep_generations, None)?;
-                        GarbageCollector::print_result(&result);
+                        run_health_check(*full || cli.full, output)?;
                     }
                 }
+                SystemAction::Gc { keep_generations, older_than, free_at_least, dry_run } => {
+                    let explicit = keep_generations.is_some() || older_than.is_some() || free_at_least.is_some();
+                    let options = GcOptions {
+                        keep_generations: keep_generations.unwrap_or(Config::get().generations.keep_last),
+                        // With only --keep-generations, everything past the kept generations goes
+                        older_than: older_than.or((keep_generations.is_some() && free_at_least.is_none()).then_some(Duration::ZERO)),
+                        free_at_least: *free_at_least,
+                        // Without any of them, the [generations] policy decides
+                        retention: (!explicit).then(|| Config::get().generations.clone()),
+                    };
+                    collect_with_plan(&options, cli.dry_run || *dry_run, cli.yes, output)?;
+                }
                 SystemAction::Verify => {
                     output.info("Verifying Nix store...");
-                    let report = HealthChecker::run()?;
+                    let report = HealthChecker::run_mode(HealthMode::Full)?;
                     if report.nix_store_ok {
                         output.success("Nix store is healthy");
                     } else {
                         output.error("Nix store has issues");
                     }
                 }
+                SystemAction::Triage => triage_store(cli, output)?,
                 SystemAction::Optimize => {
                     output.info("Optimizing Nix store...");
                     std::process::Command::new("nix-store")
                         .arg("--optimise")
-                        .status()?;
+                        .echo().status()?;
                     output.success("Optimization complete");
                 }
                 SystemAction::DiskUsage => {
                     std::process::Command::new("nix")
                         .args(["path-info", "--size", "--recursive", "/run/current-system"])
-                        .status()?;
+                        .echo().status()?;
                 }
-            }
-        }
-        Commands::Completions { shell } => {
-            use clap::CommandFactory;
-            let mut cmd = Cli::command();
-            let name = cmd.get_name().to_string();
-            
-            match shell {
-                cli::args::Shell::Bash => {
-                    clap_complete::generate(clap_complete::shells::Bash, &mut cmd, name, &mut std::io::stdout());
+                SystemAction::Builders => {
+                    let configured = system::builders::configured();
+                    if configured.is_empty() && !output.is_structured() {
+                        output.info("No remote builders configured (see the 'builders' nix.conf setting)");
+                    } else {
+                        let statuses = system::builders::probe_all(&configured);
+                        let local = system::builders::local_systems();
+                        output.print_builders(&statuses, &system::builders::unreachable_systems(&statuses, &local));
+                    }
                 }
-                cli::args::Shell::Zsh => {
-                    clap_complete::generate(clap_complete::shells::Zsh, &mut cmd, name, &mut std::io::stdout());
+                SystemAction::Rebuild { upgrade, allow_critical, watchdog, confirm_within } => {
+                    let watchdog = watchdog.then_some(*confirm_within);
+                    rebuild_system(cli, *upgrade, *allow_critical, watchdog, output)?;
                 }
-                cli::args::Shell::Fish => {
-                    clap_complete::generate(clap_complete::shells::Fish, &mut cmd, name, &mut std::io::stdout());
+                SystemAction::Confirm => {
+                    if system::watchdog::disarm()? {
+                        output.success("Configuration confirmed, automatic rollback cancelled");
+                    } else {
+                        output.info("No automatic rollback is pending");
+                    }
                 }
-                cli::args::Shell::PowerShell => {
-                    clap_complete::generate(clap_complete::shells::PowerShell, &mut cmd, name, &mut std::io::stdout());
+                SystemAction::Daemon { action } => {
+                    use cli::args::DaemonAction;
+                    match action {
+                        DaemonAction::Status => NixDaemon::status()?.print(),
+                        DaemonAction::Restart => {
+                            output.info("Restarting nix-daemon...");
+                            NixDaemon::restart()?;
+                            if NixDaemon::status()?.is_running() {
+                                output.success("nix-daemon restarted");
+                            } else {
+                                output.error("nix-daemon did not come back up; see 'nixboost system daemon logs'");
+                            }
+                        }
+                        DaemonAction::Logs { lines, follow } => NixDaemon::logs(*lines, *follow)?,
+                    }
                 }
-                cli::args::Shell::Elvish => {
-                    clap_complete::generate(clap_complete::shells::Elvish, &mut cmd, name, &mut std::io::stdout());
+            }
+        }
+        Commands::Lock { file } => {
+            let manager = PackageManager::new()?.with_profile(cli.package_profile(Config::get().general.mode));
+            let lock = manager.lock().await?;
+            lock.save(file)?;
+            output.success(&format!("Locked {} package(s) to {}", lock.packages.len(), file.display()));
+        }
+        Commands::Maintain { skip, dry_run } => {
+            return run_maintenance(cli, skip, cli.dry_run || *dry_run, output).await;
+        }
+        Commands::Stage { action, install, remove } => {
+            return stage_changes(cli, action.as_ref(), install, remove, output).await;
+        }
+        Commands::Pin { action: Some(cli::args::PinAction::List), .. } => {
+            output.print_pins(&package::pins::Pins::load()?);
+        }
+        Commands::Pin { action: None, package, rev } => {
+            let (Some(package), Some(rev)) = (package, rev) else {
+                output.error("Usage: nixboost pin <package> --rev <commit>");
+                return Ok(());
+            };
+            if cli.dry_run {
+                output.info(&format!("Would install github:NixOS/nixpkgs/{}#{} and pin it", rev, package));
+                return Ok(());
+            }
+            let pin = PackageManager::new()?.pin(package, rev).await?;
+            output.success(&format!("Pinned {} to {}", package, pin.flake_ref));
+        }
+        Commands::Unpin { packages } => {
+            let mut pins = package::pins::Pins::load()?;
+            for name in packages {
+                if pins.pins.remove(name).is_some() {
+                    output.success(&format!("Unpinned {}; 'nixboost -R {0} && nixboost -S {0}' moves it back to nixpkgs", name));
+                } else {
+                    output.warn(&format!("'{}' is not pinned", name));
                 }
             }
+            pins.save()?;
         }
-    }
-    Ok(())
-}
+        Commands::Export { locked } => {
+            let manager = PackageManager::new()?.with_profile(cli.package_profile(Config::get().general.mode));
+            let elements = manager.profile_elements().await?;
+            println!("{}", package::manifest::Manifest::from_elements(&elements, *locked).to_json()?);
+        }
+        Commands::Import { file, dry_run } => {
+            let manifest = package::manifest::Manifest::load(file)?;
+            let manager = PackageManager::new()?.with_profile(cli.package_profile(Config::get().general.mode));
+            let plan = manifest.plan(&manager.profile_elements().await?);
+            output.print_import_plan(&plan);
 
-/// List installed packages
-async fn list_installed(manager: &PackageManager, output: &Output) -> Result<()> {
-    let installed = manager.list_installed().await?;
-    output.print_installed(&installed);
-    Ok(())
-}
+            if cli.dry_run || *dry_run || plan.install.is_empty() {
+                return Ok(());
+            }
+            if !cli.yes
+                && !Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!("Install {} package(s)?", plan.install.len()))
+                    .default(true)
+                    .interact()?
+            {
+                return Ok(());
+            }
 
-/// Search packages
-async fn search_packages(manager: &PackageManager, cli: &Cli, output: &Output) -> Result<()> {
-    let query = cli.targets.join(" ");
-    let results = manager.search(&query).await?;
+            let refs: Vec<String> = plan.install.iter().map(|e| e.install_ref().to_string()).collect();
+            manager.install_refs(&refs).await?;
+            output.success(&format!("Imported {} package(s)", refs.len()));
+        }
+        Commands::Declare { file, action } => {
+            return declare_packages(cli, file.as_deref(), action, output);
+        }
+        Commands::Sync { frozen, prune, file } => {
+            if !*frozen {
+                return sync_declared(*prune, cli.skip_confirm(), output).await;
+            }
 
-    if results.is_empty() {
-        println!("No matches found.");
-    } else {
-        output.print_packages(&results[..results.len().min(cli.max_results)]);
-    }
+            let lock = package::lockfile::Lockfile::load(file)?;
+            let manager = PackageManager::new()?.with_profile(cli.package_profile(Config::get().general.mode));
+            let plan = manager.sync_frozen(&lock).await?;
 
-    Ok(())
-}
+            if plan.is_empty() {
+                output.success("Profile already matches the lockfile");
+            } else {
+                output.success(&format!(
+                    "Profile synced: {} installed, {} removed",
+                    plan.install.len(),
+                    plan.remove.len()
+                ));
+            }
+        }
+        Commands::Deps { package, depth, why } => {
+            let manager = match cache::CacheManager::new(Config::get().cache.memory_cache_size) {
+                Ok(cache) => PackageManager::with_cache(std::sync::Arc::new(cache))?,
+                Err(_) => PackageManager::new()?,
+            };
+            let tree = manager.dependency_tree(package).await?;
 
-/// Handle NUR operations
-async fn handle_nur(
-    cli: &Cli,
-    cache: Option<std::sync::Arc<cache::CacheManager>>,
-    output: &Output,
-) -> Result<()> {
-    let targets = &cli.targets;
+            match why {
+                Some(dependency) => {
+                    let target = tree.find(dependency).map(|n| n.path.clone()).unwrap_or_else(|| dependency.clone());
+                    package::deps::why_depends(&tree.path, &target).await?;
+                }
+                None => output.print_dep_tree(&tree, *depth),
+            }
+        }
+        Commands::Provides { query, update } => {
+            use package::provides::{self, Lookup, NixIndex};
 
-    if targets.is_empty() {
-        output.error("No targets specified for NUR search");
-        return Ok(());
-    }
+            let config = Config::get();
+            let (_, manager) = init_manager(
+                !cli.no_cache && config.cache.enabled,
+                config.cache.memory_cache_size,
+                cli.package_profile(config.general.mode),
+            );
+            let manager = manager?;
+            let lookup = Lookup::parse(query);
 
-    output.info("Searching NUR...");
+            let mut providers = Vec::new();
+            if let (Lookup::Command(command), Some(db), false) = (&lookup, provides::programs_db(), *update) {
+                providers = provides::query_programs(&db, command, manager.arch())?;
+            }
+            if providers.is_empty() {
+                let index = NixIndex::new();
+                if *update || index.needs_update() {
+                    let http = network::HttpClient::from_config(config);
+                    if let Err(e) = index.download(&http, manager.arch(), output.progress()).await {
+                        if index.age().is_none() {
+                            return Err(e.into());
+                        }
+                        output.warn(&format!("Using the existing nix-index database; update failed: {}", e));
+                    }
+                }
+                let pb = output.progress().spinner("Searching the nix-index database...");
+                let found = index.locate(&lookup);
+                pb.finish_and_clear();
+                providers = found?;
+            }
 
-    let mut nur = if let Some(c) = cache {
-        NurClient::with_cache(c)
-    } else {
-        NurClient::new()
-    };
+            output.print_providers(query, &providers);
+            if providers.is_empty() || output.is_structured() || cli.dry_run {
+                return Ok(());
+            }
 
-    // Search NUR
-    let mut all_results = Vec::new();
-    for target in targets {
-        match nur.search(target).await {
-            Ok(results) => {
-                for pkg in results {
-                    all_results.push(pkg.into());
+            // Keep the providers' order; one package can provide several matching paths
+            let mut seen = std::collections::HashSet::new();
+            let packages: Vec<String> = providers.iter()
+                .map(|p| p.package.clone())
+                .filter(|p| seen.insert(p.clone()))
+                .collect();
+            let installed = manager.list_installed().await.unwrap_or_default();
+            if let Some(pkg) = packages.iter().find(|p| installed.contains(p)) {
+                output.info(&format!("{} is already installed", pkg));
+                return Ok(());
+            }
+
+            let choice = match packages.as_slice() {
+                [only] => (cli.yes
+                    || Confirm::with_theme(&ColorfulTheme::default())
+                        .with_prompt(format!("Install {}?", only))
+                        .default(true)
+                        .interact()?)
+                .then(|| only.clone()),
+                _ if cli.yes => None,
+                _ => {
+                    let mut items = packages.clone();
+                    items.push("None of these".to_string());
+                    let idx = dialoguer::Select::with_theme(&ColorfulTheme::default())
+                        .with_prompt("Install one of them?")
+                        .items(&items)
+                        .default(0)
+                        .interact()?;
+                    packages.get(idx).cloned()
                 }
+            };
+            if let Some(pkg) = choice {
+                manager.install(std::slice::from_ref(&pkg)).await?;
+                output.success(&format!("Installed {}", pkg));
             }
-            Err(e) => {
-                output.warn(&format!("Failed to search NUR: {}", e));
+        }
+        Commands::Which { package } => {
+            let (_, manager) = init_manager(
+                !cli.no_cache && Config::get().cache.enabled,
+                Config::get().cache.memory_cache_size,
+                cli.package_profile(Config::get().general.mode),
+            );
+            let info = manager?.with_system(cli.for_system.clone()).which(package).await?;
+            output.print_which(&info);
+        }
+        Commands::Reinstall { packages } => {
+            let manager = PackageManager::new()?.with_profile(cli.package_profile(Config::get().general.mode));
+            if cli.dry_run {
+                output.info(&format!("Dry run - would reinstall {}", packages.join(", ")));
+                return Ok(());
+            }
+            if !cli.yes
+                && !Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!("Remove and reinstall {}?", packages.join(", ")))
+                    .default(true)
+                    .interact()?
+            {
+                return Ok(());
+            }
+
+            let refs = manager.reinstall(packages).await?;
+            for flake_ref in &refs {
+                println!("  {} {}", style("↻").cyan(), flake_ref);
             }
+            output.success(&format!("Reinstalled {} package(s)", refs.len()));
         }
-    }
+        Commands::PreviewUpgrade => {
+            let manager = PackageManager::new()?;
+            output.info("Evaluating profile against the latest nixpkgs revision...");
+            let preview = manager.preview_upgrade().await?;
+            output.print_upgrade_preview(&preview);
+        }
+        Commands::FetchBin { repo } => fetch_release_binary(cli, repo, output).await?,
+        Commands::Logs { target, tail, grep, no_pager } => {
+            use package::buildlog;
 
-    if all_results.is_empty() {
-        output.warn("No matches found in NUR");
-    } else {
-        output.print_packages(&all_results);
-    }
+            let installable = buildlog::installable(target);
+            let pb = output.progress().spinner(&format!("Fetching build log of {}...", installable));
+            let log = buildlog::fetch(&installable);
+            pb.finish_and_clear();
+            output.print_build_log(&buildlog::select(&log?, grep.as_deref(), *tail), !no_pager)?;
+        }
+        Commands::Tui => browse_packages(cli, output).await?,
+        Commands::Shell { packages } => {
+            use package::shell;
 
-    Ok(())
+            let manager = PackageManager::new()?;
+            let pb = output.progress().spinner("Resolving packages...");
+            let checks = manager.check_packages(packages).await;
+            pb.finish_and_clear();
+
+            let mut nur = NurClient::new().allow_stale(true).progress(output.progress().clone());
+            let mut installables = Vec::new();
+            for (package, exists) in checks {
+                if exists || package.contains('#') {
+                    installables.push(shell::nixpkgs_installable(&package));
+                    continue;
+                }
+
+                let attr_path = nur.resolve_attr(&package).await?;
+                let use_nur = match Config::get().general.nur_fallback {
+                    NurFallback::Auto => true,
+                    NurFallback::Never => false,
+                    // NUR packages are unreviewed, so `-y` does not imply consent
+                    NurFallback::Ask => Confirm::with_theme(&ColorfulTheme::default())
+                        .with_prompt(format!("{} is not in nixpkgs. Build {} from NUR?", package, attr_path))
+                        .default(false)
+                        .interact()?,
+                };
+                if !use_nur {
+                    return Err(core::error::PackageError::NotFound { name: package }.into());
+                }
+                installables.push(NurClient::installable(&attr_path));
+            }
+
+            if cli.dry_run {
+                output.info(&format!("Dry run - would start nix shell {}", installables.join(" ")));
+                return Ok(());
+            }
+            output.info(&format!("Starting a shell with {}; exit it to return", packages.join(", ")));
+            shell::launch(&installables)?;
+        }
+        Commands::Run { package, args } => {
+            use package::shell;
+
+            let config = Config::get();
+            let (_, manager) = init_manager(!cli.no_cache && config.cache.enabled, config.cache.memory_cache_size, None);
+            let manager = manager?;
+
+            let mut package = package.clone();
+            if !package.contains('#') && !manager.package_exists(&package).await {
+                let Some(suggested) = suggest_package(&manager, &package, cli.yes, output).await? else {
+                    return Err(core::error::PackageError::NotFound { name: package }.into());
+                };
+                package = suggested;
+            }
+
+            if cli.dry_run {
+                output.info(&format!("Dry run - would run nix run {} -- {}", shell::nixpkgs_installable(&package), args.join(" ")));
+                return Ok(());
+            }
+            let status = shell::run(&shell::nixpkgs_installable(&package), args)?;
+            if !status.success() {
+                std::process::exit(status.code().unwrap_or(1));
+            }
+        }
+        Commands::Repl => {
+            use package::repl;
+
+            let config = Config::get();
+            let manager = PackageManager::new()?;
+            let elements = manager.profile_elements().await.unwrap_or_default();
+
+            let pinned = repl::locked_url(&config.general.channel);
+            if pinned.is_none() {
+                output.warn(&format!("Could not lock {}, the session will follow the registry", config.general.channel));
+            }
+            let impure = pinned.is_none() || elements.iter().any(|e| e.url.is_none() && e.original_url.is_some());
+            let nixpkgs = pinned.unwrap_or_else(|| config.general.channel.clone());
+
+            output.info(&format!("nixpkgs from {}; installed packages are under `installed`", nixpkgs));
+            repl::launch(&repl::scope_expression(&nixpkgs, &elements), impure)?;
+        }
+        Commands::Contrib { action } => {
+            use cli::args::ContribAction;
+            match action {
+                ContribAction::Init { pname, repo, output: path, force } => {
+                    let path = path.clone().unwrap_or_else(|| std::path::Path::new(pname).join("package.nix"));
+                    if path.exists() && !force {
+                        output.error(&format!("{} already exists (use --force to overwrite)", path.display()));
+                        return Ok(());
+                    }
+
+                    let meta = match repo {
+                        Some(repo) => {
+                            let config = Config::get();
+                            let http = network::HttpClient::from_config(config);
+                            Some(package::skeleton::RepoMeta::fetch(&http, repo).await?)
+                        }
+                        None => None,
+                    };
+
+                    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    std::fs::write(&path, package::skeleton::render(pname, meta.as_ref()))?;
+                    output.success(&format!("Wrote {}", path.display()));
+                    let nix_path = if path.is_absolute() { path.display().to_string() } else { format!("./{}", path.display()) };
+                    output.info(&format!("Build it with: nix-build -E 'with import <nixpkgs> {{}}; callPackage {} {{}}'", nix_path));
+                }
+            }
+        }
+        Commands::Completions { shell } => {
+            use clap_complete::env::{Bash, Elvish, EnvCompleter, Fish, Powershell, Zsh};
+
+            // The script calls back into nixboost with COMPLETE set, which
+            // is answered at the top of try_main
+            let completer: &dyn EnvCompleter = match shell {
+                cli::args::Shell::Bash => &Bash,
+                cli::args::Shell::Zsh => &Zsh,
+                cli::args::Shell::Fish => &Fish,
+                cli::args::Shell::PowerShell => &Powershell,
+                cli::args::Shell::Elvish => &Elvish,
+            };
+            completer.write_registration("COMPLETE", "nixboost", "nixboost", "nixboost", &mut std::io::stdout())?;
+        }
+    }
+    Ok(())
+}
+
+/// Edit the declared package file, preview the change, then rebuild
+fn declare_packages(
+    cli: &Cli,
+    file: Option<&std::path::Path>,
+    action: &cli::args::DeclareAction,
+    output: &Output,
+) -> Result<()> {
+    use cli::args::DeclareAction;
+    use core::config::DeclareTarget;
+    use package::declare::{print_diff, PackageFile};
+
+    let config = Config::get();
+    let Some(path) = file.map(|f| f.to_path_buf()).or(config.packages.file.clone()) else {
+        output.error("No package file configured. Pass --file or set [packages] file = \"...\" in config.toml.");
+        return Ok(());
+    };
+    let target = config.packages.target;
+    let list = PackageFile::load(&path, target)?;
+
+    let edited = match action {
+        DeclareAction::List => {
+            output.print_declared(&path, &list.packages()?);
+            return Ok(());
+        }
+        DeclareAction::Add { packages } => list.with_added(packages)?,
+        DeclareAction::Remove { packages } => list.with_removed(packages)?,
+    };
+
+    if edited == list.content {
+        output.info("Package list already up to date");
+        return Ok(());
+    }
+    print_diff(&path, &list.content, &edited);
+    if cli.dry_run {
+        return Ok(());
+    }
+    if !cli.yes
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Write the change and rebuild?")
+            .default(true)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    std::fs::write(&path, &edited)?;
+    output.success(&format!("Updated {}", path.display()));
+    match target {
+        DeclareTarget::Nixos => rebuild_system(cli, false, false, None, output),
+        DeclareTarget::HomeManager => {
+            package::declare::home_manager_switch()?;
+            output.success("Switched to the new home-manager generation");
+            Ok(())
+        }
+    }
+}
+
+/// Install declared packages and optionally remove undeclared ones
+async fn sync_declared(prune: bool, skip_confirm: bool, output: &Output) -> Result<()> {
+    let config = Config::get();
+    let declared = &config.packages;
+
+    if declared.declared.is_empty() {
+        output.warn("No packages declared. Add a [packages] declared = [...] list to config.toml.");
+        return Ok(());
+    }
+
+    let manager = PackageManager::new()?;
+    let installed = manager.list_installed().await?;
+
+    let missing = declared.missing(&installed);
+    let undeclared = if prune || declared.remove_undeclared {
+        declared.undeclared(&installed)
+    } else {
+        Vec::new()
+    };
+
+    if missing.is_empty() && undeclared.is_empty() {
+        output.success("All declared packages are installed");
+        return Ok(());
+    }
+
+    if !missing.is_empty() {
+        output.info(&format!("Installing {} declared package(s): {}", missing.len(), missing.join(", ")));
+        manager.install(&missing).await?;
+    }
+
+    if !undeclared.is_empty() {
+        output.info(&format!("Removing {} undeclared package(s): {}", undeclared.len(), undeclared.join(", ")));
+        if skip_confirm || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Remove undeclared packages?")
+            .default(false)
+            .interact()?
+        {
+            manager.remove(&undeclared).await?;
+        }
+    }
+
+    output.success("Profile synced with declared packages");
+    Ok(())
+}
+
+/// Offer packages with names close to `name`, which is not in nixpkgs.
+/// Returns the one picked, or `None` if there is nothing close or the
+/// user declines; `-y` never picks on the user's behalf.
+async fn suggest_package(manager: &PackageManager, name: &str, yes: bool, output: &Output) -> Result<Option<String>> {
+    let pb = output.progress().spinner(&format!("{} not found, looking for similar packages...", name));
+    let candidates = manager.search(name).await;
+    pb.finish_and_clear();
+
+    let candidates = candidates.unwrap_or_default();
+    let found = search::SearchEngine::new().search_with_suggestions(name, &candidates)?;
+    let mut names: Vec<String> = if found.is_empty() {
+        found.suggestions
+    } else {
+        found.results.iter().map(|r| r.package.name.clone()).collect()
+    };
+    names.dedup();
+    names.truncate(5);
+
+    if names.is_empty() || yes {
+        return Ok(None);
+    }
+
+    let mut items = names.clone();
+    items.push("None of these".to_string());
+    let idx = dialoguer::Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("{} is not in nixpkgs. Did you mean", name))
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(names.get(idx).cloned())
+}
+
+/// List installed packages
+async fn list_installed(manager: &PackageManager, output: &Output) -> Result<()> {
+    if output.is_porcelain() {
+        output.print_profile_elements(&manager.profile_elements().await?);
+        return Ok(());
+    }
+
+    let installed = manager.list_installed().await?;
+    output.print_installed(&installed);
+    Ok(())
+}
+
+/// Search packages
+async fn search_packages(
+    manager: &PackageManager,
+    cli: &Cli,
+    config: &Config,
+    cache: Option<std::sync::Arc<cache::CacheManager>>,
+    output: &Output,
+) -> Result<()> {
+    let query = cli.targets.join(" ");
+
+    // NUR is searched alongside nixpkgs; a missing index only costs its results
+    let nur_search = async {
+        if !config.search.include_nur {
+            return Vec::new();
+        }
+        let mut nur = match cache.clone() {
+            Some(c) => NurClient::with_cache(c),
+            None => NurClient::new(),
+        }
+        .allow_stale(true)
+        .progress(output.progress().clone());
+        match nur.search(&query).await {
+            Ok(found) => found.into_iter().map(core::types::Package::from).collect(),
+            Err(e) => {
+                output.warn(&format!("Failed to search NUR: {}", e));
+                Vec::new()
+            }
+        }
+    };
+    let pb = output.progress().spinner(&format!("Searching for '{}'...", query));
+    let max_results = cli.max_results.unwrap_or(config.search.max_results);
+    let min_score = cli.min_score.unwrap_or(config.search.min_score);
+    let (packages, nur_packages) = tokio::join!(manager.search_limit(&query, max_results), nur_search);
+    let mut packages = match packages {
+        Ok(packages) => packages,
+        Err(e) => {
+            pb.finish_and_clear();
+            return Err(e.into());
+        }
+    };
+
+    if !config.sources.flakes.is_empty() {
+        pb.set_message("Searching configured flakes...");
+        let indexer = package::flakes::FlakeIndexer::new(manager.arch()).cache(cache);
+        packages.extend(indexer.index_all(&config.sources.flakes).await);
+    }
+
+    let filters = search::SearchFilters {
+        license: cli.license.clone(),
+        maintainer: cli.maintainer.clone(),
+        platform: cli.platform.clone(),
+    };
+    if !filters.is_empty() {
+        pb.set_message("Evaluating package metadata...");
+        if let Err(e) = manager.fill_metadata(&mut packages).await {
+            output.warn(&format!("Failed to evaluate package metadata: {}", e));
+        }
+    }
+    pb.finish_and_clear();
+
+    let engine = search::SearchEngine::with_settings(min_score, max_results)
+        .show_aliases(cli.show_aliases)
+        .scope(cli.scope.clone())
+        .filters(filters)
+        .exclude(config.search.default_excludes.iter().chain(&cli.exclude).cloned())
+        .install_history(install_counts(config));
+    let results = if nur_packages.is_empty() {
+        engine.search(&query, &packages)?
+    } else {
+        search::engine::MultiSourceSearch::with_engine(engine).search(&query, &packages, &nur_packages)?
+    };
+
+    if results.is_empty() && !output.is_porcelain() {
+        println!("No matches found.");
+    } else {
+        if cli.format == SearchFormat::Table || !cli.columns.is_empty() {
+            output.print_search_table(&results, &cli.columns);
+        } else {
+            output.print_grouped_results(&results, cli.scope.is_some(), 3);
+        }
+    }
+
+    Ok(())
+}
+
+/// Install counts for ranking search results, empty when `history_boost`
+/// is off or the history cannot be read
+fn install_counts(config: &Config) -> std::collections::HashMap<String, u32> {
+    if !config.search.history_boost {
+        return Default::default();
+    }
+    core::history::OperationLog::open_default()
+        .and_then(|log| log.install_counts())
+        .unwrap_or_else(|e| {
+            debug!("Search ranking without install history: {}", e);
+            Default::default()
+        })
+}
+
+/// Handle NUR operations
+async fn handle_nur(
+    manager: &PackageManager,
+    cli: &Cli,
+    cache: Option<std::sync::Arc<cache::CacheManager>>,
+    output: &Output,
+) -> Result<()> {
+    let targets = &cli.targets;
+
+    if targets.is_empty() {
+        output.error("No targets specified for NUR search");
+        return Ok(());
+    }
+
+    output.info("Searching NUR...");
+
+    // Searching an outdated index is harmless, so only warn when it cannot be refreshed
+    let mut nur = if let Some(c) = cache {
+        NurClient::with_cache(c)
+    } else {
+        NurClient::new()
+    }
+    .allow_stale(true)
+    .progress(output.progress().clone());
+
+    // Search NUR while the profile is read for the installed badges
+    let search = async {
+        let mut all_results = Vec::new();
+        for target in targets {
+            match nur.search(target).await {
+                Ok(results) => {
+                    for pkg in results {
+                        all_results.push(pkg.into());
+                    }
+                }
+                Err(e) => {
+                    output.warn(&format!("Failed to search NUR: {}", e));
+                }
+            }
+        }
+        all_results
+    };
+    let (all_results, installed) = tokio::join!(search, manager.profile_elements());
+    let installed = installed.unwrap_or_else(|e| {
+        debug!("Cannot mark installed NUR packages: {}", e);
+        Vec::new()
+    });
+
+    if all_results.is_empty() {
+        output.warn("No matches found in NUR");
+    } else {
+        output.print_nur_packages(&all_results, &installed);
+    }
+
+    Ok(())
+}
+
+/// Probe configured substituters by looking up Nix's own store path,
+/// using netrc and token credentials for private caches
+async fn check_substituters(output: &Output) -> Result<()> {
+    use network::substituter::{configured_substituters, store_hash, SubstituterClient};
+
+    let substituters = configured_substituters();
+    if substituters.is_empty() {
+        output.warn("No HTTP substituters configured");
+        return Ok(());
+    }
+
+    let nix = std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default())
+        .map(|dir| dir.join("nix"))
+        .find(|path| path.exists())
+        .and_then(|path| std::fs::canonicalize(path).ok());
+    let Some(hash) = nix.as_deref().and_then(store_hash) else {
+        output.error("Could not find Nix's store path to probe with");
+        return Ok(());
+    };
+
+    let config = Config::get();
+    let credentials = network::credentials::Credentials::for_hosts(&config.credentials, &substituters);
+    let http = network::HttpClient::from_config(config)
+        .max_retries(0)
+        .credentials(std::sync::Arc::new(credentials));
+    let client = SubstituterClient::new(http);
+    let probes = futures::future::join_all(substituters.iter().map(|s| client.probe(s, &hash))).await;
+
+    let rows = probes
+        .iter()
+        .map(|probe| {
+            let latency = probe.latency.map(|l| format!("{} ms", l.as_millis())).unwrap_or_else(|| "-".to_string());
+            let result = match (&probe.error, probe.hit) {
+                (Some(e), _) => e.clone(),
+                (None, true) => "hit".to_string(),
+                (None, false) => "miss".to_string(),
+            };
+            vec![probe.url.clone(), latency, result]
+        })
+        .collect();
+    output.print_table(vec!["Substituter", "Latency", "Result"], rows);
+    Ok(())
+}
+
+/// Install the latest release binary of `owner/repo`, patched to run on NixOS
+async fn fetch_release_binary(cli: &Cli, repo: &str, output: &Output) -> Result<()> {
+    use package::release::{install_expression, prefetch, select_asset, wrapper_expression, GithubRelease};
+
+    let Some((_, name)) = repo.split_once('/').filter(|(owner, name)| !owner.is_empty() && !name.is_empty()) else {
+        return Err(core::error::PackageError::InvalidSpec(format!("expected owner/repo, got '{}'", repo)).into());
+    };
+
+    let config = Config::get();
+    let http = network::HttpClient::from_config(config);
+    let release = GithubRelease::latest(&http, repo).await?;
+
+    let arch = PackageManager::new()?.arch().to_string();
+    let Some(asset) = select_asset(&release.assets, &arch) else {
+        let names: Vec<&str> = release.assets.iter().map(|a| a.name.as_str()).collect();
+        return Err(core::error::PackageError::NotFound {
+            name: format!("{} {} asset for {} (assets: {})", repo, release.tag_name, arch, names.join(", ")),
+        }.into());
+    };
+
+    output.info(&format!(
+        "Found {} {}: {} ({})",
+        repo,
+        release.tag_name,
+        asset.name,
+        system::garbage_collector::format_bytes(asset.size)
+    ));
+    output.info("Prefetching asset...");
+    let hash = prefetch(&asset.browser_download_url).await?;
+    let expr = wrapper_expression(name, release.version(), asset, &hash);
+
+    if cli.dry_run {
+        println!("{}", expr);
+        return Ok(());
+    }
+
+    output.warn("Release binaries are not built or reviewed by nixpkgs");
+    if !cli.yes && !Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Install {} {}?", name, release.version()))
+        .default(true)
+        .interact()?
+    {
+        return Ok(());
+    }
+
+    install_expression(name, &expr).await?;
+    output.success(&format!("Installed {} {}", name, release.version()));
+    Ok(())
+}
+
+/// Query and print how much each target adds to the store
+async fn show_install_sizes(manager: &PackageManager, targets: &[String], output: &Output) {
+    let pb = output.progress().spinner("Querying closure sizes...");
+    let sizes = manager.install_sizes(targets).await;
+    pb.finish_and_clear();
+    output.print_install_sizes(&sizes);
+}
+
+/// Install packages
+async fn install_packages(
+    manager: &PackageManager,
+    cli: &Cli,
+    config: &Config,
+    cache: Option<std::sync::Arc<cache::CacheManager>>,
+    output: &Output,
+) -> Result<()> {
+    // `alias#attr` targets come from private flakes configured under [sources.flakes]
+    let (private, public): (Vec<String>, Vec<String>) = cli.targets
+        .iter()
+        .cloned()
+        .partition(|t| config.sources.flake_ref(t).is_some());
+
+    if !private.is_empty() {
+        let refs: Vec<String> = private.iter().filter_map(|t| config.sources.flake_ref(t)).collect();
+        if cli.dry_run {
+            for flake_ref in &refs {
+                println!("  {} {}", style("→").cyan(), flake_ref);
+            }
+        } else {
+            output.info(&format!("Installing {} package(s) from private flakes...", refs.len()));
+            manager.install_refs(&refs).await?;
+            output.success(&format!("Installed {}", private.join(", ")));
+        }
+        if public.is_empty() {
+            return Ok(());
+        }
+    }
+
+    let pb = output.progress().spinner("Resolving packages...");
+    let targets = manager.resolve_targets(&public).await;
+    pb.finish_and_clear();
+    let targets = &targets?;
+    output.info(&format!("Installing {} package(s)...", targets.len()));
+
+    if cli.dry_run {
+        let pb = output.progress().spinner("Dry run - checking packages...");
+        let checks = manager.check_packages(targets).await;
+        pb.finish_and_clear();
+        let mut found = Vec::new();
+        for (pkg, exists) in checks {
+            if exists {
+                println!("  {} {}", style("✓").green(), pkg);
+                found.push(pkg);
+            } else {
+                println!("  {} {} (not found in nixpkgs)", style("?").yellow(), pkg);
+            }
+        }
+        if !found.is_empty() {
+            show_install_sizes(manager, &found, output).await;
+        }
+        return Ok(());
+    }
+
+    if cli.for_system.is_some() {
+        output.warn(&format!("--for-system only applies to queries; installing for {}", manager.arch()));
+    }
+
+    let mut nur = if let Some(c) = cache.clone() {
+        NurClient::with_cache(c)
+    } else {
+        NurClient::new()
+    }
+    .progress(output.progress().clone());
+
+    if cli.source == Some(InstallSource::Nur) {
+        let mut failed = Vec::new();
+        for target in targets {
+            if let Err(e) = install_from_nur(&mut nur, target, !cli.yes, output).await {
+                output.error(&format!("Failed to install {}: {}", target, e));
+                failed.push(target.clone());
+            }
+        }
+        return install_outcome(targets, &failed, output);
+    }
+
+    if config.general.disk_guard || config.general.memory_guard {
+        let installables: Vec<String> = targets.iter().map(|t| format!("nixpkgs#{}", t)).collect();
+        let pb = output.progress().spinner("Estimating download and build size...");
+        let estimate = system::DiskGuard::estimate(&installables);
+        pb.finish_and_clear();
+        match estimate {
+            Ok(estimate) => {
+                if config.general.disk_guard {
+                    system::DiskGuard::new(config.general.build_space_mb).check(&estimate)?;
+                }
+                if config.general.memory_guard {
+                    limit_build_memory(manager, &estimate.derivations, cli, output)?;
+                }
+            }
+            Err(e) => warn!("Skipping disk space and memory checks: {}", e),
+        }
+    }
+
+    // `--source nixpkgs` pins installs to nixpkgs regardless of config
+    let fallback = match cli.source {
+        Some(InstallSource::Nixpkgs) => NurFallback::Never,
+        _ => config.general.nur_fallback,
+    };
+
+    if !cli.skip_confirm() && std::io::stdin().is_terminal() {
+        show_install_sizes(manager, targets, output).await;
+        if !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Proceed with installation?")
+            .default(true)
+            .interact()?
+        {
+            return Ok(());
+        }
+    }
+
+    if cli.atomic {
+        let refs: Vec<String> = targets.iter().map(|t| format!("nixpkgs#{}", t)).collect();
+        return finish_transaction(manager.install_atomic(&refs).await?, cli, output);
+    }
+
+    // Try batch install first
+    let mut installed = Vec::new();
+    let mut failed = Vec::new();
+    match manager.install(targets).await {
+        Ok(()) => {
+            output.success(&format!("Installed {} package(s)", targets.len()));
+            installed.extend(targets.iter().cloned());
+        }
+        Err(_) => {
+            output.warn("Batch install failed, falling back to individual install...");
+
+            for target in targets {
+                output.info(&format!("Installing {}...", target));
+                
+                match manager.install(&[target.clone()]).await {
+                    Ok(()) => {
+                        output.success(&format!("Installed {}", target));
+                        installed.push(target.clone());
+                    }
+                    Err(e) => {
+                        // A build log means the package exists but failed to build
+                        if let Ok(log) = package::buildlog::fetch(&package::shell::nixpkgs_installable(target)) {
+                            output.error(&format!("Failed to build {}: {}", target, e));
+                            output.print_diagnoses(target, &package::diagnose::scan(&log));
+                            failed.push(target.clone());
+                            continue;
+                        }
+
+                        // Only a missing attribute falls back to NUR, not a
+                        // failed fetch or daemon error for a nixpkgs package
+                        if manager.package_exists(target).await {
+                            output.error(&format!("Failed to install {}: {}", target, e));
+                            failed.push(target.clone());
+                            continue;
+                        }
+
+                        let use_nur = match fallback {
+                            NurFallback::Auto => true,
+                            NurFallback::Never => false,
+                            // NUR packages are unreviewed, so `-y` does not imply consent
+                            NurFallback::Ask => !cli.yes,
+                        };
+
+                        if !use_nur {
+                            output.error(&format!("Failed to install {}: {}", target, e));
+                            if fallback != NurFallback::Auto {
+                                output.info("Use '--source nur' to install it from NUR explicitly");
+                            }
+                            failed.push(target.clone());
+                            continue;
+                        }
+
+                        output.warn(&format!("{} not found in nixpkgs, checking NUR...", target));
+                        match install_from_nur(&mut nur, target, !cli.yes, output).await {
+                            Ok(true) => installed.push(target.clone()),
+                            Ok(false) => {}
+                            Err(e) => {
+                                output.error(&format!("Failed to install {}: {}", target, e));
+                                failed.push(target.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    package::download::release(&installed);
+    if !cli.no_hints && !installed.is_empty() {
+        let hints = package::hints::Hints::load(cache.as_deref()).await;
+        output.print_hints(&hints.matching(&installed));
+    }
+    install_outcome(targets, &failed, output)
+}
+
+/// Finish an install of `targets` in which `failed` could not be
+/// installed; any failure makes the whole operation fail
+fn install_outcome(targets: &[String], failed: &[String], output: &Output) -> Result<()> {
+    if failed.is_empty() {
+        output.success("Operation finished");
+        return Ok(());
+    }
+    Err(core::error::PackageError::InstallFailed {
+        name: failed.join(", "),
+        reason: format!("{} of {} package(s) could not be installed", failed.len(), targets.len()),
+    }.into())
+}
+
+/// Warn when building `derivations` is likely to run out of memory and
+/// offer to build with fewer jobs and cores this time
+fn limit_build_memory(manager: &PackageManager, derivations: &[String], cli: &Cli, output: &Output) -> Result<()> {
+    use system::memory::{self, BuildParallelism};
+
+    if derivations.is_empty() {
+        return Ok(());
+    }
+    let Some(available) = memory::available_bytes() else {
+        return Ok(());
+    };
+    let Some(warning) = memory::check(derivations, BuildParallelism::detect(), available) else {
+        return Ok(());
+    };
+
+    output.warn(&format!("{} derivation(s) will be built locally: {}", derivations.len(), warning));
+    let reduce = cli.yes
+        || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Build with {} instead?", warning.suggested))
+            .default(true)
+            .interact()?;
+    if reduce {
+        manager.set_build_args(warning.suggested.args());
+    }
+    Ok(())
+}
+
+/// Rebuild the local nixpkgs index from `nix search`, returning the number
+/// of packages indexed
+async fn refresh_index(index: &search::index::LocalIndex, output: &Output) -> core::error::Result<usize> {
+    // Without a cache manager the dump is never served from cached results
+    let manager = PackageManager::new()?;
+    let pb = output.progress().spinner("dumping nixpkgs, this can take a few minutes...");
+    let packages = manager.search_nix("^").await;
+    pb.set_message("finding alias attributes...");
+    let non_aliases = manager.non_alias_attrs().await;
+    pb.finish_and_clear();
+    let mut packages = packages?;
+
+    match non_aliases {
+        Ok(attrs) => search::engine::mark_aliases(&mut packages, &attrs),
+        Err(e) => output.warn(&format!("Aliases will not be hidden from search: {}", e)),
+    }
+
+    let revision = package::revision::nixpkgs_revision(None).await;
+    index.replace(&packages, revision.as_deref())?;
+    Ok(packages.len())
 }
 
-/// Install packages
-async fn install_packages(
-    manager: &PackageManager,
+/// `nixboost maintain`: run the configured upkeep steps and report the disk
+/// space before and after
+async fn run_maintenance(cli: &Cli, skip: &[MaintainStep], dry_run: bool, output: &Output) -> Result<()> {
+    let config = &Config::get().maintain;
+    let steps = system::maintain::steps(config, skip);
+
+    if dry_run {
+        let names: Vec<String> = steps.iter().map(|s| system::maintain::step_name(*s)).collect();
+        output.info(&format!("Would run: {}", names.join(", ")));
+        return Ok(());
+    }
+
+    let cache = (!cli.no_cache && Config::get().cache.enabled)
+        .then(|| cache::CacheManager::new(Config::get().cache.memory_cache_size).ok())
+        .flatten();
+    let generations = GenerationManager::new(cli.target_profile(Config::get().general.mode));
+
+    let mut report = system::maintain::MaintainReport {
+        before: system::maintain::DiskSummary::collect(cache.as_ref()),
+        ..Default::default()
+    };
+
+    for step in steps {
+        let name = system::maintain::step_name(step);
+        let result = match step {
+            MaintainStep::CachePrune => system::maintain::prune_cache(cache.as_ref()),
+            MaintainStep::Generations => system::maintain::delete_generations(&generations, &Config::get().generations),
+            MaintainStep::Gc => {
+                let pb = output.progress().spinner("Collecting garbage...");
+                let result = system::maintain::collect_garbage();
+                pb.finish_and_clear();
+                result
+            }
+            MaintainStep::Optimise => {
+                let pb = output.progress().spinner("Optimising the store...");
+                let result = system::maintain::optimise_store();
+                pb.finish_and_clear();
+                result
+            }
+            MaintainStep::Index => match search::index::LocalIndex::open_default() {
+                Ok(index) => refresh_index(&index, output).await.map(|n| format!("Indexed {} packages", n)),
+                Err(e) => Err(e),
+            },
+        };
+        if let Err(ref e) = result {
+            warn!("Maintenance step {} failed: {}", name, e);
+        }
+        report.steps.push(system::maintain::StepOutcome::new(step, result));
+    }
+
+    report.after = system::maintain::DiskSummary::collect(cache.as_ref());
+    output.print_maintain_report(&report);
+
+    match report.failures() {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
+}
+
+/// `nixboost stage`: add to, review, apply or discard the staged changes
+async fn stage_changes(
     cli: &Cli,
-    cache: Option<std::sync::Arc<cache::CacheManager>>,
+    action: Option<&cli::args::StageAction>,
+    install: &[String],
+    remove: &[String],
     output: &Output,
 ) -> Result<()> {
-    let targets = &cli.targets;
-    output.info(&format!("Installing {} package(s)...", targets.len()));
+    use cli::args::StageAction;
+    use package::staging::Staging;
+
+    let mut staging = Staging::load()?;
+    match action {
+        None if install.is_empty() && remove.is_empty() => output.print_staged(&staging),
+        None => {
+            for pkg in install {
+                staging.stage_install(pkg);
+            }
+            for pkg in remove {
+                staging.stage_remove(pkg);
+            }
+            staging.save()?;
+            output.print_staged(&staging);
+            output.info(&format!("{} change(s) staged; 'nixboost stage apply' applies them", staging.len()));
+        }
+        Some(StageAction::Show) => output.print_staged(&staging),
+        Some(StageAction::Clear) => {
+            Staging::default().save()?;
+            output.success(&format!("Discarded {} staged change(s)", staging.len()));
+        }
+        Some(StageAction::Apply) => {
+            if staging.is_empty() {
+                output.info("Nothing staged");
+                return Ok(());
+            }
+            output.print_staged(&staging);
+            if cli.dry_run {
+                return Ok(());
+            }
+            if !cli.yes
+                && !Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Apply these changes?")
+                    .default(true)
+                    .interact()?
+            {
+                return Ok(());
+            }
+
+            let result = PackageManager::new()?.with_profile(cli.package_profile(Config::get().general.mode)).apply_staged(&staging).await?;
+            if result.error.is_none() {
+                Staging::default().save()?;
+            }
+            return finish_transaction(result, cli, output);
+        }
+    }
+    Ok(())
+}
+
+/// Report an `--atomic` install or a staged transaction, offering to
+/// restore the pre-transaction generation when it failed part-way
+fn finish_transaction(result: core::types::OperationResult, cli: &Cli, output: &Output) -> Result<()> {
+    let Some(error) = result.error else {
+        match result.operation {
+            OperationType::Install => output.success(&format!("Installed {} package(s)", result.packages.len())),
+            _ => output.success(&format!("Applied {} change(s)", result.packages.len())),
+        }
+        return Ok(());
+    };
+
+    if let Some(message) = &result.message {
+        output.warn(message);
+    }
+    if let Some(generation) = result.rollback_to {
+        let rollback = cli.yes
+            || Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Roll back to generation {} from before the transaction?", generation))
+                .default(true)
+                .interact()?;
+        if rollback {
+            GenerationManager::new(cli.target_profile(Config::get().general.mode)).rollback_to(generation)?;
+            output.success(&format!("Rolled back to generation {}", generation));
+        } else {
+            output.info(&format!("Kept partial changes; 'nixboost generation rollback {}' undoes them", generation));
+        }
+    }
+
+    let error = match result.operation {
+        OperationType::Install => core::error::PackageError::InstallFailed {
+            name: result.packages.join(", "),
+            reason: error,
+        },
+        _ => core::error::PackageError::TransactionFailed(error),
+    };
+    Err(core::error::NixBoostError::from(error).into())
+}
+
+/// Browse packages in the terminal UI, then apply the marked changes
+#[cfg(feature = "tui")]
+async fn browse_packages(cli: &Cli, output: &Output) -> Result<()> {
+    use core::types::{Package, PackageSource};
+
+    let config = Config::get();
+    let cache = if !cli.no_cache && config.cache.enabled {
+        cache::CacheManager::new(config.cache.memory_cache_size).ok().map(std::sync::Arc::new)
+    } else {
+        None
+    };
+    let manager = match &cache {
+        Some(c) => PackageManager::with_cache(c.clone())?,
+        None => PackageManager::new()?,
+    }
+    .with_profile(cli.package_profile(config.general.mode));
+    let mut nur = match &cache {
+        Some(c) => NurClient::with_cache(c.clone()),
+        None => NurClient::new(),
+    }
+    .allow_stale(true)
+    .progress(output.progress().clone());
+
+    // Seeding from `nix search` would evaluate all of nixpkgs up front
+    let index = search::index::LocalIndex::open_default()?;
+    if !index.is_populated() {
+        return Err(core::error::SearchError::IndexNotAvailable.into());
+    }
+
+    let pb = output.progress().spinner("loading package index...");
+    let mut packages = index.all()?;
+    match nur.search("").await {
+        Ok(nur_packages) => packages.extend(nur_packages.into_iter().map(Package::from)),
+        Err(e) => warn!("NUR packages unavailable: {}", e),
+    }
+    if !config.sources.flakes.is_empty() {
+        let indexer = package::flakes::FlakeIndexer::new(manager.arch()).cache(cache.clone());
+        packages.extend(indexer.index_all(&config.sources.flakes).await);
+    }
+    let installed = manager.list_installed().await.unwrap_or_default().into_iter().collect();
+    pb.finish_and_clear();
+
+    let mut app = ui::tui::App::new(packages, installed)
+        .max_results(cli.max_results.unwrap_or(config.search.max_results));
+    let Some(tx) = ui::tui::run(&mut app)? else {
+        return Ok(());
+    };
 
+    for name in &tx.remove {
+        println!("  {} {}", style("-").red(), name);
+    }
+    for pkg in &tx.install {
+        println!("  {} {}", style("+").green(), pkg.display_name());
+    }
     if cli.dry_run {
-        output.info("Dry run - checking packages...");
-        let checks = manager.check_packages(targets).await;
-        for (pkg, exists) in checks {
-            if exists {
-                println!("  {} {}", style("✓").green(), pkg);
-            } else {
-                println!("  {} {} (not found in nixpkgs)", style("?").yellow(), pkg);
+        return Ok(());
+    }
+    if !cli.yes
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Apply these changes?")
+            .default(true)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    if !tx.remove.is_empty() {
+        manager.remove(&tx.remove).await?;
+        output.success(&format!("Removed {} package(s)", tx.remove.len()));
+    }
+
+    let mut nixpkgs = Vec::new();
+    let mut refs = Vec::new();
+    for pkg in &tx.install {
+        match &pkg.source {
+            PackageSource::Nur { .. } => {
+                let target = pkg.attr_path.clone().unwrap_or_else(|| pkg.name.clone());
+                install_from_nur(&mut nur, &target, !cli.yes, output).await?;
+            }
+            PackageSource::Private { url, .. } | PackageSource::Flake { url } => {
+                refs.push(format!("{}#{}", url, pkg.name));
+            }
+            _ => nixpkgs.push(pkg.name.clone()),
+        }
+    }
+    if !refs.is_empty() {
+        manager.install_refs(&refs).await?;
+    }
+    if !nixpkgs.is_empty() {
+        manager.install(&nixpkgs).await?;
+    }
+    if !refs.is_empty() || !nixpkgs.is_empty() {
+        output.success(&format!("Installed {} package(s)", refs.len() + nixpkgs.len()));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "tui"))]
+async fn browse_packages(_cli: &Cli, output: &Output) -> Result<()> {
+    output.error("nixboost was built without the `tui` feature");
+    Ok(())
+}
+
+/// Install a package from NUR after warning that it builds from source.
+/// Returns whether it was installed; `false` when the user declined.
+async fn install_from_nur(nur: &mut NurClient, target: &str, confirm: bool, output: &Output) -> Result<bool> {
+    let attr_path = nur.resolve_attr(target).await?;
+
+    output.warn(&format!(
+        "{} comes from NUR, which has no binary cache: it will be built from source",
+        attr_path
+    ));
+    match system::DiskGuard::estimate(&[NurClient::installable(&attr_path)]) {
+        Ok(estimate) => {
+            output.info(&format!(
+                "{} derivation(s) to build, {} path(s) to fetch ({})",
+                estimate.builds,
+                estimate.fetches,
+                system::garbage_collector::format_bytes(estimate.download_bytes)
+            ));
+            let general = &Config::get().general;
+            if general.disk_guard {
+                system::DiskGuard::new(general.build_space_mb).check(&estimate)?;
             }
         }
+        Err(e) => warn!("Could not estimate build inputs for {}: {}", attr_path, e),
+    }
+    if system::capabilities::sandbox_enabled() == Some(false) {
+        output.warn("The Nix build sandbox is disabled: this package's build script runs with access to your system");
+    }
+
+    if confirm && !Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Install {} from NUR (unreviewed)?", attr_path))
+        .default(false)
+        .interact()?
+    {
+        output.info(&format!("Skipped {}", attr_path));
+        return Ok(false);
+    }
+
+    nur.install_attr(&attr_path)?;
+    output.success(&format!("Installed {}", attr_path));
+    Ok(true)
+}
+
+/// Realise `packages` into the store without installing them (`--download-only`)
+async fn download_packages(
+    manager: &PackageManager,
+    packages: &[String],
+    refresh: bool,
+    cli: &Cli,
+    output: &Output,
+) -> Result<()> {
+    if packages.is_empty() {
+        output.info("Nothing to download");
+        return Ok(());
+    }
+    if cli.dry_run {
+        for pkg in packages {
+            println!("  {} {}", style("↓").cyan(), pkg);
+        }
         return Ok(());
     }
 
-    // Try batch install first
-    match manager.install(targets).await {
-        Ok(()) => {
-            output.success(&format!("Installed {} package(s)", targets.len()));
+    let pb = output.progress().spinner(&format!("Downloading {} package(s)...", packages.len()));
+    let downloaded = manager.download(packages, refresh).await;
+    pb.finish_and_clear();
+    let downloaded = downloaded?;
+    for (pkg, path) in &downloaded {
+        println!("  {} {} {}", style("↓").cyan(), pkg, style(path).dim());
+    }
+    output.success(&format!(
+        "Downloaded {} package(s); install them later with 'nixboost -S', no download needed",
+        downloaded.len()
+    ));
+    Ok(())
+}
+
+/// Fetch and print changelogs and nixpkgs commits for the packages `preview` changes
+async fn show_release_notes(manager: &PackageManager, preview: &package::upgrade::UpgradePreview, output: &Output) {
+    let http = network::HttpClient::from_config(Config::get());
+    let pb = output.progress().spinner("Fetching release notes...");
+    let notes = manager.release_notes(preview, &http).await;
+    pb.finish_and_clear();
+    match notes {
+        Ok(notes) => output.print_release_notes(&notes),
+        Err(e) => warn!("Release notes unavailable: {}", e),
+    }
+}
+
+/// Upgrade installed packages (`-Su`)
+async fn upgrade_packages(manager: &PackageManager, cli: &Cli, output: &Output) -> Result<()> {
+    if cli.download_only {
+        let (nixpkgs, _) = manager.nixpkgs_elements().await?;
+        let attrs: Vec<String> = nixpkgs
+            .into_iter()
+            .filter(|(element, _, _)| cli.targets.is_empty() || cli.targets.contains(element))
+            .map(|(_, attr, _)| attr)
+            .collect();
+        return download_packages(manager, &attrs, true, cli, output).await;
+    }
+
+    if cli.dry_run {
+        output.info("Dry run - evaluating profile against the latest nixpkgs revision...");
+        let preview = manager.preview_upgrade().await?;
+        output.print_upgrade_preview(&preview);
+        if !output.is_structured() {
+            show_release_notes(manager, &preview, output).await;
         }
-        Err(_) => {
-            output.warn("Batch install failed, falling back to individual install...");
-            
-            let mut nur = if let Some(c) = cache {
-                NurClient::with_cache(c)
-            } else {
-                NurClient::new()
-            };
+        return Ok(());
+    }
 
-            for target in targets {
-                output.info(&format!("Installing {}...", target));
-                
-                match manager.install(&[target.clone()]).await {
-                    Ok(()) => {
-                        output.success(&format!("Installed {}", target));
-                    }
-                    Err(_) => {
-                        output.warn(&format!("{} not found in nixpkgs, checking NUR...", target));
-                        
-                        if let Err(e) = nur.install(target).await {
-                            output.error(&format!("Failed to install {}: {}", target, e));
-                        }
-                    }
-                }
+    // `nix profile upgrade` fails on attributes that were renamed or removed upstream
+    let pb = output.progress().spinner("Checking for renamed packages...");
+    let dead = manager.dead_attributes().await;
+    pb.finish_and_clear();
+    let dead = match dead {
+        Ok(dead) => dead,
+        Err(e) => {
+            warn!("Skipping renamed package check: {}", e);
+            Vec::new()
+        }
+    };
+    let dead: Vec<_> = dead
+        .into_iter()
+        .filter(|d| cli.targets.is_empty() || cli.targets.contains(&d.element))
+        .collect();
+    for d in &dead {
+        let Some(ref replacement) = d.replacement else {
+            output.warn(&format!("{} was removed from nixpkgs, skipping it", d.attr));
+            continue;
+        };
+        output.warn(&format!("{} was renamed to {} in nixpkgs", d.attr, replacement));
+        if cli.yes || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Migrate {} to {}?", d.element, replacement))
+            .default(true)
+            .interact()?
+        {
+            manager.migrate(&d.element, replacement).await?;
+            output.success(&format!("Migrated {} to {}", d.element, replacement));
+        }
+    }
+
+    let targets: Vec<String> = if dead.is_empty() {
+        cli.targets.clone()
+    } else {
+        let skip = |name: &String| dead.iter().any(|d| &d.element == name);
+        let names = if cli.targets.is_empty() {
+            manager.profile_elements().await?.into_iter().map(|el| el.name).collect()
+        } else {
+            cli.targets.clone()
+        };
+        let remaining: Vec<String> = names.into_iter().filter(|n| !skip(n)).collect();
+        if remaining.is_empty() {
+            output.success("Nothing left to upgrade");
+            return Ok(());
+        }
+        remaining
+    };
+
+    let general = &Config::get().general;
+    if general.disk_guard {
+        let (nixpkgs, _) = manager.nixpkgs_elements().await?;
+        let installables: Vec<String> = nixpkgs
+            .into_iter()
+            .filter(|(element, _, _)| targets.is_empty() || targets.contains(element))
+            .map(|(_, attr, _)| format!("nixpkgs#{}", attr))
+            .collect();
+        if !installables.is_empty() {
+            let pb = output.progress().spinner("Estimating download and build size...");
+            let estimate = system::DiskGuard::estimate(&installables);
+            pb.finish_and_clear();
+            match estimate {
+                Ok(estimate) => system::DiskGuard::new(general.build_space_mb).check(&estimate)?,
+                Err(e) => warn!("Skipping disk space check: {}", e),
             }
         }
     }
 
-    output.success("Operation finished");
+    let what = if cli.targets.is_empty() {
+        "all packages".to_string()
+    } else {
+        targets.join(", ")
+    };
+    if !cli.yes && !output.is_structured() {
+        let pb = output.progress().spinner("Checking what would change...");
+        let preview = manager.preview_upgrade().await;
+        pb.finish_and_clear();
+        match preview {
+            Ok(mut preview) => {
+                preview.changes.retain(|c| cli.targets.is_empty() || targets.contains(&c.name));
+                show_release_notes(manager, &preview, output).await;
+            }
+            Err(e) => warn!("Skipping release notes: {}", e),
+        }
+    }
+    if !cli.yes && !Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Upgrade {}?", what))
+        .default(true)
+        .interact()?
+    {
+        return Ok(());
+    }
+
+    output.info(&format!("Upgrading {}...", what));
+    let result = manager.upgrade(&targets).await?;
+    output.print_upgrade_preview(&result);
+    output.success("Upgrade finished");
     Ok(())
 }
 
@@ -516,9 +2357,59 @@ async fn remove_packages(manager: &PackageManager, cli: &Cli, output: &Output) -
         return Ok(());
     }
 
+    let elements = manager.profile_elements().await?;
+    let mut selected: Vec<ProfileElement> = Vec::new();
+    let mut missing: Vec<String> = Vec::new();
+    for target in targets {
+        let element = match resolve_element(&elements, target) {
+            ElementMatch::One(el) => el,
+            ElementMatch::None => {
+                output.warn(&format!("'{}' is not installed", target));
+                missing.push(target.clone());
+                continue;
+            }
+            ElementMatch::Ambiguous(candidates) => {
+                let labels: Vec<String> = candidates
+                    .iter()
+                    .map(|el| {
+                        let detail = el.attr_path.as_deref().or(el.store_paths.first().map(|p| p.as_str()));
+                        format!("{} ({})", el.name, detail.unwrap_or("?"))
+                    })
+                    .collect();
+                if cli.yes {
+                    return Err(core::error::PackageError::Ambiguous {
+                        query: target.clone(),
+                        candidates: labels.join(", "),
+                    }.into());
+                }
+                let idx = dialoguer::Select::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!("'{}' matches several packages, which one?", target))
+                    .items(&labels)
+                    .default(0)
+                    .interact()?;
+                candidates[idx]
+            }
+        };
+        if !selected.iter().any(|el| el.name == element.name) {
+            selected.push(element.clone());
+        }
+    }
+
+    let not_installed = || -> Result<()> {
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(core::error::PackageError::NotInstalled { name: missing.join(", ") }.into())
+    };
+
+    // Every target was reported missing above
+    if selected.is_empty() {
+        return not_installed();
+    }
+
     println!("{} The following packages will be removed:", style("::").bold().yellow());
-    for t in targets {
-        println!("   {}", t);
+    for el in &selected {
+        println!("   {}", el.name);
     }
 
     if !cli.skip_confirm() {
@@ -537,20 +2428,16 @@ async fn remove_packages(manager: &PackageManager, cli: &Cli, output: &Output) -
         return Ok(());
     }
 
-    output.info(&format!("Removing {} package(s)...", targets.len()));
-
-    if let Err(e) = manager.remove(targets).await {
-        output.error(&format!("Failed to remove packages: {}", e));
-    } else {
-        output.success("Packages removed");
-    }
+    output.info(&format!("Removing {} package(s)...", selected.len()));
 
-    Ok(())
+    manager.remove_elements(&selected).await?;
+    output.success("Packages removed");
+    not_installed()
 }
 
 /// Show cache statistics
 fn show_cache_stats(output: &Output) -> Result<()> {
-    match cache::CacheManager::new(100) {
+    match cache::CacheManager::new(Config::get().cache.memory_cache_size) {
         Ok(cache) => {
             let stats = cache.stats();
             println!("{}", style(":: Cache Statistics").bold());
@@ -567,34 +2454,215 @@ fn show_cache_stats(output: &Output) -> Result<()> {
 }
 
 /// Show nix generation history
-fn show_history(output: &Output) -> Result<()> {
+fn show_history(profile: Profile, output: &Output) -> Result<()> {
     output.info("Generation history (last 20):");
-    let generations = GenerationManager::list(20)?;
-    GenerationManager::print_list(&generations);
+    let generations = GenerationManager::new(profile).list(20)?;
+    output.print_generations(&generations);
     Ok(())
 }
 
 /// Run health check
-fn run_health_check(output: &Output) -> Result<()> {
-    output.info("Running system health check...");
-    let report = HealthChecker::run()?;
-    report.print();
+fn run_health_check(full: bool, output: &Output) -> Result<()> {
+    let mode = if full { HealthMode::Full } else { HealthMode::Quick };
+    let pb = output.progress().spinner(&format!("Running {} system health check...", if full { "full" } else { "quick" }));
+    let report = HealthChecker::run_mode(mode);
+    pb.finish_and_clear();
+    report?.print();
+    Ok(())
+}
+
+/// Classify store issues and apply the repairs the user picks
+fn triage_store(cli: &Cli, output: &Output) -> Result<()> {
+    use dialoguer::MultiSelect;
+    use system::triage;
+
+    let pb = output.progress().spinner("Verifying Nix store (this may take a while)...");
+    let (ok, lines) = HealthChecker::check_nix_store(true);
+    pb.finish_and_clear();
+    if ok {
+        output.success("Nix store is healthy, nothing to repair");
+        return Ok(());
+    }
+
+    let issues = triage::triage(&lines);
+    for issue in &issues {
+        let action = issue.action().map(|a| a.to_string()).unwrap_or_else(|| "manual".to_string());
+        println!("  {} [{}] {}", style(format!("{:<14}", issue.kind)).yellow(), action, issue.message);
+    }
+
+    let fixable: Vec<&triage::StoreIssue> = issues.iter().filter(|i| i.action().is_some()).collect();
+    if fixable.is_empty() {
+        output.warn("No issues can be repaired automatically");
+        return Ok(());
+    }
+    if cli.dry_run {
+        output.info(&format!("Dry run - {} issue(s) could be repaired", fixable.len()));
+        return Ok(());
+    }
+
+    let chosen: Vec<usize> = if cli.yes {
+        (0..fixable.len()).collect()
+    } else {
+        let labels: Vec<String> = fixable
+            .iter()
+            .map(|i| format!("{} {}", i.action().unwrap(), i.path.as_deref().unwrap_or_default()))
+            .collect();
+        MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select repairs to run")
+            .items(&labels)
+            .defaults(&vec![true; labels.len()])
+            .interact()?
+    };
+
+    let mut repaired = 0;
+    let mut failed = 0;
+    for &idx in &chosen {
+        match fixable[idx].repair() {
+            Ok(()) => repaired += 1,
+            Err(e) => {
+                output.error(&format!("{}", e));
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(core::error::SystemError::StoreVerificationFailed(format!(
+            "{} of {} repair(s) failed",
+            failed,
+            chosen.len()
+        )).into());
+    }
+    output.success(&format!("Repaired {} issue(s)", repaired));
+    Ok(())
+}
+
+/// Build the system, warn about critical changes, then switch.
+///
+/// With `watchdog`, a rollback is scheduled before switching and cancelled
+/// only once the user confirms the new system within the given time.
+fn rebuild_system(
+    cli: &Cli,
+    upgrade: bool,
+    allow_critical: bool,
+    watchdog: Option<Duration>,
+    output: &Output,
+) -> Result<()> {
+    output.info("Building system configuration...");
+    let build = system::rebuild::SystemBuild::build(upgrade)?;
+    if upgrade {
+        // --upgrade updated the channels
+        if let Ok(cache) = cache::CacheManager::new(Config::get().cache.memory_cache_size) {
+            cache.on(cache::Invalidation::ChannelUpdated);
+        }
+    }
+
+    if build.is_current() {
+        output.success("No changes relative to the running system");
+        return Ok(());
+    }
+    if build.changes.is_empty() {
+        output.info("No package changes; the system configuration changed");
+    }
+    for change in &build.changes {
+        println!("  {}", change);
+    }
+
+    let critical = build.critical_changes();
+    if !critical.is_empty() {
+        println!();
+        println!("{}", style("!! This rebuild changes critical system components:").red().bold());
+        for change in &critical {
+            let kind = change.critical.map(|k| k.to_string()).unwrap_or_default();
+            println!("   {} {}", style(format!("[{}]", kind)).red().bold(), change);
+        }
+        println!("{}", style("   Keep the previous generation available in the boot menu in case the new one fails.").yellow());
+    }
+
+    if cli.dry_run {
+        output.info("Dry run - not switching");
+        return Ok(());
+    }
+
+    // -y does not cover critical changes; those need --allow-critical or an explicit answer
+    let confirmed = if !critical.is_empty() && !allow_critical {
+        Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Switch to a configuration with kernel/bootloader/driver changes?")
+            .default(false)
+            .interact()?
+    } else {
+        cli.yes || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Switch to the new configuration?")
+            .default(true)
+            .interact()?
+    };
+
+    if !confirmed {
+        output.info(&format!("Aborted; the built system is left at {}", build.path.display()));
+        return Ok(());
+    }
+
+    let Some(timeout) = watchdog else {
+        build.switch()?;
+        output.success("Switched to the new system configuration");
+        return Ok(());
+    };
+
+    let guard = system::watchdog::RollbackWatchdog::capture(std::path::Path::new(system::rebuild::CURRENT_SYSTEM))?;
+    guard.arm(timeout)?;
+    output.warn(&format!(
+        "Automatic rollback in {}s unless confirmed (from another session: 'nixboost system confirm')",
+        timeout.as_secs()
+    ));
+
+    if let Err(e) = build.switch() {
+        output.error("Switch failed, rolling back now");
+        if let Err(rollback) = guard.trigger() {
+            output.error(&rollback.to_string());
+        }
+        return Err(e.into());
+    }
+
+    let healthy = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Is the new system working? Keep it")
+        .default(false)
+        .interact()?;
+
+    if healthy {
+        if system::watchdog::disarm()? {
+            output.success("Configuration confirmed, automatic rollback cancelled");
+        } else {
+            output.error("The rollback already ran; the previous configuration is active");
+        }
+    } else {
+        output.info("Rolling back to the previous configuration...");
+        guard.trigger()?;
+    }
     Ok(())
 }
 
 /// Run garbage collection
-fn run_garbage_collection(cli: &Cli, output: &Output) -> Result<()> {
+fn run_garbage_collection(cli: &Cli, cache: Option<&cache::CacheManager>, output: &Output) -> Result<()> {
+    if cli.older_than.is_some() || cli.free_at_least.is_some() {
+        let options = GcOptions {
+            keep_generations: 0,
+            older_than: cli.older_than,
+            free_at_least: cli.free_at_least,
+            retention: None,
+        };
+        return collect_with_plan(&options, cli.dry_run, cli.yes, output);
+    }
+
     if cli.dry_run {
-        let preview = GarbageCollector::preview()?;
-        output.info(&format!(
-            "Would delete {} paths, freeing {}",
-            preview.paths.len(),
-            preview.size_human()
-        ));
+        let pb = output.progress().spinner("Finding dead store paths...");
+        let preview = GarbageCollector::preview(cache);
+        pb.finish_and_clear();
+        output.print_gc_preview(&preview?);
     } else {
-        output.info("Collecting garbage...");
-        let result = GarbageCollector::run()?;
-        GarbageCollector::print_result(&result);
+        let pb = output.progress().spinner("Collecting garbage...");
+        let result = GarbageCollector::run();
+        pb.finish_and_clear();
+        output.print_gc_result(&result?);
     }
     Ok(())
 }
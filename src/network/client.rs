@@ -18,15 +18,26 @@
 
 use crate::core::config::Config;
 use crate::core::error::{NetworkError, Result};
-use reqwest::{Client, Response};
+use crate::network::credentials::Credentials;
+use crate::ui::progress::ProgressManager;
+use indicatif::ProgressBar;
+use reqwest::{Client, RequestBuilder, Response};
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::{debug, warn};
 
+/// Whole-request limit for bodies streamed with progress; the client's
+/// timeout also covers the body and is too short for large files
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+
 /// HTTP client with retry logic
 pub struct HttpClient {
     client: Client,
     max_retries: u32,
     retry_delay: Duration,
+    credentials: Option<Arc<Credentials>>,
+    /// Log failed attempts at debug level only
+    quiet: bool,
 }
 
 impl HttpClient {
@@ -45,6 +56,8 @@ impl HttpClient {
             client,
             max_retries: 3,
             retry_delay: Duration::from_secs(1),
+            credentials: None,
+            quiet: false,
         }
     }
 
@@ -69,6 +82,8 @@ impl HttpClient {
             client,
             max_retries: config.network.max_retries,
             retry_delay: Duration::from_millis(config.network.retry_delay_ms),
+            credentials: None,
+            quiet: false,
         }
     }
 
@@ -84,9 +99,45 @@ impl HttpClient {
         self
     }
 
+    /// Don't warn about failed attempts, for background requests
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Attach credentials for private hosts; only the substituter client
+    /// does, so other requests never carry them
+    pub fn credentials(mut self, credentials: Arc<Credentials>) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
     /// GET request with retry
     pub async fn get(&self, url: &str) -> Result<Response> {
-        self.request_with_retry(|| self.client.get(url).send()).await
+        self.request_with_retry(|| self.request(url).send()).await
+    }
+
+    /// GET request with retry, streaming the body while `progress` shows a
+    /// download bar labelled `label`, or a spinner when the server sends no
+    /// content length. The bar is cleared even if the future is dropped.
+    pub async fn get_with_progress(&self, url: &str, progress: &ProgressManager, label: &str) -> Result<Vec<u8>> {
+        let mut response = self
+            .request_with_retry(|| self.request(url).timeout(DOWNLOAD_TIMEOUT).send())
+            .await?;
+
+        let (pb, mut body) = match response.content_length() {
+            Some(total) => (ClearOnDrop(progress.download(total, label)), Vec::with_capacity(total as usize)),
+            None => (ClearOnDrop(progress.spinner(&format!("Downloading {}...", label))), Vec::new()),
+        };
+
+        while let Some(chunk) = response.chunk().await
+            .map_err(|e| NetworkError::DownloadFailed(e.to_string()))?
+        {
+            body.extend_from_slice(&chunk);
+            pb.0.inc(chunk.len() as u64);
+        }
+
+        Ok(body)
     }
 
     /// GET request returning body as string with retry
@@ -97,14 +148,6 @@ impl HttpClient {
         Ok(text)
     }
 
-    /// GET request returning body as bytes with retry
-    pub async fn get_bytes(&self, url: &str) -> Result<Vec<u8>> {
-        let response = self.get(url).await?;
-        let bytes = response.bytes().await
-            .map_err(|e| NetworkError::DownloadFailed(e.to_string()))?;
-        Ok(bytes.to_vec())
-    }
-
     /// GET request returning JSON with retry
     pub async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
         let response = self.get(url).await?;
@@ -113,6 +156,15 @@ impl HttpClient {
         Ok(json)
     }
 
+    /// GET request for `url` with its host's credentials
+    fn request(&self, url: &str) -> RequestBuilder {
+        let request = self.client.get(url);
+        match self.credentials {
+            Some(ref credentials) => credentials.apply(request, url),
+            None => request,
+        }
+    }
+
     /// Execute a request with retry logic
     async fn request_with_retry<F, Fut>(&self, make_request: F) -> Result<Response>
     where
@@ -152,7 +204,11 @@ impl HttpClient {
                             .and_then(|v| v.to_str().ok())
                             .and_then(|s| s.parse::<u64>().ok())
                         {
-                            warn!("Rate limited, waiting {}s", retry_after);
+                            if self.quiet {
+                                debug!("Rate limited, waiting {}s", retry_after);
+                            } else {
+                                warn!("Rate limited, waiting {}s", retry_after);
+                            }
                             delay = Duration::from_secs(retry_after);
                         }
                     }
@@ -170,7 +226,11 @@ impl HttpClient {
                     } else {
                         last_error = Some(NetworkError::DownloadFailed(e.to_string()));
                     }
-                    warn!("Request failed: {}", e);
+                    if self.quiet {
+                        debug!("Request failed: {}", e);
+                    } else {
+                        warn!("Request failed: {}", e);
+                    }
                 }
             }
         }
@@ -184,6 +244,15 @@ impl HttpClient {
     }
 }
 
+/// Clears a progress bar when it goes out of scope
+struct ClearOnDrop(ProgressBar);
+
+impl Drop for ClearOnDrop {
+    fn drop(&mut self) {
+        self.0.finish_and_clear();
+    }
+}
+
 impl Default for HttpClient {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,233 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Credentials for private substituters: netrc entries and bearer tokens.
+
+use crate::core::config::CredentialsConfig;
+use crate::system::capabilities::nix_setting;
+use reqwest::RequestBuilder;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tracing::{debug, warn};
+
+/// Login from a netrc `machine` entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetrcEntry {
+    pub login: String,
+    pub password: String,
+}
+
+/// Credentials for a fixed set of hosts, e.g. the configured substituters.
+/// Requests to any other host go out without them, so a netrc `default`
+/// entry never reaches unrelated servers.
+#[derive(Debug, Default)]
+pub struct Credentials {
+    config: CredentialsConfig,
+    hosts: HashSet<String>,
+    /// Read on the first request to one of `hosts`
+    logins: OnceLock<Logins>,
+}
+
+/// Tokens and netrc entries by host
+#[derive(Debug, Clone, Default)]
+struct Logins {
+    tokens: HashMap<String, String>,
+    machines: HashMap<String, NetrcEntry>,
+    default: Option<NetrcEntry>,
+}
+
+impl Credentials {
+    /// Credentials from `config` and the netrc file Nix uses, sent only to
+    /// the hosts of `urls`. Nothing is read until a request needs them.
+    pub fn for_hosts(config: &CredentialsConfig, urls: &[String]) -> Self {
+        Self {
+            config: config.clone(),
+            hosts: urls.iter().filter_map(|url| host_of(url)).map(String::from).collect(),
+            logins: OnceLock::new(),
+        }
+    }
+
+    /// Attach credentials for `url`'s host. A token wins over a netrc entry.
+    pub fn apply(&self, request: RequestBuilder, url: &str) -> RequestBuilder {
+        let Some(host) = host_of(url).filter(|host| self.hosts.contains(*host)) else {
+            return request;
+        };
+        let logins = self.logins.get_or_init(|| Logins::load(&self.config));
+
+        if let Some(token) = logins.tokens.get(host) {
+            return request.bearer_auth(token);
+        }
+
+        match logins.machines.get(host).or(logins.default.as_ref()) {
+            Some(entry) => request.basic_auth(&entry.login, Some(&entry.password)),
+            None => request,
+        }
+    }
+}
+
+impl Logins {
+    /// Load tokens from config and the netrc file.
+    ///
+    /// The netrc file is `credentials.netrc_file`, else Nix's `netrc-file`
+    /// setting, else `~/.netrc`.
+    fn load(config: &CredentialsConfig) -> Self {
+        let mut logins = Self::default();
+
+        for (host, token) in &config.tokens {
+            match resolve_secret(token) {
+                Some(token) => {
+                    logins.tokens.insert(host.clone(), token);
+                }
+                None => warn!("Token for {} references an unset environment variable", host),
+            }
+        }
+
+        let netrc = config.netrc_file.clone()
+            .or_else(|| nix_setting("netrc-file").map(PathBuf::from))
+            .or_else(|| dirs::home_dir().map(|h| h.join(".netrc")));
+        if let Some(path) = netrc {
+            match std::fs::read_to_string(&path) {
+                Ok(content) => {
+                    debug!("Loaded netrc from {:?}", path);
+                    (logins.machines, logins.default) = parse_netrc(&content);
+                }
+                Err(e) => debug!("No netrc at {:?}: {}", path, e),
+            }
+        }
+
+        logins
+    }
+}
+
+/// `env:NAME` reads the variable `NAME`; anything else is used literally
+fn resolve_secret(value: &str) -> Option<String> {
+    match value.strip_prefix("env:") {
+        Some(var) => std::env::var(var).ok(),
+        None => Some(value.to_string()),
+    }
+}
+
+/// Host part of an http(s) URL
+fn host_of(url: &str) -> Option<&str> {
+    let rest = url.split_once("://")?.1;
+    let authority = rest.split(['/', '?']).next()?;
+    let host = authority.rsplit('@').next()?;
+    Some(host.split(':').next().unwrap_or(host))
+}
+
+/// Parse netrc `machine`/`default` entries
+fn parse_netrc(content: &str) -> (HashMap<String, NetrcEntry>, Option<NetrcEntry>) {
+    let mut machines = HashMap::new();
+    let mut default = None;
+
+    // Entries are whitespace separated token pairs and may span lines
+    let mut tokens = content
+        .lines()
+        .filter(|l| !l.trim_start().starts_with('#'))
+        .flat_map(|l| l.split_whitespace());
+
+    let mut current: Option<Option<String>> = None;
+    let mut login = String::new();
+    let mut password = String::new();
+
+    let mut finish = |machine: Option<Option<String>>, login: &mut String, password: &mut String| {
+        let entry = NetrcEntry {
+            login: std::mem::take(login),
+            password: std::mem::take(password),
+        };
+        match machine {
+            Some(Some(host)) => {
+                machines.insert(host, entry);
+            }
+            Some(None) => default = Some(entry),
+            None => {}
+        }
+    };
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" => {
+                finish(current.take(), &mut login, &mut password);
+                current = tokens.next().map(|h| Some(h.to_string()));
+            }
+            "default" => {
+                finish(current.take(), &mut login, &mut password);
+                current = Some(None);
+            }
+            "login" => login = tokens.next().unwrap_or_default().to_string(),
+            "password" => password = tokens.next().unwrap_or_default().to_string(),
+            "account" | "macdef" => {
+                tokens.next();
+            }
+            _ => {}
+        }
+    }
+    finish(current, &mut login, &mut password);
+
+    (machines, default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_netrc() {
+        let (machines, default) = parse_netrc(
+            "# corporate caches\n\
+             machine cache.corp.example login ci password s3cret\n\
+             machine attic.corp.example\n  login deploy\n  password hunter2\n\
+             default login anonymous password guest\n",
+        );
+
+        assert_eq!(machines.len(), 2);
+        assert_eq!(machines["cache.corp.example"].password, "s3cret");
+        assert_eq!(machines["attic.corp.example"].login, "deploy");
+        assert_eq!(default.unwrap().login, "anonymous");
+    }
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("https://cache.corp.example/nix-cache-info"), Some("cache.corp.example"));
+        assert_eq!(host_of("https://user@cache.corp.example:8443/abc.narinfo"), Some("cache.corp.example"));
+        assert_eq!(host_of("cache.corp.example"), None);
+    }
+
+    #[test]
+    fn test_apply_only_to_listed_hosts() {
+        let (machines, default) = parse_netrc("default login anonymous password guest\n");
+        let credentials = Credentials {
+            hosts: HashSet::from(["cache.corp.example".to_string()]),
+            logins: OnceLock::from(Logins { machines, default, ..Default::default() }),
+            ..Default::default()
+        };
+        let client = reqwest::Client::new();
+        let authorized = |url: &str| {
+            let request = credentials.apply(client.get(url), url).build().unwrap();
+            request.headers().contains_key(reqwest::header::AUTHORIZATION)
+        };
+
+        assert!(authorized("https://cache.corp.example/nix-cache-info"));
+        assert!(!authorized("https://api.github.com/repos/o/r/releases/latest"));
+    }
+
+    #[test]
+    fn test_resolve_secret() {
+        assert_eq!(resolve_secret("abc").as_deref(), Some("abc"));
+        assert_eq!(resolve_secret("env:NIXBOOST_TEST_UNSET_TOKEN"), None);
+    }
+}
@@ -17,5 +17,7 @@
 //! Network module for NixBoost - HTTP client with retry and utilities.
 
 pub mod client;
+pub mod credentials;
+pub mod substituter;
 
 pub use client::HttpClient;
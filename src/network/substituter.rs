@@ -0,0 +1,164 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Binary cache (substituter) narinfo lookups and latency checks.
+
+use crate::core::error::{NetworkError, NixBoostError, Result};
+use crate::network::HttpClient;
+use crate::system::capabilities::nix_setting;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Parsed `<hash>.narinfo`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NarInfo {
+    pub store_path: String,
+    pub url: String,
+    pub compression: String,
+    pub file_size: u64,
+    pub nar_size: u64,
+    pub references: Vec<String>,
+}
+
+impl NarInfo {
+    pub fn parse(content: &str) -> Self {
+        let mut info = Self::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(": ") else {
+                continue;
+            };
+            match key {
+                "StorePath" => info.store_path = value.to_string(),
+                "URL" => info.url = value.to_string(),
+                "Compression" => info.compression = value.to_string(),
+                "FileSize" => info.file_size = value.parse().unwrap_or(0),
+                "NarSize" => info.nar_size = value.parse().unwrap_or(0),
+                "References" => info.references = value.split_whitespace().map(String::from).collect(),
+                _ => {}
+            }
+        }
+        info
+    }
+}
+
+/// Result of probing one substituter
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeResult {
+    pub url: String,
+    /// Round trip of the narinfo lookup
+    pub latency: Option<Duration>,
+    /// Whether the probed path was in the cache
+    pub hit: bool,
+    pub error: Option<String>,
+}
+
+/// Queries substituters over HTTP, with credentials attached
+pub struct SubstituterClient {
+    http: HttpClient,
+}
+
+impl SubstituterClient {
+    pub fn new(http: HttpClient) -> Self {
+        Self { http }
+    }
+
+    /// Look up the narinfo for a store path hash. `None` when the cache
+    /// does not have the path.
+    pub async fn narinfo(&self, substituter: &str, hash: &str) -> Result<Option<NarInfo>> {
+        let url = format!("{}/{}.narinfo", substituter.trim_end_matches('/'), hash);
+        debug!("Fetching {}", url);
+
+        match self.http.get_string(&url).await {
+            Ok(content) => Ok(Some(NarInfo::parse(&content))),
+            Err(NixBoostError::Network(NetworkError::HttpError { status: 404, .. })) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Time a narinfo lookup of `hash` on `substituter`
+    pub async fn probe(&self, substituter: &str, hash: &str) -> ProbeResult {
+        let start = Instant::now();
+        let result = self.narinfo(substituter, hash).await;
+        let latency = start.elapsed();
+
+        match result {
+            Ok(info) => ProbeResult {
+                url: substituter.to_string(),
+                latency: Some(latency),
+                hit: info.is_some(),
+                error: None,
+            },
+            Err(e) => ProbeResult {
+                url: substituter.to_string(),
+                latency: None,
+                hit: false,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// HTTP(S) substituters from the Nix configuration
+pub fn configured_substituters() -> Vec<String> {
+    let mut substituters: Vec<String> = Vec::new();
+    for setting in ["substituters", "extra-substituters"] {
+        for url in nix_setting(setting).unwrap_or_default().split_whitespace() {
+            if url.starts_with("http") && !substituters.iter().any(|s| s == url) {
+                substituters.push(url.to_string());
+            }
+        }
+    }
+    substituters
+}
+
+/// Hash part of a store path, e.g. the `abc…` of `/nix/store/abc…-nix-2.18.1/bin/nix`
+pub fn store_hash(path: &Path) -> Option<String> {
+    let rest = path.to_str()?.strip_prefix("/nix/store/")?;
+    let (hash, _) = rest.split_once('-')?;
+    (hash.len() == 32).then(|| hash.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_narinfo() {
+        let info = NarInfo::parse(
+            "StorePath: /nix/store/0123456789abcdfghijklmnpqrsvwxyz-hello-2.12.1\n\
+             URL: nar/1a2b.nar.xz\n\
+             Compression: xz\n\
+             FileSize: 50264\n\
+             NarSize: 226560\n\
+             References: 0123456789abcdfghijklmnpqrsvwxyz-hello-2.12.1 9876543210abcdfghijklmnpqrsvwxyz-glibc-2.39\n",
+        );
+
+        assert_eq!(info.compression, "xz");
+        assert_eq!(info.nar_size, 226560);
+        assert_eq!(info.references.len(), 2);
+    }
+
+    #[test]
+    fn test_store_hash() {
+        assert_eq!(
+            store_hash(Path::new("/nix/store/0123456789abcdfghijklmnpqrsvwxyz-nix-2.18.1/bin/nix")).as_deref(),
+            Some("0123456789abcdfghijklmnpqrsvwxyz")
+        );
+        assert_eq!(store_hash(Path::new("/usr/bin/nix")), None);
+    }
+}
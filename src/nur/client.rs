@@ -20,16 +20,91 @@ use crate::core::config::Config;
 use crate::core::error::{NixBoostError, NurError, Result};
 use crate::core::types::{Package, PackageSource};
 use crate::cache::CacheManager;
+use crate::cache::bundle::{NUR_INDEX, NUR_INDEX_META};
 use crate::cache::invalidation::{CacheKey, TTL};
+use crate::network::HttpClient;
+use crate::ui::progress::ProgressManager;
+use crate::utils::time::{format_age, unix_now};
+use crate::utils::command::Echo;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, UNIX_EPOCH};
 use tracing::{debug, info, warn};
 
 const NUR_INDEX_URL: &str = "https://raw.githubusercontent.com/nix-community/nur-search/master/data/packages.json";
 
+/// Latest commit of the repository the index is published from
+const NUR_INDEX_COMMIT_URL: &str = "https://api.github.com/repos/nix-community/nur-search/commits/master";
+
+/// Where and when the local NUR index was fetched
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NurIndexMeta {
+    /// Unix time of the download
+    pub fetched_at: u64,
+    /// nur-search commit the index was published from
+    pub commit: Option<String>,
+}
+
+impl NurIndexMeta {
+    /// Read the metadata next to the index, falling back to the index's
+    /// modification time for indexes downloaded by older versions
+    pub fn load(cache_dir: &Path) -> Option<Self> {
+        if let Some(meta) = std::fs::read_to_string(cache_dir.join(NUR_INDEX_META))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+        {
+            return Some(meta);
+        }
+
+        let modified = std::fs::metadata(cache_dir.join(NUR_INDEX)).ok()?.modified().ok()?;
+        Some(Self {
+            fetched_at: modified.duration_since(UNIX_EPOCH).ok()?.as_secs(),
+            commit: None,
+        })
+    }
+
+    /// Time since the index was fetched
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.fetched_at))
+    }
+}
+
+/// Summary shown by `nixboost nur status`
+#[derive(Debug, Clone, Serialize)]
+pub struct NurIndexStatus {
+    pub path: PathBuf,
+    pub meta: Option<NurIndexMeta>,
+    pub packages: usize,
+    pub ttl_secs: u64,
+}
+
+impl NurIndexStatus {
+    /// Whether the index is missing or older than the TTL
+    pub fn is_stale(&self) -> bool {
+        self.meta.as_ref().map(|m| m.age().as_secs() >= self.ttl_secs).unwrap_or(true)
+    }
+
+    pub fn print(&self) {
+        println!("Index:    {}", self.path.display());
+        let Some(meta) = &self.meta else {
+            println!("Fetched:  {}", console::style("never").yellow());
+            return;
+        };
+
+        let age = format!("{} ago", format_age(meta.age()));
+        if self.is_stale() {
+            println!("Fetched:  {} (older than TTL of {})", console::style(age).yellow(), format_age(Duration::from_secs(self.ttl_secs)));
+        } else {
+            println!("Fetched:  {}", console::style(age).green());
+        }
+        println!("Commit:   {}", meta.commit.as_deref().unwrap_or("unknown"));
+        println!("Packages: {}", self.packages);
+    }
+}
+
 /// NUR package information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NurPackage {
@@ -61,6 +136,7 @@ impl From<NurPackage> for Package {
             license: nur.license,
             maintainers: Vec::new(),
             platforms: Vec::new(),
+            alias: false,
         }
     }
 }
@@ -68,26 +144,29 @@ impl From<NurPackage> for Package {
 /// NUR client for searching and installing NUR packages
 pub struct NurClient {
     /// HTTP client
-    http: reqwest::Client,
+    http: HttpClient,
     /// Cache manager (optional)
     cache: Option<Arc<CacheManager>>,
     /// Index cache (in-memory for current session)
     index: Option<HashMap<String, Value>>,
+    /// Maximum index age before it is refreshed, from `[cache] nur_ttl_secs`
+    ttl: Duration,
+    /// Use an outdated index when it cannot be refreshed
+    allow_stale: bool,
+    /// Download and load progress
+    progress: ProgressManager,
 }
 
 impl NurClient {
     /// Create a new NUR client
     pub fn new() -> Self {
-        let http = reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
-            .user_agent(format!("nixboost/{}", env!("CARGO_PKG_VERSION")))
-            .build()
-            .unwrap_or_default();
-
         Self {
-            http,
+            http: Config::try_get().map(HttpClient::from_config).unwrap_or_default(),
             cache: None,
             index: None,
+            ttl: Duration::from_secs(TTL::nur_index()),
+            allow_stale: false,
+            progress: ProgressManager::new(),
         }
     }
 
@@ -98,6 +177,18 @@ impl NurClient {
         client
     }
 
+    /// Fall back to an outdated index, with a warning, when refreshing fails
+    pub fn allow_stale(mut self, allow: bool) -> Self {
+        self.allow_stale = allow;
+        self
+    }
+
+    /// Report index downloads and loads on `progress`
+    pub fn progress(mut self, progress: ProgressManager) -> Self {
+        self.progress = progress;
+        self
+    }
+
     /// Load or update the NUR index
     pub async fn load_index(&mut self) -> Result<()> {
         // Try cache first
@@ -110,65 +201,113 @@ impl NurClient {
         }
 
         // Try local file cache
-        let cache_file = Config::cache_dir().join("nur-packages.json");
-        let mut download_needed = true;
-
-        if cache_file.exists() {
-            if let Ok(metadata) = std::fs::metadata(&cache_file) {
-                if let Ok(modified) = metadata.modified() {
-                    if let Ok(elapsed) = modified.elapsed() {
-                        if elapsed.as_secs() < 86400 {
-                            download_needed = false;
-                        }
-                    }
-                }
-            }
-        }
+        let cache_dir = Config::cache_dir();
+        let cache_file = cache_dir.join(NUR_INDEX);
+        let age = NurIndexMeta::load(&cache_dir).map(|m| m.age());
 
-        if download_needed {
+        if age.map(|a| a >= self.ttl).unwrap_or(true) {
             info!("Updating NUR package index...");
-            self.download_index().await?;
+            if let Err(e) = self.download_index().await {
+                if matches!(e, NixBoostError::Nur(NurError::DownloadCancelled)) {
+                    return Err(e);
+                }
+                let Some(age) = age.filter(|_| cache_file.exists()) else {
+                    return Err(e);
+                };
+                if !self.allow_stale {
+                    return Err(NurError::IndexStale { age: format_age(age), reason: e.to_string() }.into());
+                }
+                warn!("Using NUR index from {} ago; refresh failed: {}", format_age(age), e);
+            }
         }
 
         // Load from file
-        let content = std::fs::read_to_string(&cache_file)
-            .map_err(|e| NurError::IndexNotAvailable)?;
-
-        let json: HashMap<String, Value> = serde_json::from_str(&content)
-            .map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        let pb = self.progress.spinner("Loading NUR index...");
+        let content = std::fs::read_to_string(&cache_file);
+        let json: Result<HashMap<String, Value>> = match content {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| NixBoostError::Serialization(e.to_string())),
+            Err(_) => Err(NurError::IndexNotAvailable.into()),
+        };
+        pb.finish_and_clear();
+        let json = json?;
 
         // Cache in memory cache manager
         if let Some(ref cache) = self.cache {
-            let _ = cache.set(&CacheKey::nur_index(), &json, TTL::NUR_INDEX);
+            let _ = cache.set(&CacheKey::nur_index(), &json, self.ttl.as_secs());
         }
 
         self.index = Some(json);
         Ok(())
     }
 
-    /// Download the NUR index
+    /// Download the NUR index, showing progress. The index is written to a
+    /// temporary file and only replaces the old one once complete, so Ctrl-C
+    /// leaves the previous index intact.
     async fn download_index(&self) -> Result<()> {
+        let body = tokio::select! {
+            body = self.http.get_with_progress(NUR_INDEX_URL, &self.progress, NUR_INDEX) => {
+                body.map_err(|e| NurError::IndexUpdateFailed(e.to_string()))?
+            }
+            _ = tokio::signal::ctrl_c() => return Err(NurError::DownloadCancelled.into()),
+        };
+
+        let cache_dir = Config::cache_dir();
+        std::fs::create_dir_all(&cache_dir)?;
+        let partial = cache_dir.join(format!("{}.part", NUR_INDEX));
+        if let Err(e) = tokio::fs::write(&partial, &body).await {
+            let _ = std::fs::remove_file(&partial);
+            return Err(e.into());
+        }
+        std::fs::rename(&partial, cache_dir.join(NUR_INDEX))?;
+
+        let meta = NurIndexMeta {
+            fetched_at: unix_now(),
+            commit: self.upstream_commit().await,
+        };
+        let meta_json = serde_json::to_string(&meta)
+            .map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        std::fs::write(cache_dir.join(NUR_INDEX_META), meta_json)?;
+
+        info!("NUR index updated successfully");
+        Ok(())
+    }
+
+    /// Commit the published index was built from, if GitHub answers
+    async fn upstream_commit(&self) -> Option<String> {
         let response = self.http
-            .get(NUR_INDEX_URL)
+            .inner()
+            .get(NUR_INDEX_COMMIT_URL)
+            .header("Accept", "application/vnd.github.sha")
             .send()
             .await
-            .map_err(|e| NurError::IndexUpdateFailed(e.to_string()))?;
+            .ok()?;
 
         if !response.status().is_success() {
-            return Err(NurError::IndexUpdateFailed(
-                format!("HTTP {}", response.status())
-            ).into());
+            debug!("Could not fetch NUR index commit: HTTP {}", response.status());
+            return None;
         }
 
-        let bytes = response.bytes().await
-            .map_err(|e| NurError::IndexUpdateFailed(e.to_string()))?;
+        let sha = response.text().await.ok()?.trim().to_string();
+        (!sha.is_empty()).then_some(sha)
+    }
 
+    /// Report the local index's age and provenance without refreshing it
+    pub fn status(&self) -> NurIndexStatus {
         let cache_dir = Config::cache_dir();
-        std::fs::create_dir_all(&cache_dir)?;
-        std::fs::write(cache_dir.join("nur-packages.json"), bytes)?;
-
-        info!("NUR index updated successfully");
-        Ok(())
+        let path = cache_dir.join(NUR_INDEX);
+        let packages = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<HashMap<String, Value>>(&content).ok())
+            .map(|index| index.len())
+            .unwrap_or(0);
+
+        NurIndexStatus {
+            meta: NurIndexMeta::load(&cache_dir).filter(|_| path.exists()),
+            path,
+            packages,
+            ttl_secs: self.ttl.as_secs(),
+        }
     }
 
     /// Search NUR packages
@@ -240,42 +379,45 @@ impl NurClient {
         Ok(None)
     }
 
-    /// Install a NUR package
-    pub async fn install(&mut self, package: &str) -> Result<()> {
-        let mut attr_path = package.strip_prefix("nur.")
+    /// Resolve a package name or attribute path to a full `repos.*` attribute path
+    pub async fn resolve_attr(&mut self, package: &str) -> Result<String> {
+        let attr_path = package.strip_prefix("nur.")
             .unwrap_or(package)
             .to_string();
 
-        // Resolve if not a full path
-        if !attr_path.contains("repos.") {
-            info!("Resolving NUR package: {}", package);
-            if let Some(resolved) = self.resolve(&attr_path).await? {
+        if attr_path.contains("repos.") {
+            return Ok(attr_path);
+        }
+
+        info!("Resolving NUR package: {}", package);
+        match self.resolve(&attr_path).await? {
+            Some(resolved) => {
                 debug!("Resolved {} to {}", package, resolved);
-                attr_path = resolved.strip_prefix("nur.")
-                    .unwrap_or(&resolved)
-                    .to_string();
-            } else {
-                return Err(NurError::PackageNotFound { name: package.to_string() }.into());
+                Ok(resolved.strip_prefix("nur.").unwrap_or(&resolved).to_string())
             }
+            None => Err(NurError::PackageNotFound { name: package.to_string() }.into()),
         }
+    }
+
+    /// Flake installable for a NUR attribute path
+    pub fn installable(attr_path: &str) -> String {
+        format!("github:nix-community/NUR#{}", attr_path)
+    }
 
+    /// Install an already resolved NUR attribute path
+    pub fn install_attr(&self, attr_path: &str) -> Result<()> {
         info!("Installing NUR package: {}", attr_path);
 
         let status = std::process::Command::new("nix")
-            .args(["profile", "install", &format!("github:nix-community/NUR#{}", attr_path)])
-            .status()?;
+            .args(["profile", "install", &Self::installable(attr_path)])
+            .echo().status()?;
 
         if !status.success() {
-            return Err(NurError::InvalidAttributePath { path: attr_path }.into());
+            return Err(NurError::InvalidAttributePath { path: attr_path.to_string() }.into());
         }
 
         Ok(())
     }
-
-    /// Get package count in index
-    pub fn package_count(&self) -> usize {
-        self.index.as_ref().map(|i| i.len()).unwrap_or(0)
-    }
 }
 
 impl Default for NurClient {
@@ -304,4 +446,24 @@ mod tests {
         assert_eq!(pkg.name, "hello");
         assert!(matches!(pkg.source, PackageSource::Nur { ref repo } if repo == "mic92"));
     }
+
+    #[test]
+    fn test_index_meta_and_age() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(NurIndexMeta::load(dir.path()), None);
+
+        // Indexes without metadata fall back to the file's mtime
+        std::fs::write(dir.path().join(NUR_INDEX), "{}").unwrap();
+        let meta = NurIndexMeta::load(dir.path()).unwrap();
+        assert!(meta.age() < Duration::from_secs(60));
+        assert_eq!(meta.commit, None);
+
+        let stored = NurIndexMeta { fetched_at: unix_now() - 2 * 86400, commit: Some("abc123".to_string()) };
+        std::fs::write(dir.path().join(NUR_INDEX_META), serde_json::to_string(&stored).unwrap()).unwrap();
+        let meta = NurIndexMeta::load(dir.path()).unwrap();
+        assert_eq!(meta.commit.as_deref(), Some("abc123"));
+
+        let status = NurIndexStatus { path: dir.path().join(NUR_INDEX), meta: Some(meta), packages: 0, ttl_secs: 86400 };
+        assert!(status.is_stale());
+    }
 }
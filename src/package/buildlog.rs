@@ -0,0 +1,168 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! `nixboost logs`: a derivation's build log from `nix log`, filtered,
+//! highlighted and paged.
+
+use crate::core::error::{Result, SystemError};
+use crate::package::shell::nixpkgs_installable;
+use crate::utils::command::Echo;
+use console::style;
+use serde::Serialize;
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// How a build log line is highlighted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineKind {
+    Error,
+    Warning,
+    Normal,
+}
+
+impl LineKind {
+    /// Classify a line by the markers compilers and builders commonly print
+    pub fn of(line: &str) -> Self {
+        let lower = line.to_lowercase();
+        if lower.contains("error:") || lower.contains("error[") || lower.contains("fatal")
+            || lower.contains("failed with") || lower.contains("*** ") || lower.contains("undefined reference")
+        {
+            LineKind::Error
+        } else if lower.contains("warning:") || lower.contains("warning[") || lower.contains("deprecated") {
+            LineKind::Warning
+        } else {
+            LineKind::Normal
+        }
+    }
+}
+
+/// One line of a build log
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    /// 1-based line number in the full log
+    pub number: usize,
+    pub kind: LineKind,
+    pub text: String,
+}
+
+impl LogLine {
+    /// The line colored by kind, with its number
+    pub fn highlighted(&self) -> String {
+        let number = style(format!("{:>5}", self.number)).dim();
+        let text = match self.kind {
+            LineKind::Error => style(&self.text).red().bold().to_string(),
+            LineKind::Warning => style(&self.text).yellow().to_string(),
+            LineKind::Normal => self.text.clone(),
+        };
+        format!("{} {}", number, text)
+    }
+}
+
+/// Installable for `nix log`: store paths (derivations or outputs) are
+/// used as given, anything else is a nixpkgs attribute or flake reference
+pub fn installable(target: &str) -> String {
+    if target.starts_with("/nix/store/") {
+        target.to_string()
+    } else {
+        nixpkgs_installable(target)
+    }
+}
+
+/// The build log of `installable`
+pub fn fetch(installable: &str) -> Result<String> {
+    let output = Command::new("nix").args(["log", installable]).echo().output()?;
+    if !output.status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: format!("nix log {}", installable),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }.into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Lines of `log` containing `grep` (case-insensitive), then the last `tail`
+pub fn select(log: &str, grep: Option<&str>, tail: Option<usize>) -> Vec<LogLine> {
+    let grep = grep.map(str::to_lowercase);
+    let mut lines: Vec<LogLine> = log
+        .lines()
+        .enumerate()
+        .filter(|(_, text)| grep.as_ref().is_none_or(|g| text.to_lowercase().contains(g)))
+        .map(|(i, text)| LogLine { number: i + 1, kind: LineKind::of(text), text: text.to_string() })
+        .collect();
+
+    if let Some(tail) = tail {
+        let skip = lines.len().saturating_sub(tail);
+        lines.drain(..skip);
+    }
+    lines
+}
+
+/// Show `text` through `$PAGER` (`less -R` by default) when stdout is a
+/// terminal, or print it directly otherwise
+pub fn page(text: &str, use_pager: bool) -> Result<()> {
+    if !use_pager || !std::io::stdout().is_terminal() {
+        println!("{}", text);
+        return Ok(());
+    }
+
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -R".to_string());
+    let mut words = pager.split_whitespace();
+    let Some(program) = words.next() else {
+        println!("{}", text);
+        return Ok(());
+    };
+
+    let mut child = match Command::new(program).args(words).stdin(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(_) => {
+            println!("{}", text);
+            return Ok(());
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closing early (e.g. `q` in less) is not an error
+        let _ = writeln!(stdin, "{}", text);
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "unpacking sources\n\
+        src/main.c:3: warning: unused variable 'x'\n\
+        src/main.c:9: error: expected ';'\n\
+        make: *** [Makefile:4: all] Error 1\n\
+        builder failed with exit code 2";
+
+    #[test]
+    fn test_select_and_classify() {
+        let lines = select(LOG, None, None);
+        let kinds: Vec<LineKind> = lines.iter().map(|l| l.kind).collect();
+        assert_eq!(kinds, vec![LineKind::Normal, LineKind::Warning, LineKind::Error, LineKind::Error, LineKind::Error]);
+
+        let lines = select(LOG, Some("MAIN.C"), Some(1));
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].number, 3);
+
+        assert_eq!(installable("hello"), "nixpkgs#hello");
+        assert_eq!(installable("/nix/store/abc-hello.drv"), "/nix/store/abc-hello.drv");
+    }
+}
@@ -0,0 +1,194 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Release notes for packages an upgrade changes.
+//!
+//! Each package's `meta.changelog` at the new revision is shown when it
+//! has one. When the profile element's locked nixpkgs revision is known,
+//! the nixpkgs commits that touched its derivation file since then are
+//! listed as well, fetched from the GitHub API.
+
+use crate::core::error::Result;
+use crate::network::HttpClient;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// GitHub repository nixpkgs revisions are looked up in
+const NIXPKGS_REPO: &str = "NixOS/nixpkgs";
+
+/// Commits listed per package; the rest are linked
+pub const MAX_COMMITS: usize = 10;
+
+/// A nixpkgs commit that touched a package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Commit {
+    pub sha: String,
+    /// First line of the commit message
+    pub summary: String,
+}
+
+/// What changed for one package between two nixpkgs revisions
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseNotes {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+    /// Upstream changelog from `meta.changelog`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog: Option<String>,
+    /// nixpkgs commits touching the derivation, newest first
+    pub commits: Vec<Commit>,
+    /// Web page listing every commit to the derivation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_url: Option<String>,
+}
+
+impl ReleaseNotes {
+    pub fn is_empty(&self) -> bool {
+        self.changelog.is_none() && self.commits.is_empty()
+    }
+}
+
+/// Git revision in a locked flake URL, e.g. `github:NixOS/nixpkgs/<rev>` or
+/// `path:/nix/store/...-source?narHash=...&rev=<rev>`
+pub fn locked_revision(url: &str) -> Option<String> {
+    let (base, query) = url.split_once('?').unwrap_or((url, ""));
+    query
+        .split('&')
+        .find_map(|param| param.strip_prefix("rev="))
+        .or_else(|| base.strip_prefix("github:").and_then(|rest| rest.rsplit('/').next()))
+        .filter(|rev| is_commit(rev))
+        .map(String::from)
+}
+
+/// Whether `s` is a full git commit hash
+pub fn is_commit(s: &str) -> bool {
+    s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Path of a derivation within nixpkgs from `meta.position`, e.g.
+/// `/nix/store/<hash>-source/pkgs/by-name/he/hello/package.nix:34`
+pub fn derivation_file(position: &str) -> Option<String> {
+    let (_, rest) = position.split_once("-source/")?;
+    let file = rest.rsplit_once(':').map(|(file, _)| file).unwrap_or(rest);
+    (!file.is_empty()).then(|| file.to_string())
+}
+
+/// GitHub page listing the commits to `file` up to `rev`
+pub fn history_url(rev: &str, file: &str) -> String {
+    format!("https://github.com/{}/commits/{}/{}", NIXPKGS_REPO, rev, file)
+}
+
+#[derive(Deserialize)]
+struct ApiCommit {
+    sha: String,
+    commit: ApiCommitDetail,
+}
+
+#[derive(Deserialize)]
+struct ApiCommitDetail {
+    message: String,
+    committer: ApiSignature,
+}
+
+#[derive(Deserialize)]
+struct ApiSignature {
+    date: String,
+}
+
+/// Looks up nixpkgs commits, remembering the date of each base revision
+pub struct CommitLog<'a> {
+    http: &'a HttpClient,
+    dates: HashMap<String, Option<String>>,
+}
+
+impl<'a> CommitLog<'a> {
+    pub fn new(http: &'a HttpClient) -> Self {
+        Self { http, dates: HashMap::new() }
+    }
+
+    /// Commits touching `file` after `from` up to and including `to`
+    pub async fn between(&mut self, file: &str, from: &str, to: &str) -> Result<Vec<Commit>> {
+        let Some(since) = self.commit_date(from).await else {
+            return Ok(Vec::new());
+        };
+
+        let url = format!(
+            "https://api.github.com/repos/{}/commits?sha={}&path={}&since={}&per_page=100",
+            NIXPKGS_REPO, to, file, since
+        );
+        debug!("Fetching {}", url);
+        let commits: Vec<ApiCommit> = self.http.get_json(&url).await?;
+        Ok(commits
+            .into_iter()
+            .filter(|c| c.sha != from)
+            .map(|c| Commit {
+                sha: c.sha[..c.sha.len().min(12)].to_string(),
+                summary: c.commit.message.lines().next().unwrap_or_default().to_string(),
+            })
+            .collect())
+    }
+
+    /// Committer date of `rev`, fetched once per revision
+    async fn commit_date(&mut self, rev: &str) -> Option<String> {
+        if let Some(date) = self.dates.get(rev) {
+            return date.clone();
+        }
+
+        let url = format!("https://api.github.com/repos/{}/commits/{}", NIXPKGS_REPO, rev);
+        let date = match self.http.get_json::<ApiCommit>(&url).await {
+            Ok(commit) => Some(commit.commit.committer.date),
+            Err(e) => {
+                debug!("Cannot look up nixpkgs commit {}: {}", rev, e);
+                None
+            }
+        };
+        self.dates.insert(rev.to_string(), date.clone());
+        date
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locked_revision() {
+        let rev = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(locked_revision(&format!("github:NixOS/nixpkgs/{}", rev)).as_deref(), Some(rev));
+        assert_eq!(
+            locked_revision(&format!("github:NixOS/nixpkgs/{}?narHash=sha256-abc", rev)).as_deref(),
+            Some(rev)
+        );
+        assert_eq!(
+            locked_revision(&format!("path:/nix/store/x-source?lastModified=1&narHash=sha256-abc&rev={}", rev)).as_deref(),
+            Some(rev)
+        );
+        assert_eq!(locked_revision("github:NixOS/nixpkgs/nixos-unstable"), None);
+        assert_eq!(locked_revision("path:/nix/store/x-source?narHash=sha256-abc"), None);
+    }
+
+    #[test]
+    fn test_derivation_file() {
+        assert_eq!(
+            derivation_file("/nix/store/abc-source/pkgs/by-name/he/hello/package.nix:34").as_deref(),
+            Some("pkgs/by-name/he/hello/package.nix")
+        );
+        assert_eq!(derivation_file("/home/me/overlay/hello.nix:3"), None);
+    }
+}
@@ -0,0 +1,357 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Declarative package lists (`nixboost declare`).
+//!
+//! Instead of `nix profile install`, packages are added to or removed from a
+//! list in a user-managed Nix file, such as a `packages.nix` imported into
+//! `environment.systemPackages` or a home-manager `home.packages`, and the
+//! configuration is rebuilt. Only plain attribute names in the list are
+//! touched; other expressions are left as they are.
+
+use crate::core::config::DeclareTarget;
+use crate::core::error::{NixBoostError, Result, SystemError};
+use crate::utils::command::Echo;
+use console::style;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Attribute a list is looked up under, for each target
+fn anchor(target: DeclareTarget) -> &'static str {
+    match target {
+        DeclareTarget::Nixos => "environment.systemPackages",
+        DeclareTarget::HomeManager => "home.packages",
+    }
+}
+
+/// A package list inside a Nix file
+#[derive(Debug, Clone)]
+pub struct PackageFile {
+    pub path: PathBuf,
+    pub content: String,
+    target: DeclareTarget,
+}
+
+/// A plain attribute name in the list and where it sits in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    name: String,
+    span: Range<usize>,
+}
+
+impl PackageFile {
+    pub fn load(path: &Path, target: DeclareTarget) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Self::parse(path, content, target))
+    }
+
+    pub fn parse(path: &Path, content: String, target: DeclareTarget) -> Self {
+        Self { path: path.to_path_buf(), content, target }
+    }
+
+    /// Package names in the list, without any `pkgs.` prefix
+    pub fn packages(&self) -> Result<Vec<String>> {
+        Ok(self.entries()?.into_iter().map(|e| e.name).collect())
+    }
+
+    /// File content with `names` appended to the list. Packages already
+    /// listed are skipped.
+    pub fn with_added(&self, names: &[String]) -> Result<String> {
+        let (open, close) = self.list()?;
+        let existing = self.packages()?;
+        let prefix = if self.content[..open].contains("with pkgs;") { "" } else { "pkgs." };
+
+        let new: Vec<String> = names
+            .iter()
+            .filter(|n| !existing.contains(n))
+            .map(|n| format!("{}{}", prefix, n))
+            .collect();
+        if new.is_empty() {
+            return Ok(self.content.clone());
+        }
+
+        let line_start = self.content[..close].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let before_close = &self.content[line_start..close];
+        let mut content = self.content.clone();
+
+        if before_close.trim().is_empty() {
+            // `]` on its own line: one package per line, indented like the
+            // last entry or one level deeper than the bracket
+            let indent = self
+                .entries()?
+                .last()
+                .map(|e| {
+                    let start = self.content[..e.span.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+                    self.content[start..e.span.start].to_string()
+                })
+                .filter(|i| i.trim().is_empty())
+                .unwrap_or_else(|| format!("{}  ", before_close));
+            let lines: String = new.iter().map(|n| format!("{}{}\n", indent, n)).collect();
+            content.insert_str(line_start, &lines);
+        } else {
+            let sep = if self.content[..close].ends_with(' ') { "" } else { " " };
+            content.insert_str(close, &format!("{}{} ", sep, new.join(" ")));
+        }
+
+        Ok(content)
+    }
+
+    /// File content with `names` taken out of the list. Lines left empty are
+    /// dropped.
+    pub fn with_removed(&self, names: &[String]) -> Result<String> {
+        let mut content = self.content.clone();
+        let mut entries: Vec<Entry> = self.entries()?.into_iter().filter(|e| names.contains(&e.name)).collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.span.start));
+
+        for entry in entries {
+            let line_start = content[..entry.span.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+            let line_end = content[entry.span.end..].find('\n').map(|i| entry.span.end + i + 1).unwrap_or(content.len());
+            let rest_of_line = content[line_start..entry.span.start].to_string() + &content[entry.span.end..line_end];
+
+            if strip_comments(&rest_of_line).trim().is_empty() {
+                content.replace_range(line_start..line_end, "");
+            } else {
+                let end = if content[entry.span.end..].starts_with(' ') { entry.span.end + 1 } else { entry.span.end };
+                content.replace_range(entry.span.start..end, "");
+            }
+        }
+
+        Ok(content)
+    }
+
+    /// Byte offsets of the list's `[` and `]`
+    fn list(&self) -> Result<(usize, usize)> {
+        let code = strip_comments(&self.content);
+        let start = match code.find(anchor(self.target)) {
+            Some(start) => start,
+            // A file that is only a list, like `pkgs: with pkgs; [ ... ]`;
+            // anything assigned before the first `[` is some other list
+            None if code.split('[').next().is_some_and(|head| !head.contains('=')) => 0,
+            None => return Err(self.no_list()),
+        };
+        let open = code[start..].find('[').map(|i| start + i).ok_or_else(|| self.no_list())?;
+
+        let mut depth = 0;
+        for (i, c) in code[open..].char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok((open, open + i));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(self.no_list())
+    }
+
+    /// Plain attribute names at the top level of the list
+    fn entries(&self) -> Result<Vec<Entry>> {
+        let (open, close) = self.list()?;
+        let code = strip_comments(&self.content);
+
+        let mut entries = Vec::new();
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut token: Option<usize> = None;
+        for (i, c) in code[open + 1..=close].char_indices() {
+            let pos = open + 1 + i;
+            if in_string {
+                in_string = c != '"';
+                continue;
+            }
+            if depth == 0 && (c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '\'' | '/')) {
+                token.get_or_insert(pos);
+                continue;
+            }
+            if let Some(start) = token.take() {
+                let name = &code[start..pos];
+                // Paths are not package names
+                if !name.contains('/') {
+                    entries.push(Entry {
+                        name: name.strip_prefix("pkgs.").unwrap_or(name).to_string(),
+                        span: start..pos,
+                    });
+                }
+            }
+            match c {
+                '"' => in_string = true,
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn no_list(&self) -> NixBoostError {
+        NixBoostError::Config(format!(
+            "no package list found in {} (expected '{} = [ ... ]')",
+            self.path.display(),
+            anchor(self.target)
+        ))
+    }
+}
+
+/// `content` with `#` comments blanked out, keeping byte offsets. A `#`
+/// inside a string literal is not a comment.
+fn strip_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut in_comment = false;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in content.chars() {
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+        } else {
+            match c {
+                '"' if !in_comment => in_string = true,
+                '#' => in_comment = true,
+                '\n' => in_comment = false,
+                _ => {}
+            }
+        }
+        if in_comment {
+            out.extend(std::iter::repeat_n(' ', c.len_utf8()));
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Print the lines that differ between `old` and `new`
+pub fn print_diff(path: &Path, old: &str, new: &str) {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    println!("{} {}", style("---").bold(), path.display());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            println!("{}", style(format!("+{:>4} {}", j + 1, new[j])).green());
+            j += 1;
+        } else {
+            println!("{}", style(format!("-{:>4} {}", i + 1, old[i])).red());
+            i += 1;
+        }
+    }
+}
+
+/// Apply an edited home-manager configuration
+pub fn home_manager_switch() -> Result<()> {
+    let status = Command::new("home-manager").arg("switch").echo().status()?;
+    if !status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "home-manager switch".to_string(),
+            stderr: String::new(),
+        }.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGES_NIX: &str = "\
+{ pkgs, ... }:
+{
+  environment.systemPackages = with pkgs; [
+    git
+    ripgrep # search
+    (python3.withPackages (ps: [ ps.requests ]))
+  ];
+}
+";
+
+    const HOME_NIX: &str = "\
+{ pkgs, ... }:
+{
+  programs.git.enable = true;
+  home.packages = [ pkgs.htop pkgs.jq ];
+}
+";
+
+    const CONFIGURATION_NIX: &str = "\
+{ pkgs, ... }:
+{
+  imports = [ ./hardware-configuration.nix ];
+  networking.hostName = \"host#1\"; # [ in a comment
+}
+";
+
+    fn names(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_packages_nix_edit() {
+        let file = PackageFile::parse(Path::new("packages.nix"), PACKAGES_NIX.to_string(), DeclareTarget::Nixos);
+        assert_eq!(file.packages().unwrap(), names(&["git", "ripgrep"]));
+
+        let added = file.with_added(&names(&["fd", "git"])).unwrap();
+        assert!(added.contains("    (python3.withPackages (ps: [ ps.requests ]))\n    fd\n  ];"));
+
+        let removed = file.with_removed(&names(&["ripgrep"])).unwrap();
+        assert!(!removed.contains("ripgrep"));
+        assert!(removed.contains("    git\n    (python3"));
+    }
+
+    #[test]
+    fn test_home_manager_edit() {
+        let file = PackageFile::parse(Path::new("home.nix"), HOME_NIX.to_string(), DeclareTarget::HomeManager);
+        assert_eq!(file.packages().unwrap(), names(&["htop", "jq"]));
+
+        let added = file.with_added(&names(&["fd"])).unwrap();
+        assert!(added.contains("home.packages = [ pkgs.htop pkgs.jq pkgs.fd ];"));
+
+        let removed = file.with_removed(&names(&["htop"])).unwrap();
+        assert!(removed.contains("home.packages = [ pkgs.jq ];"));
+    }
+
+    #[test]
+    fn test_missing_anchor() {
+        let file = PackageFile::parse(Path::new("configuration.nix"), CONFIGURATION_NIX.to_string(), DeclareTarget::Nixos);
+        assert!(file.packages().is_err());
+        assert!(file.with_added(&names(&["fd"])).is_err());
+
+        let bare = PackageFile::parse(Path::new("packages.nix"), "pkgs: with pkgs; [ git ]\n".to_string(), DeclareTarget::Nixos);
+        assert_eq!(bare.packages().unwrap(), names(&["git"]));
+    }
+
+    #[test]
+    fn test_strip_comments_skips_strings() {
+        let code = strip_comments("url = \"foo#bar\"; # note\n");
+        assert_eq!(code, "url = \"foo#bar\";       \n");
+    }
+}
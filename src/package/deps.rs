@@ -0,0 +1,214 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Runtime dependency trees (`nixboost deps`).
+
+use crate::core::error::{Result, SystemError};
+use crate::package::upgrade::parse_store_name;
+use crate::utils::command::Echo;
+use console::style;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+/// A store path and its runtime references
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepNode {
+    pub path: String,
+    pub name: String,
+    pub version: String,
+    /// Already expanded elsewhere in the tree (`[...]` in `nix-store --tree`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub repeated: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<DepNode>,
+}
+
+impl DepNode {
+    fn new(path: &str, repeated: bool) -> Self {
+        let (name, version) = parse_store_name(path);
+        Self { path: path.to_string(), name, version, repeated, children: Vec::new() }
+    }
+
+    /// Number of distinct store paths in the closure
+    pub fn closure_size(&self) -> usize {
+        fn collect<'a>(node: &'a DepNode, seen: &mut std::collections::HashSet<&'a str>) {
+            if seen.insert(&node.path) {
+                for child in &node.children {
+                    collect(child, seen);
+                }
+            }
+        }
+        let mut seen = std::collections::HashSet::new();
+        collect(self, &mut seen);
+        seen.len()
+    }
+
+    /// First node for package `name`, searching breadth first
+    pub fn find(&self, name: &str) -> Option<&DepNode> {
+        let mut queue = std::collections::VecDeque::from([self]);
+        while let Some(node) = queue.pop_front() {
+            if node.name == name {
+                return Some(node);
+            }
+            queue.extend(node.children.iter());
+        }
+        None
+    }
+
+    /// Print as an indented tree, `depth` levels deep (`None` for all)
+    pub fn print(&self, depth: Option<usize>) {
+        println!("{} {}", style(&self.name).bold(), style(&self.version).green());
+        self.print_children("", depth, 1);
+        println!("\n{} paths in closure", self.closure_size());
+    }
+
+    fn print_children(&self, prefix: &str, depth: Option<usize>, level: usize) {
+        if depth.map(|d| level > d).unwrap_or(false) {
+            if !self.children.is_empty() {
+                println!("{}└── {}", prefix, style(format!("({} more)", self.children.len())).dim());
+            }
+            return;
+        }
+
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == self.children.len();
+            let branch = if last { "└── " } else { "├── " };
+            let label = format!("{} {}", style(&child.name).bold(), style(&child.version).green());
+            if child.repeated {
+                println!("{}{}{} {}", prefix, branch, label, style("(see above)").dim());
+            } else {
+                println!("{}{}{}", prefix, branch, label);
+            }
+            let next = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            child.print_children(&next, depth, level + 1);
+        }
+    }
+}
+
+/// Parse `nix-store --query --tree` output. Self-references are dropped.
+pub fn parse_tree(stdout: &str) -> Option<DepNode> {
+    let mut lines = stdout.lines().filter(|l| !l.trim().is_empty());
+    let root = DepNode::new(lines.next()?.trim(), false);
+
+    // Stack of (level, node); children are attached when their level closes
+    let mut stack: Vec<(usize, DepNode)> = vec![(0, root)];
+    for line in lines {
+        let Some(pos) = line.find("+---") else {
+            continue;
+        };
+        let level = pos / 4 + 1;
+        let rest = &line[pos + 4..];
+        let (path, repeated) = match rest.trim().strip_suffix(" [...]") {
+            Some(path) => (path, true),
+            None => (rest.trim(), false),
+        };
+
+        while stack.last().map(|(l, _)| *l >= level).unwrap_or(false) {
+            close(&mut stack);
+        }
+        if stack.last().map(|(_, parent)| parent.path == path).unwrap_or(false) {
+            continue;
+        }
+        stack.push((level, DepNode::new(path, repeated)));
+    }
+
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    stack.pop().map(|(_, root)| root)
+}
+
+fn close(stack: &mut Vec<(usize, DepNode)>) {
+    if let Some((_, node)) = stack.pop() {
+        if let Some((_, parent)) = stack.last_mut() {
+            parent.children.push(node);
+        }
+    }
+}
+
+/// Dependency tree of a realised store path
+pub async fn query_tree(path: &str) -> Result<DepNode> {
+    let output = Command::new("nix-store")
+        .args(["--query", "--tree", path])
+        .echo().output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix-store --query --tree".to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }.into());
+    }
+
+    parse_tree(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+        SystemError::NixCommandFailed {
+            command: "nix-store --query --tree".to_string(),
+            stderr: "empty output".to_string(),
+        }.into()
+    })
+}
+
+/// Print the reference chains from `path` to `dependency` (`nix why-depends`)
+pub async fn why_depends(path: &str, dependency: &str) -> Result<()> {
+    let status = Command::new("nix")
+        .args(["why-depends", path, dependency])
+        .echo().status()
+        .await?;
+
+    if !status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix why-depends".to_string(),
+            stderr: format!("exited with {}", status),
+        }.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TREE_FIXTURE: &str = "\
+/nix/store/0123456789abcdfghijklmnpqrsvwxyz-hello-2.12.1
++---/nix/store/1123456789abcdfghijklmnpqrsvwxyz-glibc-2.39-52
+|   +---/nix/store/2123456789abcdfghijklmnpqrsvwxyz-libidn2-2.3.7
+|   |   +---/nix/store/3123456789abcdfghijklmnpqrsvwxyz-libunistring-1.2
+|   |   |   +---/nix/store/3123456789abcdfghijklmnpqrsvwxyz-libunistring-1.2 [...]
+|   |   +---/nix/store/2123456789abcdfghijklmnpqrsvwxyz-libidn2-2.3.7 [...]
+|   +---/nix/store/4123456789abcdfghijklmnpqrsvwxyz-xgcc-13.3.0-libgcc
+|   +---/nix/store/1123456789abcdfghijklmnpqrsvwxyz-glibc-2.39-52 [...]
++---/nix/store/0123456789abcdfghijklmnpqrsvwxyz-hello-2.12.1 [...]
++---/nix/store/3123456789abcdfghijklmnpqrsvwxyz-libunistring-1.2 [...]
+";
+
+    #[test]
+    fn test_parse_tree() {
+        let root = parse_tree(TREE_FIXTURE).unwrap();
+        assert_eq!(root.name, "hello");
+        assert_eq!(root.version, "2.12.1");
+        assert_eq!(root.children.len(), 2);
+
+        let glibc = &root.children[0];
+        assert_eq!(glibc.name, "glibc");
+        assert_eq!(glibc.children.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["libidn2", "xgcc"]);
+        assert_eq!(glibc.children[0].children[0].name, "libunistring");
+        assert!(glibc.children[0].children[0].children.is_empty());
+        assert!(root.children[1].repeated);
+
+        assert_eq!(root.closure_size(), 5);
+        assert_eq!(root.find("xgcc").unwrap().version, "13.3.0-libgcc");
+    }
+}
@@ -0,0 +1,177 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Triage of failed builds: common failure signatures in a build log,
+//! with a suggestion for each and a search for matching nixpkgs issues.
+
+use serde::Serialize;
+use std::fmt;
+
+/// nixpkgs issue search, completed with the attribute name
+const ISSUES_URL: &str = "https://github.com/NixOS/nixpkgs/issues?q=is%3Aissue+build+failure+";
+
+/// Recognised cause of a failed build
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "name", rename_all = "kebab-case")]
+pub enum Failure {
+    /// A C/C++ header was not found (`fatal error: foo.h: No such file or directory`)
+    MissingHeader(String),
+    /// pkg-config or CMake could not find a library
+    MissingDependency(String),
+    /// The build tried to reach the network from inside the sandbox
+    SandboxNetwork,
+    /// The compiler or linker was killed for lack of memory
+    OutOfMemory,
+}
+
+impl Failure {
+    /// What to try next
+    pub fn suggestion(&self) -> String {
+        match self {
+            Failure::MissingHeader(header) => format!(
+                "{} is missing: add the package providing it to buildInputs (search with `nix-locate include/{}`)",
+                header, header
+            ),
+            Failure::MissingDependency(name) => format!(
+                "{} was not found: add it to buildInputs, and pkg-config or cmake to nativeBuildInputs",
+                name
+            ),
+            Failure::SandboxNetwork => "The build needs network access, which the sandbox blocks: \
+                fetch sources with a fixed-output fetcher (fetchurl, fetchFromGitHub) instead"
+                .to_string(),
+            Failure::OutOfMemory => "The build ran out of memory: retry with fewer parallel jobs \
+                (`--option max-jobs 1 --option cores 2`) or add swap"
+                .to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::MissingHeader(header) => write!(f, "missing header {}", header),
+            Failure::MissingDependency(name) => write!(f, "missing dependency {}", name),
+            Failure::SandboxNetwork => write!(f, "network access in the sandbox"),
+            Failure::OutOfMemory => write!(f, "out of memory"),
+        }
+    }
+}
+
+/// A failure found in a build log
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnosis {
+    #[serde(flatten)]
+    pub failure: Failure,
+    /// 1-based line of the log it was found on
+    pub line: usize,
+    pub suggestion: String,
+}
+
+/// Failures in `log`, first occurrence of each, in log order
+pub fn scan(log: &str) -> Vec<Diagnosis> {
+    let mut found: Vec<Diagnosis> = Vec::new();
+    for (i, line) in log.lines().enumerate() {
+        let Some(failure) = classify(line) else { continue };
+        if found.iter().any(|d| d.failure == failure) {
+            continue;
+        }
+        found.push(Diagnosis { suggestion: failure.suggestion(), failure, line: i + 1 });
+    }
+    found
+}
+
+fn classify(line: &str) -> Option<Failure> {
+    // gcc/clang: `foo.c:1:10: fatal error: bar.h: No such file or directory`
+    if let Some(rest) = line.split("fatal error: ").nth(1) {
+        if let Some(header) = rest.strip_suffix(": No such file or directory") {
+            return Some(Failure::MissingHeader(header.trim_matches(['\'', '"']).to_string()));
+        }
+        if let Some(header) = rest.strip_suffix("' file not found") {
+            return Some(Failure::MissingHeader(header.trim_start_matches('\'').to_string()));
+        }
+    }
+    // pkg-config: `Package 'libfoo', required by 'bar', not found`
+    if let Some(rest) = line.trim_start().strip_prefix("Package '") {
+        if line.ends_with("not found") {
+            return rest.split('\'').next().map(|name| Failure::MissingDependency(name.to_string()));
+        }
+    }
+    // CMake: `Could NOT find Foo (missing: FOO_LIBRARY)`
+    if let Some(rest) = line.split("Could NOT find ").nth(1) {
+        return rest.split_whitespace().next().map(|name| Failure::MissingDependency(name.to_string()));
+    }
+
+    let lower = line.to_lowercase();
+    const NETWORK: &[&str] = &[
+        "could not resolve host",
+        "temporary failure in name resolution",
+        "name or service not known",
+        "network is unreachable",
+        "failed to connect to",
+    ];
+    if NETWORK.iter().any(|s| lower.contains(s)) {
+        return Some(Failure::SandboxNetwork);
+    }
+    const MEMORY: &[&str] = &[
+        "out of memory",
+        "cannot allocate memory",
+        "virtual memory exhausted",
+        "std::bad_alloc",
+        "killed signal terminated program",
+        "killed (program cc1",
+    ];
+    if MEMORY.iter().any(|s| lower.contains(s)) {
+        return Some(Failure::OutOfMemory);
+    }
+    None
+}
+
+/// nixpkgs issues mentioning a build failure of `attr`
+pub fn issues_url(attr: &str) -> String {
+    let name = attr.rsplit('#').next().unwrap_or(attr);
+    format!("{}{}", ISSUES_URL, name.replace(' ', "+"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_build_log() {
+        let log = "building\n\
+            src/ssl.c:4:10: fatal error: openssl/ssl.h: No such file or directory\n\
+            Package 'libpng', required by 'virtual:world', not found\n\
+            error: Could not resolve host: github.com\n\
+            src/other.c:2:10: fatal error: openssl/ssl.h: No such file or directory\n\
+            gcc: fatal error: Killed signal terminated program cc1plus";
+        let found = scan(log);
+        let failures: Vec<&Failure> = found.iter().map(|d| &d.failure).collect();
+        assert_eq!(failures, vec![
+            &Failure::MissingHeader("openssl/ssl.h".to_string()),
+            &Failure::MissingDependency("libpng".to_string()),
+            &Failure::SandboxNetwork,
+            &Failure::OutOfMemory,
+        ]);
+        assert_eq!(found[0].line, 2);
+
+        assert!(scan("all good").is_empty());
+        assert_eq!(
+            issues_url("nixpkgs#hello"),
+            "https://github.com/NixOS/nixpkgs/issues?q=is%3Aissue+build+failure+hello"
+        );
+    }
+}
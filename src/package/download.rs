@@ -0,0 +1,84 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Download-only installs (`-S --download-only`).
+//!
+//! Closures are realised into the store ahead of an install and kept alive
+//! with a GC root per package until that package is installed.
+
+use crate::core::config::Config;
+use std::path::PathBuf;
+use tracing::debug;
+
+/// Directory holding the GC roots of downloaded packages
+pub fn roots_dir() -> PathBuf {
+    Config::data_dir().join("downloads")
+}
+
+/// GC root path for `package`
+pub fn root_link(package: &str) -> PathBuf {
+    roots_dir().join(package.replace('/', "_"))
+}
+
+/// Drop the GC roots of packages that have now been installed
+pub fn release(packages: &[String]) {
+    for package in packages {
+        let link = root_link(package);
+        if link.symlink_metadata().is_ok() {
+            if let Err(e) = std::fs::remove_file(&link) {
+                debug!("Failed to remove download root {}: {}", link.display(), e);
+            }
+        }
+    }
+}
+
+/// First output path of each entry in `nix build --json` output
+pub fn parse_build_outputs(json: &serde_json::Value) -> Vec<Option<String>> {
+    json.as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|entry| {
+                    let outputs = &entry["outputs"];
+                    outputs["out"]
+                        .as_str()
+                        .or_else(|| outputs.as_object()?.values().next()?.as_str())
+                        .map(String::from)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_build_outputs() {
+        let json = serde_json::json!([
+            {"drvPath": "/nix/store/a.drv", "outputs": {"out": "/nix/store/a-hello-2.12"}},
+            {"drvPath": "/nix/store/b.drv", "outputs": {"bin": "/nix/store/b-tool-bin"}},
+            {"drvPath": "/nix/store/c.drv", "outputs": {}}
+        ]);
+        assert_eq!(
+            parse_build_outputs(&json),
+            vec![Some("/nix/store/a-hello-2.12".to_string()), Some("/nix/store/b-tool-bin".to_string()), None]
+        );
+        assert!(parse_build_outputs(&serde_json::json!({})).is_empty());
+        assert_eq!(root_link("vimPlugins/x").file_name().unwrap(), "vimPlugins_x");
+    }
+}
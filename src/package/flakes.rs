@@ -0,0 +1,163 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Package indexes for private flakes configured under `[sources.flakes]`.
+
+use crate::cache::CacheManager;
+use crate::cache::invalidation::{CacheKey, TTL};
+use crate::core::error::{NixBoostError, Result, SystemError};
+use crate::core::types::{Package, PackageSource};
+use crate::package::upgrade::parse_store_name;
+use crate::utils::command::Echo;
+use futures::future::join_all;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+/// Indexes the packages a flake exposes for the current system
+pub struct FlakeIndexer {
+    arch: String,
+    cache: Option<Arc<CacheManager>>,
+}
+
+impl FlakeIndexer {
+    /// Create an indexer for `arch` (e.g. `x86_64-linux`)
+    pub fn new(arch: impl Into<String>) -> Self {
+        Self {
+            arch: arch.into(),
+            cache: None,
+        }
+    }
+
+    /// Cache indexes between runs
+    pub fn cache(mut self, cache: Option<Arc<CacheManager>>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Packages of one flake, tagged with its alias
+    pub async fn index(&self, name: &str, url: &str) -> Result<Vec<Package>> {
+        let cache_key = CacheKey::flake_index(url);
+        if let Some(ref cache) = self.cache {
+            if let Some(cached) = cache.get::<Vec<Package>>(&cache_key) {
+                debug!("Flake index cache hit for {}", name);
+                return Ok(cached);
+            }
+        }
+
+        debug!("Indexing flake {} ({})", name, url);
+        let output = Command::new("nix")
+            .args(["flake", "show", "--json", url])
+            .echo().output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(SystemError::NixCommandFailed {
+                command: format!("nix flake show {}", url),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            }.into());
+        }
+
+        let json: Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        let packages = parse_flake_show(&json, name, url, &self.arch);
+
+        if let Some(ref cache) = self.cache {
+            if let Err(e) = cache.set(&cache_key, &packages, TTL::package()) {
+                warn!("Failed to cache flake index: {}", e);
+            }
+        }
+
+        Ok(packages)
+    }
+
+    /// Packages of all configured flakes. Flakes that fail to index are
+    /// skipped with a warning so an unreachable remote does not break search.
+    pub async fn index_all(&self, flakes: &BTreeMap<String, String>) -> Vec<Package> {
+        let results = join_all(flakes.iter().map(|(name, url)| self.index(name, url))).await;
+
+        let mut packages = Vec::new();
+        for ((name, _), result) in flakes.iter().zip(results) {
+            match result {
+                Ok(found) => packages.extend(found),
+                Err(e) => warn!("Skipping flake '{}': {}", name, e),
+            }
+        }
+        packages
+    }
+}
+
+/// Read `packages.<arch>` from `nix flake show --json`
+pub fn parse_flake_show(json: &Value, name: &str, url: &str, arch: &str) -> Vec<Package> {
+    let Some(outputs) = json["packages"][arch].as_object() else {
+        return Vec::new();
+    };
+
+    let mut packages: Vec<Package> = outputs
+        .iter()
+        .filter(|(_, out)| out["type"].as_str() == Some("derivation"))
+        .map(|(attr, out)| {
+            let version = out["name"].as_str().map(|n| parse_store_name(n).1).unwrap_or_default();
+            let mut pkg = Package::new(attr.clone(), version, out["description"].as_str().unwrap_or(""));
+            pkg.source = PackageSource::Private {
+                name: name.to_string(),
+                url: url.to_string(),
+            };
+            pkg.attr_path = Some(format!("packages.{}.{}", arch, attr));
+            pkg
+        })
+        .collect();
+
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLAKE_SHOW_FIXTURE: &str = r#"{
+        "devShells": { "x86_64-linux": { "default": { "type": "derivation", "name": "nix-shell" } } },
+        "packages": {
+            "aarch64-linux": {},
+            "x86_64-linux": {
+                "deploy-tool": { "type": "derivation", "name": "deploy-tool-1.4.2", "description": "Internal deploy CLI" },
+                "default": { "type": "derivation", "name": "deploy-tool-1.4.2", "description": "Internal deploy CLI" },
+                "vpn-config": { "type": "derivation", "name": "vpn-config" }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_flake_show() {
+        let json: Value = serde_json::from_str(FLAKE_SHOW_FIXTURE).unwrap();
+        let packages = parse_flake_show(&json, "work", "git+ssh://git@git.corp/nix/flake", "x86_64-linux");
+
+        assert_eq!(packages.len(), 3);
+        assert_eq!(packages[1].name, "deploy-tool");
+        assert_eq!(packages[1].version, "1.4.2");
+        assert_eq!(packages[1].display_name(), "work#deploy-tool");
+        assert_eq!(
+            packages[1].install_command(),
+            "nix profile install git+ssh://git@git.corp/nix/flake#deploy-tool"
+        );
+        assert_eq!(packages[2].version, "");
+
+        assert!(parse_flake_show(&json, "work", "url", "aarch64-darwin").is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Post-install setup hints.
+//!
+//! Some packages only work once something outside the profile is set up
+//! (a group membership, a NixOS service). Hints are keyed by attribute name
+//! and come from a community-maintained file that is fetched and cached,
+//! falling back to the copy bundled with NixBoost.
+
+use crate::cache::CacheManager;
+use crate::cache::invalidation::{CacheKey, TTL};
+use crate::core::config::Config;
+use crate::network::HttpClient;
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Community hints file, same format as the bundled `hints.json`
+const HINTS_URL: &str = "https://raw.githubusercontent.com/NacreousDawn596/nixboost/main/hints.json";
+
+/// Hints shipped with this release
+const BUNDLED_HINTS: &str = include_str!("../../hints.json");
+
+/// Setup hints by attribute name
+#[derive(Debug, Clone, Default)]
+pub struct Hints {
+    entries: HashMap<String, Vec<String>>,
+}
+
+impl Hints {
+    /// Hints bundled with NixBoost
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED_HINTS).unwrap_or_default()
+    }
+
+    /// Parse a hints file: a JSON object of attribute name to hint lines
+    pub fn parse(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok().map(|entries| Self { entries })
+    }
+
+    /// Load the community hints, from `cache` when fresh. Any failure falls
+    /// back to the bundled hints; hints are never worth failing an install.
+    pub async fn load(cache: Option<&CacheManager>) -> Self {
+        let key = CacheKey::install_hints();
+        if let Some(entries) = cache.and_then(|c| c.get::<HashMap<String, Vec<String>>>(&key)) {
+            return Self { entries };
+        }
+
+        let http = Config::try_get().map(HttpClient::from_config).unwrap_or_default().quiet(true);
+        let fetched = match http.get_json::<HashMap<String, Vec<String>>>(HINTS_URL).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("Using bundled install hints: {}", e);
+                return Self::bundled();
+            }
+        };
+
+        if let Some(cache) = cache {
+            let _ = cache.set(&key, &fetched, TTL::HINTS);
+        }
+        Self { entries: fetched }
+    }
+
+    /// Hints for `attr`; `python3Packages.foo` style names fall back to the
+    /// last component
+    pub fn for_package(&self, attr: &str) -> &[String] {
+        let short = attr.rsplit('.').next().unwrap_or(attr);
+        self.entries
+            .get(attr)
+            .or_else(|| self.entries.get(short))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Hints for each of `packages` that has any, in input order
+    pub fn matching<'a>(&'a self, packages: &'a [String]) -> Vec<(&'a str, &'a [String])> {
+        packages
+            .iter()
+            .map(|p| (p.as_str(), self.for_package(p)))
+            .filter(|(_, hints)| !hints.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hints_lookup() {
+        assert!(!Hints::bundled().for_package("docker").is_empty());
+
+        let hints = Hints::parse(r#"{"docker": ["join the docker group"], "foo": []}"#).unwrap();
+        assert_eq!(hints.for_package("docker"), ["join the docker group"]);
+        assert_eq!(hints.for_package("nixpkgs.docker"), ["join the docker group"]);
+        assert!(hints.for_package("podman").is_empty());
+
+        let installed = vec!["hello".to_string(), "docker".to_string(), "foo".to_string()];
+        let matching = hints.matching(&installed);
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].0, "docker");
+
+        assert!(Hints::parse("[1, 2]").is_none());
+    }
+}
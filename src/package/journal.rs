@@ -0,0 +1,167 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Operation journal: one JSON line per install, with the flake revision
+//! each package was resolved from.
+
+use crate::core::config::Config;
+use crate::core::error::{NixBoostError, Result};
+use crate::package::revision::lock_flake;
+use crate::utils::time::unix_now;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use tracing::debug;
+
+/// Kind of recorded operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Install,
+}
+
+/// A package as requested and as resolved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalPackage {
+    /// Installable as given, e.g. `nixpkgs#hello`
+    pub installable: String,
+    /// Same installable on the locked flake, e.g. `github:NixOS/nixpkgs/<rev>#hello`
+    pub locked: Option<String>,
+    pub revision: Option<String>,
+}
+
+impl JournalPackage {
+    /// Installable that reproduces this install exactly, when it was locked
+    pub fn reproducible_ref(&self) -> &str {
+        self.locked.as_deref().unwrap_or(&self.installable)
+    }
+}
+
+/// One journal line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Unix timestamp
+    pub timestamp: u64,
+    pub operation: Operation,
+    pub packages: Vec<JournalPackage>,
+}
+
+impl JournalEntry {
+    pub fn now(operation: Operation, packages: Vec<JournalPackage>) -> Self {
+        Self { timestamp: unix_now(), operation, packages }
+    }
+}
+
+/// Append-only journal file
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    /// Journal in the data directory
+    pub fn new() -> Self {
+        Self::at(Config::data_dir().join("journal.jsonl"))
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn append(&self, entry: &JournalEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let line = serde_json::to_string(entry).map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+
+    /// All entries, oldest first. Unreadable lines are skipped.
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| match serde_json::from_str(l) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    debug!("Skipping journal line: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lock the flake of each installable, looking every flake up once
+pub async fn resolve_installables(installables: &[String]) -> Vec<JournalPackage> {
+    let mut locked = HashMap::new();
+    let mut packages = Vec::new();
+
+    for installable in installables {
+        let (flake, attr) = installable.split_once('#').unwrap_or((installable.as_str(), "default"));
+        if !locked.contains_key(flake) {
+            locked.insert(flake.to_string(), lock_flake(flake).await);
+        }
+
+        let lock = locked.get(flake).cloned().flatten();
+        packages.push(JournalPackage {
+            installable: installable.clone(),
+            locked: lock.as_ref().map(|l| format!("{}#{}", l.url, attr)),
+            revision: lock.and_then(|l| l.revision),
+        });
+    }
+
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let journal = Journal::at(dir.path().join("data").join("journal.jsonl"));
+        assert!(journal.entries().unwrap().is_empty());
+
+        let package = JournalPackage {
+            installable: "nixpkgs#hello".to_string(),
+            locked: Some("github:NixOS/nixpkgs/abc123#hello".to_string()),
+            revision: Some("abc123".to_string()),
+        };
+        journal.append(&JournalEntry::now(Operation::Install, vec![package.clone()])).unwrap();
+        std::fs::OpenOptions::new().append(true).open(&journal.path).unwrap().write_all(b"not json\n").unwrap();
+        journal.append(&JournalEntry::now(Operation::Install, vec![])).unwrap();
+
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].packages[0].reproducible_ref(), "github:NixOS/nixpkgs/abc123#hello");
+    }
+}
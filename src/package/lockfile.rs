@@ -0,0 +1,229 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! `nixboost.lock` - a reproducible snapshot of an imperative profile.
+
+use crate::core::error::{NixBoostError, PackageError, Result};
+use crate::package::profile::ProfileElement;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Default lockfile name
+pub const LOCKFILE_NAME: &str = "nixboost.lock";
+
+/// Current lockfile format version
+const LOCKFILE_VERSION: u32 = 1;
+
+/// Locked state of a whole profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Format version
+    pub version: u32,
+    /// Locked packages, sorted by name
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// A single locked profile element
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    /// Profile element name
+    pub name: String,
+    /// Fully locked installable (`<locked url>#<attrPath>`)
+    pub flake_ref: String,
+    /// Installable as originally requested, for reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_ref: Option<String>,
+    /// NAR hash of the installed output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nar_hash: Option<String>,
+}
+
+/// Actions needed to make a profile match a lockfile
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncPlan {
+    /// Locked packages to install
+    pub install: Vec<LockedPackage>,
+    /// Profile element names to remove
+    pub remove: Vec<String>,
+}
+
+impl LockedPackage {
+    /// Check the NAR hash of the output this package resolved to against
+    /// the locked one. Packages locked without a hash are not checked.
+    pub fn verify_nar_hash(&self, actual: Option<&str>) -> Result<()> {
+        let Some(ref expected) = self.nar_hash else {
+            return Ok(());
+        };
+        if actual == Some(expected.as_str()) {
+            return Ok(());
+        }
+        Err(PackageError::InstallFailed {
+            name: self.name.clone(),
+            reason: format!(
+                "{} has NAR hash {}, lockfile expects {}",
+                self.flake_ref,
+                actual.unwrap_or("unknown"),
+                expected
+            ),
+        }.into())
+    }
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.install.is_empty() && self.remove.is_empty()
+    }
+}
+
+impl Lockfile {
+    /// Build a lockfile from profile elements.
+    ///
+    /// `nar_hash` is consulted for each element's first store path.
+    /// Elements without a locked reference (e.g. `nix-env` leftovers) are skipped.
+    pub fn from_elements<F>(elements: &[ProfileElement], nar_hash: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut packages: Vec<LockedPackage> = elements
+            .iter()
+            .filter_map(|el| {
+                Some(LockedPackage {
+                    name: el.name.clone(),
+                    flake_ref: el.locked_ref()?,
+                    original_ref: el.original_ref(),
+                    nar_hash: el.store_paths.first().and_then(|p| nar_hash(p)),
+                })
+            })
+            .collect();
+
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            version: LOCKFILE_VERSION,
+            packages,
+        }
+    }
+
+    /// Read a lockfile from disk
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let lock: Lockfile = toml::from_str(&content)
+            .map_err(|e| NixBoostError::Serialization(format!("Invalid lockfile {:?}: {}", path, e)))?;
+
+        if lock.version > LOCKFILE_VERSION {
+            return Err(NixBoostError::Serialization(format!(
+                "Lockfile version {} is newer than supported version {}",
+                lock.version, LOCKFILE_VERSION
+            )));
+        }
+
+        Ok(lock)
+    }
+
+    /// Write the lockfile to disk
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        std::fs::write(path, format!("# Generated by nixboost lock - do not edit\n{}", content))?;
+        Ok(())
+    }
+
+    /// Compute what must change for `elements` to exactly match this lock.
+    ///
+    /// Elements whose locked reference differs from the lock are removed and
+    /// reinstalled from the locked reference.
+    pub fn plan(&self, elements: &[ProfileElement]) -> SyncPlan {
+        let mut plan = SyncPlan::default();
+
+        for el in elements {
+            let matches = self.packages
+                .iter()
+                .any(|p| p.name == el.name && Some(&p.flake_ref) == el.locked_ref().as_ref());
+            if !matches {
+                plan.remove.push(el.name.clone());
+            }
+        }
+
+        for pkg in &self.packages {
+            let present = elements
+                .iter()
+                .any(|el| el.name == pkg.name && el.locked_ref().as_ref() == Some(&pkg.flake_ref));
+            if !present {
+                plan.install.push(pkg.clone());
+            }
+        }
+
+        plan
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(name: &str, rev: &str) -> ProfileElement {
+        ProfileElement {
+            name: name.to_string(),
+            attr_path: Some(format!("legacyPackages.x86_64-linux.{}", name)),
+            original_url: Some("flake:nixpkgs".to_string()),
+            url: Some(format!("github:NixOS/nixpkgs/{}", rev)),
+            store_paths: vec![format!("/nix/store/{}-{}", rev, name)],
+        }
+    }
+
+    #[test]
+    fn test_lockfile_roundtrip() {
+        let lock = Lockfile::from_elements(&[element("ripgrep", "aaa"), element("git", "aaa")], |_| {
+            Some("sha256-abc".to_string())
+        });
+        assert_eq!(lock.packages[0].name, "git");
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join(LOCKFILE_NAME);
+        lock.save(&path).unwrap();
+
+        let loaded = Lockfile::load(&path).unwrap();
+        assert_eq!(loaded.packages, lock.packages);
+    }
+
+    #[test]
+    fn test_sync_plan() {
+        let lock = Lockfile::from_elements(&[element("git", "aaa"), element("ripgrep", "aaa")], |_| None);
+
+        let current = vec![element("git", "aaa"), element("ripgrep", "bbb"), element("htop", "aaa")];
+        let plan = lock.plan(&current);
+
+        assert_eq!(plan.remove, vec!["ripgrep", "htop"]);
+        assert_eq!(plan.install.len(), 1);
+        assert_eq!(plan.install[0].name, "ripgrep");
+        assert!(plan.install[0].flake_ref.contains("/aaa#"));
+
+        assert!(lock.plan(&[element("git", "aaa"), element("ripgrep", "aaa")]).is_empty());
+    }
+
+    #[test]
+    fn test_verify_nar_hash() {
+        let lock = Lockfile::from_elements(&[element("git", "aaa")], |_| Some("sha256-abc".to_string()));
+        let git = &lock.packages[0];
+        assert!(git.verify_nar_hash(Some("sha256-abc")).is_ok());
+        assert!(git.verify_nar_hash(Some("sha256-xyz")).is_err());
+        assert!(git.verify_nar_hash(None).is_err());
+
+        let unhashed = Lockfile::from_elements(&[element("git", "aaa")], |_| None);
+        assert!(unhashed.packages[0].verify_nar_hash(None).is_ok());
+    }
+}
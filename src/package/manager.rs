@@ -16,13 +16,39 @@
 
 //! Package manager - core Nix operations with caching and parallel execution.
 
+use crate::core::history;
 use crate::core::error::{NixBoostError, PackageError, Result, SystemError};
-use crate::core::types::{Package, PackageSource};
-use crate::cache::CacheManager;
-use crate::cache::invalidation::{CacheKey, TTL};
+use crate::core::types::{OperationResult, OperationType, Package, PackageSource};
+use crate::cache::{CacheManager, EvalCache};
+use crate::cache::eval_cache::{EvalOutcome, UNKNOWN_REVISION};
+use crate::cache::invalidation::{CacheKey, Invalidation, TTL};
+use crate::network::HttpClient;
+use crate::package::changelog::{self, CommitLog, ReleaseNotes};
+use crate::package::deps::{query_tree, DepNode};
+use crate::package::download;
+use crate::package::journal::{resolve_installables, Journal, JournalEntry, Operation};
+use crate::package::lockfile::{LockedPackage, Lockfile, SyncPlan};
+use crate::package::pins::{Pin, Pins};
+use crate::package::profile::{parse_profile_list, resolve_element, ElementMatch, ProfileElement, ProfileSchema};
+use crate::package::revision::{nixpkgs_revision, track_revision};
+use crate::package::shell::nixpkgs_installable;
+use crate::package::size::{self, InstallSize};
+use crate::package::staging::Staging;
+use crate::package::upgrade::{parse_store_name, renamed_attr, ChangeKind, DeadAttribute, UpgradePreview};
+use crate::package::version::TargetSpec;
+use crate::package::which::{self, StorePathInfo};
+use crate::search::index::LocalIndex;
+use crate::system::DiskGuard;
+use crate::system::generations::{GenerationManager, Profile};
+use crate::system::capabilities::Capabilities;
+use crate::utils::command::Echo;
 use tokio::process::Command;
+use tokio::sync::OnceCell;
 use serde_json::Value;
+use parking_lot::Mutex;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, info, warn, error};
 use futures::future::join_all;
 
@@ -32,6 +58,18 @@ pub struct PackageManager {
     arch: String,
     /// Cache manager (optional)
     cache: Option<Arc<CacheManager>>,
+    /// `nix eval` result cache, resolved lazily on first evaluation
+    eval_cache: OnceCell<Option<EvalCache>>,
+    /// nixpkgs revision, checked against the cached one on first use
+    revision: OnceCell<Option<String>>,
+    /// Local nixpkgs index; searched instead of `nix search` once populated
+    index: Option<LocalIndex>,
+    /// Profile acted on instead of the user's default (`--profile`)
+    profile: Option<PathBuf>,
+    /// Extra flags for installs and upgrades, e.g. reduced `--max-jobs`
+    build_args: Mutex<Vec<String>>,
+    /// System queried instead of the detected one (`--for-system`)
+    system: Option<String>,
 }
 
 impl PackageManager {
@@ -43,27 +81,130 @@ impl PackageManager {
         Ok(Self { 
             arch,
             cache: None,
+            eval_cache: OnceCell::new(),
+            revision: OnceCell::new(),
+            index: None,
+            profile: None,
+            build_args: Mutex::new(Vec::new()),
+            system: None,
         })
     }
 
     /// Create with cache manager
     pub fn with_cache(cache: Arc<CacheManager>) -> Result<Self> {
-        let arch = detect_system_arch()?;
+        let arch = match cache.get::<String>(&CacheKey::current_system()) {
+            Some(arch) => arch,
+            None => {
+                let arch = detect_system_arch()?;
+                if let Err(e) = cache.set(&CacheKey::current_system(), &arch, TTL::LONG) {
+                    warn!("Failed to cache system architecture: {}", e);
+                }
+                arch
+            }
+        };
+
+        let index = match LocalIndex::open_default() {
+            Ok(index) => Some(index),
+            Err(e) => {
+                warn!("Local package index unavailable: {}", e);
+                None
+            }
+        };
+
         Ok(Self {
             arch,
             cache: Some(cache),
+            eval_cache: OnceCell::new(),
+            revision: OnceCell::new(),
+            index,
+            profile: None,
+            build_args: Mutex::new(Vec::new()),
+            system: None,
         })
     }
 
+    /// Act on `profile` instead of the user's default profile
+    pub fn with_profile(mut self, profile: Option<PathBuf>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Evaluate searches, package info and existence checks for `system`
+    /// instead of the detected architecture
+    pub fn with_system(mut self, system: Option<String>) -> Self {
+        self.system = system.filter(|s| *s != self.arch);
+        self
+    }
+
+    /// System searches and metadata queries evaluate for
+    pub fn query_system(&self) -> &str {
+        self.system.as_deref().unwrap_or(&self.arch)
+    }
+
+    /// Installable for a nixpkgs attribute of the queried system
+    fn nixpkgs_attr(&self, attr: &str) -> String {
+        match self.system {
+            Some(ref system) => format!("nixpkgs#legacyPackages.{}.{}", system, attr),
+            None => format!("nixpkgs#{}", attr),
+        }
+    }
+
+    /// Pass `args` to the nix builds of later installs and upgrades
+    pub fn set_build_args(&self, args: Vec<String>) {
+        *self.build_args.lock() = args;
+    }
+
+    /// `nix profile <subcommand>` on the profile this manager acts on
+    fn nix_profile(&self, subcommand: &str) -> Command {
+        let mut cmd = Command::new("nix");
+        cmd.args(["profile", subcommand]);
+        if let Some(ref profile) = self.profile {
+            cmd.arg("--profile").arg(profile);
+        }
+        if matches!(subcommand, "install" | "upgrade") {
+            cmd.args(self.build_args.lock().iter());
+        }
+        cmd
+    }
+
+    /// Generations of the profile this manager acts on
+    fn generations(&self) -> GenerationManager {
+        match self.profile {
+            Some(ref profile) => GenerationManager::new(Profile::Path(profile.clone())),
+            None => GenerationManager::default(),
+        }
+    }
+
     /// Get the system architecture
     pub fn arch(&self) -> &str {
         &self.arch
     }
 
-    /// Search nixpkgs for packages
+    /// Search nixpkgs for packages, from the local index when it has been built
     pub async fn search(&self, query: &str) -> Result<Vec<Package>> {
+        self.search_limit(query, 0).await
+    }
+
+    /// Like [`search`](Self::search), returning enough candidates for
+    /// `max_results` ranked results
+    pub async fn search_limit(&self, query: &str, max_results: usize) -> Result<Vec<Package>> {
+        // The local index only covers the detected system
+        if let Some(index) = self.index.as_ref().filter(|i| i.is_populated() && self.system.is_none()) {
+            return index.candidates(query, max_results);
+        }
+        self.search_nix(query).await
+    }
+
+    /// Search nixpkgs with `nix search`
+    pub async fn search_nix(&self, query: &str) -> Result<Vec<Package>> {
+        // Cached results of an older nixpkgs are dropped here
+        self.revision().await;
+
         // Check cache first
-        let cache_key = CacheKey::search(query);
+        let cache_key = match self.system {
+            Some(ref system) => format!("{}:{}", CacheKey::search(query), system),
+            None => CacheKey::search(query),
+        };
         if let Some(ref cache) = self.cache {
             if let Some(cached) = cache.get::<Vec<Package>>(&cache_key) {
                 debug!("Search cache hit for '{}'", query);
@@ -72,11 +213,15 @@ impl PackageManager {
         }
 
         debug!("Searching nixpkgs for '{}'", query);
-        let legacy_prefix = format!("legacyPackages.{}.", self.arch);
+        let legacy_prefix = format!("legacyPackages.{}.", self.query_system());
+        let installable = match self.system {
+            Some(ref system) => format!("nixpkgs#legacyPackages.{}", system),
+            None => "nixpkgs".to_string(),
+        };
 
         let output = Command::new("nix")
-            .args(["search", "--json", "nixpkgs", query])
-            .output()
+            .args(["search", "--json", &installable, query])
+            .echo().output()
             .await?;
 
         if !output.status.success() {
@@ -108,7 +253,7 @@ impl PackageManager {
 
         // Cache results
         if let Some(ref cache) = self.cache {
-            if let Err(e) = cache.set(&cache_key, &results, TTL::SEARCH) {
+            if let Err(e) = cache.set(&cache_key, &results, TTL::search()) {
                 warn!("Failed to cache search results: {}", e);
             }
         }
@@ -117,49 +262,175 @@ impl PackageManager {
         Ok(results)
     }
 
-    /// Install packages (batch operation)
-    pub async fn install(&self, packages: &[String]) -> Result<()> {
-        if packages.is_empty() {
-            return Ok(());
+    /// Resolve version-constrained targets (`nodejs@20`, `nodejs>=18`) to
+    /// concrete attribute names; unconstrained targets pass through as-is
+    pub async fn resolve_targets(&self, targets: &[String]) -> Result<Vec<String>> {
+        let mut resolved = Vec::with_capacity(targets.len());
+
+        for target in targets {
+            let spec = TargetSpec::parse(target)?;
+            if spec.constraint.is_none() {
+                resolved.push(spec.name);
+                continue;
+            }
+
+            let candidates = self.search(&format!("^{}", spec.name)).await?;
+            let pkg = spec.select(&candidates)?;
+            info!("Resolved {} to {} ({})", target, pkg.name, pkg.version);
+            resolved.push(pkg.name.clone());
         }
 
-        info!("Installing {} package(s)", packages.len());
+        Ok(resolved)
+    }
 
-        let install_args: Vec<String> = packages.iter()
+    /// Install packages (batch operation)
+    pub async fn install(&self, packages: &[String]) -> Result<()> {
+        let refs: Vec<String> = packages.iter()
             .map(|p| format!("nixpkgs#{}", p))
             .collect();
+        self.install_refs(&refs).await
+    }
 
-        let mut args = vec!["profile", "install"];
-        let refs: Vec<&str> = install_args.iter().map(|s| s.as_str()).collect();
-        args.extend(refs);
+    /// Realise nixpkgs `packages` into the store without adding them to the
+    /// profile, rooting each closure under [`download::roots_dir`] so garbage
+    /// collection keeps it until it is installed. Returns the output paths.
+    ///
+    /// With `refresh`, the latest nixpkgs revision is fetched first.
+    pub async fn download(&self, packages: &[String], refresh: bool) -> Result<Vec<(String, String)>> {
+        let refs: Vec<String> = packages.iter().map(|p| format!("nixpkgs#{}", p)).collect();
+        let mut cmd = Command::new("nix");
+        cmd.args(["build", "--no-link", "--json"]).args(&refs);
+        if refresh {
+            cmd.arg("--refresh");
+        }
+        let output = cmd.echo().output().await?;
 
-        let status = Command::new("nix")
-            .args(&args)
-            .status()
+        if !output.status.success() {
+            return Err(PackageError::InstallFailed {
+                name: packages.join(", "),
+                reason: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            }.into());
+        }
+        if refresh {
+            self.invalidate(Invalidation::ChannelUpdated);
+        }
+
+        let json: Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        let roots = download::roots_dir();
+        std::fs::create_dir_all(&roots)?;
+
+        let mut downloaded = Vec::new();
+        for (package, path) in packages.iter().zip(download::parse_build_outputs(&json)) {
+            let Some(path) = path else { continue };
+            let link = download::root_link(package);
+            let status = std::process::Command::new("nix-store")
+                .arg("--add-root")
+                .arg(&link)
+                .args(["--realise", &path])
+                .stdout(std::process::Stdio::null())
+                .echo().status()?;
+            if !status.success() {
+                warn!("Failed to add a GC root for {}", package);
+            }
+            downloaded.push((package.clone(), path));
+        }
+        Ok(downloaded)
+    }
+
+    /// Install full flake references such as `git+ssh://host/flake#tool`
+    pub async fn install_refs(&self, refs: &[String]) -> Result<()> {
+        if refs.is_empty() {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+        let result = self.profile_install(refs).await;
+        history::record(OperationType::Install, refs, start, &result);
+        result
+    }
+
+    async fn profile_install(&self, refs: &[String]) -> Result<()> {
+        info!("Installing {} package(s)", refs.len());
+
+        let status = self.nix_profile("install")
+            .args(refs)
+            .echo().status()
             .await?;
 
         if !status.success() {
             return Err(PackageError::InstallFailed {
-                name: packages.join(", "),
+                name: refs.join(", "),
                 reason: "nix profile install failed".to_string(),
             }.into());
         }
 
-        // Invalidate installed packages cache
-        if let Some(ref cache) = self.cache {
-            let _ = cache.disk.delete(&CacheKey::installed());
+        let names: Vec<String> = refs.iter().map(|r| installable_attr(r).to_string()).collect();
+        self.invalidate(Invalidation::ProfileChanged(&names));
+
+        // Record the revisions the refs resolved to, for `history revisions`
+        let entry = JournalEntry::now(Operation::Install, resolve_installables(refs).await);
+        if let Err(e) = Journal::new().append(&entry) {
+            warn!("Failed to write operation journal: {}", e);
         }
 
         Ok(())
     }
 
+    /// Install refs as a transaction (`--atomic`).
+    ///
+    /// The generation from before is recorded and each ref is installed on
+    /// its own, stopping at the first failure. If earlier refs already went
+    /// in, the result carries the recorded generation in `rollback_to` so the
+    /// caller can restore it.
+    pub async fn install_atomic(&self, refs: &[String]) -> Result<OperationResult> {
+        let before = self.generations().current()?.map(|g| g.number);
+        debug!("Transaction started at generation {:?}", before);
+
+        Ok(install_transaction(refs, before, |r| async move {
+            self.install_refs(std::slice::from_ref(&r)).await
+        }).await)
+    }
+
+    /// Apply staged removals and installs as one transaction.
+    ///
+    /// Removals are resolved up front so a typo fails before anything
+    /// changes. If the install fails after the removals went through, the
+    /// result carries the generation from before in `rollback_to`.
+    pub async fn apply_staged(&self, staging: &Staging) -> Result<OperationResult> {
+        let start = Instant::now();
+        let changes: Vec<String> = staging.install.iter().chain(&staging.remove).cloned().collect();
+        let elements = self.profile_elements().await?;
+        let selected = resolve_all(&elements, &staging.remove)?;
+        let before = self.generations().current()?.map(|g| g.number);
+        debug!("Applying {} staged change(s) from generation {:?}", changes.len(), before);
+
+        let failed = |e: NixBoostError, rollback_to: Option<u64>| {
+            let mut result = OperationResult::failure(OperationType::Update, changes.clone(), e.to_string());
+            result.duration_ms = start.elapsed().as_millis() as u64;
+            result.rollback_to = rollback_to;
+            result
+        };
+
+        if let Err(e) = self.remove_elements(&selected).await {
+            return Ok(failed(e, None));
+        }
+        let refs: Vec<String> = staging.install.iter().map(|p| nixpkgs_installable(p)).collect();
+        if let Err(e) = self.install_refs(&refs).await {
+            let rollback_to = if selected.is_empty() { None } else { before };
+            return Ok(failed(e, rollback_to));
+        }
+
+        Ok(OperationResult::success(OperationType::Update, changes, start.elapsed().as_millis() as u64))
+    }
+
     /// Install a single package with detailed error reporting
     pub async fn install_single(&self, package: &str) -> Result<()> {
         debug!("Installing package: {}", package);
 
-        let status = Command::new("nix")
-            .args(["profile", "install", &format!("nixpkgs#{}", package)])
-            .status()
+        let status = self.nix_profile("install")
+            .arg(format!("nixpkgs#{}", package))
+            .echo().status()
             .await?;
 
         if !status.success() {
@@ -191,42 +462,300 @@ impl PackageManager {
         all_results
     }
 
-    /// Remove packages (batch operation)
+    /// Remove packages by name (batch operation).
+    ///
+    /// Names are resolved with [`resolve_element`]; an unknown or ambiguous
+    /// name fails the whole batch before anything is removed.
     pub async fn remove(&self, packages: &[String]) -> Result<()> {
         if packages.is_empty() {
             return Ok(());
         }
 
-        info!("Removing {} package(s)", packages.len());
+        let elements = self.profile_elements().await?;
+        let selected = resolve_all(&elements, packages)?;
+        self.remove_elements(&selected).await
+    }
 
-        let mut args = vec!["profile", "remove"];
-        let refs: Vec<&str> = packages.iter().map(|s| s.as_str()).collect();
-        args.extend(refs);
+    /// Remove already-resolved profile elements
+    pub async fn remove_elements(&self, elements: &[ProfileElement]) -> Result<()> {
+        if elements.is_empty() {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+        let result = self.profile_remove(elements).await;
+        let names: Vec<String> = elements.iter().map(|el| el.name.clone()).collect();
+        history::record(OperationType::Remove, &names, start, &result);
+        result
+    }
 
-        let status = Command::new("nix")
-            .args(&args)
-            .status()
+    async fn profile_remove(&self, elements: &[ProfileElement]) -> Result<()> {
+        info!("Removing {} package(s)", elements.len());
+
+        let status = self.nix_profile("remove")
+            .args(element_targets(elements))
+            .echo().status()
             .await?;
 
         if !status.success() {
+            let names: Vec<&str> = elements.iter().map(|el| el.name.as_str()).collect();
             return Err(PackageError::RemoveFailed {
-                name: packages.join(", "),
+                name: names.join(", "),
                 reason: "nix profile remove failed".to_string(),
             }.into());
         }
 
-        // Invalidate installed packages cache
+        let names: Vec<String> = elements.iter().map(|el| el.name.clone()).collect();
+        self.invalidate(Invalidation::ProfileChanged(&names));
+
+        Ok(())
+    }
+
+    /// Remove and reinstall profile elements from the flake reference they
+    /// were originally installed from, keeping flake and NUR provenance.
+    /// Returns the reinstalled references.
+    pub async fn reinstall(&self, packages: &[String]) -> Result<Vec<String>> {
+        let elements = self.profile_elements().await?;
+        let selected = resolve_all(&elements, packages)?;
+
+        let mut refs = Vec::with_capacity(selected.len());
+        for el in &selected {
+            match el.original_ref() {
+                Some(flake_ref) => refs.push(flake_ref),
+                None => {
+                    return Err(PackageError::InvalidSpec(format!(
+                        "{} was installed from a store path and has no flake reference to reinstall from",
+                        el.name
+                    )).into());
+                }
+            }
+        }
+
+        self.remove_elements(&selected).await?;
+        if let Err(e) = self.install_refs(&refs).await {
+            return Err(PackageError::InstallFailed {
+                name: refs.join(" "),
+                reason: format!("removed but not reinstalled ({}); run 'nix profile install {}'", e, refs.join(" ")),
+            }.into());
+        }
+
+        Ok(refs)
+    }
+
+    /// Upgrade profile elements by name, or every element when `packages`
+    /// is empty, and report the resulting version changes
+    pub async fn upgrade(&self, packages: &[String]) -> Result<UpgradePreview> {
+        let before = self.profile_elements().await?;
+        let requested = resolve_all(&before, packages)?;
+
+        // Pinned packages stay at their commit until unpinned
+        let pins = Pins::load()?;
+        let scope = if requested.is_empty() { before.clone() } else { requested };
+        let (pinned, selected): (Vec<ProfileElement>, Vec<ProfileElement>) =
+            scope.into_iter().partition(|el| pins.is_pinned(&el.name));
+        for el in &pinned {
+            info!("Skipping {}: pinned to nixpkgs {}", el.name, pins.pins[&el.name].rev);
+        }
+        if selected.is_empty() {
+            return Ok(UpgradePreview::default());
+        }
+
+        let mut cmd = self.nix_profile("upgrade");
+        if packages.is_empty() && pinned.is_empty() {
+            info!("Upgrading all {} package(s)", before.len());
+            if Capabilities::detect().profile_remove_by_name {
+                cmd.arg("--all");
+            } else {
+                cmd.arg(".*");
+            }
+        } else {
+            info!("Upgrading {} package(s)", selected.len());
+            cmd.args(element_targets(&selected));
+        }
+
+        let status = cmd.echo().status().await?;
+
+        // The profile may have changed even if some elements failed, and
+        // the upgrade fetched the latest nixpkgs
+        let names: Vec<String> = selected.iter().map(|el| el.name.clone()).collect();
+        self.invalidate(Invalidation::ProfileChanged(&names));
+        self.invalidate(Invalidation::ChannelUpdated);
+
+        if !status.success() {
+            return Err(PackageError::TransactionFailed("nix profile upgrade failed".to_string()).into());
+        }
+
+        let after = self.profile_elements().await?;
+        let version = |el: &ProfileElement| el.store_paths.first().map(|p| parse_store_name(p).1);
+        let installed: Vec<(String, String)> = selected
+            .iter()
+            .filter_map(|el| Some((el.name.clone(), version(el)?)))
+            .collect();
+
+        Ok(UpgradePreview::from_versions(&installed, |name| {
+            after.iter().find(|el| el.name == name).and_then(version)
+        }))
+    }
+
+    /// Install `package` from nixpkgs commit `rev` and record the pin.
+    /// An installed copy is replaced.
+    pub async fn pin(&self, package: &str, rev: &str) -> Result<Pin> {
+        let pin = Pin::new(package, rev)?;
+        let name = package.rsplit('.').next().unwrap_or(package);
+
+        let elements = self.profile_elements().await?;
+        match resolve_element(&elements, name) {
+            ElementMatch::One(el) => self.remove_elements(std::slice::from_ref(el)).await?,
+            ElementMatch::None => {}
+            ElementMatch::Ambiguous(candidates) => {
+                return Err(PackageError::Ambiguous {
+                    query: name.to_string(),
+                    candidates: candidates.iter().map(|el| el.name.as_str()).collect::<Vec<_>>().join(", "),
+                }.into());
+            }
+        }
+        self.install_refs(std::slice::from_ref(&pin.flake_ref)).await?;
+
+        let mut pins = Pins::load()?;
+        pins.pins.insert(name.to_string(), pin.clone());
+        pins.save()?;
+        Ok(pin)
+    }
+
+    /// Store path of an installed package, or of the nixpkgs attribute
+    /// (built or substituted on demand)
+    pub async fn store_path(&self, package: &str) -> Result<String> {
+        let elements = self.profile_elements().await?;
+        if let ElementMatch::One(el) = resolve_element(&elements, package) {
+            if let Some(path) = el.store_paths.first() {
+                return Ok(path.clone());
+            }
+        }
+
+        let output = Command::new("nix")
+            .args(["build", "--no-link", "--print-out-paths", &format!("nixpkgs#{}", package)])
+            .echo().output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(PackageError::NotFound { name: package.to_string() }.into());
+        }
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .map(String::from)
+            .ok_or_else(|| PackageError::NotFound { name: package.to_string() }.into())
+    }
+
+    /// Store paths `package` resolves to, the element owning them and their
+    /// size. The cached installed list saves a `nix profile list` for
+    /// packages that are not installed.
+    pub async fn which(&self, package: &str) -> Result<StorePathInfo> {
+        let installed = self.list_installed().await?;
+        let elements = if which::may_be_installed(&installed, package) {
+            self.profile_elements().await?
+        } else {
+            Vec::new()
+        };
+        let owner = which::owner(&elements, package)?;
+
+        let installable = if package.contains('#') { package.to_string() } else { self.nixpkgs_attr(package) };
+        let store_paths = match owner {
+            Some(el) => el.store_paths.clone(),
+            None => {
+                let outcome = self.nix_eval(&["--raw", &format!("{}.outPath", installable)]).await?;
+                if !outcome.success || outcome.stdout.is_empty() {
+                    return Err(PackageError::NotFound { name: package.to_string() }.into());
+                }
+                vec![outcome.stdout]
+            }
+        };
+
+        let in_store = store_paths.iter().all(|path| std::path::Path::new(path).exists());
+        let (closure_bytes, download_bytes) = if in_store {
+            let sizes = crate::system::garbage_collector::closure_sizes(&store_paths)?;
+            (Some(sizes.iter().map(|(_, size)| size).sum()), None)
+        } else {
+            match size::query(&installable).await {
+                Ok(size) => (Some(size.closure_bytes), size.download_bytes),
+                Err(e) => {
+                    debug!("No binary cache size for {}: {}", package, e);
+                    (None, None)
+                }
+            }
+        };
+
+        Ok(StorePathInfo {
+            package: package.to_string(),
+            store_paths,
+            element: owner.map(|el| el.name.clone()),
+            in_store,
+            closure_bytes,
+            download_bytes,
+        })
+    }
+
+    /// Runtime dependency tree of a package, cached by store path
+    pub async fn dependency_tree(&self, package: &str) -> Result<DepNode> {
+        let path = self.store_path(package).await?;
+        let cache_key = CacheKey::dependencies(&path);
         if let Some(ref cache) = self.cache {
-            let _ = cache.disk.delete(&CacheKey::installed());
+            if let Some(cached) = cache.get::<DepNode>(&cache_key) {
+                debug!("Dependency cache hit for {}", path);
+                return Ok(cached);
+            }
         }
 
-        Ok(())
+        let tree = query_tree(&path).await?;
+
+        if let Some(ref cache) = self.cache {
+            if let Err(e) = cache.set(&cache_key, &tree, TTL::DEPENDENCIES) {
+                warn!("Failed to cache dependency tree: {}", e);
+            }
+        }
+
+        Ok(tree)
+    }
+
+    /// Closure and download size of each package, `None` where the binary
+    /// cache could not be asked
+    pub async fn install_sizes(&self, packages: &[String]) -> Vec<(String, Option<InstallSize>)> {
+        let futures: Vec<_> = packages.iter()
+            .map(|pkg| async move {
+                let installable = self.nixpkgs_attr(pkg);
+                let cache_key = CacheKey::size(&installable);
+                if let Some(cached) = self.cache.as_ref().and_then(|c| c.get::<InstallSize>(&cache_key)) {
+                    return (pkg.clone(), Some(cached));
+                }
+
+                match size::query(&installable).await {
+                    Ok(install_size) => {
+                        if let Some(ref cache) = self.cache {
+                            if let Err(e) = cache.set(&cache_key, &install_size, TTL::PACKAGE) {
+                                warn!("Failed to cache install size: {}", e);
+                            }
+                        }
+                        (pkg.clone(), Some(install_size))
+                    }
+                    Err(e) => {
+                        debug!("No install size for {}: {}", pkg, e);
+                        (pkg.clone(), None)
+                    }
+                }
+            })
+            .collect();
+
+        join_all(futures).await
     }
 
     /// List installed packages
     pub async fn list_installed(&self) -> Result<Vec<String>> {
         // Check cache first
-        let cache_key = CacheKey::installed();
+        let cache_key = match self.profile {
+            Some(ref profile) => format!("{}:{}", CacheKey::installed(), profile.display()),
+            None => CacheKey::installed(),
+        };
         if let Some(ref cache) = self.cache {
             if let Some(cached) = cache.get::<Vec<String>>(&cache_key) {
                 debug!("Installed packages cache hit");
@@ -234,9 +763,45 @@ impl PackageManager {
             }
         }
 
-        let output = Command::new("nix")
-            .args(["profile", "list", "--json"])
-            .output()
+        let mut installed: Vec<String> = self.profile_elements()
+            .await?
+            .into_iter()
+            .map(|el| el.name)
+            .collect();
+
+        installed.sort();
+
+        // Cache results
+        if let Some(ref cache) = self.cache {
+            if let Err(e) = cache.set(&cache_key, &installed, TTL::INSTALLED) {
+                warn!("Failed to cache installed packages: {}", e);
+            }
+        }
+
+        Ok(installed)
+    }
+
+    /// Drop cached data that `event` makes stale
+    fn invalidate(&self, event: Invalidation) {
+        if let Some(ref cache) = self.cache {
+            cache.on(event);
+        }
+    }
+
+    /// Get the full profile elements from `nix profile list`
+    pub async fn profile_elements(&self) -> Result<Vec<ProfileElement>> {
+        let caps = Capabilities::detect();
+        if !caps.profile_list_json {
+            let version = caps.version.map(|v| v.to_string()).unwrap_or_else(|| "unknown".to_string());
+            return Err(SystemError::NixCommandFailed {
+                command: "nix profile list --json".to_string(),
+                stderr: format!("Nix {} does not support JSON profile listings; 2.17 or newer is required", version),
+            }.into());
+        }
+
+        let output = self.nix_profile("list")
+            .arg("--json")
+            .echo().output()
             .await?;
 
         if !output.status.success() {
@@ -249,24 +814,99 @@ impl PackageManager {
         let json: Value = serde_json::from_slice(&output.stdout)
             .map_err(|e| NixBoostError::Serialization(e.to_string()))?;
 
-        let mut installed = Vec::new();
+        let schema = ProfileSchema::detect(&json);
+        let expected = if caps.profile_elements_by_name { ProfileSchema::ByName } else { ProfileSchema::Indexed };
+        if schema != Some(expected) {
+            debug!("Profile list uses {:?} schema, expected {:?} for this Nix version", schema, expected);
+        }
+
+        Ok(parse_profile_list(&json))
+    }
+
+    /// Snapshot the current profile into a lockfile
+    pub async fn lock(&self) -> Result<Lockfile> {
+        let elements = self.profile_elements().await?;
+
+        let paths: Vec<String> = elements.iter()
+            .filter_map(|el| el.store_paths.first().cloned())
+            .collect();
+        let hashes = nar_hashes(&paths).await?;
+
+        Ok(Lockfile::from_elements(&elements, |path| hashes.get(path).cloned()))
+    }
+
+    /// Make the profile match a lockfile exactly
+    pub async fn sync_frozen(&self, lock: &Lockfile) -> Result<SyncPlan> {
+        let elements = self.profile_elements().await?;
+        let plan = lock.plan(&elements);
+        self.verify_locked_hashes(lock, &elements).await?;
+
+        if !plan.remove.is_empty() {
+            self.remove(&plan.remove).await?;
+        }
 
-        if let Some(elements) = json["elements"].as_object() {
-            for (name, _) in elements {
-                installed.push(name.clone());
+        for pkg in &plan.install {
+            info!("Installing locked {}", pkg.flake_ref);
+            let status = self.nix_profile("install")
+                .arg(&pkg.flake_ref)
+                .echo().status()
+                .await?;
+
+            if !status.success() {
+                return Err(PackageError::InstallFailed {
+                    name: pkg.name.clone(),
+                    reason: format!("nix profile install {} failed", pkg.flake_ref),
+                }.into());
             }
         }
 
-        installed.sort();
+        let names: Vec<String> = plan.install.iter().map(|pkg| pkg.name.clone()).collect();
+        self.invalidate(Invalidation::ProfileChanged(&names));
 
-        // Cache results
-        if let Some(ref cache) = self.cache {
-            if let Err(e) = cache.set(&cache_key, &installed, TTL::INSTALLED) {
-                warn!("Failed to cache installed packages: {}", e);
-            }
+        Ok(plan)
+    }
+
+    /// Compare each locked package's NAR hash with `nix path-info` of its
+    /// output before the profile is touched. Outputs that are not installed
+    /// yet are built (or substituted) from the locked reference.
+    async fn verify_locked_hashes(&self, lock: &Lockfile, elements: &[ProfileElement]) -> Result<()> {
+        let mut outputs = Vec::new();
+        for pkg in lock.packages.iter().filter(|p| p.nar_hash.is_some()) {
+            let installed = elements.iter()
+                .find(|el| el.name == pkg.name && el.locked_ref().as_ref() == Some(&pkg.flake_ref))
+                .and_then(|el| el.store_paths.first().cloned());
+            let path = match installed {
+                Some(path) => path,
+                None => self.build_locked(pkg).await?,
+            };
+            outputs.push((pkg, path));
         }
 
-        Ok(installed)
+        let paths: Vec<String> = outputs.iter().map(|(_, path)| path.clone()).collect();
+        let hashes = nar_hashes(&paths).await?;
+        for (pkg, path) in outputs {
+            pkg.verify_nar_hash(hashes.get(&path).map(String::as_str))?;
+        }
+        Ok(())
+    }
+
+    /// Output path of a locked reference, built without touching the profile
+    async fn build_locked(&self, pkg: &LockedPackage) -> Result<String> {
+        let failed = |reason: String| PackageError::InstallFailed { name: pkg.name.clone(), reason };
+
+        let output = Command::new("nix")
+            .args(["build", "--no-link", "--print-out-paths", &pkg.flake_ref])
+            .echo().output()
+            .await?;
+        if !output.status.success() {
+            return Err(failed(String::from_utf8_lossy(&output.stderr).trim().to_string()).into());
+        }
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .map(String::from)
+            .ok_or_else(|| failed(format!("nix build {} produced no output", pkg.flake_ref)).into())
     }
 
     /// Dry run install - check if packages exist without installing
@@ -283,55 +923,545 @@ impl PackageManager {
 
     /// Check if a package exists in nixpkgs
     pub async fn package_exists(&self, package: &str) -> bool {
-        let output = Command::new("nix")
-            .args(["eval", "--raw", &format!("nixpkgs#{}.meta.name", package)])
-            .output()
-            .await;
-
-        match output {
-            Ok(o) => o.status.success(),
+        let attr = format!("{}.meta.name", self.nixpkgs_attr(package));
+        match self.nix_eval(&["--raw", &attr]).await {
+            Ok(outcome) => outcome.success,
             Err(_) => false,
         }
     }
 
-    /// Get package info
-    pub async fn package_info(&self, package: &str) -> Result<Option<Package>> {
-        debug!("Getting info for package: {}", package);
+    /// Get package info for one or more attributes.
+    ///
+    /// All names are evaluated in a single `nix eval` call; results come back
+    /// in input order with `None` for attributes that do not exist.
+    pub async fn package_info(&self, packages: &[String]) -> Result<Vec<(String, Option<Package>)>> {
+        debug!("Getting info for {} package(s)", packages.len());
+
+        let eval_cache = self.eval_cache().await;
+        let mut found: std::collections::HashMap<String, Value> = std::collections::HashMap::new();
+        let mut missing: Vec<String> = Vec::new();
+
+        for name in packages {
+            let cached = eval_cache
+                .and_then(|c| c.get(&info_cache_expr(name, self.query_system())))
+                .and_then(|o| serde_json::from_str::<Value>(&o.stdout).ok());
+            match cached {
+                Some(meta) => {
+                    found.insert(name.clone(), meta);
+                }
+                None if !missing.contains(name) => missing.push(name.clone()),
+                None => {}
+            }
+        }
+
+        if !missing.is_empty() {
+            let json = self.eval_package_meta(&missing, false).await?;
+
+            for name in &missing {
+                let meta = json[name.as_str()].clone();
+                if let Some(cache) = eval_cache {
+                    cache.set(&info_cache_expr(name, self.query_system()), &EvalOutcome {
+                        success: !meta.is_null(),
+                        stdout: meta.to_string(),
+                    });
+                }
+                found.insert(name.clone(), meta);
+            }
+        }
+
+        Ok(packages
+            .iter()
+            .map(|name| {
+                let pkg = found.get(name).and_then(|meta| package_from_meta(name, meta));
+                (name.clone(), pkg)
+            })
+            .collect())
+    }
+
+    /// Fill in license, maintainer and platform metadata for nixpkgs
+    /// packages that came from `nix search` or the index without it
+    pub async fn fill_metadata(&self, packages: &mut [Package]) -> Result<()> {
+        let names: Vec<String> = packages
+            .iter()
+            .filter(|p| p.source == PackageSource::Nixpkgs && p.license.is_none() && p.platforms.is_empty())
+            .map(|p| p.attr_path.clone().unwrap_or_else(|| p.name.clone()))
+            .collect();
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        let info: std::collections::HashMap<String, Package> = self
+            .package_info(&names)
+            .await?
+            .into_iter()
+            .filter_map(|(name, pkg)| pkg.map(|p| (name, p)))
+            .collect();
+
+        for pkg in packages.iter_mut() {
+            let key = pkg.attr_path.as_deref().unwrap_or(&pkg.name);
+            if let Some(meta) = info.get(key) {
+                pkg.license = meta.license.clone();
+                pkg.maintainers = meta.maintainers.clone();
+                pkg.platforms = meta.platforms.clone();
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate metadata for several nixpkgs attributes in one `nix eval`.
+    ///
+    /// With `refresh`, the `nixpkgs` flake is re-fetched instead of using the
+    /// locally cached revision.
+    async fn eval_package_meta(&self, names: &[String], refresh: bool) -> Result<Value> {
+        let installable = format!("nixpkgs#legacyPackages.{}", self.query_system());
+        let apply = info_apply_expr(names);
+
+        let mut cmd = Command::new("nix");
+        cmd.args(["eval", "--json", &installable, "--apply", &apply]);
+        if refresh {
+            cmd.arg("--refresh");
+        }
+        let output = cmd.echo().output().await?;
+
+        if !output.status.success() {
+            return Err(SystemError::NixCommandFailed {
+                command: "nix eval".to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            }.into());
+        }
+
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| NixBoostError::Serialization(e.to_string()))
+    }
+
+    /// Top-level nixpkgs attributes that remain with `config.allowAliases = false`.
+    /// Attributes `nix search` reports outside this set are aliases.
+    pub async fn non_alias_attrs(&self) -> Result<std::collections::HashSet<String>> {
+        let installable = format!("nixpkgs#legacyPackages.{}", self.query_system());
+        let apply = "pkgs: builtins.attrNames (import pkgs.path { \
+            localSystem = pkgs.stdenv.hostPlatform.system; \
+            config.allowAliases = false; overlays = []; })";
 
         let output = Command::new("nix")
-            .args(["eval", "--json", &format!("nixpkgs#{}", package)])
-            .output()
+            .args(["eval", "--json", &installable, "--apply", apply])
+            .echo().output()
             .await?;
 
         if !output.status.success() {
-            return Ok(None);
+            return Err(SystemError::NixCommandFailed {
+                command: "nix eval".to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            }.into());
         }
 
-        let json: Value = serde_json::from_slice(&output.stdout)
-            .map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| NixBoostError::Serialization(e.to_string()))
+    }
+
+    /// Split profile elements into nixpkgs ones, as `(element, attr, version)`,
+    /// and the names of the rest
+    pub async fn nixpkgs_elements(&self) -> Result<(Vec<(String, String, String)>, Vec<String>)> {
+        let elements = self.profile_elements().await?;
+        let prefix = format!("legacyPackages.{}.", self.arch);
+
+        let mut nixpkgs = Vec::new();
+        let mut skipped = Vec::new();
+        for el in &elements {
+            let from_nixpkgs = el.original_url.as_deref().map(|u| u.contains("nixpkgs")).unwrap_or(false);
+            let attr = el.attr_path.as_deref().and_then(|a| a.strip_prefix(prefix.as_str()));
+            match (from_nixpkgs, attr, el.store_paths.first()) {
+                (true, Some(attr), Some(path)) => {
+                    nixpkgs.push((el.name.clone(), attr.to_string(), parse_store_name(path).1));
+                }
+                _ => skipped.push(el.name.clone()),
+            }
+        }
+        Ok((nixpkgs, skipped))
+    }
 
-        let name = json["pname"].as_str().unwrap_or(package);
-        let version = json["version"].as_str().unwrap_or("unknown");
-        let description = json["meta"]["description"].as_str().unwrap_or("");
+    /// The attribute `attr` was renamed to, read from the error nixpkgs'
+    /// alias throws. `None` if it still evaluates or was removed outright.
+    async fn alias_target(&self, attr: &str) -> Option<String> {
+        let installable = format!("nixpkgs#legacyPackages.{}.{}.name", self.arch, attr);
+        let output = Command::new("nix")
+            .args(["eval", "--raw", &installable])
+            .echo().output()
+            .await
+            .ok()?;
+        if output.status.success() {
+            return None;
+        }
+        renamed_attr(&String::from_utf8_lossy(&output.stderr))
+    }
 
-        let mut pkg = Package::from_nixpkgs(name, version, description);
-        
-        if let Some(homepage) = json["meta"]["homepage"].as_str() {
-            pkg.homepage = Some(homepage.to_string());
+    /// Profile elements whose nixpkgs attribute no longer exists at the
+    /// latest revision, with the attribute each was renamed to, if any
+    pub async fn dead_attributes(&self) -> Result<Vec<DeadAttribute>> {
+        let (nixpkgs, _) = self.nixpkgs_elements().await?;
+        if nixpkgs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let attrs: Vec<String> = nixpkgs.iter().map(|(_, attr, _)| attr.clone()).collect();
+        let latest = self.eval_package_meta(&attrs, true).await?;
+
+        let mut dead = Vec::new();
+        for (element, attr, _) in nixpkgs {
+            if latest[attr.as_str()].is_null() {
+                let replacement = self.alias_target(&attr).await;
+                dead.push(DeadAttribute { element, attr, replacement });
+            }
+        }
+        Ok(dead)
+    }
+
+    /// Replace the profile element `element` with nixpkgs attribute `attr`
+    pub async fn migrate(&self, element: &str, attr: &str) -> Result<()> {
+        let elements = self.profile_elements().await?;
+        let Some(old) = elements.iter().find(|el| el.name == element) else {
+            return Err(PackageError::NotInstalled { name: element.to_string() }.into());
+        };
+
+        // Remove first: the renamed package usually ships the same files
+        self.remove_elements(std::slice::from_ref(old)).await?;
+        self.install(&[attr.to_string()]).await
+    }
+
+    /// Preview what upgrading every nixpkgs element to the latest revision would change
+    pub async fn preview_upgrade(&self) -> Result<UpgradePreview> {
+        let (nixpkgs, skipped) = self.nixpkgs_elements().await?;
+        let installed: Vec<(String, String)> = nixpkgs
+            .into_iter()
+            .map(|(_, attr, version)| (attr, version))
+            .collect();
+
+        if installed.is_empty() {
+            return Ok(UpgradePreview { skipped, ..UpgradePreview::default() });
+        }
+
+        let names: Vec<String> = installed.iter().map(|(name, _)| name.clone()).collect();
+        let latest = self.eval_package_meta(&names, true).await?;
+        self.invalidate(Invalidation::ChannelUpdated);
+
+        let mut preview = UpgradePreview::from_versions(&installed, |name| {
+            latest[name]["version"].as_str().map(|v| v.to_string())
+        });
+        preview.skipped = skipped;
+
+        // The --refresh above updated the flake cache, so these see the new revision
+        for change in preview.changes.iter_mut().filter(|c| c.kind == ChangeKind::Removed) {
+            let Some(attr) = self.alias_target(&change.name).await else {
+                continue;
+            };
+            let meta = self.eval_package_meta(std::slice::from_ref(&attr), false).await?;
+            change.kind = ChangeKind::Renamed;
+            change.to = meta[attr.as_str()]["version"].as_str().map(|v| v.to_string());
+            change.renamed_to = Some(attr);
         }
-        if let Some(license) = json["meta"]["license"]["spdxId"].as_str() {
-            pkg.license = Some(license.to_string());
+
+        let installables: Vec<String> = preview.changes
+            .iter()
+            .filter(|c| c.to.is_some())
+            .map(|c| format!("nixpkgs#{}", c.renamed_to.as_ref().unwrap_or(&c.name)))
+            .collect();
+        if !installables.is_empty() {
+            let estimate = DiskGuard::estimate(&installables)?;
+            preview.download_bytes = estimate.download_bytes;
+            preview.unpacked_bytes = estimate.unpacked_bytes;
+            preview.local_builds = estimate.builds;
+        }
+
+        Ok(preview)
+    }
+
+    /// Changelog links and the nixpkgs commits touching each package that
+    /// `preview` upgrades, downgrades or changes
+    pub async fn release_notes(&self, preview: &UpgradePreview, http: &HttpClient) -> Result<Vec<ReleaseNotes>> {
+        let changed: Vec<_> = preview.changes.iter().filter(|c| c.to.is_some()).collect();
+        if changed.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let prefix = format!("legacyPackages.{}.", self.arch);
+        let elements = self.profile_elements().await?;
+        let installed_revision = |attr: &str| {
+            elements
+                .iter()
+                .find(|el| el.attr_path.as_deref().and_then(|a| a.strip_prefix(prefix.as_str())) == Some(attr))
+                .and_then(|el| el.url.as_deref())
+                .and_then(changelog::locked_revision)
+        };
+        let latest_revision = nixpkgs_revision(self.cache.as_deref()).await.filter(|rev| changelog::is_commit(rev));
+
+        let attrs: Vec<String> = changed.iter().map(|c| c.renamed_to.clone().unwrap_or_else(|| c.name.clone())).collect();
+        let meta = self.eval_package_meta(&attrs, false).await?;
+
+        let mut log = CommitLog::new(http);
+        let mut notes = Vec::with_capacity(changed.len());
+        for (change, attr) in changed.into_iter().zip(&attrs) {
+            let meta = &meta[attr.as_str()];
+            let file = meta["position"].as_str().and_then(changelog::derivation_file);
+
+            let mut commits = Vec::new();
+            let mut history_url = None;
+            if let (Some(file), Some(from), Some(to)) = (file, installed_revision(&change.name), latest_revision.as_deref()) {
+                match log.between(&file, &from, to).await {
+                    Ok(found) => commits = found,
+                    Err(e) => debug!("No nixpkgs history for {}: {}", change.name, e),
+                }
+                history_url = Some(changelog::history_url(to, &file));
+            }
+
+            notes.push(ReleaseNotes {
+                name: change.name.clone(),
+                from: change.from.clone(),
+                to: change.to.clone().unwrap_or_default(),
+                changelog: meta["changelog"].as_str().map(String::from),
+                commits,
+                history_url,
+            });
+        }
+        Ok(notes)
+    }
+
+    /// Run `nix eval`, consulting the eval cache first
+    async fn nix_eval(&self, args: &[&str]) -> Result<EvalOutcome> {
+        let expr = args.join(" ");
+        let eval_cache = self.eval_cache().await;
+
+        if let Some(cached) = eval_cache.and_then(|c| c.get(&expr)) {
+            return Ok(cached);
         }
 
-        Ok(Some(pkg))
+        let output = Command::new("nix")
+            .arg("eval")
+            .args(args)
+            .echo().output()
+            .await?;
+
+        let outcome = EvalOutcome {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        };
+
+        // Only cache failures that are a property of nixpkgs itself (missing
+        // attributes), not transient ones like network or daemon errors
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if outcome.success || stderr.contains("does not provide attribute") {
+            if let Some(cache) = eval_cache {
+                cache.set(&expr, &outcome);
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Get the nixpkgs revision, invalidating caches of an older one the
+    /// first time it is resolved (see [`track_revision`])
+    async fn revision(&self) -> Option<&str> {
+        let cache = self.cache.as_ref()?;
+        self.revision
+            .get_or_init(|| track_revision(cache))
+            .await
+            .as_deref()
+    }
+
+    /// Get the eval cache, resolving the nixpkgs revision on first use
+    async fn eval_cache(&self) -> Option<&EvalCache> {
+        let cache = self.cache.as_ref()?;
+        self.eval_cache
+            .get_or_init(|| async {
+                let revision = self.revision().await.unwrap_or(UNKNOWN_REVISION).to_string();
+                debug!("Eval cache keyed to nixpkgs revision {}", revision);
+                Some(EvalCache::new(cache.clone(), revision))
+            })
+            .await
+            .as_ref()
     }
 }
 
+/// Install `refs` one at a time with `install`, stopping at the first
+/// failure. A failure after some refs went in rolls back to `before`.
+async fn install_transaction<F, Fut>(refs: &[String], before: Option<u64>, mut install: F) -> OperationResult
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let start = Instant::now();
+    for (installed, r) in refs.iter().enumerate() {
+        if let Err(e) = install(r.clone()).await {
+            let mut result = OperationResult::failure(OperationType::Install, refs.to_vec(), e.to_string());
+            result.duration_ms = start.elapsed().as_millis() as u64;
+            if installed > 0 {
+                result.rollback_to = before;
+                result.message = Some(format!("{} of {} package(s) were installed before {} failed", installed, refs.len(), r));
+            }
+            return result;
+        }
+    }
+    OperationResult::success(OperationType::Install, refs.to_vec(), start.elapsed().as_millis() as u64)
+}
+
+/// Query NAR hashes for store paths in one `nix path-info` call
+async fn nar_hashes(paths: &[String]) -> Result<std::collections::HashMap<String, String>> {
+    let mut hashes = std::collections::HashMap::new();
+    if paths.is_empty() {
+        return Ok(hashes);
+    }
+
+    let output = Command::new("nix")
+        .args(["path-info", "--json"])
+        .args(paths)
+        .echo().output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix path-info".to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }.into());
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+
+    // Older Nix returns an array of objects with a "path" field, newer a map keyed by path
+    if let Some(map) = json.as_object() {
+        for (path, info) in map {
+            if let Some(hash) = info["narHash"].as_str() {
+                hashes.insert(path.clone(), hash.to_string());
+            }
+        }
+    } else if let Some(list) = json.as_array() {
+        for info in list {
+            if let (Some(path), Some(hash)) = (info["path"].as_str(), info["narHash"].as_str()) {
+                hashes.insert(path.to_string(), hash.to_string());
+            }
+        }
+    }
+
+    Ok(hashes)
+}
+
+/// Eval cache key for a single package's metadata
+fn info_cache_expr(name: &str, system: &str) -> String {
+    format!("info {} {}", system, name)
+}
+
+/// Resolve every name to exactly one profile element, failing on unknown
+/// or ambiguous names before anything is changed
+fn resolve_all(elements: &[ProfileElement], names: &[String]) -> Result<Vec<ProfileElement>> {
+    let mut selected = Vec::with_capacity(names.len());
+    for name in names {
+        match resolve_element(elements, name) {
+            ElementMatch::One(el) => selected.push(el.clone()),
+            ElementMatch::None => {
+                return Err(PackageError::NotInstalled { name: name.clone() }.into());
+            }
+            ElementMatch::Ambiguous(candidates) => {
+                return Err(PackageError::Ambiguous {
+                    query: name.clone(),
+                    candidates: candidates.iter().map(|el| el.name.as_str()).collect::<Vec<_>>().join(", "),
+                }.into());
+            }
+        }
+    }
+    Ok(selected)
+}
+
+/// Attribute part of an installable (`nixpkgs#hello` -> `hello`)
+fn installable_attr(installable: &str) -> &str {
+    installable.rsplit_once('#').map(|(_, attr)| attr).unwrap_or(installable)
+}
+
+/// Arguments addressing elements in `nix profile remove`/`upgrade`.
+///
+/// Nix before 2.20 does not accept element names, so elements are
+/// addressed by store path there.
+fn element_targets(elements: &[ProfileElement]) -> Vec<String> {
+    let by_name = Capabilities::detect().profile_remove_by_name;
+    elements
+        .iter()
+        .map(|el| match el.store_paths.first() {
+            Some(path) if !by_name => path.clone(),
+            _ => el.name.clone(),
+        })
+        .collect()
+}
+
+/// Quote a string as a Nix string literal
+pub(crate) fn nix_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace("${", "\\${"))
+}
+
+/// Build the `--apply` function that maps attribute names to their metadata.
+///
+/// Each attribute is evaluated under `tryEval` so one broken or missing
+/// package does not fail the whole batch.
+fn info_apply_expr(names: &[String]) -> String {
+    let list = names.iter().map(|n| nix_string(n)).collect::<Vec<_>>().join(" ");
+    format!(
+        r#"pkgs: let
+  lib = pkgs.lib;
+  str = v: if builtins.isString v then v else null;
+  first = v: if builtins.isList v then (if v == [] then null else builtins.head v) else v;
+  meta = n: let
+    p = lib.attrByPath (lib.splitString "." n) null pkgs;
+    m = p.meta or {{}};
+    lic = first (m.license or null);
+  in if p == null || !(lib.isDerivation p) then null else {{
+    pname = p.pname or (lib.getName p);
+    version = p.version or (lib.getVersion p);
+    description = m.description or "";
+    homepage = str (first (m.homepage or null));
+    changelog = str (first (m.changelog or null));
+    position = str (m.position or null);
+    license = if builtins.isAttrs lic then (lic.spdxId or lic.shortName or null) else str lic;
+    maintainers = map (x: x.github or x.name or "") (m.maintainers or []);
+    platforms = builtins.filter builtins.isString (m.platforms or []);
+  }};
+  safe = n: let r = builtins.tryEval (let v = meta n; in builtins.deepSeq v v); in if r.success then r.value else null;
+in builtins.listToAttrs (map (n: {{ name = n; value = safe n; }}) [ {} ])"#,
+        list
+    )
+}
+
+/// Convert evaluated metadata into a package
+fn package_from_meta(name: &str, meta: &Value) -> Option<Package> {
+    if meta.is_null() {
+        return None;
+    }
+
+    let version = meta["version"].as_str().filter(|v| !v.is_empty()).unwrap_or("unknown");
+    let description = meta["description"].as_str().unwrap_or("");
+
+    let mut pkg = Package::from_nixpkgs(name, version, description);
+    pkg.attr_path = Some(name.to_string());
+    pkg.homepage = meta["homepage"].as_str().map(|s| s.to_string());
+    pkg.license = meta["license"].as_str().map(|s| s.to_string());
+    pkg.maintainers = string_list(&meta["maintainers"]);
+    pkg.platforms = string_list(&meta["platforms"]);
+
+    Some(pkg)
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value.as_array()
+        .map(|items| {
+            items.iter()
+                .filter_map(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Detect the system architecture using Nix
 fn detect_system_arch() -> Result<String> {
     let output = std::process::Command::new("nix")
         .args(["eval", "--raw", "--impure", "--expr", "builtins.currentSystem"])
-        .output()?;
+        .echo().output()?;
 
     if !output.status.success() {
         return Err(SystemError::ArchDetectionFailed.into());
@@ -348,6 +1478,67 @@ fn detect_system_arch() -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::types::OperationStatus;
+
+    #[tokio::test]
+    async fn test_install_transaction_partial_failure() {
+        let refs: Vec<String> = ["nixpkgs#git", "nixpkgs#nope", "nixpkgs#htop"].iter().map(|s| s.to_string()).collect();
+        let attempted = Mutex::new(Vec::new());
+        let install = |fail: &'static str| {
+            let attempted = &attempted;
+            move |r: String| async move {
+                attempted.lock().push(r.clone());
+                if r == fail {
+                    return Err(PackageError::InstallFailed { name: r, reason: "no such attribute".to_string() }.into());
+                }
+                Ok(())
+            }
+        };
+
+        let result = install_transaction(&refs, Some(7), install("nixpkgs#nope")).await;
+        assert_eq!(result.status, OperationStatus::Failed);
+        assert_eq!(result.rollback_to, Some(7));
+        assert_eq!(*attempted.lock(), vec!["nixpkgs#git", "nixpkgs#nope"]);
+
+        // Nothing went in, so there is nothing to roll back
+        let result = install_transaction(&refs, Some(7), install("nixpkgs#git")).await;
+        assert_eq!(result.rollback_to, None);
+
+        let result = install_transaction(&refs, Some(7), install("")).await;
+        assert_eq!(result.status, OperationStatus::Success);
+    }
+
+    #[test]
+    fn test_nix_string_escaping() {
+        assert_eq!(nix_string("hello"), r#""hello""#);
+        assert_eq!(nix_string(r#"a"b"#), r#""a\"b""#);
+        assert_eq!(nix_string("${x}"), r#""\${x}""#);
+    }
+
+    #[test]
+    fn test_info_apply_expr_lists_names() {
+        let expr = info_apply_expr(&["hello".to_string(), "python3Packages.requests".to_string()]);
+        assert!(expr.starts_with("pkgs:"));
+        assert!(expr.contains(r#"[ "hello" "python3Packages.requests" ]"#));
+    }
+
+    #[test]
+    fn test_package_from_meta() {
+        let meta: Value = serde_json::from_str(r#"{
+            "pname": "hello", "version": "2.12.1", "description": "A program that produces a familiar, friendly greeting",
+            "homepage": "https://www.gnu.org/software/hello/", "license": "GPL-3.0-or-later",
+            "maintainers": ["stv0g", ""], "platforms": ["x86_64-linux", "aarch64-darwin"]
+        }"#).unwrap();
+
+        let pkg = package_from_meta("hello", &meta).unwrap();
+        assert_eq!(pkg.name, "hello");
+        assert_eq!(pkg.version, "2.12.1");
+        assert_eq!(pkg.license.as_deref(), Some("GPL-3.0-or-later"));
+        assert_eq!(pkg.maintainers, vec!["stv0g"]);
+        assert_eq!(pkg.platforms.len(), 2);
+
+        assert!(package_from_meta("missing", &Value::Null).is_none());
+    }
 
     #[test]
     fn test_detect_arch() {
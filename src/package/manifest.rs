@@ -0,0 +1,214 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! `nixboost export` / `nixboost import`: a portable JSON list of installed
+//! packages with where each came from, for replicating a profile on
+//! another machine.
+
+use crate::core::error::{NixBoostError, Result};
+use crate::package::profile::{resolve_element, ElementMatch, ProfileElement};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Current manifest format version
+const MANIFEST_VERSION: u32 = 1;
+
+/// Where a package is installed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Nixpkgs,
+    Nur,
+    Flake,
+}
+
+/// One installed package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Profile element name
+    pub name: String,
+    pub source: Source,
+    /// Installable without the system, e.g. `nixpkgs#hello`
+    pub installable: String,
+    /// Locked installable pinning the exact revision (`export --locked`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked: Option<String>,
+}
+
+impl ManifestEntry {
+    /// What `import` installs: the locked reference when there is one
+    pub fn install_ref(&self) -> &str {
+        self.locked.as_deref().unwrap_or(&self.installable)
+    }
+}
+
+/// Exported packages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    #[serde(default)]
+    pub packages: Vec<ManifestEntry>,
+}
+
+/// What `import` would change
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportPlan {
+    /// Manifest packages that are not installed
+    pub install: Vec<ManifestEntry>,
+    /// Manifest packages already installed
+    pub present: Vec<String>,
+    /// Installed packages the manifest does not list; left alone
+    pub extra: Vec<String>,
+}
+
+impl Manifest {
+    /// Manifest of profile `elements`, with locked references if `locked`.
+    /// Elements without a flake reference (`nix-env` leftovers) are skipped.
+    pub fn from_elements(elements: &[ProfileElement], locked: bool) -> Self {
+        let mut packages: Vec<ManifestEntry> = elements
+            .iter()
+            .filter_map(|el| {
+                let (url, attr) = (el.original_url.as_deref()?, el.attr_path.as_deref()?);
+                let (source, installable) = installable(url, attr);
+                Some(ManifestEntry {
+                    name: el.name.clone(),
+                    source,
+                    installable,
+                    locked: if locked { el.locked_ref() } else { None },
+                })
+            })
+            .collect();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self { version: MANIFEST_VERSION, packages }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| NixBoostError::Serialization(e.to_string()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let manifest: Manifest = serde_json::from_str(&content)
+            .map_err(|e| NixBoostError::Serialization(format!("Invalid manifest {}: {}", path.display(), e)))?;
+
+        if manifest.version > MANIFEST_VERSION {
+            return Err(NixBoostError::Serialization(format!(
+                "Manifest version {} is newer than supported version {}",
+                manifest.version, MANIFEST_VERSION
+            )));
+        }
+        Ok(manifest)
+    }
+
+    /// Compare with the installed `elements`
+    pub fn plan(&self, elements: &[ProfileElement]) -> ImportPlan {
+        let mut plan = ImportPlan::default();
+        let mut matched: Vec<&str> = Vec::new();
+
+        for entry in &self.packages {
+            let found = elements.iter().find(|el| el.name == entry.name).or_else(|| {
+                match resolve_element(elements, &entry.installable) {
+                    ElementMatch::One(el) => Some(el),
+                    _ => None,
+                }
+            });
+            match found {
+                Some(el) => {
+                    matched.push(&el.name);
+                    plan.present.push(entry.name.clone());
+                }
+                None => plan.install.push(entry.clone()),
+            }
+        }
+
+        plan.extra = elements
+            .iter()
+            .filter(|el| !matched.contains(&el.name.as_str()))
+            .map(|el| el.name.clone())
+            .collect();
+        plan
+    }
+}
+
+/// Source and system-independent installable of an element installed from
+/// `url` with attribute path `attr`
+fn installable(url: &str, attr: &str) -> (Source, String) {
+    // `legacyPackages.x86_64-linux.hello` resolves on any system as `hello`
+    let attr = ["legacyPackages.", "packages."]
+        .iter()
+        .find_map(|prefix| attr.strip_prefix(prefix).and_then(|rest| rest.split_once('.')).map(|(_, a)| a))
+        .unwrap_or(attr);
+    let flake = url.strip_prefix("flake:").unwrap_or(url);
+
+    if flake == "nixpkgs" {
+        (Source::Nixpkgs, format!("nixpkgs#{}", attr))
+    } else if flake.starts_with("github:NixOS/nixpkgs") {
+        // Kept as given: a revision in the URL is a pin
+        (Source::Nixpkgs, format!("{}#{}", flake, attr))
+    } else if flake.starts_with("github:nix-community/NUR") {
+        (Source::Nur, format!("{}#{}", flake, attr))
+    } else {
+        (Source::Flake, format!("{}#{}", flake, attr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(name: &str, url: &str, attr: &str) -> ProfileElement {
+        ProfileElement {
+            name: name.to_string(),
+            attr_path: Some(attr.to_string()),
+            original_url: Some(url.to_string()),
+            url: Some(format!("{}/0123abcd", url)),
+            store_paths: vec![format!("/nix/store/aaa-{}", name)],
+        }
+    }
+
+    #[test]
+    fn test_manifest_export_and_plan() {
+        let elements = vec![
+            element("hello", "flake:nixpkgs", "legacyPackages.x86_64-linux.hello"),
+            element("foo", "github:nix-community/NUR", "legacyPackages.x86_64-linux.repos.bar.foo"),
+            element("tool", "git+ssh://git@git.corp/tools", "packages.x86_64-linux.tool"),
+        ];
+        let manifest = Manifest::from_elements(&elements, false);
+        let entries: Vec<(&str, Source, &str)> = manifest.packages
+            .iter()
+            .map(|e| (e.name.as_str(), e.source, e.installable.as_str()))
+            .collect();
+        assert_eq!(entries, vec![
+            ("foo", Source::Nur, "github:nix-community/NUR#repos.bar.foo"),
+            ("hello", Source::Nixpkgs, "nixpkgs#hello"),
+            ("tool", Source::Flake, "git+ssh://git@git.corp/tools#tool"),
+        ]);
+        assert!(manifest.packages[0].locked.is_none());
+        assert!(Manifest::from_elements(&elements, true).packages[1].install_ref().contains("/0123abcd#"));
+
+        let json = manifest.to_json().unwrap();
+        assert_eq!(serde_json::from_str::<Manifest>(&json).unwrap(), manifest);
+
+        let current = vec![elements[1].clone(), element("htop", "flake:nixpkgs", "legacyPackages.x86_64-linux.htop")];
+        let plan = manifest.plan(&current);
+        let install: Vec<&str> = plan.install.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(install, vec!["hello", "tool"]);
+        assert_eq!(plan.present, vec!["foo"]);
+        assert_eq!(plan.extra, vec!["htop"]);
+    }
+}
@@ -17,5 +17,31 @@
 //! Package management module for NixBoost.
 
 pub mod manager;
+pub mod declare;
+pub mod deps;
+pub mod diagnose;
+pub mod download;
+pub mod flakes;
+pub mod hints;
+pub mod release;
+pub mod skeleton;
+pub mod journal;
+pub mod lockfile;
+pub mod manifest;
+pub mod pins;
+pub mod profile;
+pub mod profiles;
+pub mod provides;
+pub mod repl;
+pub mod resolution;
+pub mod shell;
+pub mod size;
+pub mod staging;
+pub mod revision;
+pub mod upgrade;
+pub mod version;
+pub mod which;
+pub mod buildlog;
+pub mod changelog;
 
 pub use manager::PackageManager;
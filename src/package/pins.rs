@@ -0,0 +1,115 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Packages pinned to a nixpkgs commit (`nixboost pin`), recorded in
+//! `pins.toml` next to the config. Upgrades leave pinned packages alone.
+
+use crate::core::config::Config;
+use crate::core::error::{NixBoostError, PackageError, Result};
+use crate::utils::time::unix_now;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A package held at a nixpkgs commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pin {
+    /// nixpkgs commit
+    pub rev: String,
+    /// Installable the package was installed from
+    pub flake_ref: String,
+    /// Unix timestamp
+    pub pinned_at: u64,
+}
+
+impl Pin {
+    /// Pin `package` to nixpkgs commit `rev`
+    pub fn new(package: &str, rev: &str) -> Result<Self> {
+        if !(7..=40).contains(&rev.len()) || !rev.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(PackageError::InvalidSpec(format!("'{}' is not a nixpkgs commit hash", rev)).into());
+        }
+        Ok(Self {
+            rev: rev.to_string(),
+            flake_ref: format!("github:NixOS/nixpkgs/{}#{}", rev, package),
+            pinned_at: unix_now(),
+        })
+    }
+}
+
+/// Pinned packages by profile element name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pins {
+    #[serde(flatten)]
+    pub pins: BTreeMap<String, Pin>,
+}
+
+impl Pins {
+    pub fn path() -> PathBuf {
+        Config::config_dir().join("pins.toml")
+    }
+
+    /// Pins from the default file; none if it does not exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        toml::from_str(&content).map_err(|e| NixBoostError::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn is_pinned(&self, package: &str) -> bool {
+        self.pins.contains_key(package)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pins_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("pins.toml");
+        assert!(Pins::load_from(&path).unwrap().pins.is_empty());
+
+        let mut pins = Pins::default();
+        pins.pins.insert("ripgrep".to_string(), Pin::new("ripgrep", "0a1b2c3d4e5f").unwrap());
+        pins.save_to(&path).unwrap();
+
+        let loaded = Pins::load_from(&path).unwrap();
+        assert!(loaded.is_pinned("ripgrep"));
+        assert_eq!(loaded.pins["ripgrep"].flake_ref, "github:NixOS/nixpkgs/0a1b2c3d4e5f#ripgrep");
+        assert!(Pin::new("ripgrep", "main").is_err());
+    }
+}
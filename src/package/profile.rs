@@ -0,0 +1,292 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Parsing of `nix profile list --json` output.
+
+use crate::package::upgrade::parse_store_name;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A single element of a Nix profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileElement {
+    /// Element name as accepted by `nix profile remove`
+    pub name: String,
+    /// Attribute path within the flake (e.g. `legacyPackages.x86_64-linux.hello`)
+    pub attr_path: Option<String>,
+    /// Flake reference as originally given (e.g. `flake:nixpkgs`)
+    pub original_url: Option<String>,
+    /// Locked flake reference
+    pub url: Option<String>,
+    /// Store paths this element provides
+    pub store_paths: Vec<String>,
+}
+
+impl ProfileElement {
+    /// Fully locked installable (`<url>#<attrPath>`), if known
+    pub fn locked_ref(&self) -> Option<String> {
+        match (&self.url, &self.attr_path) {
+            (Some(url), Some(attr)) => Some(format!("{}#{}", url, attr)),
+            _ => None,
+        }
+    }
+
+    /// Installable as originally requested (`<originalUrl>#<attrPath>`), if known
+    pub fn original_ref(&self) -> Option<String> {
+        match (&self.original_url, &self.attr_path) {
+            (Some(url), Some(attr)) => Some(format!("{}#{}", url, attr)),
+            _ => None,
+        }
+    }
+
+    fn from_json(name: String, val: &Value) -> Self {
+        let str_field = |key: &str| val[key].as_str().map(|s| s.to_string());
+        Self {
+            name,
+            attr_path: str_field("attrPath"),
+            original_url: str_field("originalUrl"),
+            url: str_field("url"),
+            store_paths: val["storePaths"]
+                .as_array()
+                .map(|paths| paths.iter().filter_map(|p| p.as_str()).map(|p| p.to_string()).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Result of resolving a user-supplied name against profile elements
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElementMatch<'a> {
+    One(&'a ProfileElement),
+    Ambiguous(Vec<&'a ProfileElement>),
+    None,
+}
+
+/// Resolve `query` to a profile element.
+///
+/// Matching is tried in tiers and the first tier with any match wins:
+/// element name, attribute path (full or trailing components), flake
+/// reference (`nixpkgs#hello`), then store path or store name.
+pub fn resolve_element<'a>(elements: &'a [ProfileElement], query: &str) -> ElementMatch<'a> {
+    let tiers: [&dyn Fn(&ProfileElement) -> bool; 4] = [
+        &|el| el.name == query,
+        &|el| el.attr_path.as_deref().map(|attr| attr_matches(attr, query)).unwrap_or(false),
+        &|el| flake_ref_matches(el, query),
+        &|el| el.store_paths.iter().any(|path| store_path_matches(path, query)),
+    ];
+
+    for matches in tiers {
+        let found: Vec<&ProfileElement> = elements.iter().filter(|el| matches(el)).collect();
+        match found.len() {
+            0 => continue,
+            1 => return ElementMatch::One(found[0]),
+            _ => return ElementMatch::Ambiguous(found),
+        }
+    }
+
+    ElementMatch::None
+}
+
+/// `legacyPackages.x86_64-linux.python3Packages.requests` matches itself,
+/// `python3Packages.requests` and `requests`
+fn attr_matches(attr: &str, query: &str) -> bool {
+    attr == query || attr.ends_with(&format!(".{}", query))
+}
+
+/// `nixpkgs#hello` matches an element installed from `flake:nixpkgs` with a
+/// matching attribute path; a full original or locked reference matches exactly
+fn flake_ref_matches(el: &ProfileElement, query: &str) -> bool {
+    if el.original_ref().as_deref() == Some(query) || el.locked_ref().as_deref() == Some(query) {
+        return true;
+    }
+
+    let Some((flake, attr)) = query.split_once('#') else {
+        return false;
+    };
+    let same_flake = |url: &str| url == flake || url.strip_prefix("flake:") == Some(flake);
+    el.original_url.as_deref().map(same_flake).unwrap_or(false)
+        && el.attr_path.as_deref().map(|a| attr_matches(a, attr)).unwrap_or(false)
+}
+
+/// Exact store path, `<name>-<version>` suffix, or bare package name
+fn store_path_matches(path: &str, query: &str) -> bool {
+    path == query || path.ends_with(&format!("-{}", query)) || parse_store_name(path).0 == query
+}
+
+/// Shape of the `elements` field in `nix profile list --json`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileSchema {
+    /// Object keyed by element name
+    ByName,
+    /// Array of elements; names are derived from the attribute path
+    Indexed,
+}
+
+impl ProfileSchema {
+    /// Detect the schema from the document itself
+    pub fn detect(json: &Value) -> Option<Self> {
+        match &json["elements"] {
+            Value::Object(_) => Some(ProfileSchema::ByName),
+            Value::Array(_) => Some(ProfileSchema::Indexed),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the elements of a `nix profile list --json` document in either schema
+pub fn parse_profile_list(json: &Value) -> Vec<ProfileElement> {
+    match (&json["elements"], ProfileSchema::detect(json)) {
+        (Value::Object(map), Some(ProfileSchema::ByName)) => map
+            .iter()
+            .map(|(name, val)| ProfileElement::from_json(name.clone(), val))
+            .collect(),
+        (Value::Array(items), Some(ProfileSchema::Indexed)) => items
+            .iter()
+            .enumerate()
+            .map(|(idx, val)| ProfileElement::from_json(indexed_name(idx, val), val))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Name for an array-schema element: the last attribute path component,
+/// falling back to the store path name, then the index
+fn indexed_name(idx: usize, val: &Value) -> String {
+    if let Some(attr) = val["attrPath"].as_str() {
+        if let Some(last) = attr.rsplit('.').next().filter(|s| !s.is_empty()) {
+            return last.to_string();
+        }
+    }
+
+    val["storePaths"][0]
+        .as_str()
+        .map(|path| parse_store_name(path).0)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| idx.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `nix profile list --json` from Nix 2.24 (elements keyed by name)
+    const BY_NAME_FIXTURE: &str = r#"{
+        "elements": {
+            "hello": {
+                "active": true,
+                "attrPath": "legacyPackages.x86_64-linux.hello",
+                "originalUrl": "flake:nixpkgs",
+                "outputs": null,
+                "priority": 5,
+                "storePaths": ["/nix/store/abc-hello-2.12.1"],
+                "url": "github:NixOS/nixpkgs/0123abcd"
+            }
+        },
+        "version": 3
+    }"#;
+
+    /// `nix profile list --json` from Nix 2.18 (elements as an array)
+    const INDEXED_FIXTURE: &str = r#"{
+        "elements": [
+            {
+                "active": true,
+                "attrPath": "legacyPackages.x86_64-linux.hello",
+                "originalUrl": "flake:nixpkgs",
+                "outputs": null,
+                "priority": 5,
+                "storePaths": ["/nix/store/abc-hello-2.12.1"],
+                "url": "github:NixOS/nixpkgs/0123abcd"
+            },
+            {
+                "active": true,
+                "priority": 5,
+                "storePaths": ["/nix/store/0123456789abcdfghijklmnpqrsvwxyz-htop-3.3.0"]
+            }
+        ],
+        "version": 2
+    }"#;
+
+    #[test]
+    fn test_parse_profile_list() {
+        let json: Value = serde_json::from_str(BY_NAME_FIXTURE).unwrap();
+        assert_eq!(ProfileSchema::detect(&json), Some(ProfileSchema::ByName));
+
+        let elements = parse_profile_list(&json);
+        assert_eq!(elements.len(), 1);
+        assert_eq!(elements[0].name, "hello");
+        assert_eq!(
+            elements[0].locked_ref().as_deref(),
+            Some("github:NixOS/nixpkgs/0123abcd#legacyPackages.x86_64-linux.hello")
+        );
+        assert_eq!(elements[0].store_paths, vec!["/nix/store/abc-hello-2.12.1"]);
+    }
+
+    #[test]
+    fn test_parse_indexed_profile_list() {
+        let json: Value = serde_json::from_str(INDEXED_FIXTURE).unwrap();
+        assert_eq!(ProfileSchema::detect(&json), Some(ProfileSchema::Indexed));
+
+        let elements = parse_profile_list(&json);
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0].name, "hello");
+        assert_eq!(
+            elements[0].locked_ref().as_deref(),
+            Some("github:NixOS/nixpkgs/0123abcd#legacyPackages.x86_64-linux.hello")
+        );
+        assert_eq!(elements[1].name, "htop");
+        assert_eq!(elements[1].locked_ref(), None);
+    }
+
+    fn element(name: &str, attr: Option<&str>, store_path: &str) -> ProfileElement {
+        ProfileElement {
+            name: name.to_string(),
+            attr_path: attr.map(|a| a.to_string()),
+            original_url: attr.map(|_| "flake:nixpkgs".to_string()),
+            url: attr.map(|_| "github:NixOS/nixpkgs/0123abcd".to_string()),
+            store_paths: vec![store_path.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_resolve_element() {
+        let elements = vec![
+            element("hello", Some("legacyPackages.x86_64-linux.hello"), "/nix/store/aaa-hello-2.12.1"),
+            element("requests", Some("legacyPackages.x86_64-linux.python3Packages.requests"), "/nix/store/bbb-python3.11-requests-2.31.0"),
+            element("requests-1", Some("legacyPackages.x86_64-linux.python312Packages.requests"), "/nix/store/ccc-python3.12-requests-2.31.0"),
+            element("htop", None, "/nix/store/ddd-htop-3.3.0"),
+        ];
+
+        assert_eq!(resolve_element(&elements, "hello"), ElementMatch::One(&elements[0]));
+        assert_eq!(resolve_element(&elements, "nixpkgs#hello"), ElementMatch::One(&elements[0]));
+        assert_eq!(resolve_element(&elements, "python312Packages.requests"), ElementMatch::One(&elements[2]));
+        assert_eq!(resolve_element(&elements, "htop-3.3.0"), ElementMatch::One(&elements[3]));
+        assert_eq!(resolve_element(&elements, "firefox"), ElementMatch::None);
+
+        // Exact element name wins over attribute path matches
+        assert_eq!(resolve_element(&elements, "requests"), ElementMatch::One(&elements[1]));
+
+        assert_eq!(
+            resolve_element(&elements, "requests-2.31.0"),
+            ElementMatch::Ambiguous(vec![&elements[1], &elements[2]])
+        );
+    }
+
+    #[test]
+    fn test_unknown_schema() {
+        assert_eq!(ProfileSchema::detect(&Value::Null), None);
+        assert!(parse_profile_list(&serde_json::json!({"elements": 3})).is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Named profiles (`nixboost profile`): extra `nix profile`s kept under
+//! `profiles/` in the data directory and registered in `profiles.toml`, so
+//! `--profile <name>` can act on them and `profile switch` can make one the
+//! user's active profile.
+
+use crate::core::config::Config;
+use crate::core::error::{NixBoostError, PackageError, Result, SystemError};
+use crate::utils::command::Echo;
+use crate::utils::time::unix_now;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// `profile switch` target for the user's original profile
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Names `--profile` reserves for the built-in profiles
+const RESERVED: &[&str] = &["user", "system", "home-manager", DEFAULT_PROFILE];
+
+/// A registered named profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedProfile {
+    pub name: String,
+    /// Unix timestamp of `profile create`
+    pub created_at: u64,
+}
+
+/// State of a named profile for `profile list`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileStatus {
+    pub name: String,
+    pub path: PathBuf,
+    /// Number of generations; 0 until something is installed
+    pub generations: usize,
+    /// Whether `~/.nix-profile` points to it
+    pub active: bool,
+}
+
+/// The named profile registry
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedProfiles {
+    #[serde(default, rename = "profile")]
+    pub profiles: Vec<NamedProfile>,
+}
+
+impl NamedProfiles {
+    pub fn registry_path() -> PathBuf {
+        Config::data_dir().join("profiles.toml")
+    }
+
+    /// Directory the profiles and their generation links live in
+    pub fn dir() -> PathBuf {
+        Config::data_dir().join("profiles")
+    }
+
+    /// Profile path of `name`, for `nix profile --profile`
+    pub fn path(name: &str) -> PathBuf {
+        Self::dir().join(name)
+    }
+
+    /// The registry from the default file; empty if it does not exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::registry_path())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        toml::from_str(&content).map_err(|e| NixBoostError::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::registry_path())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.profiles.iter().any(|p| p.name == name)
+    }
+
+    /// Register `name`; nix creates the profile itself on the first install
+    pub fn create(&mut self, name: &str) -> Result<()> {
+        validate_name(name)?;
+        if self.contains(name) {
+            return Err(profile_error(name, "already exists"));
+        }
+        std::fs::create_dir_all(Self::dir())?;
+        self.profiles.push(NamedProfile { name: name.to_string(), created_at: unix_now() });
+        Ok(())
+    }
+
+    /// Registered profiles with their generation count and whether active
+    pub fn status(&self) -> Vec<ProfileStatus> {
+        let active = active_profile();
+        self.profiles
+            .iter()
+            .map(|p| {
+                let path = Self::path(&p.name);
+                ProfileStatus {
+                    name: p.name.clone(),
+                    generations: count_generations(&path),
+                    active: active.as_deref() == Some(path.as_path()),
+                    path,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Check `name` can be used as a profile name and on the command line
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with(['.', '-'])
+        && !name.ends_with("-link")
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(profile_error(name, "names may only contain letters, digits, '-', '_' and '.'"));
+    }
+    if RESERVED.contains(&name) {
+        return Err(profile_error(name, "the name is reserved"));
+    }
+    Ok(())
+}
+
+fn profile_error(name: &str, reason: &str) -> NixBoostError {
+    PackageError::Profile { name: name.to_string(), reason: reason.to_string() }.into()
+}
+
+/// Number of `<profile>-<n>-link` generation links next to `profile`
+fn count_generations(profile: &Path) -> usize {
+    let (Some(dir), Some(name)) = (profile.parent(), profile.file_name()) else {
+        return 0;
+    };
+    let prefix = format!("{}-", name.to_string_lossy());
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let file = e.file_name().to_string_lossy().into_owned();
+            file.strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix("-link"))
+                .is_some_and(|n| n.parse::<u64>().is_ok())
+        })
+        .count()
+}
+
+/// Profile `~/.nix-profile` points to
+fn active_profile() -> Option<PathBuf> {
+    std::fs::read_link(dirs::home_dir()?.join(".nix-profile")).ok()
+}
+
+/// The user's original profile, which `profile switch default` returns to
+pub fn default_profile() -> PathBuf {
+    // Nix 2.14+ keeps user profiles in the XDG state directory
+    let state = dirs::home_dir().map(|home| home.join(".local/state/nix/profiles"));
+    match state {
+        Some(dir) if dir.exists() => dir.join("profile"),
+        _ => PathBuf::from(format!("/nix/var/nix/profiles/per-user/{}/profile", std::env::var("USER").unwrap_or_default())),
+    }
+}
+
+/// Point `~/.nix-profile` at `profile` (`nix-env --switch-profile`)
+pub fn switch(profile: &Path) -> Result<()> {
+    let status = Command::new("nix-env").arg("--switch-profile").arg(profile).echo().status()?;
+    if !status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: format!("nix-env --switch-profile {}", profile.display()),
+            stderr: "Command failed".to_string(),
+        }.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_profiles() {
+        assert!(validate_name("dev-tools_2").is_ok());
+        for bad in ["", "user", "default", "../x", "a/b", ".hidden", "x-3-link"] {
+            assert!(validate_name(bad).is_err(), "{} should be rejected", bad);
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let registry = dir.path().join("profiles.toml");
+        let mut profiles = NamedProfiles::default();
+        profiles.profiles.push(NamedProfile { name: "dev".to_string(), created_at: 1 });
+        profiles.save_to(&registry).unwrap();
+        let loaded = NamedProfiles::load_from(&registry).unwrap();
+        assert!(loaded.contains("dev"));
+        assert_eq!(loaded, profiles);
+
+        let profile = dir.path().join("dev");
+        for link in ["dev-1-link", "dev-2-link", "dev-old", "devtools-1-link"] {
+            std::fs::write(dir.path().join(link), "").unwrap();
+        }
+        assert_eq!(count_generations(&profile), 2);
+    }
+}
@@ -0,0 +1,230 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Which package provides a command or file (`nixboost provides`).
+//!
+//! Commands are looked up in the `programs.sqlite` database that NixOS
+//! channels ship, when one is present. Everything else goes through
+//! `nix-locate` against a prebuilt nix-index database, which is downloaded
+//! into the cache directory and refreshed once it is a week old.
+
+use crate::core::config::Config;
+use crate::core::error::{CacheError, Result, SystemError};
+use crate::network::HttpClient;
+use crate::ui::ProgressManager;
+use crate::utils::command::Echo;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Prebuilt nix-index databases, one per system
+const NIX_INDEX_URL: &str = "https://github.com/nix-community/nix-index-database/releases/latest/download";
+
+/// `programs.sqlite` of the root user's NixOS channel
+const CHANNEL_PROGRAMS: &str = "/nix/var/nix/profiles/per-user/root/channels/nixos/programs.sqlite";
+
+/// Age after which the nix-index database is downloaded again
+const MAX_INDEX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// What to look up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lookup {
+    /// A command on `PATH`, e.g. `rg`
+    Command(String),
+    /// A file path or suffix, e.g. `include/zlib.h`
+    File(String),
+}
+
+impl Lookup {
+    /// Anything with a `/` is a file, the rest are commands
+    pub fn parse(query: &str) -> Self {
+        if query.contains('/') {
+            Lookup::File(query.to_string())
+        } else {
+            Lookup::Command(query.to_string())
+        }
+    }
+}
+
+/// A package providing the looked-up command or file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Provider {
+    /// nixpkgs attribute
+    pub package: String,
+    /// Path inside the package, e.g. `bin/rg`
+    pub path: String,
+}
+
+/// The channel's `programs.sqlite`, if this system has one
+pub fn programs_db() -> Option<PathBuf> {
+    Some(PathBuf::from(CHANNEL_PROGRAMS)).filter(|path| path.exists())
+}
+
+/// Packages providing `command` for `system` according to `programs.sqlite`
+pub fn query_programs(db: &Path, command: &str, system: &str) -> Result<Vec<Provider>> {
+    let sql_error = |e: rusqlite::Error| CacheError::DatabaseError(format!("{}: {}", db.display(), e));
+    let conn = Connection::open(db).map_err(sql_error)?;
+    let mut stmt = conn
+        .prepare("SELECT DISTINCT package FROM Programs WHERE name = ?1 AND system = ?2 ORDER BY package")
+        .map_err(sql_error)?;
+    let packages = stmt
+        .query_map([command, system], |row| row.get::<_, String>(0))
+        .map_err(sql_error)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(sql_error)?;
+
+    Ok(packages
+        .into_iter()
+        .map(|package| Provider { package, path: format!("bin/{}", command) })
+        .collect())
+}
+
+/// A nix-index database in nixboost's cache directory
+pub struct NixIndex {
+    /// Directory holding the `files` database, as `nix-locate --db` expects
+    dir: PathBuf,
+}
+
+impl NixIndex {
+    pub fn new() -> Self {
+        Self { dir: Config::cache_dir().join("nix-index") }
+    }
+
+    fn files(&self) -> PathBuf {
+        self.dir.join("files")
+    }
+
+    /// Time since the database was downloaded, `None` if it never was
+    pub fn age(&self) -> Option<Duration> {
+        let modified = std::fs::metadata(self.files()).and_then(|m| m.modified()).ok()?;
+        Some(modified.elapsed().unwrap_or_default())
+    }
+
+    /// Whether the database is missing or older than a week
+    pub fn needs_update(&self) -> bool {
+        self.age().is_none_or(|age| age > MAX_INDEX_AGE)
+    }
+
+    /// Download the database for `system`. It replaces the old one only once
+    /// complete, so an interrupted download leaves the previous one usable.
+    pub async fn download(&self, http: &HttpClient, system: &str, progress: &ProgressManager) -> Result<()> {
+        let url = format!("{}/index-{}", NIX_INDEX_URL, system);
+        let body = http.get_with_progress(&url, progress, "nix-index database").await?;
+
+        std::fs::create_dir_all(&self.dir)?;
+        let partial = self.dir.join("files.part");
+        if let Err(e) = tokio::fs::write(&partial, &body).await {
+            let _ = std::fs::remove_file(&partial);
+            return Err(e.into());
+        }
+        std::fs::rename(&partial, self.files())?;
+        info!("nix-index database updated");
+        Ok(())
+    }
+
+    /// Run `nix-locate` for `lookup`, through `nix shell` when it is not installed
+    pub fn locate(&self, lookup: &Lookup) -> Result<Vec<Provider>> {
+        let mut cmd = if which_nix_locate() {
+            Command::new("nix-locate")
+        } else {
+            let mut cmd = Command::new("nix");
+            cmd.args(["shell", "nixpkgs#nix-index", "--command", "nix-locate"]);
+            cmd
+        };
+        cmd.arg("--db").arg(&self.dir).arg("--top-level");
+        match lookup {
+            Lookup::Command(name) => {
+                cmd.args(["--whole-name", "--at-root", "--type", "x", "--type", "s"]);
+                cmd.arg(format!("/bin/{}", name));
+            }
+            Lookup::File(path) => {
+                cmd.arg(path);
+            }
+        }
+
+        let output = cmd.echo().output()?;
+        if !output.status.success() {
+            return Err(SystemError::NixCommandFailed {
+                command: "nix-locate".to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            }.into());
+        }
+        Ok(parse_locate(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+impl Default for NixIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `nix-locate` is on `PATH`
+fn which_nix_locate() -> bool {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|dir| dir.join("nix-locate").is_file()))
+        .unwrap_or(false)
+}
+
+/// Parse `nix-locate` lines: `ripgrep.out  4,512,152 x /nix/store/<hash>-ripgrep-14.1.0/bin/rg`
+fn parse_locate(stdout: &str) -> Vec<Provider> {
+    let mut providers: Vec<Provider> = Vec::new();
+    for line in stdout.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(attr), Some(store_path)) = (fields.next(), fields.last()) else {
+            continue;
+        };
+        let package = attr.strip_suffix(".out").unwrap_or(attr).trim_matches(|c| c == '(' || c == ')');
+        let path = store_path
+            .strip_prefix("/nix/store/")
+            .and_then(|rest| rest.split_once('/'))
+            .map(|(_, path)| path.to_string())
+            .unwrap_or_else(|| store_path.to_string());
+        if !providers.iter().any(|p| p.package == package && p.path == path) {
+            providers.push(Provider { package: package.to_string(), path });
+        }
+    }
+    debug!("nix-locate found {} provider(s)", providers.len());
+    providers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locate() {
+        let output = "\
+ripgrep.out                                   4,512,152 x /nix/store/aaaa-ripgrep-14.1.0/bin/rg
+ripgrep-all.out                                 123,456 x /nix/store/bbbb-ripgrep-all-0.10.6/bin/rg
+(zlib.dev)                                       20,000 r /nix/store/cccc-zlib-1.3-dev/include/zlib.h
+";
+        assert_eq!(
+            parse_locate(output),
+            vec![
+                Provider { package: "ripgrep".into(), path: "bin/rg".into() },
+                Provider { package: "ripgrep-all".into(), path: "bin/rg".into() },
+                Provider { package: "zlib.dev".into(), path: "include/zlib.h".into() },
+            ]
+        );
+        assert_eq!(Lookup::parse("rg"), Lookup::Command("rg".into()));
+        assert_eq!(Lookup::parse("include/zlib.h"), Lookup::File("include/zlib.h".into()));
+    }
+}
@@ -0,0 +1,259 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Install prebuilt GitHub release binaries (`nixboost fetch-bin`).
+//!
+//! The asset is fetched with `fetchurl` and patched with `autoPatchelfHook`
+//! so dynamically linked binaries run on NixOS.
+
+use crate::core::error::{NixBoostError, PackageError, Result, SystemError};
+use crate::network::HttpClient;
+use crate::package::manager::nix_string;
+use crate::utils::command::Echo;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::process::Command;
+use tracing::{debug, info};
+
+/// File extensions of assets that are never the binary itself
+const IGNORED_SUFFIXES: &[&str] = &[
+    ".sha256", ".sha256sum", ".sha512", ".md5", ".sig", ".asc", ".pem", ".sbom", ".json", ".txt",
+    ".deb", ".rpm", ".apk", ".msi", ".exe", ".dmg", ".pkg", ".AppImage",
+];
+
+/// A release asset
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// The latest release of a repository
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubRelease {
+    pub tag_name: String,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+impl GithubRelease {
+    /// Fetch the latest release of `owner/repo`
+    pub async fn latest(http: &HttpClient, repo: &str) -> Result<Self> {
+        let url = format!("https://api.github.com/repos/{}/releases/latest", repo);
+        debug!("Fetching {}", url);
+        http.get_json(&url).await
+    }
+
+    /// Version without a leading `v`
+    pub fn version(&self) -> &str {
+        self.tag_name.strip_prefix('v').unwrap_or(&self.tag_name)
+    }
+}
+
+/// How the asset has to be unpacked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    Tarball,
+    Zip,
+    /// A bare executable
+    Binary,
+}
+
+impl AssetKind {
+    pub fn from_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+        if [".tar.gz", ".tgz", ".tar.xz", ".txz", ".tar.bz2", ".tar.zst"].iter().any(|s| name.ends_with(s)) {
+            AssetKind::Tarball
+        } else if name.ends_with(".zip") {
+            AssetKind::Zip
+        } else {
+            AssetKind::Binary
+        }
+    }
+}
+
+/// Pick the asset for `arch` (a Nix system such as `x86_64-linux`).
+///
+/// Only Linux assets are considered: the wrapper patches binaries with
+/// `autoPatchelfHook`, which does not exist on Darwin. Static musl builds
+/// are preferred since they need no patching.
+pub fn select_asset<'a>(assets: &'a [ReleaseAsset], arch: &str) -> Option<&'a ReleaseAsset> {
+    const X86_64: &[&str] = &["x86_64", "amd64", "x64"];
+
+    let (cpu, os) = arch.split_once('-')?;
+    // `x86` also names 64-bit assets (`x86_64`), so those are excluded for i686
+    let (cpu_aliases, excluded): (&[&str], &[&str]) = match cpu {
+        "x86_64" => (X86_64, &[]),
+        "aarch64" => (&["aarch64", "arm64"], &[]),
+        "i686" => (&["i686", "i386", "x86"], X86_64),
+        _ => return None,
+    };
+    if os != "linux" {
+        return None;
+    }
+
+    assets
+        .iter()
+        .filter(|a| !IGNORED_SUFFIXES.iter().any(|s| a.name.ends_with(s)))
+        .filter(|a| {
+            let name = a.name.to_lowercase();
+            cpu_aliases.iter().any(|c| has_token(&name, c))
+                && !excluded.iter().any(|c| has_token(&name, c))
+                && name.contains("linux")
+        })
+        .max_by_key(|a| {
+            let name = a.name.to_lowercase();
+            (name.contains("musl"), AssetKind::from_name(&name) != AssetKind::Binary)
+        })
+}
+
+/// Whether `token` occurs in `name` as a whole word, not inside a longer one
+fn has_token(name: &str, token: &str) -> bool {
+    name.match_indices(token).any(|(idx, _)| {
+        let before = name[..idx].chars().next_back();
+        let after = name[idx + token.len()..].chars().next();
+        !before.is_some_and(|c| c.is_ascii_alphanumeric()) && !after.is_some_and(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+/// Nix expression that wraps a release asset into a package
+pub fn wrapper_expression(pname: &str, version: &str, asset: &ReleaseAsset, hash: &str) -> String {
+    let kind = AssetKind::from_name(&asset.name);
+    let unpack = match kind {
+        AssetKind::Tarball => "",
+        AssetKind::Zip => "  nativeBuildInputs = [ pkgs.autoPatchelfHook pkgs.unzip ];\n",
+        AssetKind::Binary => "  dontUnpack = true;\n",
+    };
+    let native = if kind == AssetKind::Zip { "" } else { "  nativeBuildInputs = [ pkgs.autoPatchelfHook ];\n" };
+    let install = match kind {
+        AssetKind::Binary => format!("install -Dm755 $src $out/bin/{}", pname),
+        _ => "find . -type f -perm -u+x ! -name '*.so*' -exec install -Dm755 -t $out/bin {} +".to_string(),
+    };
+
+    format!(
+        "let pkgs = import (builtins.getFlake \"nixpkgs\") {{}}; in\n\
+         pkgs.stdenv.mkDerivation {{\n  \
+         pname = {pname};\n  \
+         version = {version};\n  \
+         src = pkgs.fetchurl {{ url = {url}; hash = {hash}; }};\n  \
+         sourceRoot = \".\";\n\
+         {unpack}{native}  \
+         buildInputs = [ pkgs.stdenv.cc.cc.lib pkgs.zlib ];\n  \
+         dontConfigure = true;\n  \
+         dontBuild = true;\n  \
+         installPhase = ''\n    \
+         runHook preInstall\n    \
+         {install}\n    \
+         runHook postInstall\n  \
+         '';\n\
+         }}\n",
+        pname = nix_string(pname),
+        version = nix_string(version),
+        url = nix_string(&asset.browser_download_url),
+        hash = nix_string(hash),
+        unpack = unpack,
+        native = native,
+        install = install,
+    )
+}
+
+/// SRI hash of a URL's content via `nix store prefetch-file`
+pub async fn prefetch(url: &str) -> Result<String> {
+    let output = Command::new("nix")
+        .args(["store", "prefetch-file", "--json", url])
+        .echo().output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix store prefetch-file".to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }.into());
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+    json["hash"]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| NixBoostError::Serialization("prefetch-file returned no hash".to_string()))
+}
+
+/// Build the expression and add it to the profile
+pub async fn install_expression(name: &str, expr: &str) -> Result<()> {
+    info!("Installing {} from release asset", name);
+
+    let status = Command::new("nix")
+        .args(["profile", "install", "--impure", "--expr", expr])
+        .echo().status()
+        .await?;
+
+    if !status.success() {
+        return Err(PackageError::InstallFailed {
+            name: name.to_string(),
+            reason: "building the release wrapper failed".to_string(),
+        }.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(name: &str) -> ReleaseAsset {
+        ReleaseAsset {
+            name: name.to_string(),
+            browser_download_url: format!("https://github.com/o/r/releases/download/v1.2.0/{}", name),
+            size: 0,
+        }
+    }
+
+    #[test]
+    fn test_select_asset() {
+        let assets = vec![
+            asset("tool-1.2.0-x86_64-unknown-linux-gnu.tar.gz"),
+            asset("tool-1.2.0-x86_64-unknown-linux-musl.tar.gz"),
+            asset("tool-1.2.0-x86_64-unknown-linux-musl.tar.gz.sha256"),
+            asset("tool-1.2.0-aarch64-unknown-linux-gnu.tar.gz"),
+            asset("tool-1.2.0-x86_64-apple-darwin.zip"),
+            asset("tool_1.2.0_amd64.deb"),
+        ];
+
+        assert_eq!(select_asset(&assets, "x86_64-linux").unwrap().name, "tool-1.2.0-x86_64-unknown-linux-musl.tar.gz");
+        assert_eq!(select_asset(&assets, "aarch64-linux").unwrap().name, "tool-1.2.0-aarch64-unknown-linux-gnu.tar.gz");
+        assert!(select_asset(&assets, "x86_64-darwin").is_none());
+        assert!(select_asset(&assets, "riscv64-linux").is_none());
+        assert!(select_asset(&assets, "i686-linux").is_none());
+
+        let assets = vec![asset("tool-linux-x86_64.tar.gz"), asset("tool-linux-x86.tar.gz")];
+        assert_eq!(select_asset(&assets, "i686-linux").unwrap().name, "tool-linux-x86.tar.gz");
+    }
+
+    #[test]
+    fn test_wrapper_expression() {
+        let expr = wrapper_expression("tool", "1.2.0", &asset("tool-linux-amd64"), "sha256-AAAA");
+        assert!(expr.contains("dontUnpack = true;"));
+        assert!(expr.contains("install -Dm755 $src $out/bin/tool"));
+        assert!(expr.contains("hash = \"sha256-AAAA\";"));
+        assert!(expr.contains("pkgs.autoPatchelfHook"));
+
+        let expr = wrapper_expression("tool", "1.2.0", &asset("tool-linux-amd64.zip"), "sha256-AAAA");
+        assert!(expr.contains("pkgs.unzip"));
+        assert_eq!(expr.matches("nativeBuildInputs").count(), 1);
+    }
+}
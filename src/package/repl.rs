@@ -0,0 +1,123 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! `nixboost repl`: `nix repl` with nixpkgs and the installed packages in scope.
+//!
+//! The scope is all of `pkgs` plus `pkgs`, `lib`, and `installed`, an
+//! attribute set of the profile's elements taken from the exact flake
+//! revisions they were installed from.
+
+use crate::core::error::{Result, SystemError};
+use crate::package::manager::nix_string;
+use crate::package::profile::ProfileElement;
+use crate::utils::command::Echo;
+use serde_json::Value;
+use std::process::Command;
+use tracing::debug;
+
+/// Locked URL of a flake reference, so the session does not drift if the
+/// registry entry moves while it is open
+pub fn locked_url(flake_ref: &str) -> Option<String> {
+    let output = Command::new("nix")
+        .args(["flake", "metadata", "--json", flake_ref])
+        .echo().output()
+        .ok()?;
+
+    if !output.status.success() {
+        debug!("nix flake metadata {} failed: {}", flake_ref, String::from_utf8_lossy(&output.stderr));
+        return None;
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout).ok()?;
+    json["url"].as_str().map(String::from)
+}
+
+/// Nix expression evaluated as the repl's scope
+pub fn scope_expression(nixpkgs: &str, elements: &[ProfileElement]) -> String {
+    let mut installed = String::new();
+    for el in elements {
+        let (Some(url), Some(attr)) = (el.url.as_ref().or(el.original_url.as_ref()), &el.attr_path) else {
+            continue;
+        };
+        let path: Vec<String> = attr.split('.').map(nix_string).collect();
+        installed.push_str(&format!(
+            "    {} = (builtins.getFlake {}).{};\n",
+            nix_string(&el.name),
+            nix_string(url),
+            path.join(".")
+        ));
+    }
+
+    format!(
+        "let\n  \
+         pkgs = import (builtins.getFlake {nixpkgs}) {{ }};\n  \
+         installed = {{\n{installed}  }};\n\
+         in\n\
+         pkgs // {{ inherit pkgs installed; inherit (pkgs) lib; }}\n",
+        nixpkgs = nix_string(nixpkgs),
+        installed = installed,
+    )
+}
+
+/// Open `nix repl` on `expr`. Unlocked flake references need `--impure`.
+pub fn launch(expr: &str, impure: bool) -> Result<()> {
+    let mut cmd = Command::new("nix");
+    cmd.args(["repl", "--expr", expr]);
+    if impure {
+        cmd.arg("--impure");
+    }
+
+    let status = cmd.echo().status()?;
+    if !status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix repl".to_string(),
+            stderr: format!("exited with {}", status),
+        }.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_expression() {
+        let elements = vec![
+            ProfileElement {
+                name: "ripgrep".to_string(),
+                attr_path: Some("legacyPackages.x86_64-linux.ripgrep".to_string()),
+                original_url: Some("flake:nixpkgs".to_string()),
+                url: Some("github:NixOS/nixpkgs/abc123".to_string()),
+                store_paths: vec![],
+            },
+            ProfileElement {
+                name: "local".to_string(),
+                attr_path: None,
+                original_url: None,
+                url: None,
+                store_paths: vec!["/nix/store/0123456789abcdfghijklmnpqrsvwxyz-local".to_string()],
+            },
+        ];
+
+        let expr = scope_expression("github:NixOS/nixpkgs/def456", &elements);
+        assert!(expr.contains("pkgs = import (builtins.getFlake \"github:NixOS/nixpkgs/def456\") { };"));
+        assert!(expr.contains(
+            "\"ripgrep\" = (builtins.getFlake \"github:NixOS/nixpkgs/abc123\").\"legacyPackages\".\"x86_64-linux\".\"ripgrep\";"
+        ));
+        assert!(!expr.contains("local"));
+    }
+}
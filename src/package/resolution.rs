@@ -0,0 +1,197 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Environment that changes how installables resolve.
+//!
+//! Variables such as `NIXPKGS_ALLOW_UNFREE` or `NIX_PATH` and flake
+//! registry entries make the same command resolve differently from one
+//! shell or user to the next. They are reported as warnings, and the full
+//! resolution context is printed with `--verbose`.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Variables that change evaluation, and how
+const IMPURE_VARS: &[(&str, &str)] = &[
+    ("NIXPKGS_ALLOW_UNFREE", "allows unfree packages, but only in --impure evaluations"),
+    ("NIXPKGS_ALLOW_INSECURE", "allows packages with known vulnerabilities, but only in --impure evaluations"),
+    ("NIXPKGS_ALLOW_BROKEN", "allows packages marked broken, but only in --impure evaluations"),
+    ("NIXPKGS_ALLOW_UNSUPPORTED_SYSTEM", "allows packages not built for this system, but only in --impure evaluations"),
+    ("NIXPKGS_CONFIG", "replaces ~/.config/nixpkgs/config.nix in --impure evaluations"),
+    ("NIX_CONFIG", "adds Nix settings on top of nix.conf"),
+    ("NIX_USER_CONF_FILES", "replaces the user's nix.conf files"),
+];
+
+/// `NIX_PATH` entries for nixpkgs that follow the system's channels or registry
+const DEFAULT_NIXPKGS_PATHS: &[&str] = &[
+    "/nix/var/nix/profiles/per-user/root/channels",
+    "/etc/nix/path",
+    "flake:nixpkgs",
+];
+
+/// Something in the environment that affects resolution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Impurity {
+    /// Where it comes from, e.g. `NIXPKGS_ALLOW_UNFREE=1`
+    pub source: String,
+    pub effect: String,
+}
+
+/// A flake registry entry redirecting `nixpkgs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryOverride {
+    pub registry: PathBuf,
+    pub target: String,
+}
+
+/// Impure variables from `vars` (name, value pairs), in [`IMPURE_VARS`] order
+pub fn impure_vars(vars: &[(String, String)]) -> Vec<Impurity> {
+    IMPURE_VARS
+        .iter()
+        .filter_map(|(name, effect)| {
+            let (_, value) = vars.iter().find(|(var, value)| var == name && !value.is_empty())?;
+            Some(Impurity { source: format!("{}={}", name, value), effect: effect.to_string() })
+        })
+        .collect()
+}
+
+/// The `nixpkgs=` entry of a `NIX_PATH` when it points somewhere other than
+/// the system's channels or registry
+pub fn nix_path_override(nix_path: &str) -> Option<String> {
+    nix_path
+        .split(':')
+        .scan(None::<String>, |url, entry| {
+            // `nixpkgs=https://...` contains a colon of its own
+            if let Some(prefix) = url.take() {
+                return Some(Some(format!("{}:{}", prefix, entry)));
+            }
+            if matches!(entry, "nixpkgs=https" | "nixpkgs=http" | "nixpkgs=flake") {
+                *url = Some(entry.to_string());
+                return Some(None);
+            }
+            Some(Some(entry.to_string()))
+        })
+        .flatten()
+        .find_map(|entry| entry.strip_prefix("nixpkgs=").map(String::from))
+        .filter(|target| !DEFAULT_NIXPKGS_PATHS.iter().any(|default| target.starts_with(default)))
+}
+
+/// Where the `nixpkgs` entry of a registry file (`registry.json`) points
+pub fn registry_target(json: &Value) -> Option<String> {
+    let entry = json["flakes"]
+        .as_array()?
+        .iter()
+        .find(|flake| flake["from"]["id"].as_str() == Some("nixpkgs"))?;
+    let to = &entry["to"];
+
+    let target = match to["type"].as_str()? {
+        "github" | "gitlab" | "sourcehut" => {
+            let mut url = format!("{}:{}/{}", to["type"].as_str()?, to["owner"].as_str()?, to["repo"].as_str()?);
+            if let Some(rev) = to["rev"].as_str().or_else(|| to["ref"].as_str()) {
+                url = format!("{}/{}", url, rev);
+            }
+            url
+        }
+        "path" => format!("path:{}", to["path"].as_str()?),
+        kind => format!("{}:{}", kind, to["url"].as_str().unwrap_or_default()),
+    };
+    Some(target)
+}
+
+/// The user's flake registry file
+pub fn user_registry() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(config.join("nix/registry.json"))
+}
+
+/// System flake registry file
+pub const SYSTEM_REGISTRY: &str = "/etc/nix/registry.json";
+
+/// `nixpkgs` override in the registry file at `path`, if any
+pub fn registry_override(path: &Path) -> Option<RegistryOverride> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let json: Value = serde_json::from_str(&contents).ok()?;
+    Some(RegistryOverride { registry: path.to_path_buf(), target: registry_target(&json)? })
+}
+
+/// Everything in the current environment that makes resolution differ from
+/// a default setup. The system registry is left out: NixOS flake
+/// configurations routinely pin `nixpkgs` there.
+pub fn detect() -> Vec<Impurity> {
+    let vars: Vec<(String, String)> = std::env::vars().collect();
+    let mut impurities = impure_vars(&vars);
+
+    if let Some(target) = std::env::var("NIX_PATH").ok().and_then(|p| nix_path_override(&p)) {
+        impurities.push(Impurity {
+            source: format!("NIX_PATH nixpkgs={}", target),
+            effect: "changes <nixpkgs> for nix-env, nix-shell and --impure evaluations".to_string(),
+        });
+    }
+    if let Some(entry) = user_registry().and_then(|path| registry_override(&path)) {
+        impurities.push(Impurity {
+            source: format!("{} maps nixpkgs to {}", entry.registry.display(), entry.target),
+            effect: "changes which nixpkgs installs, searches and upgrades use".to_string(),
+        });
+    }
+    impurities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_impure_vars() {
+        let vars = vec![
+            ("NIX_CONFIG".to_string(), "allow-import-from-derivation = false".to_string()),
+            ("NIXPKGS_ALLOW_UNFREE".to_string(), "1".to_string()),
+            ("NIXPKGS_ALLOW_BROKEN".to_string(), String::new()),
+            ("HOME".to_string(), "/home/me".to_string()),
+        ];
+        let found: Vec<String> = impure_vars(&vars).into_iter().map(|i| i.source).collect();
+        assert_eq!(found, vec!["NIXPKGS_ALLOW_UNFREE=1", "NIX_CONFIG=allow-import-from-derivation = false"]);
+    }
+
+    #[test]
+    fn test_nix_path_override() {
+        assert_eq!(nix_path_override("nixpkgs=/nix/var/nix/profiles/per-user/root/channels/nixos:nixos-config=/etc/nixos/configuration.nix"), None);
+        assert_eq!(nix_path_override("nixpkgs=flake:nixpkgs:/nix/var/nix/profiles/per-user/root/channels"), None);
+        assert_eq!(nix_path_override("nixpkgs=/home/me/src/nixpkgs").as_deref(), Some("/home/me/src/nixpkgs"));
+        assert_eq!(
+            nix_path_override("nixpkgs=https://github.com/NixOS/nixpkgs/archive/master.tar.gz").as_deref(),
+            Some("https://github.com/NixOS/nixpkgs/archive/master.tar.gz")
+        );
+        assert_eq!(nix_path_override("home-manager=/etc/hm"), None);
+    }
+
+    #[test]
+    fn test_registry_target() {
+        let registry = json!({"version": 2, "flakes": [
+            {"from": {"id": "home-manager", "type": "indirect"}, "to": {"type": "path", "path": "/etc/hm"}},
+            {"from": {"id": "nixpkgs", "type": "indirect"}, "to": {"type": "github", "owner": "NixOS", "repo": "nixpkgs", "ref": "nixos-24.05"}},
+        ]});
+        assert_eq!(registry_target(&registry).as_deref(), Some("github:NixOS/nixpkgs/nixos-24.05"));
+
+        let pinned = json!({"flakes": [{"from": {"id": "nixpkgs"}, "to": {"type": "path", "path": "/nix/store/abc-source"}}]});
+        assert_eq!(registry_target(&pinned).as_deref(), Some("path:/nix/store/abc-source"));
+
+        assert_eq!(registry_target(&json!({"flakes": []})), None);
+    }
+}
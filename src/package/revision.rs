@@ -0,0 +1,171 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! nixpkgs revision detection via flake metadata.
+
+use crate::cache::CacheManager;
+use crate::cache::invalidation::{CacheKey, Invalidation, TTL};
+use crate::utils::command::Echo;
+use serde_json::Value;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+/// Resolve the revision the `nixpkgs` flake reference currently points to.
+///
+/// The lookup is cached for [`TTL::REVISION`] so repeated invocations do not
+/// hit the flake registry every time.
+pub async fn nixpkgs_revision(cache: Option<&CacheManager>) -> Option<String> {
+    let key = CacheKey::nixpkgs_revision();
+    if let Some(cache) = cache {
+        if let Some(rev) = cache.get::<String>(&key) {
+            return Some(rev);
+        }
+    }
+
+    let output = Command::new("nix")
+        .args(["flake", "metadata", "nixpkgs", "--json"])
+        .echo().output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        debug!("nix flake metadata failed: {}", String::from_utf8_lossy(&output.stderr));
+        return None;
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout).ok()?;
+    let rev = parse_revision(&json)?;
+
+    if let Some(cache) = cache {
+        if let Err(e) = cache.set(&key, &rev, TTL::REVISION) {
+            warn!("Failed to cache nixpkgs revision: {}", e);
+        }
+    }
+
+    Some(rev)
+}
+
+/// Resolve the nixpkgs revision and, if it differs from the one seen last
+/// time, drop cached search results and package metadata, which describe
+/// the old revision.
+pub async fn track_revision(cache: &CacheManager) -> Option<String> {
+    let revision = nixpkgs_revision(Some(cache)).await?;
+
+    let key = CacheKey::seen_revision();
+    let seen = cache.get::<String>(&key);
+    if seen.as_deref() != Some(revision.as_str()) {
+        info!(
+            "nixpkgs moved from {} to {}, invalidating search results",
+            seen.as_deref().unwrap_or("an unknown revision"),
+            revision
+        );
+        cache.on(Invalidation::ChannelUpdated);
+        if let Err(e) = cache.set(&key, &revision, TTL::LONG) {
+            warn!("Failed to record nixpkgs revision: {}", e);
+        }
+    }
+
+    Some(revision)
+}
+
+/// A flake reference pinned by `nix flake metadata`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedFlake {
+    /// Locked URL, e.g. `github:NixOS/nixpkgs/<rev>`
+    pub url: String,
+    pub revision: Option<String>,
+}
+
+/// Lock an arbitrary flake reference to what it currently resolves to
+pub async fn lock_flake(flake_ref: &str) -> Option<LockedFlake> {
+    let output = Command::new("nix")
+        .args(["flake", "metadata", flake_ref, "--json"])
+        .echo().output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        debug!("nix flake metadata {} failed: {}", flake_ref, String::from_utf8_lossy(&output.stderr));
+        return None;
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout).ok()?;
+    parse_locked(&json)
+}
+
+fn parse_locked(json: &Value) -> Option<LockedFlake> {
+    Some(LockedFlake {
+        url: json["url"].as_str()?.to_string(),
+        revision: parse_revision(json),
+    })
+}
+
+/// Extract the revision from `nix flake metadata --json` output
+fn parse_revision(json: &Value) -> Option<String> {
+    json["revision"].as_str()
+        .or_else(|| json["locked"]["rev"].as_str())
+        .or_else(|| json["locked"]["narHash"].as_str())
+        .map(|s| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_revision() {
+        let json: Value = serde_json::from_str(r#"{"revision": "abc123", "locked": {"rev": "abc123"}}"#).unwrap();
+        assert_eq!(parse_revision(&json), Some("abc123".to_string()));
+
+        let locked_only: Value = serde_json::from_str(r#"{"locked": {"rev": "def456"}}"#).unwrap();
+        assert_eq!(parse_revision(&locked_only), Some("def456".to_string()));
+
+        let path_flake: Value = serde_json::from_str(r#"{"locked": {"narHash": "sha256-xyz"}}"#).unwrap();
+        assert_eq!(parse_revision(&path_flake), Some("sha256-xyz".to_string()));
+
+        assert_eq!(parse_revision(&Value::Null), None);
+    }
+
+    #[tokio::test]
+    async fn test_revision_change_invalidates_search() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let disk = crate::cache::DiskCache::with_path(tmp.path().join("cache.db")).unwrap();
+        let cache = CacheManager::with_disk(disk, 10);
+
+        // The revision lookup is served from cache, so no nix is needed
+        cache.set(&CacheKey::nixpkgs_revision(), &"aaa".to_string(), TTL::REVISION).unwrap();
+        cache.set(&CacheKey::seen_revision(), &"aaa".to_string(), TTL::LONG).unwrap();
+        cache.set(&CacheKey::search("hello"), &vec!["hello"], TTL::SEARCH).unwrap();
+
+        assert_eq!(track_revision(&cache).await.as_deref(), Some("aaa"));
+        assert!(cache.get::<Vec<String>>(&CacheKey::search("hello")).is_some());
+
+        cache.set(&CacheKey::nixpkgs_revision(), &"bbb".to_string(), TTL::REVISION).unwrap();
+        assert_eq!(track_revision(&cache).await.as_deref(), Some("bbb"));
+        assert!(cache.get::<Vec<String>>(&CacheKey::search("hello")).is_none());
+        assert_eq!(cache.get::<String>(&CacheKey::seen_revision()).as_deref(), Some("bbb"));
+    }
+
+    #[test]
+    fn test_parse_locked() {
+        let json: Value = serde_json::from_str(
+            r#"{"url": "github:NixOS/nixpkgs/abc123", "revision": "abc123", "originalUrl": "flake:nixpkgs"}"#,
+        ).unwrap();
+        let locked = parse_locked(&json).unwrap();
+        assert_eq!(locked.url, "github:NixOS/nixpkgs/abc123");
+        assert_eq!(locked.revision.as_deref(), Some("abc123"));
+    }
+}
@@ -0,0 +1,60 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! `nixboost shell` and `nixboost run`: packages from a temporary
+//! `nix shell` or `nix run`, without touching the profile.
+
+use crate::core::error::{Result, SystemError};
+use crate::utils::command::Echo;
+use std::process::{Command, ExitStatus};
+
+/// Installable for a nixpkgs package; flake references are kept as given
+pub fn nixpkgs_installable(package: &str) -> String {
+    if package.contains('#') {
+        package.to_string()
+    } else {
+        format!("nixpkgs#{}", package)
+    }
+}
+
+/// Run an interactive `nix shell` with `installables` until the user exits it
+pub fn launch(installables: &[String]) -> Result<()> {
+    let status = Command::new("nix").arg("shell").args(installables).echo().status()?;
+    if !status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix shell".to_string(),
+            stderr: format!("exited with {}", status),
+        }.into());
+    }
+    Ok(())
+}
+
+/// Run the main program of `installable` with `args` and return its exit
+/// status, which belongs to the program rather than to NixBoost
+pub fn run(installable: &str, args: &[String]) -> Result<ExitStatus> {
+    Ok(Command::new("nix").args(["run", installable, "--"]).args(args).echo().status()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nixpkgs_installable() {
+        assert_eq!(nixpkgs_installable("ripgrep"), "nixpkgs#ripgrep");
+        assert_eq!(nixpkgs_installable("github:owner/repo#tool"), "github:owner/repo#tool");
+    }
+}
@@ -0,0 +1,120 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Disk cost of installing a package, queried from the binary cache.
+//!
+//! The closure size is what the package occupies in the store once
+//! installed; the download size is the compressed NARs fetched for it.
+//! Paths already in the local store are counted too, so both are upper
+//! bounds.
+
+use crate::core::error::{Result, SystemError};
+use crate::network::substituter::configured_substituters;
+use crate::utils::command::Echo;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::process::Command;
+
+/// Binary cache asked when no HTTP substituter is configured
+const DEFAULT_SUBSTITUTER: &str = "https://cache.nixos.org";
+
+/// Closure and download size of one package
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct InstallSize {
+    pub closure_bytes: u64,
+    /// Compressed size to download; unknown for paths that must be built
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_bytes: Option<u64>,
+}
+
+impl InstallSize {
+    /// Sum of several packages' sizes; the download is unknown if any is
+    pub fn total(sizes: &[InstallSize]) -> InstallSize {
+        InstallSize {
+            closure_bytes: sizes.iter().map(|s| s.closure_bytes).sum(),
+            download_bytes: sizes.iter().map(|s| s.download_bytes).sum(),
+        }
+    }
+}
+
+/// Run `nix path-info -S --json` for `installable` against the first binary cache
+pub async fn query(installable: &str) -> Result<InstallSize> {
+    let store = configured_substituters().into_iter().next().unwrap_or_else(|| DEFAULT_SUBSTITUTER.to_string());
+    let output = Command::new("nix")
+        .args(["path-info", "-S", "--json", "--eval-store", "auto", "--store", &store])
+        .arg(installable)
+        .echo().output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: format!("nix path-info -S {}", installable),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }.into());
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| SystemError::NixCommandFailed { command: "nix path-info".to_string(), stderr: e.to_string() })?;
+    parse_path_info(&json).ok_or_else(|| SystemError::NixCommandFailed {
+        command: "nix path-info -S".to_string(),
+        stderr: format!("no size reported for {}", installable),
+    }.into())
+}
+
+/// Sizes from `nix path-info -S --json`, summed over the package's outputs.
+/// Nix before 2.19 prints an array of objects, later versions an object
+/// keyed by path; paths missing from the cache are `null`.
+fn parse_path_info(json: &Value) -> Option<InstallSize> {
+    let infos: Vec<&Value> = match json {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(map) => map.values().collect(),
+        _ => return None,
+    };
+    let sizes: Vec<InstallSize> = infos
+        .into_iter()
+        .filter_map(|info| {
+            Some(InstallSize {
+                closure_bytes: info["closureSize"].as_u64()?,
+                download_bytes: info["closureDownloadSize"].as_u64(),
+            })
+        })
+        .collect();
+    (!sizes.is_empty()).then(|| InstallSize::total(&sizes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_path_info() {
+        let current = json!({
+            "/nix/store/aaa-hello-2.12.1": {"closureSize": 1000, "closureDownloadSize": 300, "narSize": 200},
+            "/nix/store/bbb-hello-2.12.1-man": {"closureSize": 500, "closureDownloadSize": 100},
+        });
+        assert_eq!(
+            parse_path_info(&current),
+            Some(InstallSize { closure_bytes: 1500, download_bytes: Some(400) })
+        );
+
+        let legacy = json!([{"path": "/nix/store/aaa-local", "closureSize": 800}]);
+        assert_eq!(parse_path_info(&legacy), Some(InstallSize { closure_bytes: 800, download_bytes: None }));
+
+        assert_eq!(parse_path_info(&json!({"/nix/store/ccc-missing": null})), None);
+    }
+}
@@ -0,0 +1,233 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Derivation skeletons for packaging new software (`nixboost contrib init`).
+
+use crate::core::error::{NetworkError, NixBoostError, Result};
+use crate::network::HttpClient;
+use crate::package::manager::nix_string;
+use crate::package::release::GithubRelease;
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Repository metadata used to fill in `src` and `meta`
+#[derive(Debug, Clone, Default)]
+pub struct RepoMeta {
+    pub owner: String,
+    pub repo: String,
+    pub description: Option<String>,
+    pub homepage: Option<String>,
+    pub license: Option<String>,
+    pub language: Option<String>,
+    pub default_branch: String,
+    /// Latest release tag, if the project publishes releases
+    pub tag: Option<String>,
+}
+
+impl RepoMeta {
+    /// Fetch metadata for `owner/repo` from the GitHub API
+    pub async fn fetch(http: &HttpClient, repo: &str) -> Result<Self> {
+        let json: Value = http.get_json(&format!("https://api.github.com/repos/{}", repo)).await?;
+        let (owner, name) = repo.split_once('/').unwrap_or((repo, repo));
+
+        let tag = match GithubRelease::latest(http, repo).await {
+            Ok(release) => Some(release.tag_name),
+            Err(NixBoostError::Network(NetworkError::HttpError { status: 404, .. })) => None,
+            Err(e) => return Err(e),
+        };
+
+        let text = |key: &str| json[key].as_str().filter(|s| !s.is_empty()).map(String::from);
+        Ok(Self {
+            owner: owner.to_string(),
+            repo: name.to_string(),
+            description: text("description"),
+            homepage: text("homepage").or_else(|| text("html_url")),
+            license: json["license"]["spdx_id"].as_str().filter(|s| *s != "NOASSERTION").map(String::from),
+            language: text("language"),
+            default_branch: text("default_branch").unwrap_or_else(|| "main".to_string()),
+            tag,
+        })
+    }
+}
+
+/// Builder function for the repository's main language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Builder {
+    Stdenv,
+    Rust,
+    Go,
+    Python,
+}
+
+impl Builder {
+    fn for_language(language: Option<&str>) -> Self {
+        match language {
+            Some("Rust") => Builder::Rust,
+            Some("Go") => Builder::Go,
+            Some("Python") => Builder::Python,
+            _ => Builder::Stdenv,
+        }
+    }
+
+    /// Function arguments and the call that opens the derivation
+    fn header(self) -> (&'static str, &'static str) {
+        match self {
+            Builder::Stdenv => ("stdenv", "stdenv.mkDerivation"),
+            Builder::Rust => ("rustPlatform", "rustPlatform.buildRustPackage"),
+            Builder::Go => ("buildGoModule", "buildGoModule"),
+            Builder::Python => ("python3Packages", "python3Packages.buildPythonApplication"),
+        }
+    }
+
+    /// Builder-specific attributes
+    fn extra_attrs(self) -> &'static str {
+        match self {
+            Builder::Stdenv => "",
+            Builder::Rust => "  cargoHash = lib.fakeHash;\n\n",
+            Builder::Go => "  vendorHash = lib.fakeHash;\n\n",
+            Builder::Python => "  pyproject = true;\n  build-system = [ python3Packages.setuptools ];\n\n",
+        }
+    }
+}
+
+/// nixpkgs `lib.licenses` attribute for an SPDX identifier
+fn nix_license(spdx: &str) -> Option<&'static str> {
+    Some(match spdx {
+        "MIT" => "mit",
+        "Apache-2.0" => "asl20",
+        "BSD-2-Clause" => "bsd2",
+        "BSD-3-Clause" => "bsd3",
+        "GPL-2.0" | "GPL-2.0-only" => "gpl2Only",
+        "GPL-2.0-or-later" => "gpl2Plus",
+        "GPL-3.0" | "GPL-3.0-only" => "gpl3Only",
+        "GPL-3.0-or-later" => "gpl3Plus",
+        "LGPL-2.1" | "LGPL-2.1-only" => "lgpl21Only",
+        "LGPL-3.0" | "LGPL-3.0-only" => "lgpl3Only",
+        "AGPL-3.0" | "AGPL-3.0-only" => "agpl3Only",
+        "MPL-2.0" => "mpl20",
+        "ISC" => "isc",
+        "Unlicense" => "unlicense",
+        "0BSD" => "bsd0",
+        "Zlib" => "zlib",
+        _ => return None,
+    })
+}
+
+/// Render a `package.nix` for `pname`, prefilled from `meta` when available
+pub fn render(pname: &str, meta: Option<&RepoMeta>) -> String {
+    let builder = Builder::for_language(meta.and_then(|m| m.language.as_deref()));
+    let (arg, call) = builder.header();
+    let placeholder = RepoMeta {
+        owner: "OWNER".to_string(),
+        repo: pname.to_string(),
+        default_branch: "main".to_string(),
+        ..Default::default()
+    };
+    let meta = meta.unwrap_or(&placeholder);
+
+    let (version, rev) = match &meta.tag {
+        Some(tag) => {
+            let version = tag.strip_prefix('v').unwrap_or(tag);
+            let rev = if tag.starts_with('v') { "\"v${version}\"" } else { "version" };
+            (version.to_string(), rev.to_string())
+        }
+        None => ("0-unstable".to_string(), nix_string(&meta.default_branch)),
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{{\n  lib,\n  {},\n  fetchFromGitHub,\n}}:\n", arg);
+    let _ = writeln!(out, "{} rec {{", call);
+    let _ = writeln!(out, "  pname = {};", nix_string(pname));
+    let _ = writeln!(out, "  version = {};\n", nix_string(&version));
+    let _ = writeln!(out, "  src = fetchFromGitHub {{");
+    let _ = writeln!(out, "    owner = {};", nix_string(&meta.owner));
+    let _ = writeln!(out, "    repo = {};", nix_string(&meta.repo));
+    let _ = writeln!(out, "    rev = {};", rev);
+    let _ = writeln!(out, "    hash = lib.fakeHash; # replace with the hash from the first build");
+    let _ = writeln!(out, "  }};\n");
+    out.push_str(builder.extra_attrs());
+
+    let license = match meta.license.as_deref() {
+        Some(spdx) => match nix_license(spdx) {
+            Some(attr) => format!("lib.licenses.{}", attr),
+            None => format!("lib.licenses.unfree; # TODO: map SPDX {}", spdx),
+        },
+        None => "lib.licenses.unfree; # TODO: set the license".to_string(),
+    };
+    let license_line = if license.contains(';') { license } else { format!("{};", license) };
+
+    let _ = writeln!(out, "  meta = {{");
+    let _ = writeln!(
+        out,
+        "    description = {};",
+        nix_string(meta.description.as_deref().unwrap_or("TODO: one-line description"))
+    );
+    let _ = writeln!(
+        out,
+        "    homepage = {};",
+        nix_string(meta.homepage.as_deref().unwrap_or(&format!("https://github.com/{}/{}", meta.owner, meta.repo)))
+    );
+    let _ = writeln!(out, "    license = {}", license_line);
+    let _ = writeln!(out, "    maintainers = [ ];");
+    let _ = writeln!(out, "    mainProgram = {};", nix_string(pname));
+    let _ = writeln!(out, "  }};");
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_from_repo_meta() {
+        let meta = RepoMeta {
+            owner: "BurntSushi".to_string(),
+            repo: "ripgrep".to_string(),
+            description: Some("ripgrep recursively searches directories".to_string()),
+            homepage: None,
+            license: Some("Unlicense".to_string()),
+            language: Some("Rust".to_string()),
+            default_branch: "master".to_string(),
+            tag: Some("14.1.0".to_string()),
+        };
+        let nix = render("ripgrep", Some(&meta));
+
+        assert!(nix.contains("rustPlatform.buildRustPackage rec {"));
+        assert!(nix.contains("  version = \"14.1.0\";"));
+        assert!(nix.contains("    rev = version;"));
+        assert!(nix.contains("cargoHash = lib.fakeHash;"));
+        assert!(nix.contains("license = lib.licenses.unlicense;"));
+        assert!(nix.contains("homepage = \"https://github.com/BurntSushi/ripgrep\";"));
+    }
+
+    #[test]
+    fn test_render_placeholder() {
+        let nix = render("mytool", None);
+        assert!(nix.starts_with("{\n  lib,\n  stdenv,\n  fetchFromGitHub,\n}:\n"));
+        assert!(nix.contains("    owner = \"OWNER\";"));
+        assert!(nix.contains("    rev = \"main\";"));
+        assert!(nix.contains("license = lib.licenses.unfree; # TODO: set the license"));
+    }
+
+    #[test]
+    fn test_v_prefixed_tag() {
+        let meta = RepoMeta { tag: Some("v2.0.1".to_string()), ..Default::default() };
+        let nix = render("tool", Some(&meta));
+        assert!(nix.contains("version = \"2.0.1\";"));
+        assert!(nix.contains("rev = \"v${version}\";"));
+    }
+}
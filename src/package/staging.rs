@@ -0,0 +1,132 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Staged changes (`nixboost stage`), kept in `staged.toml` in the data
+//! directory until `nixboost stage apply` runs them as one transaction.
+
+use crate::core::config::Config;
+use crate::core::error::{NixBoostError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Pending installs and removals
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Staging {
+    /// Packages (or flake installables) to install
+    #[serde(default)]
+    pub install: Vec<String>,
+    /// Installed package names to remove
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+impl Staging {
+    pub fn path() -> PathBuf {
+        Config::data_dir().join("staged.toml")
+    }
+
+    /// Staged changes from the default file; none if it does not exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        toml::from_str(&content).map_err(|e| NixBoostError::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Write to the default file, deleting it when nothing is staged
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if self.is_empty() {
+            return match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            };
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.install.is_empty() && self.remove.is_empty()
+    }
+
+    /// Number of staged changes
+    pub fn len(&self) -> usize {
+        self.install.len() + self.remove.len()
+    }
+
+    /// Stage an install; cancels a staged removal of the same package instead
+    pub fn stage_install(&mut self, package: &str) {
+        if let Some(pos) = self.remove.iter().position(|p| p == package) {
+            self.remove.remove(pos);
+        } else if !self.install.iter().any(|p| p == package) {
+            self.install.push(package.to_string());
+        }
+    }
+
+    /// Stage a removal; cancels a staged install of the same package instead
+    pub fn stage_remove(&mut self, package: &str) {
+        if let Some(pos) = self.install.iter().position(|p| p == package) {
+            self.install.remove(pos);
+        } else if !self.remove.iter().any(|p| p == package) {
+            self.remove.push(package.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staging_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("staged.toml");
+        assert!(Staging::load_from(&path).unwrap().is_empty());
+
+        let mut staging = Staging::default();
+        staging.stage_install("ripgrep");
+        staging.stage_install("fd");
+        staging.stage_install("ripgrep");
+        staging.stage_remove("htop");
+        staging.stage_remove("fd");
+        assert_eq!(staging.install, vec!["ripgrep"]);
+        assert_eq!(staging.remove, vec!["htop"]);
+        assert_eq!(staging.len(), 2);
+
+        staging.save_to(&path).unwrap();
+        assert_eq!(Staging::load_from(&path).unwrap(), staging);
+
+        // Un-staging everything removes the file
+        staging.stage_install("htop");
+        staging.stage_remove("ripgrep");
+        staging.save_to(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,265 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Upgrade impact preview (`nixboost preview-upgrade`).
+
+use crate::package::version::compare_versions;
+use crate::system::garbage_collector::format_bytes;
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// How a package's version would change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Upgrade,
+    Downgrade,
+    /// Version string changed but is not comparable
+    Changed,
+    /// No longer available at the new revision
+    Removed,
+    /// The attribute was renamed; see [`VersionChange::renamed_to`]
+    Renamed,
+}
+
+impl ChangeKind {
+    /// nvd-style marker
+    fn marker(self) -> &'static str {
+        match self {
+            ChangeKind::Upgrade => "[U.]",
+            ChangeKind::Downgrade => "[D.]",
+            ChangeKind::Changed => "[C.]",
+            ChangeKind::Removed => "[R.]",
+            ChangeKind::Renamed => "[M.]",
+        }
+    }
+}
+
+/// A single package that would change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionChange {
+    pub name: String,
+    pub kind: ChangeKind,
+    pub from: String,
+    pub to: Option<String>,
+    /// New attribute name when upstream renamed the package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub renamed_to: Option<String>,
+}
+
+/// A profile element whose nixpkgs attribute no longer evaluates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadAttribute {
+    /// Profile element name
+    pub element: String,
+    /// Attribute the element was installed from
+    pub attr: String,
+    /// Attribute it was renamed to, if upstream left a pointer
+    pub replacement: Option<String>,
+}
+
+/// What upgrading the profile to the latest revision would do
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpgradePreview {
+    pub changes: Vec<VersionChange>,
+    /// Packages already at the latest version
+    pub unchanged: usize,
+    /// Profile elements that are not from nixpkgs and were not evaluated
+    pub skipped: Vec<String>,
+    pub download_bytes: u64,
+    pub unpacked_bytes: u64,
+    pub local_builds: usize,
+}
+
+impl UpgradePreview {
+    /// Compare installed versions with the versions at the new revision
+    pub fn from_versions(installed: &[(String, String)], latest: impl Fn(&str) -> Option<String>) -> Self {
+        let mut preview = Self::default();
+
+        for (name, current) in installed {
+            let change = match latest(name) {
+                None => Some((ChangeKind::Removed, None)),
+                Some(new) if new == *current => None,
+                Some(new) => {
+                    let kind = match compare_versions(&new, current) {
+                        Ordering::Greater => ChangeKind::Upgrade,
+                        Ordering::Less => ChangeKind::Downgrade,
+                        Ordering::Equal => ChangeKind::Changed,
+                    };
+                    Some((kind, Some(new)))
+                }
+            };
+
+            match change {
+                Some((kind, to)) => preview.changes.push(VersionChange {
+                    name: name.clone(),
+                    kind,
+                    from: current.clone(),
+                    to,
+                    renamed_to: None,
+                }),
+                None => preview.unchanged += 1,
+            }
+        }
+
+        preview.changes.sort_by(|a, b| a.name.cmp(&b.name));
+        preview
+    }
+
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+
+    /// Print an nvd-style summary
+    pub fn print(&self) {
+        if self.changes.is_empty() {
+            println!("{}", style("Everything is up to date").green());
+        } else {
+            let width = self.changes.iter().map(|c| c.name.len()).max().unwrap_or(0);
+            for change in &self.changes {
+                let marker = match change.kind {
+                    ChangeKind::Upgrade => style(change.kind.marker()).green(),
+                    ChangeKind::Downgrade | ChangeKind::Removed => style(change.kind.marker()).red(),
+                    ChangeKind::Changed | ChangeKind::Renamed => style(change.kind.marker()).yellow(),
+                };
+                let to = change.to.as_deref().unwrap_or("(removed)");
+                match &change.renamed_to {
+                    Some(attr) => println!(
+                        "{}  {:<width$}  {} → {} {}",
+                        marker, change.name, change.from, style(attr).bold(), to, width = width
+                    ),
+                    None => println!("{}  {:<width$}  {} → {}", marker, change.name, change.from, to, width = width),
+                }
+            }
+        }
+
+        println!();
+        println!(
+            "{} upgrade(s), {} downgrade(s), {} removed, {} renamed, {} unchanged",
+            self.count(ChangeKind::Upgrade),
+            self.count(ChangeKind::Downgrade),
+            self.count(ChangeKind::Removed),
+            self.count(ChangeKind::Renamed),
+            self.unchanged
+        );
+        if self.count(ChangeKind::Renamed) > 0 {
+            println!("{}", style("Renamed packages are migrated to their new attribute by `nixboost -Su`").dim());
+        }
+        // Only previews carry size estimates
+        if self.download_bytes > 0 || self.local_builds > 0 {
+            println!(
+                "Download: {} ({} unpacked), local builds: {}",
+                format_bytes(self.download_bytes),
+                format_bytes(self.unpacked_bytes),
+                self.local_builds
+            );
+        }
+        if !self.skipped.is_empty() {
+            println!("{}", style(format!("Skipped (not from nixpkgs): {}", self.skipped.join(", "))).dim());
+        }
+    }
+}
+
+/// Extract the new attribute from a nixpkgs alias error such as
+/// `'foo' has been renamed to/replaced by 'bar'`
+pub fn renamed_attr(message: &str) -> Option<String> {
+    let rest = ["replaced by", "renamed to"]
+        .iter()
+        .filter_map(|marker| message.find(marker).map(|i| &message[i + marker.len()..]))
+        .next()?;
+
+    let rest = rest.trim_start();
+    let quote = rest.chars().next().filter(|c| matches!(c, '\'' | '`' | '"'))?;
+    let attr = rest[1..].split(quote).next()?;
+    let attr = attr.strip_prefix("pkgs.").unwrap_or(attr);
+
+    let valid = !attr.is_empty()
+        && attr.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '\''));
+    valid.then(|| attr.to_string())
+}
+
+/// Split a store path's name into `(pname, version)` like Nix's `parseDrvName`:
+/// the version starts at the first `-` followed by a non-letter.
+pub fn parse_store_name(store_path: &str) -> (String, String) {
+    let base = store_path.rsplit('/').next().unwrap_or(store_path);
+    // Drop the 32-character hash prefix
+    let name = match base.split_once('-') {
+        Some((hash, rest)) if hash.len() == 32 => rest,
+        _ => base,
+    };
+
+    let bytes = name.as_bytes();
+    for (i, b) in bytes.iter().enumerate() {
+        if *b == b'-' && bytes.get(i + 1).map(|c| !c.is_ascii_alphabetic()).unwrap_or(false) {
+            return (name[..i].to_string(), name[i + 1..].to_string());
+        }
+    }
+
+    (name.to_string(), String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_store_name() {
+        assert_eq!(
+            parse_store_name("/nix/store/0123456789abcdfghijklmnpqrsvwxyz-firefox-122.0.1"),
+            ("firefox".to_string(), "122.0.1".to_string())
+        );
+        assert_eq!(
+            parse_store_name("/nix/store/0123456789abcdfghijklmnpqrsvwxyz-python3.11-requests-2.31.0"),
+            ("python3.11-requests".to_string(), "2.31.0".to_string())
+        );
+        assert_eq!(
+            parse_store_name("/nix/store/0123456789abcdfghijklmnpqrsvwxyz-hello"),
+            ("hello".to_string(), String::new())
+        );
+    }
+
+    #[test]
+    fn test_renamed_attr() {
+        let err = "error: 'exa' has been renamed to/replaced by 'eza'";
+        assert_eq!(renamed_attr(err).as_deref(), Some("eza"));
+        assert_eq!(renamed_attr("… has been renamed to `pkgs.nodejs_20`").as_deref(), Some("nodejs_20"));
+        assert_eq!(renamed_attr("error: 'oldtool' has been removed, as it is unmaintained"), None);
+        assert_eq!(renamed_attr("error: renamed to something else"), None);
+    }
+
+    #[test]
+    fn test_preview_from_versions() {
+        let installed = vec![
+            ("firefox".to_string(), "121.0".to_string()),
+            ("git".to_string(), "2.44.0".to_string()),
+            ("oldtool".to_string(), "1.0".to_string()),
+            ("ripgrep".to_string(), "14.1.0".to_string()),
+        ];
+        let preview = UpgradePreview::from_versions(&installed, |name| match name {
+            "firefox" => Some("122.0".to_string()),
+            "git" => Some("2.44.0".to_string()),
+            "ripgrep" => Some("14.0.3".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(preview.unchanged, 1);
+        assert_eq!(preview.count(ChangeKind::Upgrade), 1);
+        assert_eq!(preview.count(ChangeKind::Downgrade), 1);
+        assert_eq!(preview.count(ChangeKind::Removed), 1);
+        assert_eq!(preview.changes[0].name, "firefox");
+    }
+}
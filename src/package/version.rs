@@ -0,0 +1,276 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Version constraint parsing for install targets (`nodejs@20`, `nodejs>=18`).
+//!
+//! Constraints select among the versioned attributes of the current nixpkgs
+//! (`nodejs_20`, `python311`, ...). Older nixpkgs revisions are not searched;
+//! `nixboost pin --rev` installs from a specific commit.
+
+use crate::core::error::{PackageError, Result};
+use crate::core::types::Package;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Comparison operator in a version constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionOp {
+    /// `@` - version starts with the given components
+    Prefix,
+    /// `=` / `==`
+    Eq,
+    /// `>=`
+    Ge,
+    /// `>`
+    Gt,
+    /// `<=`
+    Le,
+    /// `<`
+    Lt,
+}
+
+impl fmt::Display for VersionOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionOp::Prefix => write!(f, "@"),
+            VersionOp::Eq => write!(f, "=="),
+            VersionOp::Ge => write!(f, ">="),
+            VersionOp::Gt => write!(f, ">"),
+            VersionOp::Le => write!(f, "<="),
+            VersionOp::Lt => write!(f, "<"),
+        }
+    }
+}
+
+/// A version requirement attached to a target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionConstraint {
+    pub op: VersionOp,
+    pub version: String,
+}
+
+impl VersionConstraint {
+    /// Check whether a concrete version satisfies this constraint
+    pub fn matches(&self, version: &str) -> bool {
+        let ord = compare_versions(version, &self.version);
+        match self.op {
+            VersionOp::Prefix => {
+                let want = components(&self.version);
+                let have = components(version);
+                have.len() >= want.len() && have[..want.len()] == want[..]
+            }
+            VersionOp::Eq => ord == Ordering::Equal,
+            VersionOp::Ge => ord != Ordering::Less,
+            VersionOp::Gt => ord == Ordering::Greater,
+            VersionOp::Le => ord != Ordering::Greater,
+            VersionOp::Lt => ord == Ordering::Less,
+        }
+    }
+}
+
+impl fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.op, self.version)
+    }
+}
+
+/// An install target, optionally constrained to a version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetSpec {
+    pub name: String,
+    pub constraint: Option<VersionConstraint>,
+}
+
+impl TargetSpec {
+    /// Parse `name`, `name@ver`, `name>=ver`, `name>ver`, `name<=ver`,
+    /// `name<ver` or `name==ver`. Flake references (`github:o/r?ref=x#pkg`,
+    /// `path:/a@b#pkg`, `alias#attr`) are taken whole, without a constraint.
+    pub fn parse(target: &str) -> Result<Self> {
+        if target.contains([':', '#']) {
+            return Ok(Self { name: target.to_string(), constraint: None });
+        }
+
+        const OPS: &[(&str, VersionOp)] = &[
+            (">=", VersionOp::Ge),
+            ("<=", VersionOp::Le),
+            ("==", VersionOp::Eq),
+            ("@", VersionOp::Prefix),
+            (">", VersionOp::Gt),
+            ("<", VersionOp::Lt),
+            ("=", VersionOp::Eq),
+        ];
+
+        let found = OPS.iter()
+            .filter_map(|(token, op)| target.find(token).map(|idx| (idx, *token, *op)))
+            .min_by_key(|(idx, token, _)| (*idx, std::cmp::Reverse(token.len())));
+
+        let Some((idx, token, op)) = found else {
+            return Ok(Self { name: target.to_string(), constraint: None });
+        };
+
+        let name = target[..idx].trim();
+        let version = target[idx + token.len()..].trim();
+
+        if name.is_empty() || version.is_empty() {
+            return Err(PackageError::InvalidSpec(target.to_string()).into());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            constraint: Some(VersionConstraint { op, version: version.to_string() }),
+        })
+    }
+
+    /// Whether an attribute name is a versioned variant of this target
+    /// (e.g. `nodejs_20` or `python311` for `nodejs`/`python`)
+    pub fn is_variant(&self, attr: &str) -> bool {
+        match attr.strip_prefix(self.name.as_str()) {
+            Some("") => true,
+            Some(rest) => {
+                let rest = rest.trim_start_matches(['_', '-']);
+                !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit() || c == '_')
+            }
+            None => false,
+        }
+    }
+
+    /// Pick the newest candidate satisfying the constraint.
+    ///
+    /// Returns `PackageError::VersionMismatch` listing the available
+    /// candidates when nothing matches.
+    pub fn select<'a>(&self, candidates: &'a [Package]) -> Result<&'a Package> {
+        let variants: Vec<&Package> = candidates.iter()
+            .filter(|p| self.is_variant(&p.name))
+            .collect();
+
+        let best = variants.iter()
+            .filter(|p| self.constraint.as_ref().map(|c| c.matches(&p.version)).unwrap_or(true))
+            .max_by(|a, b| compare_versions(&a.version, &b.version));
+
+        match best {
+            Some(pkg) => Ok(pkg),
+            None => {
+                let available = if variants.is_empty() {
+                    "no versioned variants found".to_string()
+                } else {
+                    let list = variants.iter()
+                        .map(|p| format!("{} ({})", p.name, p.version))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("available: {}", list)
+                };
+                let constraint = self.constraint.as_ref().map(|c| c.to_string()).unwrap_or_default();
+                Err(PackageError::VersionMismatch(
+                    format!("{}{}: {}", self.name, constraint, available)
+                ).into())
+            }
+        }
+    }
+}
+
+/// Split a version into numeric components, ignoring suffixes like `-rc1`
+fn components(version: &str) -> Vec<u64> {
+    version.split(['.', '-', '+'])
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+/// Compare two version strings component-wise
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let a = components(a);
+    let b = components(b);
+    for i in 0..a.len().max(b.len()) {
+        let l = a.get(i).copied().unwrap_or(0);
+        let r = b.get(i).copied().unwrap_or(0);
+        match l.cmp(&r) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target_spec() {
+        let spec = TargetSpec::parse("nodejs@20").unwrap();
+        assert_eq!(spec.name, "nodejs");
+        assert_eq!(spec.constraint, Some(VersionConstraint { op: VersionOp::Prefix, version: "20".to_string() }));
+
+        let spec = TargetSpec::parse("nodejs>=18").unwrap();
+        assert_eq!(spec.constraint.unwrap().op, VersionOp::Ge);
+
+        let spec = TargetSpec::parse("firefox").unwrap();
+        assert!(spec.constraint.is_none());
+
+        assert!(TargetSpec::parse("nodejs>=").is_err());
+
+        for flake_ref in ["github:o/r?ref=x#pkg", "path:/a@b#pkg", "corp#tool"] {
+            let spec = TargetSpec::parse(flake_ref).unwrap();
+            assert_eq!(spec.name, flake_ref);
+            assert_eq!(spec.constraint, None);
+        }
+    }
+
+    #[test]
+    fn test_constraint_matching() {
+        let prefix = VersionConstraint { op: VersionOp::Prefix, version: "20".to_string() };
+        assert!(prefix.matches("20.11.1"));
+        assert!(!prefix.matches("2.0"));
+        assert!(!prefix.matches("200.1"));
+
+        let ge = VersionConstraint { op: VersionOp::Ge, version: "18".to_string() };
+        assert!(ge.matches("18.0.0"));
+        assert!(ge.matches("20.1"));
+        assert!(!ge.matches("16.20.2"));
+    }
+
+    #[test]
+    fn test_is_variant() {
+        let spec = TargetSpec::parse("nodejs@20").unwrap();
+        assert!(spec.is_variant("nodejs"));
+        assert!(spec.is_variant("nodejs_20"));
+        assert!(!spec.is_variant("nodejs-slim_20"));
+        assert!(!spec.is_variant("nodePackages.npm"));
+    }
+
+    #[test]
+    fn test_select_candidates() {
+        let candidates = vec![
+            Package::new("nodejs", "20.11.1", ""),
+            Package::new("nodejs_18", "18.19.1", ""),
+            Package::new("nodejs_20", "20.11.1", ""),
+            Package::new("nodejs_22", "22.0.0", ""),
+            Package::new("nodejs-slim_22", "22.0.0", ""),
+        ];
+
+        let spec = TargetSpec::parse("nodejs>=18").unwrap();
+        assert_eq!(spec.select(&candidates).unwrap().name, "nodejs_22");
+
+        let spec = TargetSpec::parse("nodejs@18").unwrap();
+        assert_eq!(spec.select(&candidates).unwrap().name, "nodejs_18");
+
+        let spec = TargetSpec::parse("nodejs@14").unwrap();
+        let err = spec.select(&candidates).unwrap_err();
+        assert!(err.to_string().contains("nodejs_18 (18.19.1)"));
+    }
+}
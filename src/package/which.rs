@@ -0,0 +1,87 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Where a package lives in the store (`nixboost which`).
+
+use crate::core::error::{PackageError, Result};
+use crate::package::profile::{resolve_element, ElementMatch, ProfileElement};
+use serde::Serialize;
+
+/// Store paths a package resolves to, and what owns them
+#[derive(Debug, Clone, Serialize)]
+pub struct StorePathInfo {
+    pub package: String,
+    pub store_paths: Vec<String>,
+    /// Profile element providing the package, when installed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element: Option<String>,
+    /// Whether every path is already in the local store
+    pub in_store: bool,
+    /// Closure size of the local paths, or of the binary cache's copy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closure_bytes: Option<u64>,
+    /// What fetching paths missing from the store would download
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_bytes: Option<u64>,
+}
+
+/// Whether `package` may name one of the `installed` elements, by element
+/// name or a trailing attribute (`python3Packages.requests` for `requests`)
+pub fn may_be_installed(installed: &[String], package: &str) -> bool {
+    package.starts_with("/nix/store/")
+        || installed.iter().any(|name| name == package || package.ends_with(&format!(".{}", name)))
+}
+
+/// The element owning `package`, if it is installed
+pub fn owner<'a>(elements: &'a [ProfileElement], package: &str) -> Result<Option<&'a ProfileElement>> {
+    match resolve_element(elements, package) {
+        ElementMatch::One(el) => Ok(Some(el)),
+        ElementMatch::None => Ok(None),
+        ElementMatch::Ambiguous(candidates) => Err(PackageError::Ambiguous {
+            query: package.to_string(),
+            candidates: candidates.iter().map(|el| el.name.as_str()).collect::<Vec<_>>().join(", "),
+        }.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(name: &str, attr: &str) -> ProfileElement {
+        ProfileElement {
+            name: name.to_string(),
+            attr_path: Some(format!("legacyPackages.x86_64-linux.{}", attr)),
+            original_url: Some("flake:nixpkgs".to_string()),
+            url: None,
+            store_paths: vec![format!("/nix/store/abc-{}-1.0", name)],
+        }
+    }
+
+    #[test]
+    fn test_owner() {
+        let installed = vec!["hello".to_string(), "requests".to_string()];
+        assert!(may_be_installed(&installed, "hello"));
+        assert!(may_be_installed(&installed, "python3Packages.requests"));
+        assert!(may_be_installed(&installed, "/nix/store/abc-hello-1.0"));
+        assert!(!may_be_installed(&installed, "cowsay"));
+
+        let elements = vec![element("hello", "hello"), element("requests", "python3Packages.requests")];
+        assert_eq!(owner(&elements, "python3Packages.requests").unwrap().map(|el| el.name.as_str()), Some("requests"));
+        assert!(owner(&elements, "cowsay").unwrap().is_none());
+    }
+}
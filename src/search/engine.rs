@@ -16,11 +16,13 @@
 
 //! Parallel fuzzy search engine for NixBoost.
 
+use crate::core::config::Config;
 use crate::core::error::{Result, SearchError};
 use crate::core::types::{Package, PackageSource, SearchResult, MatchType};
 use fuzzy_matcher::FuzzyMatcher;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tracing::{debug, info};
 
@@ -32,15 +34,79 @@ pub struct SearchEngine {
     min_score: i64,
     /// Maximum results to return
     max_results: usize,
+    /// Keep alias attributes listed and wrapper variants at full rank
+    show_aliases: bool,
+    /// Restrict results to attribute namespaces matching this prefix
+    scope: Option<String>,
+    /// Metadata filters applied before ranking
+    filters: SearchFilters,
+    /// Successful installs per lowercase package name
+    install_counts: HashMap<String, u32>,
+    /// Lowercase glob patterns of attribute names to leave out
+    excludes: Vec<String>,
 }
 
+/// Metadata filters for search results.
+///
+/// Packages without the relevant metadata never match an active filter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchFilters {
+    /// License identifier substring (case-insensitive, e.g. `MIT`, `gpl`)
+    pub license: Option<String>,
+    /// Maintainer handle (case-insensitive)
+    pub maintainer: Option<String>,
+    /// Supported platform (e.g. `aarch64-linux`)
+    pub platform: Option<String>,
+}
+
+impl SearchFilters {
+    /// Whether no filter is set
+    pub fn is_empty(&self) -> bool {
+        self.license.is_none() && self.maintainer.is_none() && self.platform.is_none()
+    }
+
+    /// Whether `package` satisfies every set filter
+    pub fn matches(&self, package: &Package) -> bool {
+        let license = self.license.as_ref().is_none_or(|want| {
+            package.license.as_ref().is_some_and(|l| l.to_lowercase().contains(&want.to_lowercase()))
+        });
+        let maintainer = self.maintainer.as_ref().is_none_or(|want| {
+            package.maintainers.iter().any(|m| m.eq_ignore_ascii_case(want))
+        });
+        let platform = self.platform.as_ref().is_none_or(|want| {
+            package.platforms.iter().any(|p| p == want)
+        });
+        license && maintainer && platform
+    }
+}
+
+/// Attribute name suffixes that usually denote wrapper internals or
+/// prebuilt variants rather than the package users are looking for
+const NOISE_SUFFIXES: &[&str] = &["-unwrapped", "-bin", "-wrapped", "-debug"];
+
+/// Score multiplier applied to noisy attributes
+const NOISE_PENALTY: f64 = 0.6;
+
+/// Share of the remaining score gap closed for a previously installed package
+const HISTORY_BOOST: f64 = 0.25;
+
+/// Extra gap share per repeat install, up to `HISTORY_BOOST_MAX`
+const HISTORY_BOOST_STEP: f64 = 0.05;
+const HISTORY_BOOST_MAX: f64 = 0.5;
+
 impl SearchEngine {
-    /// Create a new search engine
+    /// Create a new search engine with the `[search]` limits
     pub fn new() -> Self {
+        let search = Config::try_get().map(|c| &c.search);
         Self {
             matcher: Arc::new(SkimMatcherV2::default()),
-            min_score: 30,
-            max_results: 50,
+            min_score: search.map(|s| s.min_score).unwrap_or(30),
+            max_results: search.map(|s| s.max_results).unwrap_or(50),
+            show_aliases: false,
+            scope: None,
+            filters: SearchFilters::default(),
+            install_counts: HashMap::new(),
+            excludes: Vec::new(),
         }
     }
 
@@ -50,6 +116,11 @@ impl SearchEngine {
             matcher: Arc::new(SkimMatcherV2::default()),
             min_score,
             max_results,
+            show_aliases: false,
+            scope: None,
+            filters: SearchFilters::default(),
+            install_counts: HashMap::new(),
+            excludes: Vec::new(),
         }
     }
 
@@ -65,6 +136,40 @@ impl SearchEngine {
         self
     }
 
+    /// Include alias attributes and wrapper variants without penalties
+    pub fn show_aliases(mut self, show: bool) -> Self {
+        self.show_aliases = show;
+        self
+    }
+
+    /// Only match packages in namespaces starting with `scope`
+    /// (e.g. `python` matches `python3Packages` and `python311Packages`)
+    pub fn scope(mut self, scope: Option<String>) -> Self {
+        self.scope = scope.map(|s| s.to_lowercase());
+        self
+    }
+
+    /// Only match packages satisfying the license/maintainer/platform `filters`
+    pub fn filters(mut self, filters: SearchFilters) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Leave out packages whose name matches any of the glob `patterns`
+    /// (`*` and `?` wildcards, case-insensitive)
+    pub fn exclude(mut self, patterns: impl IntoIterator<Item = String>) -> Self {
+        self.excludes.extend(patterns.into_iter().map(|p| p.to_lowercase()));
+        self
+    }
+
+    /// Rank packages that were installed before (see
+    /// [`OperationLog::install_counts`](crate::core::history::OperationLog::install_counts))
+    /// above equally matching ones that were not
+    pub fn install_history(mut self, counts: HashMap<String, u32>) -> Self {
+        self.install_counts = counts;
+        self
+    }
+
     /// Search packages with fuzzy matching
     pub fn search(&self, query: &str, packages: &[Package]) -> Result<Vec<SearchResult>> {
         if query.is_empty() {
@@ -81,7 +186,18 @@ impl SearchEngine {
         // Parallel search
         let mut results: Vec<SearchResult> = packages
             .par_iter()
+            .filter(|pkg| self.show_aliases || !pkg.alias)
+            .filter(|pkg| self.in_scope(pkg))
+            .filter(|pkg| !self.is_excluded(pkg))
+            .filter(|pkg| self.filters.matches(pkg))
             .filter_map(|pkg| self.score_package(&query_lower, pkg))
+            .map(|mut result| {
+                if !self.show_aliases && is_noise(&result.package, &query_lower) {
+                    result.score *= NOISE_PENALTY;
+                }
+                result.score += (1.0 - result.score) * self.history_boost(&result.package);
+                result
+            })
             .collect();
 
         // Sort by score (highest first)
@@ -94,6 +210,36 @@ impl SearchEngine {
         Ok(results)
     }
 
+    /// Whether a package falls inside the configured scope
+    fn in_scope(&self, package: &Package) -> bool {
+        match &self.scope {
+            Some(scope) => package
+                .namespace()
+                .map(|ns| ns.to_lowercase().starts_with(scope.as_str()))
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+
+    /// Whether a package matches one of the exclude patterns
+    fn is_excluded(&self, package: &Package) -> bool {
+        if self.excludes.is_empty() {
+            return false;
+        }
+        let name = package.name.to_lowercase();
+        self.excludes.iter().any(|pattern| glob_match(pattern, &name))
+    }
+
+    /// Fraction of the gap to a perfect score closed by install history
+    fn history_boost(&self, package: &Package) -> f64 {
+        match self.install_counts.get(&package.name.to_lowercase()) {
+            Some(&count) if count > 0 => {
+                (HISTORY_BOOST + HISTORY_BOOST_STEP * (count - 1) as f64).min(HISTORY_BOOST_MAX)
+            }
+            _ => 0.0,
+        }
+    }
+
     /// Score a single package against the query
     fn score_package(&self, query: &str, package: &Package) -> Option<SearchResult> {
         let name_lower = package.name.to_lowercase();
@@ -228,6 +374,80 @@ impl SearchEngine {
     }
 }
 
+/// Group results by top-level attribute namespace.
+///
+/// Top-level packages come first under an empty namespace, followed by
+/// namespaces in order of their best-ranked result.
+pub fn group_by_namespace(results: &[SearchResult]) -> Vec<(String, Vec<&SearchResult>)> {
+    let mut groups: Vec<(String, Vec<&SearchResult>)> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+
+    for result in results {
+        let ns = result.package.namespace().unwrap_or("");
+        match index.get(ns) {
+            Some(&idx) => groups[idx].1.push(result),
+            None => {
+                index.insert(ns, groups.len());
+                groups.push((ns.to_string(), vec![result]));
+            }
+        }
+    }
+
+    groups.sort_by_key(|(ns, _)| !ns.is_empty());
+    groups
+}
+
+/// Flag packages whose top-level attribute is missing from `non_aliases`,
+/// the attribute names of nixpkgs evaluated with `allowAliases = false`
+pub fn mark_aliases(packages: &mut [Package], non_aliases: &HashSet<String>) {
+    for pkg in packages.iter_mut() {
+        let top = pkg.name.split('.').next().unwrap_or(&pkg.name);
+        pkg.alias = !non_aliases.contains(top);
+    }
+}
+
+/// Match `text` against a glob `pattern` where `*` matches any run of
+/// characters and `?` exactly one
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character
+                Some((sp, st)) => {
+                    p = sp;
+                    t = st + 1;
+                    star = Some((sp, st + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Whether a package looks like a wrapper internal or variant the user
+/// did not explicitly ask for
+fn is_noise(package: &Package, query: &str) -> bool {
+    let name = package.name.to_lowercase();
+    NOISE_SUFFIXES
+        .iter()
+        .any(|suffix| name.ends_with(suffix) && !query.ends_with(suffix))
+}
+
 impl Default for SearchEngine {
     fn default() -> Self {
         Self::new()
@@ -262,6 +482,11 @@ impl MultiSourceSearch {
         }
     }
 
+    /// Search every source with `engine`'s settings
+    pub fn with_engine(engine: SearchEngine) -> Self {
+        Self { engine }
+    }
+
     /// Search across multiple package sources in parallel
     pub fn search(
         &self,
@@ -280,12 +505,42 @@ impl MultiSourceSearch {
 
         // Re-sort combined results
         all_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut all_results = dedup_results(all_results);
         all_results.truncate(self.engine.max_results);
 
         Ok(all_results)
     }
 }
 
+/// Collapse results that share a package name across sources.
+///
+/// Expects `results` sorted by score; the best-scoring entry is kept and
+/// the sources of the others are listed as its alternatives.
+pub fn dedup_results(results: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut merged: Vec<SearchResult> = Vec::with_capacity(results.len());
+    let mut by_name: HashMap<String, usize> = HashMap::new();
+
+    for result in results {
+        let key = result.package.name.to_lowercase();
+        match by_name.get(&key) {
+            Some(&idx) => {
+                let primary = &mut merged[idx];
+                let source = result.package.source;
+                if source != primary.package.source && !primary.alternatives.contains(&source) {
+                    primary.alternatives.push(source);
+                }
+            }
+            None => {
+                by_name.insert(key, merged.len());
+                merged.push(result);
+            }
+        }
+    }
+
+    merged
+}
+
 impl Default for MultiSourceSearch {
     fn default() -> Self {
         Self::new()
@@ -374,6 +629,10 @@ mod tests {
         let results = engine.search("e", &packages).unwrap();
         
         assert!(results.len() <= 2);
+
+        // Larger limits are not capped at the default of 50
+        let many: Vec<Package> = (0..120).map(|i| Package::new(format!("lib{}", i), "1.0", "")).collect();
+        assert_eq!(SearchEngine::with_settings(0, 100).search("lib", &many).unwrap().len(), 100);
     }
 
     #[test]
@@ -399,6 +658,178 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_alias_penalties() {
+        let packages = vec![
+            Package::new("firefox", "120.0", "Web browser"),
+            Package::new("firefox-unwrapped", "120.0", "Web browser"),
+            Package { alias: true, ..Package::new("firefox-wayland", "120.0", "Web browser") },
+            Package::new("firefox-esr", "115.0", "Web browser, replaces the deprecated firefox-esr-102"),
+        ];
+
+        let results = SearchEngine::new().search("firefox", &packages).unwrap();
+        assert!(!results.iter().any(|r| r.package.name == "firefox-wayland"));
+        // Mentioning deprecation in the description does not hide a package
+        assert!(results.iter().any(|r| r.package.name == "firefox-esr"));
+        let unwrapped = results.iter().find(|r| r.package.name == "firefox-unwrapped").unwrap();
+        assert!(unwrapped.score < 0.9 * NOISE_PENALTY + f64::EPSILON);
+
+        // Explicitly searching for the variant is not penalized
+        let results = SearchEngine::new().search("firefox-unwrapped", &packages).unwrap();
+        assert_eq!(results[0].package.name, "firefox-unwrapped");
+        assert_eq!(results[0].score, 1.0);
+
+        let results = SearchEngine::new().show_aliases(true).search("firefox", &packages).unwrap();
+        assert_eq!(results.len(), 4);
+    }
+
+    #[test]
+    fn test_mark_aliases() {
+        let mut packages = vec![
+            Package::new("firefox", "120.0", "Web browser"),
+            Package::new("firefox-wayland", "120.0", "Web browser"),
+            Package::new("python3Packages.requests", "2.31", "HTTP for humans"),
+        ];
+        let non_aliases: HashSet<String> = ["firefox", "python3Packages"].iter().map(|s| s.to_string()).collect();
+
+        mark_aliases(&mut packages, &non_aliases);
+        let aliases: Vec<&str> = packages.iter().filter(|p| p.alias).map(|p| p.name.as_str()).collect();
+        assert_eq!(aliases, vec!["firefox-wayland"]);
+    }
+
+    #[test]
+    fn test_scope_filter() {
+        let packages = vec![
+            Package::new("requests", "1.0", "HTTP for humans"),
+            Package::new("python3Packages.requests", "2.31", "HTTP for humans"),
+            Package::new("python311Packages.requests", "2.31", "HTTP for humans"),
+            Package::new("nodePackages.requests", "0.1", "HTTP"),
+        ];
+
+        let results = SearchEngine::new()
+            .scope(Some("python".to_string()))
+            .search("requests", &packages)
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.package.name.starts_with("python")));
+    }
+
+    #[test]
+    fn test_metadata_filters() {
+        let mut hello = Package::new("hello", "2.12", "Greeting");
+        hello.license = Some("GPL-3.0-or-later".to_string());
+        hello.maintainers = vec!["Alice".to_string()];
+        hello.platforms = vec!["x86_64-linux".to_string(), "aarch64-linux".to_string()];
+        let mut hello_rs = Package::new("hello-rs", "0.1", "Greeting in Rust");
+        hello_rs.license = Some("MIT".to_string());
+        hello_rs.platforms = vec!["x86_64-linux".to_string()];
+        let packages = vec![hello, hello_rs, Package::new("hello-go", "1.0", "Greeting in Go")];
+
+        let search = |filters: SearchFilters| {
+            SearchEngine::new()
+                .filters(filters)
+                .search("hello", &packages)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.package.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(search(SearchFilters::default()).len(), 3);
+        assert_eq!(search(SearchFilters { license: Some("mit".into()), ..Default::default() }), vec!["hello-rs"]);
+        assert_eq!(search(SearchFilters { maintainer: Some("alice".into()), ..Default::default() }), vec!["hello"]);
+        assert_eq!(search(SearchFilters { platform: Some("aarch64-linux".into()), ..Default::default() }), vec!["hello"]);
+        assert!(search(SearchFilters {
+            license: Some("MIT".into()),
+            platform: Some("aarch64-linux".into()),
+            ..Default::default()
+        }).is_empty());
+    }
+
+    #[test]
+    fn test_history_boost() {
+        let packages = vec![
+            Package::new("vimPlugins.vim-airline", "0.11", "Lean status line for vim"),
+            Package::new("neovim", "0.10", "Vim-fork focused on extensibility"),
+            Package::new("vim-full", "9.1", "The most popular clone of the VI editor"),
+        ];
+        let names = |engine: SearchEngine| {
+            engine.search("vim", &packages).unwrap().into_iter().map(|r| r.package.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(SearchEngine::new()), vec!["vim-full", "vimPlugins.vim-airline", "neovim"]);
+
+        // A previously installed package overtakes close matches, not much better ones
+        let history = HashMap::from([("neovim".to_string(), 1)]);
+        let boosted = SearchEngine::new().install_history(history);
+        assert_eq!(names(boosted), vec!["vim-full", "neovim", "vimPlugins.vim-airline"]);
+
+        // Frequent installs weigh more, up to the cap
+        let engine = SearchEngine::new().install_history(HashMap::from([("vim-full".to_string(), 3), ("neovim".to_string(), 50)]));
+        assert_eq!(engine.history_boost(&packages[2]), HISTORY_BOOST + 2.0 * HISTORY_BOOST_STEP);
+        assert_eq!(engine.history_boost(&packages[1]), HISTORY_BOOST_MAX);
+        assert_eq!(engine.history_boost(&packages[0]), 0.0);
+    }
+
+    #[test]
+    fn test_exclude_patterns() {
+        assert!(glob_match("*-unwrapped", "firefox-unwrapped"));
+        assert!(glob_match("python3packages.*", "python3packages.requests"));
+        assert!(glob_match("fire?ox*", "firefox-esr"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("*-unwrapped", "firefox"));
+        assert!(!glob_match("python3packages.*", "python311packages.requests"));
+
+        let packages = vec![
+            Package::new("requests", "1.0", "HTTP for humans"),
+            Package::new("python3Packages.requests", "2.31", "HTTP for humans"),
+            Package::new("requests-unwrapped", "2.31", "HTTP for humans"),
+        ];
+        let results = SearchEngine::new()
+            .show_aliases(true)
+            .exclude(["*-unwrapped".to_string(), "Python3Packages.*".to_string()])
+            .search("requests", &packages)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].package.name, "requests");
+    }
+
+    #[test]
+    fn test_group_by_namespace() {
+        let results: Vec<SearchResult> = [
+            "python3Packages.requests",
+            "requests",
+            "nodePackages.request",
+            "python3Packages.requests-mock",
+        ]
+        .iter()
+        .map(|name| SearchResult::new(Package::new(*name, "1.0", ""), 0.5, MatchType::NameContains))
+        .collect();
+
+        let groups = group_by_namespace(&results);
+        let names: Vec<&str> = groups.iter().map(|(ns, _)| ns.as_str()).collect();
+        assert_eq!(names, vec!["", "python3Packages", "nodePackages"]);
+        assert_eq!(groups[1].1.len(), 2);
+    }
+
+    #[test]
+    fn test_multi_source_dedup() {
+        let nixpkgs = vec![Package::from_nixpkgs("hello", "2.12", "Greeting")];
+        let nur = vec![
+            Package::from_nur("hello", "2.10", "Greeting", "alice"),
+            Package::from_nur("hello", "2.11", "Greeting", "bob"),
+            Package::from_nur("hello-rs", "0.1", "Greeting in Rust", "alice"),
+        ];
+
+        let results = MultiSourceSearch::new().search("hello", &nixpkgs, &nur).unwrap();
+
+        let hello: Vec<_> = results.iter().filter(|r| r.package.name == "hello").collect();
+        assert_eq!(hello.len(), 1);
+        assert_eq!(hello[0].package.source, PackageSource::Nixpkgs);
+        assert_eq!(hello[0].alternatives.len(), 2);
+        assert!(results.iter().any(|r| r.package.name == "hello-rs"));
+    }
+
     #[test]
     fn test_empty_query() {
         let engine = SearchEngine::new();
@@ -0,0 +1,265 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Persistent nixpkgs index (`nixboost index update`).
+//!
+//! A full `nix search --json nixpkgs ^` dump is stored in an FTS5 table of
+//! the cache database. Queries select candidates by token prefix and leave
+//! the ranking to [`SearchEngine`](super::SearchEngine), so search works
+//! offline and without evaluating nixpkgs. Alias attributes found at update
+//! time are kept in a side table so search can hide them.
+
+use crate::cache::bundle::CACHE_DB;
+use crate::core::config::Config;
+use crate::core::error::{CacheError, Result};
+use crate::core::types::Package;
+use crate::utils::time::{format_age, unix_now};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::debug;
+
+/// Candidates returned per query before fuzzy ranking
+const MAX_CANDIDATES: usize = 2000;
+
+/// Size and age of the local index
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexStatus {
+    pub path: PathBuf,
+    pub packages: usize,
+    /// Unix timestamp of the last `index update`
+    pub updated_at: Option<u64>,
+    pub revision: Option<String>,
+}
+
+impl IndexStatus {
+    pub fn print(&self) {
+        println!("Index:    {}", self.path.display());
+        match self.updated_at {
+            Some(at) => println!("Updated:  {} ago", format_age(Duration::from_secs(unix_now().saturating_sub(at)))),
+            None => println!("Updated:  {} (run 'nixboost index update')", console::style("never").yellow()),
+        }
+        println!("Revision: {}", self.revision.as_deref().unwrap_or("unknown"));
+        println!("Packages: {}", self.packages);
+    }
+}
+
+/// FTS5 package index
+pub struct LocalIndex {
+    conn: Mutex<Connection>,
+    path: PathBuf,
+    /// Whether the index holds any packages, kept current by `replace`
+    populated: AtomicBool,
+}
+
+impl LocalIndex {
+    /// Index in the default cache database
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Config::cache_dir().join(CACHE_DB))
+    }
+
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| CacheError::InitFailed(e.to_string()))?;
+        }
+
+        let conn = Connection::open(path).map_err(|e| CacheError::InitFailed(e.to_string()))?;
+        conn.execute_batch(
+            "
+            CREATE VIRTUAL TABLE IF NOT EXISTS package_index USING fts5(
+                name, version UNINDEXED, description
+            );
+
+            CREATE TABLE IF NOT EXISTS package_index_alias (
+                name TEXT PRIMARY KEY
+            );
+
+            CREATE TABLE IF NOT EXISTS package_index_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            "
+        ).map_err(|e| CacheError::InitFailed(e.to_string()))?;
+
+        let populated = conn
+            .query_row("SELECT EXISTS (SELECT 1 FROM package_index)", [], |row| row.get(0))
+            .map_err(|e| CacheError::InitFailed(e.to_string()))?;
+
+        Ok(Self { conn: Mutex::new(conn), path: path.to_path_buf(), populated: AtomicBool::new(populated) })
+    }
+
+    /// Whether `index update` has been run
+    pub fn is_populated(&self) -> bool {
+        self.populated.load(Ordering::Relaxed)
+    }
+
+    /// Replace the whole index in one transaction
+    pub fn replace(&self, packages: &[Package], revision: Option<&str>) -> Result<()> {
+        let mut conn = self.conn.lock().map_err(|e| CacheError::WriteError(e.to_string()))?;
+        let write = |e: rusqlite::Error| CacheError::WriteError(e.to_string());
+
+        let tx = conn.transaction().map_err(write)?;
+        tx.execute("DELETE FROM package_index", []).map_err(write)?;
+        tx.execute("DELETE FROM package_index_alias", []).map_err(write)?;
+        {
+            let mut insert = tx
+                .prepare("INSERT INTO package_index (name, version, description) VALUES (?1, ?2, ?3)")
+                .map_err(write)?;
+            let mut insert_alias = tx
+                .prepare("INSERT OR IGNORE INTO package_index_alias (name) VALUES (?1)")
+                .map_err(write)?;
+            for pkg in packages {
+                insert.execute(params![pkg.name, pkg.version, pkg.description]).map_err(write)?;
+                if pkg.alias {
+                    insert_alias.execute(params![pkg.name]).map_err(write)?;
+                }
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO package_index_meta (key, value) VALUES ('updated_at', ?1)",
+            params![unix_now().to_string()],
+        ).map_err(write)?;
+        match revision {
+            Some(rev) => tx.execute(
+                "INSERT OR REPLACE INTO package_index_meta (key, value) VALUES ('revision', ?1)",
+                params![rev],
+            ),
+            None => tx.execute("DELETE FROM package_index_meta WHERE key = 'revision'", []),
+        }.map_err(write)?;
+        tx.commit().map_err(write)?;
+        self.populated.store(!packages.is_empty(), Ordering::Relaxed);
+
+        debug!("Indexed {} packages", packages.len());
+        Ok(())
+    }
+
+    /// Packages whose name or description has a token starting with each
+    /// query word, at least `max_results` of them when there are that many.
+    /// A query without words (such as `^`) selects the whole index.
+    pub fn candidates(&self, query: &str, max_results: usize) -> Result<Vec<Package>> {
+        let Some(expr) = match_expression(query) else {
+            return self.all();
+        };
+
+        let packages = self.select(
+            "SELECT name, version, description, name IN (SELECT name FROM package_index_alias) FROM package_index WHERE package_index MATCH ?1 ORDER BY rank LIMIT ?2",
+            params![expr, MAX_CANDIDATES.max(max_results) as i64],
+        )?;
+        debug!("{} index candidates for '{}'", packages.len(), query);
+        Ok(packages)
+    }
+
+    /// Every indexed package
+    pub fn all(&self) -> Result<Vec<Package>> {
+        self.select("SELECT name, version, description, name IN (SELECT name FROM package_index_alias) FROM package_index", [])
+    }
+
+    fn select<P: rusqlite::Params>(&self, sql: &str, params: P) -> Result<Vec<Package>> {
+        let conn = self.conn.lock().map_err(|e| CacheError::ReadError(e.to_string()))?;
+        let read = |e: rusqlite::Error| CacheError::ReadError(e.to_string());
+
+        let mut stmt = conn.prepare(sql).map_err(read)?;
+        let rows = stmt
+            .query_map(params, |row| {
+                let mut pkg = Package::from_nixpkgs(
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                );
+                pkg.alias = row.get(3)?;
+                Ok(pkg)
+            })
+            .map_err(read)?;
+        let packages = rows.collect::<rusqlite::Result<Vec<_>>>().map_err(read)?;
+        Ok(packages)
+    }
+
+    pub fn status(&self) -> Result<IndexStatus> {
+        let conn = self.conn.lock().map_err(|e| CacheError::ReadError(e.to_string()))?;
+        let read = |e: rusqlite::Error| CacheError::ReadError(e.to_string());
+
+        let packages: i64 = conn
+            .query_row("SELECT COUNT(*) FROM package_index", [], |row| row.get(0))
+            .map_err(read)?;
+        let meta = |key: &str| {
+            conn.query_row("SELECT value FROM package_index_meta WHERE key = ?1", params![key], |row| {
+                row.get::<_, String>(0)
+            })
+            .optional()
+        };
+
+        Ok(IndexStatus {
+            path: self.path.clone(),
+            packages: packages as usize,
+            updated_at: meta("updated_at").map_err(read)?.and_then(|v| v.parse().ok()),
+            revision: meta("revision").map_err(read)?,
+        })
+    }
+}
+
+/// FTS5 query matching every word of `query` as a token prefix.
+/// Words are quoted so FTS5 operators in user input stay literal.
+fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| format!("\"{}\"*", w))
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_index_candidates() {
+        let tmp = TempDir::new().unwrap();
+        let index = LocalIndex::open(&tmp.path().join(CACHE_DB)).unwrap();
+        assert!(!index.is_populated());
+
+        index.replace(&[
+            Package::from_nixpkgs("ripgrep", "14.1.0", "Utility that combines the usability of The Silver Searcher with grep"),
+            Package::from_nixpkgs("python3Packages.requests", "2.31.0", "HTTP library for Python"),
+            Package { alias: true, ..Package::from_nixpkgs("fd", "10.1.0", "Simple, fast and user-friendly alternative to find") },
+        ], Some("abc123")).unwrap();
+
+        let names = |q: &str| -> Vec<String> { index.candidates(q, 50).unwrap().into_iter().map(|p| p.name).collect() };
+        assert!(index.all().unwrap().iter().all(|p| p.alias == (p.name == "fd")));
+        assert_eq!(names("rip"), vec!["ripgrep"]);
+        assert_eq!(names("requests"), vec!["python3Packages.requests"]);
+        assert_eq!(names("fast find"), vec!["fd"]);
+        assert!(names("rgrep").is_empty());
+        assert_eq!(names("^").len(), 3);
+
+        assert!(index.is_populated());
+        let status = index.status().unwrap();
+        assert_eq!(status.packages, 3);
+        assert_eq!(status.revision.as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn test_match_expression() {
+        assert_eq!(match_expression("nodejs 20").as_deref(), Some("\"nodejs\"* \"20\"*"));
+        assert_eq!(match_expression("\"OR\" NEAR("), Some("\"OR\"* \"NEAR\"*".to_string()));
+        assert_eq!(match_expression("^"), None);
+    }
+}
@@ -17,5 +17,6 @@
 //! Search module for NixBoost - parallel fuzzy search engine.
 
 pub mod engine;
+pub mod index;
 
-pub use engine::SearchEngine;
+pub use engine::{SearchEngine, SearchFilters};
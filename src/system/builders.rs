@@ -0,0 +1,282 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Remote builders from the `builders` setting and their reachability.
+//!
+//! Each machine is probed over SSH in parallel: the same login Nix would
+//! use reports its load average and the systems its Nix can build, so a
+//! builder that is down, misconfigured or overloaded shows up before a
+//! build is dispatched to it.
+
+use crate::system::capabilities::nix_setting;
+use crate::utils::command::Echo;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::process::Command;
+use std::time::Instant;
+use tracing::debug;
+
+/// Seconds SSH waits for a builder to accept the connection
+const CONNECT_TIMEOUT_SECS: u32 = 5;
+
+/// Remote script printing the load average and the builder's Nix systems
+const PROBE_SCRIPT: &str = "cat /proc/loadavg 2>/dev/null; \
+    nix show-config 2>/dev/null | grep -E '^(system|extra-platforms) ='";
+
+/// One machine from the `builders` setting or a machines file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Builder {
+    /// Store URI, e.g. `ssh-ng://builder@host`
+    pub uri: String,
+    /// Systems the machine is configured to build for
+    pub systems: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh_key: Option<String>,
+    pub max_jobs: u32,
+    pub speed_factor: u32,
+    pub features: Vec<String>,
+    pub mandatory_features: Vec<String>,
+}
+
+impl Builder {
+    /// Parse one machine line; `-` keeps a field's default
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let uri = fields.next()?.to_string();
+        let mut next = || fields.next().filter(|f| *f != "-");
+        let list = |field: Option<&str>| -> Vec<String> {
+            field.map(|f| f.split(',').filter(|s| !s.is_empty()).map(String::from).collect()).unwrap_or_default()
+        };
+
+        let systems = list(next());
+        let ssh_key = next().map(String::from);
+        let max_jobs = next().and_then(|n| n.parse().ok()).unwrap_or(1);
+        let speed_factor = next().and_then(|n| n.parse().ok()).unwrap_or(1);
+        let features = list(next());
+        let mandatory_features = list(next());
+        Some(Self { uri, systems, ssh_key, max_jobs, speed_factor, features, mandatory_features })
+    }
+
+    /// `user@host` to hand to ssh, without the store scheme or parameters
+    pub fn host(&self) -> &str {
+        let rest = self.uri.split_once("://").map(|(_, rest)| rest).unwrap_or(&self.uri);
+        rest.split(['?', '/']).next().unwrap_or(rest)
+    }
+
+    /// Whether this builder is used over SSH (as opposed to a local or remote store URI)
+    pub fn is_ssh(&self) -> bool {
+        !self.uri.contains("://") || self.uri.starts_with("ssh://") || self.uri.starts_with("ssh-ng://")
+    }
+}
+
+/// Parse a `builders` value: machines separated by `;` or newlines, with
+/// `@file` entries read from machines files
+pub fn parse_builders(spec: &str) -> Vec<Builder> {
+    let mut builders = Vec::new();
+    for entry in spec.split([';', '\n']).map(|e| e.split('#').next().unwrap_or("").trim()) {
+        if let Some(file) = entry.strip_prefix('@') {
+            match std::fs::read_to_string(file) {
+                Ok(contents) => builders.extend(
+                    contents.lines().filter(|l| !l.trim_start().starts_with('@')).filter_map(|l| {
+                        Builder::parse(l.split('#').next().unwrap_or(""))
+                    }),
+                ),
+                Err(e) => debug!("Cannot read machines file {}: {}", file, e),
+            }
+        } else if let Some(builder) = Builder::parse(entry) {
+            builders.push(builder);
+        }
+    }
+    builders
+}
+
+/// Builders Nix is configured with
+pub fn configured() -> Vec<Builder> {
+    nix_setting("builders").map(|spec| parse_builders(&spec)).unwrap_or_default()
+}
+
+/// Systems the local machine builds itself (`system` and `extra-platforms`)
+pub fn local_systems() -> BTreeSet<String> {
+    ["system", "extra-platforms"]
+        .into_iter()
+        .filter_map(nix_setting)
+        .flat_map(|v| v.split_whitespace().map(String::from).collect::<Vec<_>>())
+        .collect()
+}
+
+/// Result of probing one builder
+#[derive(Debug, Clone, Serialize)]
+pub struct BuilderStatus {
+    #[serde(flatten)]
+    pub builder: Builder,
+    pub reachable: bool,
+    /// One-minute load average
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load: Option<f64>,
+    /// Systems the builder's own Nix reports, when it could be asked
+    pub remote_systems: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BuilderStatus {
+    fn unreachable(builder: Builder, error: String) -> Self {
+        Self { builder, reachable: false, load: None, remote_systems: vec![], latency_ms: None, error: Some(error) }
+    }
+
+    /// Configured systems the builder's Nix does not report supporting
+    pub fn unsupported_systems(&self) -> Vec<&str> {
+        if self.remote_systems.is_empty() {
+            return vec![];
+        }
+        self.builder
+            .systems
+            .iter()
+            .filter(|s| !self.remote_systems.contains(s))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Connect to a builder and read its load and supported systems
+pub fn probe(builder: &Builder) -> BuilderStatus {
+    if !builder.is_ssh() {
+        return BuilderStatus::unreachable(builder.clone(), format!("cannot probe non-SSH store {}", builder.uri));
+    }
+
+    let mut cmd = Command::new("ssh");
+    cmd.args(["-o", "BatchMode=yes", "-o", &format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS)]);
+    if let Some(key) = &builder.ssh_key {
+        cmd.args(["-i", key]);
+    }
+    cmd.args([builder.host(), PROBE_SCRIPT]);
+
+    let started = Instant::now();
+    let output = match cmd.echo().output() {
+        Ok(output) => output,
+        Err(e) => return BuilderStatus::unreachable(builder.clone(), format!("failed to run ssh: {}", e)),
+    };
+    // ssh exits with 255 for its own failures; the probe script's status is irrelevant
+    if output.status.code() == Some(255) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("connection failed");
+        return BuilderStatus::unreachable(builder.clone(), reason.trim().to_string());
+    }
+
+    let (load, remote_systems) = parse_probe(&String::from_utf8_lossy(&output.stdout));
+    BuilderStatus {
+        builder: builder.clone(),
+        reachable: true,
+        load,
+        remote_systems,
+        latency_ms: Some(started.elapsed().as_millis() as u64),
+        error: None,
+    }
+}
+
+/// Probe every builder concurrently
+pub fn probe_all(builders: &[Builder]) -> Vec<BuilderStatus> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = builders.iter().map(|b| scope.spawn(move || probe(b))).collect();
+        handles
+            .into_iter()
+            .zip(builders)
+            .map(|(h, b)| h.join().unwrap_or_else(|_| BuilderStatus::unreachable(b.clone(), "probe panicked".into())))
+            .collect()
+    })
+}
+
+/// Parse the probe output: `/proc/loadavg`, then `system = ...` lines
+fn parse_probe(stdout: &str) -> (Option<f64>, Vec<String>) {
+    let mut load = None;
+    let mut systems = Vec::new();
+    for line in stdout.lines() {
+        if let Some((key, value)) = line.split_once(" = ") {
+            if matches!(key.trim(), "system" | "extra-platforms") {
+                systems.extend(value.split_whitespace().map(String::from));
+            }
+        } else if load.is_none() {
+            load = line.split_whitespace().next().and_then(|l| l.parse().ok());
+        }
+    }
+    (load, systems)
+}
+
+/// Systems only remote builders provide whose builders are all unreachable
+pub fn unreachable_systems(statuses: &[BuilderStatus], local: &BTreeSet<String>) -> Vec<String> {
+    let needed: BTreeSet<&String> = statuses
+        .iter()
+        .flat_map(|s| &s.builder.systems)
+        .filter(|system| !local.contains(*system))
+        .collect();
+
+    needed
+        .into_iter()
+        .filter(|system| {
+            statuses
+                .iter()
+                .filter(|s| s.builder.systems.contains(system))
+                .all(|s| !s.reachable)
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_builders() {
+        let spec = "ssh-ng://nix@arm64 aarch64-linux,armv7l-linux /root/.ssh/id_builder 8 2 big-parallel,kvm - -;\
+                    builder@x86 x86_64-linux - 4 # comment";
+        let builders = parse_builders(spec);
+        assert_eq!(builders.len(), 2);
+
+        assert_eq!(builders[0].host(), "nix@arm64");
+        assert_eq!(builders[0].systems, vec!["aarch64-linux", "armv7l-linux"]);
+        assert_eq!(builders[0].ssh_key.as_deref(), Some("/root/.ssh/id_builder"));
+        assert_eq!(builders[0].max_jobs, 8);
+        assert_eq!(builders[0].speed_factor, 2);
+        assert_eq!(builders[0].features, vec!["big-parallel", "kvm"]);
+        assert!(builders[0].mandatory_features.is_empty());
+
+        assert_eq!(builders[1].host(), "builder@x86");
+        assert_eq!(builders[1].ssh_key, None);
+        assert_eq!(builders[1].max_jobs, 4);
+        assert!(builders[1].is_ssh());
+    }
+
+    #[test]
+    fn test_unreachable_systems() {
+        let (load, systems) = parse_probe("0.52 0.58 0.59 1/389 12345\nsystem = aarch64-linux\nextra-platforms = armv7l-linux\n");
+        assert_eq!(load, Some(0.52));
+        assert_eq!(systems, vec!["aarch64-linux", "armv7l-linux"]);
+
+        let builders = parse_builders("a aarch64-linux;b aarch64-linux,x86_64-linux;c riscv64-linux");
+        let mut statuses: Vec<_> = builders
+            .into_iter()
+            .map(|b| BuilderStatus::unreachable(b, "timeout".into()))
+            .collect();
+        statuses[1].reachable = true;
+
+        let local = BTreeSet::from(["x86_64-linux".to_string()]);
+        assert_eq!(unreachable_systems(&statuses, &local), vec!["riscv64-linux"]);
+    }
+}
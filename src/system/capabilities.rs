@@ -0,0 +1,170 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Installed Nix version, the CLI behaviour it supports and its settings.
+
+use crate::utils::command::Echo;
+use std::fmt;
+use std::process::Command;
+use std::sync::OnceLock;
+use tracing::debug;
+
+/// A Nix release version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NixVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl NixVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Parse `nix --version` output, e.g. `nix (Nix) 2.18.1`
+    pub fn parse(output: &str) -> Option<Self> {
+        let version = output.split_whitespace().last()?;
+        let mut parts = version.split(['.', 'p', 'r', '-']).map(|p| p.parse::<u32>().ok());
+        Some(Self {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next().flatten().unwrap_or(0),
+        })
+    }
+}
+
+impl fmt::Display for NixVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// CLI behaviour that differs between Nix 2.x releases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub version: Option<NixVersion>,
+    /// `nix profile list --json` is available
+    pub profile_list_json: bool,
+    /// Profile JSON keys elements by name (manifest v3) rather than an array
+    pub profile_elements_by_name: bool,
+    /// `nix profile remove` accepts element names (older versions take indices or store paths)
+    pub profile_remove_by_name: bool,
+}
+
+impl Capabilities {
+    /// Capabilities of a given version. An unknown version assumes a current Nix.
+    pub fn for_version(version: Option<NixVersion>) -> Self {
+        let at_least = |min: NixVersion| version.map(|v| v >= min).unwrap_or(true);
+        Self {
+            version,
+            profile_list_json: at_least(NixVersion::new(2, 17, 0)),
+            profile_elements_by_name: at_least(NixVersion::new(2, 20, 0)),
+            profile_remove_by_name: at_least(NixVersion::new(2, 20, 0)),
+        }
+    }
+
+    /// Detect the installed Nix once per process
+    pub fn detect() -> &'static Self {
+        static CAPABILITIES: OnceLock<Capabilities> = OnceLock::new();
+        CAPABILITIES.get_or_init(|| {
+            let version = Command::new("nix")
+                .arg("--version")
+                .echo().output()
+                .ok()
+                .and_then(|o| NixVersion::parse(&String::from_utf8_lossy(&o.stdout)));
+            debug!("Detected Nix version: {:?}", version);
+            Self::for_version(version)
+        })
+    }
+}
+
+/// Current value of a Nix setting from `nix show-config`
+pub fn nix_setting(name: &str) -> Option<String> {
+    let output = Command::new("nix").arg("show-config").echo().output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_setting(&String::from_utf8_lossy(&output.stdout), name)
+}
+
+/// Whether builds run in the sandbox (`relaxed` counts as enabled)
+pub fn sandbox_enabled() -> Option<bool> {
+    nix_setting("sandbox").map(|v| v != "false")
+}
+
+/// Parse a Nix system double such as `aarch64-linux` or `x86_64-darwin`
+pub fn parse_system(s: &str) -> std::result::Result<String, String> {
+    let valid = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    match s.split_once('-') {
+        Some((arch, os)) if valid(arch) && os.split('-').all(valid) => Ok(s.to_string()),
+        _ => Err(format!("invalid system '{}' (expected e.g. aarch64-linux)", s)),
+    }
+}
+
+/// Find `name = value` in `nix show-config` output
+fn parse_setting(output: &str, name: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (key, value) = line.split_once(" = ")?;
+        (key.trim() == name).then(|| value.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(NixVersion::parse("nix (Nix) 2.18.1\n"), Some(NixVersion::new(2, 18, 1)));
+        assert_eq!(NixVersion::parse("nix (Nix) 2.24.0pre20240101_abcdef"), Some(NixVersion::new(2, 24, 0)));
+        assert_eq!(NixVersion::parse("nix (Nix) 2.3"), Some(NixVersion::new(2, 3, 0)));
+        assert_eq!(NixVersion::parse(""), None);
+    }
+
+    #[test]
+    fn test_capability_matrix() {
+        let old = Capabilities::for_version(Some(NixVersion::new(2, 18, 1)));
+        assert!(old.profile_list_json);
+        assert!(!old.profile_remove_by_name);
+        assert!(!old.profile_elements_by_name);
+
+        let new = Capabilities::for_version(Some(NixVersion::new(2, 24, 9)));
+        assert!(new.profile_remove_by_name);
+
+        let ancient = Capabilities::for_version(Some(NixVersion::new(2, 3, 16)));
+        assert!(!ancient.profile_list_json);
+
+        assert!(Capabilities::for_version(None).profile_remove_by_name);
+    }
+
+    #[test]
+    fn test_parse_setting() {
+        let config = "sandbox = relaxed\nsandbox-fallback = true\nsubstituters = https://cache.nixos.org/\n";
+        assert_eq!(parse_setting(config, "sandbox").as_deref(), Some("relaxed"));
+        assert_eq!(parse_setting(config, "substituters").as_deref(), Some("https://cache.nixos.org/"));
+        assert_eq!(parse_setting(config, "cores"), None);
+    }
+
+    #[test]
+    fn test_parse_system() {
+        assert_eq!(parse_system("aarch64-linux").as_deref(), Ok("aarch64-linux"));
+        assert_eq!(parse_system("x86_64-darwin").as_deref(), Ok("x86_64-darwin"));
+        assert!(parse_system("aarch64").is_err());
+        assert!(parse_system("aarch64-").is_err());
+        assert!(parse_system("x86 64-linux").is_err());
+    }
+}
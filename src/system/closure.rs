@@ -0,0 +1,202 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Closure diffs and classification of critical package changes.
+
+use crate::core::error::{Result, SystemError};
+use crate::utils::command::Echo;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+use tracing::debug;
+
+/// Kernel package names (`linux` plus common flavours)
+const KERNEL_PACKAGES: &[&str] = &[
+    "linux", "linux-zen", "linux-lqx", "linux-xanmod", "linux-hardened", "linux-rt", "linux-libre",
+];
+
+/// Bootloader package names
+const BOOTLOADER_PACKAGES: &[&str] = &["grub", "systemd-boot", "refind", "limine", "syslinux", "shim"];
+
+/// Display driver package names; `xf86-video-*` is matched by prefix
+const DISPLAY_DRIVER_PACKAGES: &[&str] = &[
+    "nvidia-x11", "nvidia-open", "nvidia-settings", "mesa", "xorg-server", "amdvlk",
+];
+
+/// Category of a change that can leave a system unbootable or without graphics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CriticalKind {
+    Kernel,
+    Bootloader,
+    DisplayDriver,
+}
+
+impl CriticalKind {
+    /// Classify a package name
+    pub fn classify(name: &str) -> Option<Self> {
+        if KERNEL_PACKAGES.contains(&name) {
+            Some(CriticalKind::Kernel)
+        } else if BOOTLOADER_PACKAGES.contains(&name) {
+            Some(CriticalKind::Bootloader)
+        } else if DISPLAY_DRIVER_PACKAGES.contains(&name) || name.starts_with("xf86-video-") {
+            Some(CriticalKind::DisplayDriver)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for CriticalKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CriticalKind::Kernel => write!(f, "kernel"),
+            CriticalKind::Bootloader => write!(f, "bootloader"),
+            CriticalKind::DisplayDriver => write!(f, "display driver"),
+        }
+    }
+}
+
+/// One line of `nix store diff-closures`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClosureChange {
+    pub name: String,
+    /// Versions before (empty when added)
+    pub from: Vec<String>,
+    /// Versions after (empty when removed)
+    pub to: Vec<String>,
+    /// Size change as printed by Nix, e.g. `+1024.0 KiB`
+    pub size_delta: Option<String>,
+    pub critical: Option<CriticalKind>,
+}
+
+impl fmt::Display for ClosureChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let versions = |v: &[String]| if v.is_empty() { "∅".to_string() } else { v.join(", ") };
+        write!(f, "{}: {} → {}", self.name, versions(&self.from), versions(&self.to))?;
+        if let Some(delta) = &self.size_delta {
+            write!(f, ", {}", delta)?;
+        }
+        Ok(())
+    }
+}
+
+/// Diff two closures with `nix store diff-closures`
+pub fn diff_closures(from: &Path, to: &Path) -> Result<Vec<ClosureChange>> {
+    debug!("Diffing closures {:?} -> {:?}", from, to);
+
+    let output = Command::new("nix")
+        .args(["store", "diff-closures"])
+        .arg(from)
+        .arg(to)
+        .echo().output()?;
+
+    if !output.status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix store diff-closures".to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }.into());
+    }
+
+    Ok(parse_diff_closures(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `name: 1.0 → 1.1, +12.3 KiB` lines
+pub fn parse_diff_closures(stdout: &str) -> Vec<ClosureChange> {
+    stdout.lines().filter_map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Option<ClosureChange> {
+    let line = strip_ansi(line);
+    let (name, rest) = line.trim().split_once(": ")?;
+
+    let (versions, size_delta) = match rest.rsplit_once(", ") {
+        Some((v, size)) if size.starts_with('+') || size.starts_with('-') => (v, Some(size.to_string())),
+        _ => (rest, None),
+    };
+
+    let (from, to) = match versions.split_once(" → ") {
+        Some((from, to)) => (parse_versions(from), parse_versions(to)),
+        None => (vec![], vec![]),
+    };
+
+    Some(ClosureChange {
+        name: name.to_string(),
+        critical: CriticalKind::classify(name),
+        from,
+        to,
+        size_delta,
+    })
+}
+
+fn parse_versions(s: &str) -> Vec<String> {
+    s.split(", ")
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty() && *v != "∅" && *v != "ε")
+        .map(|v| v.to_string())
+        .collect()
+}
+
+/// Remove terminal color escapes Nix adds to version numbers
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diff_closures() {
+        let changes = parse_diff_closures(
+            "firefox: 121.0 → 122.0, +2048.0 KiB\n\
+             linux: 6.6.10 → 6.6.12, +512.3 KiB\n\
+             nvidia-x11: 545.29.06 → 550.40.07\n\
+             hello: ∅ → 2.12.1, +120.0 KiB\n\
+             \u{1b}[1mgrub\u{1b}[0m: 2.06 → 2.12\n",
+        );
+
+        assert_eq!(changes.len(), 5);
+        assert_eq!(changes[0].critical, None);
+        assert_eq!(changes[0].size_delta.as_deref(), Some("+2048.0 KiB"));
+        assert_eq!(changes[1].critical, Some(CriticalKind::Kernel));
+        assert_eq!(changes[1].to, vec!["6.6.12"]);
+        assert_eq!(changes[2].critical, Some(CriticalKind::DisplayDriver));
+        assert!(changes[3].from.is_empty());
+        assert_eq!(changes[4].critical, Some(CriticalKind::Bootloader));
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(CriticalKind::classify("linux-zen"), Some(CriticalKind::Kernel));
+        assert_eq!(CriticalKind::classify("linux-firmware"), None);
+        assert_eq!(CriticalKind::classify("xf86-video-amdgpu"), Some(CriticalKind::DisplayDriver));
+    }
+}
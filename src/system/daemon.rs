@@ -0,0 +1,222 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! nix-daemon status, restart and logs via systemctl/journalctl.
+
+use crate::core::error::{Result, SystemError};
+use crate::utils::command::{is_nix_program, nix_config_env, Echo};
+use console::style;
+use std::path::Path;
+use std::process::Command;
+use tracing::{debug, info};
+
+/// systemd unit name of the daemon
+const DAEMON_UNIT: &str = "nix-daemon";
+
+/// Socket the daemon listens on
+const DAEMON_SOCKET: &str = "/nix/var/nix/daemon-socket/socket";
+
+/// Current state of nix-daemon
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DaemonStatus {
+    /// systemd ActiveState (`active`, `failed`, `inactive`, ...)
+    pub active_state: String,
+    /// systemd SubState (`running`, `dead`, ...)
+    pub sub_state: String,
+    /// Main PID, if running
+    pub main_pid: Option<u32>,
+    /// When the unit last entered the active state
+    pub since: Option<String>,
+    /// Whether the daemon socket exists
+    pub socket_present: bool,
+}
+
+impl DaemonStatus {
+    pub fn is_running(&self) -> bool {
+        self.active_state == "active"
+    }
+
+    pub fn print(&self) {
+        if self.is_running() {
+            println!("{} nix-daemon is {} ({})", style("✓").green(), self.active_state, self.sub_state);
+        } else {
+            println!("{} nix-daemon is {} ({})", style("✗").red(), self.active_state, self.sub_state);
+        }
+
+        if let Some(pid) = self.main_pid {
+            println!("  PID:    {}", pid);
+        }
+        if let Some(since) = &self.since {
+            println!("  Since:  {}", since);
+        }
+        println!(
+            "  Socket: {}",
+            if self.socket_present { DAEMON_SOCKET } else { "missing" }
+        );
+    }
+}
+
+/// Helpers for inspecting and controlling nix-daemon
+pub struct NixDaemon;
+
+impl NixDaemon {
+    /// Query the daemon unit with `systemctl show`
+    pub fn status() -> Result<DaemonStatus> {
+        debug!("Querying {} status", DAEMON_UNIT);
+
+        let output = Command::new("systemctl")
+            .args(["show", DAEMON_UNIT, "--property=ActiveState,SubState,MainPID,ActiveEnterTimestamp"])
+            .echo().output()
+            .map_err(|e| SystemError::HealthCheckFailed(format!("systemctl unavailable: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(SystemError::NixCommandFailed {
+                command: format!("systemctl show {}", DAEMON_UNIT),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            }.into());
+        }
+
+        let mut status = parse_show(&String::from_utf8_lossy(&output.stdout));
+        status.socket_present = Path::new(DAEMON_SOCKET).exists();
+        Ok(status)
+    }
+
+    /// Restart the daemon, escalating with sudo when not root
+    pub fn restart() -> Result<()> {
+        info!("Restarting {}", DAEMON_UNIT);
+
+        let mut cmd = privileged("systemctl")?;
+        let output = cmd.args(["restart", DAEMON_UNIT]).echo().output()?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if is_permission_error(&stderr) {
+            return Err(SystemError::PermissionDenied(format!("cannot restart {}", DAEMON_UNIT)).into());
+        }
+
+        Err(SystemError::NixCommandFailed {
+            command: format!("systemctl restart {}", DAEMON_UNIT),
+            stderr,
+        }.into())
+    }
+
+    /// Show recent daemon logs, optionally following them
+    pub fn logs(lines: usize, follow: bool) -> Result<()> {
+        let mut cmd = Command::new("journalctl");
+        cmd.args(["-u", DAEMON_UNIT, "-n", &lines.to_string(), "--no-pager"]);
+        if follow {
+            cmd.arg("-f");
+        }
+
+        let status = cmd.echo().status()?;
+        if !status.success() {
+            return Err(SystemError::PermissionDenied(
+                format!("cannot read {} logs (try adding your user to the systemd-journal group)", DAEMON_UNIT)
+            ).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether the current process runs as root
+pub(crate) fn is_root() -> bool {
+    Command::new("id")
+        .arg("-u")
+        .echo().output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim() == "0")
+        .unwrap_or(false)
+}
+
+/// Command running `program` as root, going through sudo when needed
+pub(crate) fn privileged(program: &str) -> Result<Command> {
+    if is_root() {
+        return Ok(Command::new(program));
+    }
+
+    if Command::new("sudo").arg("--version").output().is_err() {
+        return Err(SystemError::PermissionDenied(
+            format!("{} requires root and sudo is not available", program)
+        ).into());
+    }
+
+    let mut sudo = Command::new("sudo");
+    // sudo resets the environment, so `--option` settings go through env(1)
+    match nix_config_env().filter(|_| is_nix_program(program.as_ref())) {
+        Some(config) => sudo.arg("env").arg(format!("NIX_CONFIG={}", config)).arg(program),
+        None => sudo.arg(program),
+    };
+    Ok(sudo)
+}
+
+/// Whether command stderr indicates missing privileges
+pub(crate) fn is_permission_error(stderr: &str) -> bool {
+    let lower = stderr.to_lowercase();
+    lower.contains("access denied")
+        || lower.contains("interactive authentication required")
+        || lower.contains("not permitted")
+        || lower.contains("password is required")
+}
+
+/// Parse `systemctl show` key=value output
+fn parse_show(stdout: &str) -> DaemonStatus {
+    let mut status = DaemonStatus::default();
+
+    for line in stdout.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "ActiveState" => status.active_state = value.to_string(),
+            "SubState" => status.sub_state = value.to_string(),
+            "MainPID" => status.main_pid = value.parse().ok().filter(|pid| *pid != 0),
+            "ActiveEnterTimestamp" if !value.is_empty() => status.since = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_show() {
+        let status = parse_show(
+            "ActiveState=active\nSubState=running\nMainPID=812\nActiveEnterTimestamp=Mon 2025-01-06 09:12:44 UTC\n",
+        );
+        assert!(status.is_running());
+        assert_eq!(status.main_pid, Some(812));
+        assert_eq!(status.since.as_deref(), Some("Mon 2025-01-06 09:12:44 UTC"));
+
+        let dead = parse_show("ActiveState=failed\nSubState=failed\nMainPID=0\nActiveEnterTimestamp=\n");
+        assert!(!dead.is_running());
+        assert_eq!(dead.main_pid, None);
+        assert_eq!(dead.since, None);
+    }
+
+    #[test]
+    fn test_permission_error_detection() {
+        assert!(is_permission_error("Failed to restart nix-daemon.service: Access denied"));
+        assert!(is_permission_error("sudo: a password is required"));
+        assert!(!is_permission_error("Unit nix-daemon.service not found."));
+    }
+}
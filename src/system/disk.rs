@@ -0,0 +1,219 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Free space guard for build-heavy operations.
+
+use crate::core::error::{Result, SystemError};
+use crate::system::garbage_collector::{format_bytes, GarbageCollector};
+use crate::utils::command::Echo;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, info};
+
+/// Estimated work for realising a set of installables
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildEstimate {
+    /// Derivations that will be built locally
+    pub builds: usize,
+    /// Store paths of those derivations
+    pub derivations: Vec<String>,
+    /// Store paths that will be substituted
+    pub fetches: usize,
+    /// Download size of substituted paths
+    pub download_bytes: u64,
+    /// Unpacked size of substituted paths
+    pub unpacked_bytes: u64,
+}
+
+/// Checks free space in the Nix store and build directory
+pub struct DiskGuard {
+    /// Space reserved per local build, in bytes
+    build_allowance: u64,
+}
+
+impl DiskGuard {
+    /// Create a guard reserving `build_space_mb` per local build
+    pub fn new(build_space_mb: u64) -> Self {
+        Self {
+            build_allowance: build_space_mb * 1024 * 1024,
+        }
+    }
+
+    /// Estimate builds and downloads with `nix build --dry-run`
+    pub fn estimate(installables: &[String]) -> Result<BuildEstimate> {
+        debug!("Estimating build requirements for {:?}", installables);
+
+        let output = Command::new("nix")
+            .args(["build", "--dry-run", "--no-link"])
+            .args(installables)
+            .echo().output()?;
+
+        if !output.status.success() {
+            return Err(SystemError::NixCommandFailed {
+                command: "nix build --dry-run".to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            }.into());
+        }
+
+        Ok(parse_dry_run(&String::from_utf8_lossy(&output.stderr)))
+    }
+
+    /// Refuse when the store or build directory cannot fit the estimate
+    pub fn check(&self, estimate: &BuildEstimate) -> Result<()> {
+        let store_required = estimate.unpacked_bytes + estimate.builds as u64 * self.build_allowance;
+        self.require(Path::new("/nix/store"), store_required)?;
+
+        if estimate.builds > 0 {
+            self.require(&build_dir(), self.build_allowance)?;
+        }
+
+        info!(
+            "Disk space check passed ({} builds, {} to unpack)",
+            estimate.builds,
+            format_bytes(estimate.unpacked_bytes)
+        );
+        Ok(())
+    }
+
+    fn require(&self, path: &Path, required: u64) -> Result<()> {
+        let Some(available) = free_bytes(path) else {
+            debug!("Could not determine free space on {:?}, skipping check", path);
+            return Ok(());
+        };
+
+        if available < required {
+            return Err(SystemError::InsufficientDiskSpace {
+                path: path.display().to_string(),
+                required: format_bytes(required),
+                available: format_bytes(available),
+            }.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Directory Nix builds in (`$TMPDIR`, falling back to `/tmp`)
+pub fn build_dir() -> PathBuf {
+    std::env::var_os("TMPDIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+}
+
+/// Available bytes on the filesystem containing `path`
+pub fn free_bytes(path: &Path) -> Option<u64> {
+    let output = Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .echo().output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().nth(1)?;
+    let avail_kb: u64 = line.split_whitespace().nth(3)?.parse().ok()?;
+    Some(avail_kb * 1024)
+}
+
+/// Parse the summary `nix build --dry-run` prints to stderr
+fn parse_dry_run(stderr: &str) -> BuildEstimate {
+    let mut estimate = BuildEstimate::default();
+    let mut in_builds = false;
+
+    for line in stderr.lines() {
+        let line = line.trim();
+        if in_builds && line.starts_with("/nix/store/") && line.ends_with(".drv") {
+            estimate.derivations.push(line.to_string());
+            continue;
+        }
+        in_builds = false;
+
+        if line.starts_with("this derivation will be built") {
+            estimate.builds = 1;
+            in_builds = true;
+        } else if line.starts_with("these ") && line.contains("derivations will be built") {
+            estimate.builds = leading_count(line);
+            in_builds = true;
+        } else if line.starts_with("this path will be fetched") {
+            estimate.fetches = 1;
+            estimate.download_bytes = summary_size(line, " download");
+            estimate.unpacked_bytes = summary_size(line, " unpacked");
+        } else if line.starts_with("these ") && line.contains("will be fetched") {
+            estimate.fetches = leading_count(line);
+            estimate.download_bytes = summary_size(line, " download");
+            estimate.unpacked_bytes = summary_size(line, " unpacked");
+        }
+    }
+
+    estimate
+}
+
+/// Number after "these" in a dry-run summary line
+fn leading_count(line: &str) -> usize {
+    line.split_whitespace()
+        .nth(1)
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Extract a size from "(12.3 MiB download, 45.6 MiB unpacked)" by its label
+fn summary_size(line: &str, label: &str) -> u64 {
+    let Some(end) = line.find(label) else {
+        return 0;
+    };
+    let start = line[..end].rfind([',', '(']).map(|i| i + 1).unwrap_or(0);
+    GarbageCollector::parse_size_string(&line[start..end].replace(' ', ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dry_run() {
+        let stderr = "\
+these 2 derivations will be built:
+  /nix/store/aaa-foo.drv
+  /nix/store/bbb-bar.drv
+these 12 paths will be fetched (30.50 MiB download, 120.00 MiB unpacked):
+  /nix/store/ccc-baz
+";
+        let estimate = parse_dry_run(stderr);
+        assert_eq!(estimate.builds, 2);
+        assert_eq!(estimate.derivations, vec!["/nix/store/aaa-foo.drv", "/nix/store/bbb-bar.drv"]);
+        assert_eq!(estimate.fetches, 12);
+        assert_eq!(estimate.unpacked_bytes, 120 * 1024 * 1024);
+        assert_eq!(estimate.download_bytes, (30.5 * 1024.0 * 1024.0) as u64);
+    }
+
+    #[test]
+    fn test_parse_dry_run_single() {
+        let stderr = "this path will be fetched (0.05 MiB download, 0.20 MiB unpacked):\n  /nix/store/x-hello\n";
+        let estimate = parse_dry_run(stderr);
+        assert_eq!(estimate.builds, 0);
+        assert_eq!(estimate.fetches, 1);
+        assert!(estimate.unpacked_bytes > 0);
+    }
+
+    #[test]
+    fn test_check_passes_for_empty_estimate() {
+        let guard = DiskGuard::new(1024);
+        assert!(guard.check(&BuildEstimate::default()).is_ok());
+    }
+}
@@ -0,0 +1,143 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Packages installed both in the user profile and the system environment
+//! (`nixboost diff profile system`).
+
+use crate::core::error::{Result, SystemError};
+use crate::package::upgrade::parse_store_name;
+use crate::package::version::compare_versions;
+use crate::utils::command::Echo;
+use console::style;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::process::Command;
+
+/// The NixOS system environment (`environment.systemPackages`)
+pub const SYSTEM_ENVIRONMENT: &str = "/run/current-system/sw";
+
+/// Which copy of a duplicated package to drop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DropFrom {
+    /// Remove it from the user profile
+    Profile,
+    /// Remove it from `environment.systemPackages`
+    System,
+}
+
+/// A package present in both environments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Duplicate {
+    pub name: String,
+    pub profile_version: String,
+    pub system_version: String,
+    pub drop: DropFrom,
+}
+
+impl Duplicate {
+    pub fn suggestion(&self) -> String {
+        match (self.drop, compare_versions(&self.profile_version, &self.system_version)) {
+            (DropFrom::System, _) => format!(
+                "profile copy is newer; drop it from environment.systemPackages or keep using the profile (nixboost -R {} otherwise)",
+                self.name
+            ),
+            (DropFrom::Profile, Ordering::Equal) => format!("identical version; nixboost -R {}", self.name),
+            (DropFrom::Profile, _) => format!("system copy is newer; nixboost -R {}", self.name),
+        }
+    }
+}
+
+/// Store paths directly referenced by the system environment
+pub fn system_packages() -> Result<Vec<String>> {
+    let output = Command::new("nix-store")
+        .args(["--query", "--references", SYSTEM_ENVIRONMENT])
+        .echo().output()?;
+
+    if !output.status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix-store --query --references".to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }.into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).lines().filter(|l| !l.is_empty()).map(String::from).collect())
+}
+
+/// Packages whose name appears in both lists of store paths.
+///
+/// The profile copy is suggested for removal unless it is newer, since the
+/// system copy is shared by every user.
+pub fn find_duplicates(profile: &[String], system: &[String]) -> Vec<Duplicate> {
+    let system: BTreeMap<String, String> = system.iter().map(|p| parse_store_name(p)).collect();
+
+    let mut duplicates: Vec<Duplicate> = profile
+        .iter()
+        .map(|p| parse_store_name(p))
+        .filter_map(|(name, profile_version)| {
+            let system_version = system.get(&name)?.clone();
+            let drop = match compare_versions(&profile_version, &system_version) {
+                Ordering::Greater => DropFrom::System,
+                _ => DropFrom::Profile,
+            };
+            Some(Duplicate { name, profile_version, system_version, drop })
+        })
+        .collect();
+
+    duplicates.sort_by(|a, b| a.name.cmp(&b.name));
+    duplicates.dedup_by(|a, b| a.name == b.name);
+    duplicates
+}
+
+/// Print duplicates with a suggestion for each
+pub fn print_duplicates(duplicates: &[Duplicate]) {
+    if duplicates.is_empty() {
+        println!("{}", style("No package is installed both in the profile and the system").green());
+        return;
+    }
+
+    println!("{} {} package(s) installed both ways:", style("::").bold().yellow(), duplicates.len());
+    for dup in duplicates {
+        let versions = if dup.profile_version == dup.system_version {
+            dup.profile_version.clone()
+        } else {
+            format!("profile {} / system {}", dup.profile_version, dup.system_version)
+        };
+        println!("  {} {}", style(&dup.name).bold(), style(versions).dim());
+        println!("      {} {}", style("→").cyan(), dup.suggestion());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(name: &str) -> String {
+        format!("/nix/store/0123456789abcdfghijklmnpqrsvwxyz-{}", name)
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let profile = vec![path("git-2.44.0"), path("ripgrep-14.1.0"), path("htop-3.3.0"), path("firefox-122.0")];
+        let system = vec![path("git-2.44.0"), path("htop-3.2.2"), path("firefox-123.0"), path("bash-5.2p26")];
+
+        let duplicates = find_duplicates(&profile, &system);
+        let summary: Vec<(&str, DropFrom)> = duplicates.iter().map(|d| (d.name.as_str(), d.drop)).collect();
+        assert_eq!(summary, vec![("firefox", DropFrom::Profile), ("git", DropFrom::Profile), ("htop", DropFrom::System)]);
+        assert!(duplicates[1].suggestion().starts_with("identical version"));
+    }
+}
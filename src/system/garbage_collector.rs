@@ -16,23 +16,103 @@
 
 //! Garbage collection for NixBoost.
 
+use crate::cache::CacheManager;
+use crate::cache::invalidation::{CacheKey, TTL};
+use crate::core::config::{Config, GenerationsConfig};
 use crate::core::error::{Result, SystemError};
-use crate::core::types::GCPreview;
+use crate::core::history;
+use crate::core::types::{GCPreview, Generation, OperationType};
+use crate::system::generations::{expired, GenerationManager};
+use crate::utils::command::Echo;
+use crate::utils::time::unix_now;
 use console::style;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::process::Command;
+use std::time::{Duration, Instant, SystemTime};
 use tracing::{debug, info, warn};
 
+/// Number of largest dead paths listed in a preview
+const PREVIEW_LARGEST: usize = 10;
+
+/// File in the cache directory remembering the last preview's total
+const PREVIEW_SNAPSHOT: &str = "gc-preview.json";
+
+/// Total of the previous `--clean --dry-run`, for the delta shown next time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PreviewSnapshot {
+    timestamp: u64,
+    size_bytes: u64,
+}
+
+impl PreviewSnapshot {
+    fn of(preview: &GCPreview) -> Self {
+        Self { timestamp: unix_now(), size_bytes: preview.size_bytes }
+    }
+
+    fn load(path: &std::path::Path) -> Option<Self> {
+        serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+    }
+
+    fn save(&self, path: &std::path::Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(self).map_err(|e| crate::core::error::NixBoostError::Serialization(e.to_string()))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// What to delete besides unreferenced store paths
+#[derive(Debug, Clone, Default)]
+pub struct GcOptions {
+    /// Never delete the newest N generations
+    pub keep_generations: usize,
+    /// Delete generations older than this
+    pub older_than: Option<Duration>,
+    /// Delete the oldest generations until about this many bytes can be freed
+    pub free_at_least: Option<u64>,
+    /// Delete the generations this policy does not retain
+    pub retention: Option<GenerationsConfig>,
+}
+
+/// Generations to delete before collecting, and the space expected back
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GcPlan {
+    /// Oldest first
+    pub generations: Vec<u64>,
+    /// Size of the paths that are already unreferenced
+    pub dead_bytes: u64,
+    /// Estimated bytes freed once the generations are deleted
+    pub estimated_bytes: u64,
+    /// Stop collecting after this many bytes (`--max-freed`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_freed: Option<u64>,
+    /// Store paths expected to be deleted with their NAR sizes, largest first
+    #[serde(default)]
+    pub paths: Vec<(String, u64)>,
+}
+
 /// Smart garbage collector
 pub struct GarbageCollector;
 
 impl GarbageCollector {
     /// Run garbage collection
     pub fn run() -> Result<GCResult> {
+        let start = Instant::now();
+        let result = Self::collect();
+        history::record(OperationType::GarbageCollect, &[], start, &result);
+        result
+    }
+
+    fn collect() -> Result<GCResult> {
         info!("Running garbage collection");
 
         let output = Command::new("nix-collect-garbage")
             .arg("-d")
-            .output()?;
+            .echo().output()?;
 
         if !output.status.success() {
             return Err(SystemError::GarbageCollectionFailed(
@@ -50,29 +130,82 @@ impl GarbageCollector {
         })
     }
 
-    /// Run garbage collection with options
-    pub fn run_with_options(keep_generations: usize, delete_older_than: Option<&str>) -> Result<GCResult> {
-        info!("Running garbage collection (keep {} generations)", keep_generations);
+    /// Work out which generations `options` deletes and how much that frees.
+    ///
+    /// Generations are picked by age first; for `free_at_least`, older ones
+    /// are added until the estimate reaches the target or only the kept
+    /// generations are left.
+    pub fn plan(options: &GcOptions) -> Result<GcPlan> {
+        info!("Planning garbage collection");
+
+        let generations = GenerationManager::default().list(usize::MAX)?;
+        let mut selected = select_generations(&generations, options, SystemTime::now());
+        let dead = path_sizes(&dead_paths()?)?;
+        let dead_bytes = dead.iter().map(|(_, size)| size).sum();
+
+        let mut candidates: Vec<u64> = deletable(&generations, options.keep_generations)
+            .filter(|n| !selected.contains(n))
+            .collect();
+        candidates.sort_unstable();
+        let mut candidates = candidates.into_iter();
 
-        let mut args = vec!["-d"];
-        
-        // Note: nix-collect-garbage doesn't directly support keep_generations
-        // We need to use nix-env to delete old generations first
-        if keep_generations > 0 {
-            Self::delete_old_generations(keep_generations)?;
+        let mut exclusive = if selected.is_empty() && options.free_at_least.is_none() {
+            Reclaimable::default()
+        } else {
+            Reclaimable::query(&generations)?
+        };
+        for &number in &selected {
+            exclusive.select(number)?;
         }
 
-        // Add older-than option if specified
-        let older_than_arg: String;
-        if let Some(older_than) = delete_older_than {
-            older_than_arg = format!("--delete-older-than {}", older_than);
-            args.push("--delete-older-than");
-            args.push(older_than);
+        loop {
+            let estimated_bytes = dead_bytes + exclusive.bytes();
+            let reached = options.free_at_least.map(|target| estimated_bytes >= target).unwrap_or(true);
+            let next = if reached { None } else { candidates.next() };
+            match next {
+                Some(number) => {
+                    debug!("Adding generation {} ({} estimated so far)", number, format_bytes(estimated_bytes));
+                    selected.push(number);
+                    exclusive.select(number)?;
+                }
+                None => {
+                    selected.sort_unstable();
+                    let mut paths: Vec<(String, u64)> = dead.into_iter().chain(exclusive.paths).collect();
+                    paths.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+                    return Ok(GcPlan {
+                        generations: selected,
+                        dead_bytes,
+                        estimated_bytes,
+                        max_freed: options.free_at_least,
+                        paths,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Delete the planned generations, then collect garbage
+    pub fn run_plan(plan: &GcPlan) -> Result<GCResult> {
+        let start = Instant::now();
+        let result = Self::collect_plan(plan);
+        history::record(OperationType::GarbageCollect, &[], start, &result);
+        result
+    }
+
+    fn collect_plan(plan: &GcPlan) -> Result<GCResult> {
+        info!("Running garbage collection ({} generations to delete)", plan.generations.len());
+
+        GenerationManager::default().delete(&plan.generations)?;
+
+        let max_freed = plan.max_freed.map(|bytes| bytes.to_string());
+        let mut args = Vec::new();
+        if let Some(ref bytes) = max_freed {
+            args.extend(["--max-freed", bytes.as_str()]);
         }
 
         let output = Command::new("nix-collect-garbage")
             .args(&args)
-            .output()?;
+            .echo().output()?;
 
         if !output.status.success() {
             return Err(SystemError::GarbageCollectionFailed(
@@ -90,67 +223,85 @@ impl GarbageCollector {
         })
     }
 
-    /// Preview what would be garbage collected
-    pub fn preview() -> Result<GCPreview> {
+    /// Preview what would be garbage collected.
+    ///
+    /// The dead paths and their sizes are cached under the store's
+    /// modification time, so repeated previews skip the store scan until
+    /// paths are added or deleted (or nixboost changes the profile).
+    pub fn preview(cache: Option<&CacheManager>) -> Result<GCPreview> {
         info!("Previewing garbage collection");
 
-        let output = Command::new("nix-store")
-            .args(["--gc", "--print-dead"])
-            .output()?;
-
-        if !output.status.success() {
-            return Err(SystemError::GarbageCollectionFailed(
-                String::from_utf8_lossy(&output.stderr).to_string()
-            ).into());
-        }
+        let key = store_marker().map(CacheKey::gc_dead_paths);
+        let cached = cache.zip(key.as_ref()).and_then(|(c, key)| c.get::<Vec<(String, u64)>>(key));
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let paths: Vec<String> = stdout
-            .lines()
-            .filter(|l| !l.is_empty())
-            .map(|l| l.to_string())
-            .collect();
+        let mut sizes = match cached {
+            Some(sizes) => {
+                debug!("GC preview cache hit");
+                sizes
+            }
+            None => {
+                // NAR sizes count whole directory trees, unlike fs::metadata
+                let sizes = path_sizes(&dead_paths()?)?;
+                if let (Some(cache), Some(key)) = (cache, key.as_ref()) {
+                    // Only one marker is ever current
+                    let _ = cache.clear_namespace("gc");
+                    if let Err(e) = cache.set(key, &sizes, TTL::GC_PREVIEW) {
+                        debug!("Failed to cache GC preview: {}", e);
+                    }
+                }
+                sizes
+            }
+        };
 
-        let size_bytes = Self::calculate_size(&paths);
+        let paths: Vec<String> = sizes.iter().map(|(path, _)| path.clone()).collect();
+        sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        let size_bytes = sizes.iter().map(|(_, size)| size).sum();
+        sizes.truncate(PREVIEW_LARGEST);
 
-        Ok(GCPreview {
+        let mut preview = GCPreview {
             paths,
             size_bytes,
-            affected_generations: vec![],
-        })
-    }
-
-    /// Delete old generations (keeping the last N)
-    fn delete_old_generations(keep: usize) -> Result<()> {
-        debug!("Deleting old generations, keeping {}", keep);
-
-        // Get list of generations
-        let output = Command::new("nix-env")
-            .args(["--list-generations"])
-            .output()?;
-
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let generations: Vec<u64> = stdout
-                .lines()
-                .filter_map(|l| l.split_whitespace().next())
-                .filter_map(|s| s.parse().ok())
-                .collect();
-
-            if generations.len() > keep {
-                let to_delete = generations.len() - keep;
-                let delete_gens: Vec<_> = generations.iter().take(to_delete).collect();
-
-                for gen in delete_gens {
-                    debug!("Deleting generation {}", gen);
-                    let _ = Command::new("nix-env")
-                        .args(["--delete-generations", &gen.to_string()])
-                        .output();
-                }
-            }
+            largest: sizes,
+            ..Default::default()
+        };
+
+        let snapshot_path = Config::cache_dir().join(PREVIEW_SNAPSHOT);
+        if let Some(previous) = PreviewSnapshot::load(&snapshot_path) {
+            preview.delta_bytes = Some(size_bytes as i64 - previous.size_bytes as i64);
+            preview.previous_at = Some(previous.timestamp);
+        }
+        if let Err(e) = PreviewSnapshot::of(&preview).save(&snapshot_path) {
+            debug!("Failed to save GC preview snapshot: {}", e);
         }
 
-        Ok(())
+        Ok(preview)
+    }
+
+    /// Space reclaimed by removing `targets` from the profile and collecting
+    /// garbage: the part of their closure that no other GC root, and none of
+    /// `kept` (the profile's remaining store paths), references.
+    ///
+    /// Roots held by generations of the user profile are ignored, since those
+    /// generations still contain the package until they are deleted.
+    pub fn impact(package: &str, targets: &[String], kept: &[String]) -> Result<GCImpact> {
+        info!("Computing GC impact of {}", package);
+
+        let closure = query_requisites(targets)?;
+
+        let mut roots = non_profile_roots()?;
+        roots.extend(kept.iter().cloned());
+
+        let live: HashSet<String> = query_requisites(&roots)?.into_iter().collect();
+        let exclusive: Vec<String> = closure.iter().filter(|p| !live.contains(*p)).cloned().collect();
+        let mut sizes = path_sizes(&exclusive)?;
+        sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+
+        Ok(GCImpact {
+            package: package.to_string(),
+            closure_paths: closure.len(),
+            reclaimable_bytes: sizes.iter().map(|(_, size)| size).sum(),
+            reclaimable: sizes,
+        })
     }
 
     /// Parse freed space from nix-collect-garbage output
@@ -176,7 +327,7 @@ impl GarbageCollector {
     }
 
     /// Parse size string like "1.5 GiB" to bytes
-    fn parse_size_string(s: &str) -> u64 {
+    pub(crate) fn parse_size_string(s: &str) -> u64 {
         let s = s.trim();
         
         if let Some(mib) = s.strip_suffix("MiB") {
@@ -198,17 +349,6 @@ impl GarbageCollector {
         s.parse().unwrap_or(0)
     }
 
-    /// Calculate total size of paths
-    fn calculate_size(paths: &[String]) -> u64 {
-        let mut total: u64 = 0;
-        for path in paths {
-            if let Ok(meta) = std::fs::metadata(path) {
-                total += meta.len();
-            }
-        }
-        total
-    }
-
     /// Print GC result
     pub fn print_result(result: &GCResult) {
         if result.success {
@@ -220,8 +360,273 @@ impl GarbageCollector {
     }
 }
 
+/// Generations that may be deleted: not current and not among the newest
+/// `keep`. `generations` is newest first.
+fn deletable(generations: &[Generation], keep: usize) -> impl Iterator<Item = u64> + '_ {
+    generations.iter().skip(keep).filter(|g| !g.is_current).map(|g| g.number)
+}
+
+/// Generations `options` deletes by age or retention policy, oldest first
+pub(crate) fn select_generations(generations: &[Generation], options: &GcOptions, now: SystemTime) -> Vec<u64> {
+    let by_policy = options.retention.as_ref().map(|policy| expired(generations, policy, now));
+    let cutoff = options.older_than.map(|age| now.checked_sub(age).unwrap_or(SystemTime::UNIX_EPOCH));
+    if by_policy.is_none() && cutoff.is_none() {
+        return Vec::new();
+    }
+
+    let mut selected: Vec<u64> = generations
+        .iter()
+        .skip(options.keep_generations)
+        .filter(|g| !g.is_current)
+        .filter(|g| cutoff.is_none_or(|cutoff| g.created_at < cutoff))
+        .filter(|g| by_policy.as_ref().is_none_or(|expired| expired.contains(&g.number)))
+        .map(|g| g.number)
+        .collect();
+    selected.sort_unstable();
+    selected
+}
+
+/// Store paths no GC root references (`nix-store --gc --print-dead`)
+fn dead_paths() -> Result<Vec<String>> {
+    let output = Command::new("nix-store").args(["--gc", "--print-dead"]).echo().output()?;
+    if !output.status.success() {
+        return Err(SystemError::GarbageCollectionFailed(
+            String::from_utf8_lossy(&output.stderr).to_string()
+        ).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).lines().filter(|l| !l.is_empty()).map(String::from).collect())
+}
+
+/// Modification time of the store directory in nanoseconds, which changes
+/// whenever a path is added or deleted
+fn store_marker() -> Option<u64> {
+    let modified = std::fs::metadata("/nix/store").ok()?.modified().ok()?;
+    u64::try_from(modified.duration_since(SystemTime::UNIX_EPOCH).ok()?.as_nanos()).ok()
+}
+
+/// GC roots other than the user profile's generation links
+fn non_profile_roots() -> Result<Vec<String>> {
+    let profile_links = dirs::home_dir()
+        .and_then(|home| std::fs::read_link(home.join(".nix-profile")).ok())
+        .map(|p| format!("{}-", p.display()));
+    let output = Command::new("nix-store").args(["--gc", "--print-roots"]).echo().output()?;
+    Ok(parse_roots(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .filter(|(link, _)| profile_links.as_ref().map(|p| !link.starts_with(p.as_str())).unwrap_or(true))
+        .map(|(_, target)| target)
+        .collect())
+}
+
+/// Store paths, with their sizes, freed by deleting a growing set of
+/// generations. Each generation's closure is queried once; a path is freed
+/// when every generation holding it is selected and no other root does.
+#[derive(Debug, Default)]
+struct Reclaimable {
+    /// Closure of each generation not yet selected
+    closures: HashMap<u64, Vec<String>>,
+    /// Number of unselected generations whose closure contains each path
+    holders: HashMap<String, usize>,
+    /// Closure of the GC roots other than the profile's generations
+    live: HashSet<String>,
+    /// Paths freed by the generations selected so far
+    paths: Vec<(String, u64)>,
+}
+
+impl Reclaimable {
+    fn query(generations: &[Generation]) -> Result<Self> {
+        let mut reclaimable = Self {
+            live: query_requisites(&non_profile_roots()?)?.into_iter().collect(),
+            ..Default::default()
+        };
+        for generation in generations {
+            let closure = query_requisites(std::slice::from_ref(&generation.path))?;
+            for path in &closure {
+                *reclaimable.holders.entry(path.clone()).or_default() += 1;
+            }
+            reclaimable.closures.insert(generation.number, closure);
+        }
+        Ok(reclaimable)
+    }
+
+    /// Add `generation` to the deleted set
+    fn select(&mut self, generation: u64) -> Result<()> {
+        let Some(closure) = self.closures.remove(&generation) else {
+            return Ok(());
+        };
+
+        let mut freed = Vec::new();
+        for path in closure {
+            let Some(holders) = self.holders.get_mut(&path) else {
+                continue;
+            };
+            *holders -= 1;
+            if *holders == 0 && !self.live.contains(&path) {
+                freed.push(path);
+            }
+        }
+        self.paths.extend(path_sizes(&freed)?);
+        Ok(())
+    }
+
+    fn bytes(&self) -> u64 {
+        self.paths.iter().map(|(_, size)| size).sum()
+    }
+}
+
+/// Parse an age such as `30d` or `2w` (`--older-than`)
+pub fn parse_age(s: &str) -> std::result::Result<Duration, String> {
+    crate::utils::time::parse_duration(s).ok_or_else(|| format!("invalid age '{}' (use e.g. 12h, 30d or 2w)", s))
+}
+
+/// Parse a size such as `5G`, `500M`, `1.5GiB` or a byte count (`--free-at-least`)
+pub fn parse_size(s: &str) -> std::result::Result<u64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let value: f64 = num.parse().map_err(|_| format!("invalid size '{}'", s))?;
+    let unit = unit.trim().trim_end_matches("iB").trim_end_matches('B');
+    let scale: u64 = match unit.to_ascii_uppercase().as_str() {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(format!("invalid size unit in '{}' (use K, M, G or T)", s)),
+    };
+    Ok((value * scale as f64) as u64)
+}
+
+/// Closure of the given paths (`nix-store --query --requisites`)
+fn query_requisites(paths: &[String]) -> Result<Vec<String>> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let output = Command::new("nix-store")
+        .args(["--query", "--requisites"])
+        .args(paths)
+        .echo().output()?;
+
+    if !output.status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix-store --query --requisites".to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }.into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).lines().filter(|l| !l.is_empty()).map(String::from).collect())
+}
+
+/// `(link, store path)` pairs from `nix-store --gc --print-roots`
+fn parse_roots(stdout: &str) -> Vec<(String, String)> {
+    stdout
+        .lines()
+        .filter_map(|line| line.split_once(" -> "))
+        .filter(|(_, target)| target.starts_with("/nix/store/"))
+        .map(|(link, target)| (link.to_string(), target.to_string()))
+        .collect()
+}
+
+/// NAR size of each path (`nix path-info --json`)
+fn path_sizes(paths: &[String]) -> Result<Vec<(String, u64)>> {
+    let mut sizes = Vec::with_capacity(paths.len());
+
+    // Dead paths can number in the tens of thousands, too many for one command line
+    for chunk in paths.chunks(1000) {
+        let output = Command::new("nix").args(["path-info", "--json"]).args(chunk).echo().output()?;
+        if !output.status.success() {
+            return Err(SystemError::NixCommandFailed {
+                command: "nix path-info".to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            }.into());
+        }
+
+        let json: Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| SystemError::NixCommandFailed { command: "nix path-info".to_string(), stderr: e.to_string() })?;
+        sizes.extend(parse_path_sizes(&json));
+    }
+
+    Ok(sizes)
+}
+
+/// Closure sizes of store paths (`nix path-info -S`)
+pub(crate) fn closure_sizes(paths: &[String]) -> Result<Vec<(String, u64)>> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let output = Command::new("nix").args(["path-info", "--json", "--closure-size"]).args(paths).echo().output()?;
+    if !output.status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix path-info --closure-size".to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }.into());
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| SystemError::NixCommandFailed { command: "nix path-info".to_string(), stderr: e.to_string() })?;
+    Ok(parse_path_info(&json, "closureSize"))
+}
+
+fn parse_path_sizes(json: &Value) -> Vec<(String, u64)> {
+    parse_path_info(json, "narSize")
+}
+
+/// `field` of each path in `nix path-info --json` output. Nix before 2.19
+/// prints an array of objects, later versions an object keyed by path.
+fn parse_path_info(json: &Value, field: &str) -> Vec<(String, u64)> {
+    let size = |info: &Value| info[field].as_u64().unwrap_or(0);
+    match json {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|info| Some((info["path"].as_str()?.to_string(), size(info))))
+            .collect(),
+        Value::Object(map) => map.iter().map(|(path, info)| (path.clone(), size(info))).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// What removing a package and collecting garbage would free
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GCImpact {
+    pub package: String,
+    /// Paths in the package's closure
+    pub closure_paths: usize,
+    /// Paths only this package keeps alive, largest first
+    pub reclaimable: Vec<(String, u64)>,
+    pub reclaimable_bytes: u64,
+}
+
+impl GCImpact {
+    pub fn print(&self) {
+        println!(
+            "Removing {} and collecting garbage frees {} ({} of {} paths in its closure)",
+            style(&self.package).bold(),
+            style(format_bytes(self.reclaimable_bytes)).green().bold(),
+            self.reclaimable.len(),
+            self.closure_paths
+        );
+        let shared = self.closure_paths - self.reclaimable.len();
+        if shared > 0 {
+            println!("{} paths are shared with other packages or GC roots and stay", shared);
+        }
+
+        for (path, size) in self.reclaimable.iter().take(10) {
+            let name = path.strip_prefix("/nix/store/").and_then(|p| p.get(33..)).unwrap_or(path);
+            println!("  {:>10}  {}", format_bytes(*size), name);
+        }
+        if self.reclaimable.len() > 10 {
+            println!("  {}", style(format!("... and {} more", self.reclaimable.len() - 10)).dim());
+        }
+        println!(
+            "{}",
+            style("Older profile generations still reference it until they are deleted (nixboost generation delete)").dim()
+        );
+    }
+}
+
 /// Garbage collection result
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GCResult {
     pub success: bool,
     pub bytes_freed: u64,
@@ -234,7 +639,7 @@ impl GCResult {
     }
 }
 
-fn format_bytes(bytes: u64) -> String {
+pub(crate) fn format_bytes(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{} B", bytes)
     } else if bytes < 1024 * 1024 {
@@ -257,10 +662,89 @@ mod tests {
         assert_eq!(GarbageCollector::parse_size_string("1.5GiB"), 1610612736);
     }
 
+    #[test]
+    fn test_parse_roots() {
+        let roots = parse_roots(
+            "/home/alice/.local/state/nix/profiles/profile-12-link -> /nix/store/0123456789abcdfghijklmnpqrsvwxyz-profile\n\
+             /proc/1234/maps -> /nix/store/1123456789abcdfghijklmnpqrsvwxyz-glibc-2.39-52\n\
+             {censored} -> /nix/store/2123456789abcdfghijklmnpqrsvwxyz-bash-5.2\n",
+        );
+        assert_eq!(roots.len(), 3);
+        assert_eq!(roots[0].0, "/home/alice/.local/state/nix/profiles/profile-12-link");
+    }
+
+    #[test]
+    fn test_parse_path_sizes() {
+        let old: Value = serde_json::from_str(r#"[{"path": "/nix/store/a-x", "narSize": 10}]"#).unwrap();
+        assert_eq!(parse_path_sizes(&old), vec![("/nix/store/a-x".to_string(), 10)]);
+
+        let new: Value = serde_json::from_str(r#"{"/nix/store/a-x": {"narSize": 10}}"#).unwrap();
+        assert_eq!(parse_path_sizes(&new), vec![("/nix/store/a-x".to_string(), 10)]);
+
+        let closure: Value = serde_json::from_str(r#"{"/nix/store/a-x": {"narSize": 10, "closureSize": 250}}"#).unwrap();
+        assert_eq!(parse_path_info(&closure, "closureSize"), vec![("/nix/store/a-x".to_string(), 250)]);
+    }
+
+    #[test]
+    fn test_preview_snapshot() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(PREVIEW_SNAPSHOT);
+        assert!(PreviewSnapshot::load(&path).is_none());
+
+        let preview = GCPreview { size_bytes: 4096, ..Default::default() };
+        PreviewSnapshot::of(&preview).save(&path).unwrap();
+        assert_eq!(PreviewSnapshot::load(&path).unwrap().size_bytes, 4096);
+    }
+
+    #[test]
+    fn test_plan_json() {
+        let plan = GcPlan {
+            generations: vec![3, 4],
+            dead_bytes: 100,
+            estimated_bytes: 300,
+            max_freed: None,
+            paths: vec![("/nix/store/b-y".to_string(), 200), ("/nix/store/a-x".to_string(), 100)],
+        };
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["paths"][0], serde_json::json!(["/nix/store/b-y", 200]));
+        assert!(json.get("max_freed").is_none());
+        assert_eq!(serde_json::from_value::<GcPlan>(json).unwrap(), plan);
+    }
+
     #[test]
     fn test_format_bytes() {
         assert_eq!(format_bytes(512), "512 B");
         assert_eq!(format_bytes(1536), "1.5 KiB");
         assert_eq!(format_bytes(1572864), "1.5 MiB");
     }
+
+    #[test]
+    fn test_select_generations() {
+        let day = Duration::from_secs(86400);
+        let now = SystemTime::UNIX_EPOCH + day * 100;
+        // Newest first, as GenerationManager::list returns them
+        let generations: Vec<Generation> = [(5, 99, true), (4, 90, false), (3, 60, false), (2, 50, false), (1, 10, false)]
+            .iter()
+            .map(|&(number, created, is_current)| Generation {
+                number,
+                created_at: SystemTime::UNIX_EPOCH + day * created,
+                is_current,
+                path: String::new(),
+                closure_size: None,
+                size_delta: None,
+            })
+            .collect();
+
+        let options = GcOptions { older_than: Some(day * 30), ..Default::default() };
+        assert_eq!(select_generations(&generations, &options, now), vec![1, 2, 3]);
+
+        let options = GcOptions { keep_generations: 4, older_than: Some(day * 30), ..Default::default() };
+        assert_eq!(select_generations(&generations, &options, now), vec![1]);
+        assert_eq!(deletable(&generations, 2).collect::<Vec<_>>(), vec![3, 2, 1]);
+
+        assert_eq!(parse_size("5G"), Ok(5 << 30));
+        assert_eq!(parse_size("1.5GiB"), Ok(3 << 29));
+        assert_eq!(parse_size("500M"), Ok(500 << 20));
+        assert!(parse_size("5X").is_err());
+    }
 }
@@ -16,24 +16,200 @@
 
 //! Generation management for NixBoost.
 
+use crate::cache::CacheManager;
+use crate::cache::invalidation::{CacheKey, TTL};
+use crate::core::config::{Config, GenerationsConfig};
 use crate::core::error::{Result, SystemError};
-use crate::core::types::Generation;
+use crate::core::history;
+use crate::core::types::{Generation, OperationType};
+use crate::system::daemon::privileged;
+use crate::system::garbage_collector::{closure_sizes, format_bytes};
+use crate::package::profiles::NamedProfiles;
+use crate::system::quarantine::{Quarantine, QuarantinedGeneration};
+use crate::ui::theme::{DiffKind, DiffTheme};
+use crate::utils::command::Echo;
+use crate::utils::time::unix_now;
 use console::style;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
 use std::process::Command;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tracing::{debug, info};
 
+/// Generations `policy` does not retain, oldest first. `generations` is
+/// newest first; the current generation is always retained.
+pub fn expired(generations: &[Generation], policy: &GenerationsConfig, now: SystemTime) -> Vec<u64> {
+    let cutoff = now.checked_sub(Duration::from_secs(policy.keep_days * 86400)).unwrap_or(UNIX_EPOCH);
+    let mut months = Vec::new();
+
+    let mut expired: Vec<u64> = generations
+        .iter()
+        .enumerate()
+        .filter(|(i, gen)| {
+            let month = month_of(gen.created_at);
+            let monthly = !months.contains(&month) && months.len() < policy.keep_monthly;
+            if monthly {
+                months.push(month);
+            }
+            let kept = gen.is_current || *i < policy.keep_last || (policy.keep_days > 0 && gen.created_at >= cutoff);
+            !kept && !monthly
+        })
+        .map(|(_, gen)| gen.number)
+        .collect();
+    expired.reverse();
+    expired
+}
+
+/// `(year, month)` of a time, in UTC
+fn month_of(time: SystemTime) -> (i64, u32) {
+    let days = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs() / 86400).unwrap_or(0) as i64;
+
+    // Inverse of the days-from-civil conversion in `parse_timestamp`
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month as u32)
+}
+
+/// System profile switched by `nixos-rebuild`
+pub const SYSTEM_PROFILE: &str = "/nix/var/nix/profiles/system";
+
+/// Profile whose generations are managed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Profile {
+    /// The user profile `~/.nix-profile` points to
+    #[default]
+    User,
+    /// The NixOS system profile
+    System,
+    /// The home-manager profile
+    HomeManager,
+    /// Any other profile, by path
+    Path(PathBuf),
+}
+
+impl Profile {
+    /// Profile path; generation `n` is linked at `<path>-<n>-link`
+    pub fn path(&self) -> String {
+        match self {
+            Profile::User => dirs::home_dir()
+                .and_then(|home| std::fs::read_link(home.join(".nix-profile")).ok())
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "/nix/var/nix/profiles/default".to_string()),
+            Profile::System => SYSTEM_PROFILE.to_string(),
+            Profile::HomeManager => {
+                // home-manager moved its profile to the XDG state directory in 22.11
+                let state = dirs::home_dir().map(|home| home.join(".local/state/nix/profiles/home-manager"));
+                match state {
+                    Some(path) if path.exists() => path.display().to_string(),
+                    _ => format!("/nix/var/nix/profiles/per-user/{}/home-manager", std::env::var("USER").unwrap_or_default()),
+                }
+            }
+            Profile::Path(path) => path.display().to_string(),
+        }
+    }
+
+    /// `--profile` for `nix profile` commands; `None` for the user's default
+    /// profile. The system profile is changed through its configuration.
+    pub fn nix_profile(&self) -> Option<PathBuf> {
+        match self {
+            Profile::User | Profile::System => None,
+            _ => Some(PathBuf::from(self.path())),
+        }
+    }
+
+    /// Link of generation `number`
+    pub fn generation_link(&self, number: u64) -> String {
+        format!("{}-{}-link", self.path(), number)
+    }
+
+    /// `nix-env` acting on this profile; the system profile is changed as root
+    fn nix_env(&self, modify: bool) -> Result<Command> {
+        let mut cmd = match self {
+            Profile::User => return Ok(Command::new("nix-env")),
+            Profile::System if modify => privileged("nix-env")?,
+            _ => Command::new("nix-env"),
+        };
+        cmd.arg("-p").arg(self.path());
+        Ok(cmd)
+    }
+
+    /// Activate the profile's current generation: `switch-to-configuration`
+    /// for the system, the generation's `activate` script for home-manager.
+    /// Other profiles need no activation.
+    fn activate(&self) -> Result<()> {
+        let mut cmd = match self {
+            Profile::System => {
+                let mut cmd = privileged(&format!("{}/bin/switch-to-configuration", SYSTEM_PROFILE))?;
+                cmd.arg("switch");
+                cmd
+            }
+            Profile::HomeManager => Command::new(format!("{}/activate", self.path())),
+            _ => return Ok(()),
+        };
+
+        if !cmd.echo().status()?.success() {
+            return Err(SystemError::RollbackFailed(format!("activating the {} profile failed", self)).into());
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Profile::User => write!(f, "user"),
+            Profile::System => write!(f, "system"),
+            Profile::HomeManager => write!(f, "home-manager"),
+            Profile::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Parse a `--profile` value: `user`, `system`, `home-manager`, a profile
+/// created with `nixboost profile create`, or a path
+pub fn parse_profile(s: &str) -> std::result::Result<Profile, String> {
+    match s {
+        "user" => Ok(Profile::User),
+        "system" => Ok(Profile::System),
+        "home-manager" => Ok(Profile::HomeManager),
+        "" => Err("profile must not be empty".to_string()),
+        path if path.contains('/') => Ok(Profile::Path(PathBuf::from(path))),
+        name => match NamedProfiles::load() {
+            Ok(profiles) if profiles.contains(name) => Ok(Profile::Path(NamedProfiles::path(name))),
+            _ => Err(format!(
+                "unknown profile '{}': use user, system, home-manager, a path or a name from 'nixboost profile list'",
+                name
+            )),
+        },
+    }
+}
+
 /// Generation manager
-pub struct GenerationManager;
+#[derive(Debug, Clone, Default)]
+pub struct GenerationManager {
+    profile: Profile,
+}
 
 impl GenerationManager {
+    /// Manage the generations of `profile`
+    pub fn new(profile: Profile) -> Self {
+        Self { profile }
+    }
+
     /// List all generations
-    pub fn list(limit: usize) -> Result<Vec<Generation>> {
-        debug!("Listing generations (limit: {})", limit);
+    pub fn list(&self, limit: usize) -> Result<Vec<Generation>> {
+        debug!("Listing {:?} generations (limit: {})", self.profile, limit);
 
-        let output = Command::new("nix-env")
+        let output = self.profile.nix_env(false)?
             .args(["--list-generations"])
-            .output()?;
+            .echo().output()?;
 
         if !output.status.success() {
             return Err(SystemError::NixCommandFailed {
@@ -45,7 +221,7 @@ impl GenerationManager {
         let stdout = String::from_utf8_lossy(&output.stdout);
         let mut generations: Vec<Generation> = stdout
             .lines()
-            .filter_map(|line| Self::parse_generation_line(line))
+            .filter_map(|line| Self::parse_generation_line(line, &self.profile))
             .collect();
 
         generations.reverse();
@@ -55,7 +231,7 @@ impl GenerationManager {
     }
 
     /// Parse a generation line from nix-env output
-    fn parse_generation_line(line: &str) -> Option<Generation> {
+    fn parse_generation_line(line: &str, profile: &Profile) -> Option<Generation> {
         let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.is_empty() {
             return None;
@@ -76,51 +252,120 @@ impl GenerationManager {
             number,
             created_at,
             is_current,
-            path: format!("/nix/var/nix/profiles/default-{}-link", number),
+            path: profile.generation_link(number),
+            closure_size: None,
+            size_delta: None,
         })
     }
 
-    /// Parse timestamp from string
+    /// Parse a `YYYY-MM-DD HH:MM:SS` timestamp. nix-env prints local time;
+    /// it is read as UTC, which is close enough for ages in days.
     fn parse_timestamp(s: &str) -> Option<SystemTime> {
-        // Try common formats
-        // This is a simplified parser - in production you'd use chrono
-        None // For simplicity, return None for now
+        let (date, time) = s.trim().split_once(' ')?;
+        let mut date = date.split('-').map(|p| p.parse::<i64>());
+        let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+        let mut time = time.split(':').map(|p| p.parse::<u64>());
+        let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+
+        // Days since the epoch for a proleptic Gregorian date
+        let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let doy = (153 * m + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = u64::try_from(era * 146097 + doe - 719468).ok()?;
+
+        Some(UNIX_EPOCH + std::time::Duration::from_secs(days * 86400 + hour * 3600 + minute * 60 + second))
+    }
+
+    /// Fill in closure sizes and the change from each previous generation.
+    /// `generations` is newest first; sizes are cached by store path.
+    pub fn with_sizes(generations: &mut [Generation], cache: Option<&CacheManager>) -> Result<()> {
+        let store_paths: Vec<Option<String>> = generations
+            .iter()
+            .map(|g| std::fs::canonicalize(&g.path).ok().map(|p| p.display().to_string()))
+            .collect();
+
+        let mut missing = Vec::new();
+        for (gen, store_path) in generations.iter_mut().zip(&store_paths) {
+            let Some(store_path) = store_path else {
+                continue;
+            };
+            gen.closure_size = cache.and_then(|c| c.get::<u64>(&CacheKey::generation_size(store_path)));
+            if gen.closure_size.is_none() && !missing.contains(store_path) {
+                missing.push(store_path.clone());
+            }
+        }
+
+        for (path, size) in closure_sizes(&missing)? {
+            if let Some(cache) = cache {
+                if let Err(e) = cache.set(&CacheKey::generation_size(&path), &size, TTL::GENERATIONS) {
+                    debug!("Failed to cache closure size: {}", e);
+                }
+            }
+            for (gen, store_path) in generations.iter_mut().zip(&store_paths) {
+                if store_path.as_deref() == Some(path.as_str()) {
+                    gen.closure_size = Some(size);
+                }
+            }
+        }
+
+        for i in 0..generations.len().saturating_sub(1) {
+            if let (Some(size), Some(previous)) = (generations[i].closure_size, generations[i + 1].closure_size) {
+                generations[i].size_delta = Some(size as i64 - previous as i64);
+            }
+        }
+        Ok(())
     }
 
-    /// Get the current generation
-    pub fn current() -> Result<Option<Generation>> {
-        let generations = Self::list(1)?;
+    /// Get the current generation. After a rollback it is not the newest.
+    pub fn current(&self) -> Result<Option<Generation>> {
+        let generations = self.list(usize::MAX)?;
         Ok(generations.into_iter().find(|g| g.is_current))
     }
 
     /// Rollback to previous generation
-    pub fn rollback() -> Result<()> {
+    pub fn rollback(&self) -> Result<()> {
+        let start = Instant::now();
+        let result = self.switch_previous();
+        history::record(OperationType::Rollback, &[], start, &result);
+        result
+    }
+
+    fn switch_previous(&self) -> Result<()> {
         info!("Rolling back to previous generation");
 
-        let status = Command::new("nix-env")
+        let status = self.profile.nix_env(true)?
             .args(["--rollback"])
-            .status()?;
+            .echo().status()?;
 
         if !status.success() {
             return Err(SystemError::RollbackFailed("nix-env --rollback failed".to_string()).into());
         }
 
-        Ok(())
+        self.profile.activate()
     }
 
     /// Rollback to a specific generation
-    pub fn rollback_to(generation: u64) -> Result<()> {
+    pub fn rollback_to(&self, generation: u64) -> Result<()> {
+        let start = Instant::now();
+        let result = self.switch_to(generation);
+        history::record(OperationType::Rollback, &[format!("generation {}", generation)], start, &result);
+        result
+    }
+
+    fn switch_to(&self, generation: u64) -> Result<()> {
         info!("Rolling back to generation {}", generation);
 
         // First check if generation exists
-        let generations = Self::list(100)?;
+        let generations = self.list(100)?;
         if !generations.iter().any(|g| g.number == generation) {
             return Err(SystemError::GenerationNotFound { generation }.into());
         }
 
-        let status = Command::new("nix-env")
+        let status = self.profile.nix_env(true)?
             .args(["--switch-generation", &generation.to_string()])
-            .status()?;
+            .echo().status()?;
 
         if !status.success() {
             return Err(SystemError::RollbackFailed(
@@ -128,22 +373,37 @@ impl GenerationManager {
             ).into());
         }
 
-        Ok(())
+        self.profile.activate()
     }
 
     /// Delete specific generations
-    pub fn delete(generations: &[u64]) -> Result<()> {
+    pub fn delete(&self, generations: &[u64]) -> Result<()> {
         if generations.is_empty() {
             return Ok(());
         }
 
         info!("Deleting {} generation(s)", generations.len());
 
+        let quarantine_days = Config::try_get()
+            .map(|c| c.generations.quarantine_days)
+            .unwrap_or_else(|| GenerationsConfig::default().quarantine_days);
+        let mut quarantine = if quarantine_days > 0 {
+            Some((Quarantine::load()?, self.list(usize::MAX)?))
+        } else {
+            None
+        };
+
         for gen in generations {
+            if let Some((quarantine, known)) = quarantine.as_mut() {
+                if let Some(entry) = self.quarantine_entry(*gen, known) {
+                    quarantine.add(entry);
+                }
+            }
+
             debug!("Deleting generation {}", gen);
-            let status = Command::new("nix-env")
+            let status = self.profile.nix_env(true)?
                 .args(["--delete-generations", &gen.to_string()])
-                .status()?;
+                .echo().status()?;
 
             if !status.success() {
                 return Err(SystemError::NixCommandFailed {
@@ -153,43 +413,101 @@ impl GenerationManager {
             }
         }
 
+        if let Some((mut quarantine, _)) = quarantine {
+            quarantine.prune(quarantine_days, unix_now());
+            quarantine.save()?;
+        }
+
         Ok(())
     }
 
-    /// Delete generations keeping the last N
-    pub fn delete_old(keep: usize) -> Result<usize> {
-        let generations = Self::list(1000)?;
-        
-        if generations.len() <= keep {
-            return Ok(0);
+    /// What `restore` needs to recreate generation `number`'s link
+    fn quarantine_entry(&self, number: u64, known: &[Generation]) -> Option<QuarantinedGeneration> {
+        let store_path = std::fs::canonicalize(self.profile.generation_link(number)).ok()?;
+        let created_at = known.iter()
+            .find(|g| g.number == number)
+            .and_then(|g| g.created_at.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Some(QuarantinedGeneration {
+            profile: self.profile.path(),
+            number,
+            store_path: store_path.display().to_string(),
+            created_at,
+            deleted_at: unix_now(),
+        })
+    }
+
+    /// Generations of this profile deleted recently enough to be restored
+    pub fn quarantined(&self) -> Result<Vec<QuarantinedGeneration>> {
+        let quarantine = Quarantine::load()?;
+        Ok(quarantine.for_profile(&self.profile.path()).into_iter().cloned().collect())
+    }
+
+    /// Recreate a deleted generation from the quarantine, as long as garbage
+    /// collection has not removed its store path
+    pub fn restore(&self, number: u64) -> Result<()> {
+        let mut quarantine = Quarantine::load()?;
+        let entry = quarantine.take(&self.profile.path(), number)
+            .ok_or(SystemError::GenerationNotFound { generation: number })?;
+
+        if entry.is_collected() {
+            quarantine.save()?;
+            return Err(SystemError::GenerationCollected { generation: number }.into());
         }
 
-        let to_delete: Vec<u64> = generations
-            .iter()
-            .skip(keep)
-            .filter(|g| !g.is_current)
-            .map(|g| g.number)
-            .collect();
+        info!("Restoring generation {}", number);
+        let link = self.profile.generation_link(number);
+        let mut commands = vec![
+            ("ln", vec!["-s".to_string(), entry.store_path.clone(), link.clone()]),
+        ];
+        if entry.created_at > 0 {
+            // nix-env dates generations by the link's mtime
+            commands.push(("touch", vec!["-h".to_string(), "-d".to_string(), format!("@{}", entry.created_at), link]));
+        }
+
+        for (program, args) in commands {
+            let mut cmd = match self.profile {
+                Profile::System => privileged(program)?,
+                _ => Command::new(program),
+            };
+            let status = cmd.args(&args).echo().status()?;
+            if !status.success() {
+                return Err(SystemError::NixCommandFailed {
+                    command: format!("{} {}", program, args.join(" ")),
+                    stderr: "Command failed".to_string(),
+                }.into());
+            }
+        }
+
+        quarantine.save()
+    }
+
+    /// Delete the generations `policy` does not retain
+    pub fn delete_old(&self, policy: &GenerationsConfig) -> Result<usize> {
+        let generations = self.list(usize::MAX)?;
+        let to_delete = expired(&generations, policy, SystemTime::now());
 
         let count = to_delete.len();
         if count > 0 {
-            Self::delete(&to_delete)?;
+            self.delete(&to_delete)?;
         }
 
         Ok(count)
     }
 
     /// Diff two generations
-    pub fn diff(from: u64, to: u64) -> Result<GenerationDiff> {
+    pub fn diff(&self, from: u64, to: u64) -> Result<GenerationDiff> {
         debug!("Diffing generations {} -> {}", from, to);
 
         let output = Command::new("nix-store")
             .args([
                 "--diff-closures",
-                &format!("/nix/var/nix/profiles/default-{}-link", from),
-                &format!("/nix/var/nix/profiles/default-{}-link", to),
+                &self.profile.generation_link(from),
+                &self.profile.generation_link(to),
             ])
-            .output()?;
+            .echo().output()?;
 
         if !output.status.success() {
             return Err(SystemError::NixCommandFailed {
@@ -236,7 +554,7 @@ impl GenerationManager {
 
         let mut table = Table::new();
         table.load_preset(UTF8_FULL);
-        table.set_header(vec!["Generation", "Status", "Path"]);
+        table.set_header(vec!["Generation", "Status", "Closure", "Change", "Path"]);
 
         for gen in generations {
             let status = if gen.is_current {
@@ -244,10 +562,18 @@ impl GenerationManager {
             } else {
                 String::new()
             };
+            let delta = match gen.size_delta {
+                Some(d) if d > 0 => style(format!("+{}", format_bytes(d as u64))).yellow().to_string(),
+                Some(d) if d < 0 => style(format!("-{}", format_bytes(d.unsigned_abs()))).green().to_string(),
+                Some(_) => "0 B".to_string(),
+                None => String::new(),
+            };
 
             table.add_row(vec![
                 gen.number.to_string(),
                 status,
+                gen.closure_size.map(format_bytes).unwrap_or_default(),
+                delta,
                 gen.path.clone(),
             ]);
         }
@@ -257,7 +583,7 @@ impl GenerationManager {
 }
 
 /// Generation diff result
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenerationDiff {
     pub from: u64,
     pub to: u64,
@@ -267,28 +593,22 @@ pub struct GenerationDiff {
 }
 
 impl GenerationDiff {
-    pub fn print(&self) {
+    pub fn print(&self, theme: &DiffTheme) {
         println!("Generation {} → {}:", self.from, self.to);
         println!();
 
-        if !self.added.is_empty() {
-            println!("{}", style("Added:").green().bold());
-            for pkg in &self.added {
-                println!("  + {}", style(pkg).green());
-            }
-        }
-
-        if !self.removed.is_empty() {
-            println!("{}", style("Removed:").red().bold());
-            for pkg in &self.removed {
-                println!("  - {}", style(pkg).red());
+        let sections = [
+            (DiffKind::Added, &self.added),
+            (DiffKind::Removed, &self.removed),
+            (DiffKind::Changed, &self.changed),
+        ];
+        for (kind, entries) in sections {
+            if entries.is_empty() {
+                continue;
             }
-        }
-
-        if !self.changed.is_empty() {
-            println!("{}", style("Changed:").yellow().bold());
-            for pkg in &self.changed {
-                println!("  ~ {}", style(pkg).yellow());
+            println!("{}", theme.header(kind));
+            for pkg in entries {
+                println!("{}", theme.line(kind, pkg));
             }
         }
 
@@ -305,16 +625,66 @@ mod tests {
     #[test]
     fn test_parse_generation_line() {
         let line = "   1   2024-01-01 12:00:00   ";
-        let gen = GenerationManager::parse_generation_line(line);
+        let gen = GenerationManager::parse_generation_line(line, &Profile::User);
         assert!(gen.is_some());
         assert_eq!(gen.unwrap().number, 1);
 
         let current = "   5   2024-01-15 12:00:00   (current)";
-        let gen = GenerationManager::parse_generation_line(current);
+        let gen = GenerationManager::parse_generation_line(current, &Profile::System);
         assert!(gen.is_some());
         let gen = gen.unwrap();
         assert_eq!(gen.number, 5);
         assert!(gen.is_current);
+        assert_eq!(gen.path, "/nix/var/nix/profiles/system-5-link");
+        assert_eq!(gen.created_at.duration_since(UNIX_EPOCH).unwrap().as_secs(), 1705320000);
+    }
+
+    #[test]
+    fn test_retention_policy() {
+        let day = 86400;
+        // 2024-03-31; newest first
+        let now = UNIX_EPOCH + Duration::from_secs(19813 * day);
+        let generations: Vec<Generation> = [
+            (9, 19813, true),  // 2024-03-31, current
+            (8, 19800, false), // 2024-03-18
+            (7, 19760, false), // 2024-02-07
+            (6, 19755, false), // 2024-02-02
+            (5, 19740, false), // 2024-01-18
+            (4, 19730, false), // 2024-01-08
+            (3, 19700, false), // 2023-12-09
+            (2, 19650, false), // 2023-10-20
+            (1, 19600, false), // 2023-08-31
+        ]
+        .iter()
+        .map(|&(number, days, is_current)| Generation {
+            number,
+            created_at: UNIX_EPOCH + Duration::from_secs(days * day),
+            is_current,
+            path: String::new(),
+            closure_size: None,
+            size_delta: None,
+        })
+        .collect();
+
+        assert_eq!(month_of(now), (2024, 3));
+        assert_eq!(month_of(UNIX_EPOCH + Duration::from_secs(19700 * day)), (2023, 12));
+
+        // Newest two, plus 9 for being current
+        assert_eq!(expired(&generations, &GenerationsConfig::keep_last(2), now), vec![1, 2, 3, 4, 5, 6, 7]);
+
+        // Last 30 days keeps 8; monthly keeps 9 (March), 7 (February), 5 (January)
+        let policy = GenerationsConfig { keep_last: 1, keep_days: 30, keep_monthly: 3, ..Default::default() };
+        assert_eq!(expired(&generations, &policy, now), vec![1, 2, 3, 4, 6]);
+
+        assert!(expired(&generations, &GenerationsConfig::default(), now).is_empty());
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        assert_eq!(parse_profile("home-manager"), Ok(Profile::HomeManager));
+        assert_eq!(parse_profile("/nix/var/nix/profiles/per-user/alice/dev"), Ok(Profile::Path(PathBuf::from("/nix/var/nix/profiles/per-user/alice/dev"))));
+        assert!(parse_profile("nightly").is_err());
+        assert_eq!(Profile::Path(PathBuf::from("/tmp/p")).generation_link(3), "/tmp/p-3-link");
     }
 
     #[test]
@@ -326,4 +696,18 @@ mod tests {
         assert_eq!(removed.len(), 1);
         assert_eq!(changed.len(), 1);
     }
+
+    #[test]
+    fn test_diff_serializes() {
+        let diff = GenerationDiff {
+            from: 3,
+            to: 4,
+            added: vec!["ripgrep: ∅ → 14.1.0".to_string()],
+            removed: vec![],
+            changed: vec![],
+        };
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["from"], 3);
+        assert_eq!(json["added"][0], "ripgrep: ∅ → 14.1.0");
+    }
 }
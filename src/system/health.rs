@@ -17,25 +17,114 @@
 //! System health checks for NixBoost.
 
 use crate::core::error::{Result, SystemError};
+use crate::system::builders;
+use crate::system::disk::build_dir;
+use crate::system::garbage_collector::format_bytes;
+use crate::utils::command::Echo;
 use console::style;
 use std::process::Command;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+/// Usage percentage above which a filesystem is reported as a warning
+const WARN_USAGE_PCT: u32 = 80;
+
+/// Usage percentage above which a filesystem fails the health check
+const CRITICAL_USAGE_PCT: u32 = 90;
+
+/// tmpfs build directories smaller than this are flagged
+const TMPFS_MIN_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Which tier of checks to run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HealthMode {
+    /// Cheap checks; store verification skips content hashing
+    #[default]
+    Quick,
+    /// Everything, including `nix-store --verify --check-contents`
+    Full,
+}
+
+impl HealthMode {
+    /// Timeout for the store verification in this mode
+    fn store_timeout(self) -> Duration {
+        match self {
+            HealthMode::Quick => Duration::from_secs(60),
+            HealthMode::Full => Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+/// Timeout for the cheap systemd, daemon and disk checks
+const SHORT_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How long a single check took
+#[derive(Debug, Clone)]
+pub struct CheckTiming {
+    pub name: &'static str,
+    pub elapsed: Duration,
+    pub timed_out: bool,
+}
+
+/// A check running on its own thread
+struct PendingCheck<T> {
+    name: &'static str,
+    timeout: Duration,
+    started: Instant,
+    rx: mpsc::Receiver<T>,
+}
+
+impl<T: Send + 'static> PendingCheck<T> {
+    fn spawn<F>(name: &'static str, timeout: Duration, check: F) -> Self
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(check());
+        });
+        Self { name, timeout, started: Instant::now(), rx }
+    }
+
+    /// Wait for the result, substituting `on_timeout` if the check overruns
+    fn wait(self, on_timeout: T) -> (T, CheckTiming) {
+        let remaining = self.timeout.saturating_sub(self.started.elapsed());
+        let (value, timed_out) = match self.rx.recv_timeout(remaining) {
+            Ok(value) => (value, false),
+            Err(_) => {
+                warn!("Health check '{}' timed out after {:?}", self.name, self.timeout);
+                (on_timeout, true)
+            }
+        };
+        let timing = CheckTiming {
+            name: self.name,
+            elapsed: self.started.elapsed(),
+            timed_out,
+        };
+        (value, timing)
+    }
+}
+
 /// Health check results
 #[derive(Debug, Clone)]
 pub struct HealthReport {
+    pub mode: HealthMode,
     pub systemd_ok: bool,
     pub systemd_failed: Vec<String>,
     pub nix_store_ok: bool,
     pub nix_store_issues: Vec<String>,
     pub disk_space_ok: bool,
-    pub disk_space_warning: Option<String>,
+    pub disk_space_warnings: Vec<String>,
     pub nix_daemon_ok: bool,
+    /// Systems that only remote builders provide, none of them reachable
+    pub builders_unreachable: Vec<String>,
+    pub timings: Vec<CheckTiming>,
 }
 
 impl HealthReport {
     pub fn is_healthy(&self) -> bool {
-        self.systemd_ok && self.nix_store_ok && self.nix_daemon_ok
+        self.systemd_ok && self.nix_store_ok && self.nix_daemon_ok && self.builders_unreachable.is_empty()
     }
 
     pub fn print(&self) {
@@ -61,11 +150,35 @@ impl HealthReport {
             println!("{}", style("✓ Nix daemon is running").green());
         } else {
             println!("{}", style("⚠ Nix daemon not detected (multi-user mode may not work)").yellow());
+            println!("  Run 'nixboost system daemon restart' to restart it");
+        }
+
+        if !self.builders_unreachable.is_empty() {
+            println!(
+                "{}",
+                style(format!("✗ No reachable remote builder for: {}", self.builders_unreachable.join(", "))).red()
+            );
+            println!("  Run 'nixboost system builders' for details");
         }
 
-        if let Some(ref warning) = self.disk_space_warning {
+        for warning in &self.disk_space_warnings {
             println!("{}", style(format!("⚠ {}", warning)).yellow());
         }
+
+        println!();
+        for timing in &self.timings {
+            let note = if timing.timed_out { " (timed out)" } else { "" };
+            println!(
+                "  {} {:.2}s{}",
+                style(format!("{:<12}", timing.name)).dim(),
+                timing.elapsed.as_secs_f64(),
+                note
+            );
+        }
+
+        if self.mode == HealthMode::Quick {
+            println!("{}", style("Store contents were not hashed; run with --full for a complete check").dim());
+        }
     }
 }
 
@@ -73,23 +186,35 @@ impl HealthReport {
 pub struct HealthChecker;
 
 impl HealthChecker {
-    /// Run all health checks
-    pub fn run() -> Result<HealthReport> {
-        info!("Running system health check");
+    /// Run all checks of the given tier concurrently
+    pub fn run_mode(mode: HealthMode) -> Result<HealthReport> {
+        info!("Running {:?} system health check", mode);
+
+        let full = mode == HealthMode::Full;
+        let systemd = PendingCheck::spawn("systemd", SHORT_CHECK_TIMEOUT, Self::check_systemd);
+        let store = PendingCheck::spawn("nix store", mode.store_timeout(), move || Self::check_nix_store(full));
+        let daemon = PendingCheck::spawn("nix daemon", SHORT_CHECK_TIMEOUT, Self::check_nix_daemon);
+        let disk = PendingCheck::spawn("disk", SHORT_CHECK_TIMEOUT, Self::check_disk_space);
+        let remote = PendingCheck::spawn("builders", SHORT_CHECK_TIMEOUT, Self::check_builders);
 
-        let systemd_result = Self::check_systemd();
-        let nix_store_result = Self::check_nix_store();
-        let nix_daemon_ok = Self::check_nix_daemon();
-        let disk_check = Self::check_disk_space();
+        let store_timeout = format!("Store verification timed out after {}s", mode.store_timeout().as_secs());
+        let (systemd_result, systemd_time) = systemd.wait((true, vec![]));
+        let (nix_store_result, store_time) = store.wait((false, vec![store_timeout]));
+        let (nix_daemon_ok, daemon_time) = daemon.wait(false);
+        let (disk_check, disk_time) = disk.wait((true, vec!["Disk check timed out".to_string()]));
+        let (builders_unreachable, builders_time) = remote.wait(vec![]);
 
         Ok(HealthReport {
+            mode,
             systemd_ok: systemd_result.0,
             systemd_failed: systemd_result.1,
             nix_store_ok: nix_store_result.0,
             nix_store_issues: nix_store_result.1,
             disk_space_ok: disk_check.0,
-            disk_space_warning: disk_check.1,
+            disk_space_warnings: disk_check.1,
             nix_daemon_ok,
+            builders_unreachable,
+            timings: vec![systemd_time, store_time, daemon_time, disk_time, builders_time],
         })
     }
 
@@ -99,7 +224,7 @@ impl HealthChecker {
 
         let output = Command::new("systemctl")
             .args(["--failed", "--no-pager", "--plain"])
-            .output();
+            .echo().output();
 
         match output {
             Ok(o) if o.status.success() => {
@@ -121,14 +246,16 @@ impl HealthChecker {
         }
     }
 
-    /// Check Nix store integrity
-    fn check_nix_store() -> (bool, Vec<String>) {
-        debug!("Checking Nix store integrity");
+    /// Check Nix store integrity, hashing contents when `check_contents` is set
+    pub(crate) fn check_nix_store(check_contents: bool) -> (bool, Vec<String>) {
+        debug!("Checking Nix store integrity (contents: {})", check_contents);
 
-        let output = Command::new("nix-store")
-            .arg("--verify")
-            .arg("--check-contents")
-            .output();
+        let mut cmd = Command::new("nix-store");
+        cmd.arg("--verify");
+        if check_contents {
+            cmd.arg("--check-contents");
+        }
+        let output = cmd.echo().output();
 
         match output {
             Ok(o) => {
@@ -151,7 +278,7 @@ impl HealthChecker {
 
         let output = Command::new("systemctl")
             .args(["is-active", "nix-daemon"])
-            .output();
+            .echo().output();
 
         match output {
             Ok(o) => {
@@ -165,41 +292,72 @@ impl HealthChecker {
         }
     }
 
-    /// Check disk space
-    fn check_disk_space() -> (bool, Option<String>) {
+    /// Systems whose remote builders are all unreachable
+    fn check_builders() -> Vec<String> {
+        debug!("Checking remote builders");
+
+        let configured = builders::configured();
+        if configured.is_empty() {
+            return vec![];
+        }
+        builders::unreachable_systems(&builders::probe_all(&configured), &builders::local_systems())
+    }
+
+    /// Check disk space, inode usage on /nix and the build directory
+    fn check_disk_space() -> (bool, Vec<String>) {
         debug!("Checking disk space");
 
-        let output = Command::new("df")
-            .args(["-h", "/nix/store"])
-            .output();
+        let mut ok = true;
+        let mut warnings = Vec::new();
 
-        match output {
-            Ok(o) if o.status.success() => {
-                let stdout = String::from_utf8_lossy(&o.stdout);
-                for line in stdout.lines().skip(1) {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 5 {
-                        let usage = parts[4].trim_end_matches('%');
-                        if let Ok(pct) = usage.parse::<u32>() {
-                            if pct > 90 {
-                                return (false, Some(format!("Disk usage is at {}%", pct)));
-                            } else if pct > 80 {
-                                return (true, Some(format!("Disk usage is at {}%", pct)));
-                            }
-                        }
-                    }
-                }
-                (true, None)
+        let checks = [("-Pk", "/nix/store", "Disk usage"), ("-Pi", "/nix", "Inode usage")];
+        for (flag, path, label) in checks {
+            let Some(pct) = df_row(flag, path).and_then(|row| usage_percent(&row)) else {
+                continue;
+            };
+            if pct > CRITICAL_USAGE_PCT {
+                ok = false;
+                warnings.push(format!("{} on {} is at {}%", label, path, pct));
+            } else if pct > WARN_USAGE_PCT {
+                warnings.push(format!("{} on {} is at {}%", label, path, pct));
             }
-            _ => (true, None),
         }
+
+        if let Some(warning) = Self::check_build_tmpfs() {
+            warnings.push(warning);
+        }
+
+        (ok, warnings)
+    }
+
+    /// Warn when builds run in a small tmpfs
+    fn check_build_tmpfs() -> Option<String> {
+        let dir = build_dir();
+        let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+        let (mount_point, fstype) = mount_for(&mounts, &dir)?;
+        if fstype != "tmpfs" {
+            return None;
+        }
+
+        let total = df_row("-Pk", &dir.to_string_lossy())?.get(1)?.parse::<u64>().ok()? * 1024;
+        if total >= TMPFS_MIN_BYTES {
+            return None;
+        }
+
+        Some(format!(
+            "Build directory {} is on a {} tmpfs ({}); large builds may fail. \
+             Set 'build-dir = /var/tmp' in nix.conf or point TMPDIR elsewhere",
+            dir.display(),
+            format_bytes(total),
+            mount_point
+        ))
     }
 
     /// Quick check - just essential services
     pub fn quick_check() -> bool {
         let nix_ok = Command::new("nix")
             .arg("--version")
-            .output()
+            .echo().output()
             .map(|o| o.status.success())
             .unwrap_or(false);
 
@@ -209,6 +367,37 @@ impl HealthChecker {
     }
 }
 
+/// Second line of `df <flag> <path>` split into columns
+fn df_row(flag: &str, path: &str) -> Option<Vec<String>> {
+    let output = Command::new("df").args([flag, path]).echo().output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().nth(1)?;
+    Some(line.split_whitespace().map(|s| s.to_string()).collect())
+}
+
+/// The `Use%`/`IUse%` column of a POSIX `df` row
+fn usage_percent(row: &[String]) -> Option<u32> {
+    row.get(4)?.trim_end_matches('%').parse().ok()
+}
+
+/// Mount point and filesystem type containing `path`, from `/proc/mounts`
+fn mount_for(mounts: &str, path: &std::path::Path) -> Option<(String, String)> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = fields.next()?;
+            let fstype = fields.next()?;
+            Some((mount_point.to_string(), fstype.to_string()))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,4 +411,42 @@ mod tests {
             assert!(ok);
         }
     }
+
+    #[test]
+    fn test_pending_check_timeout() {
+        let fast = PendingCheck::spawn("fast", Duration::from_secs(5), || 42);
+        let (value, timing) = fast.wait(0);
+        assert_eq!(value, 42);
+        assert!(!timing.timed_out);
+
+        let slow = PendingCheck::spawn("slow", Duration::from_millis(10), || {
+            std::thread::sleep(Duration::from_millis(500));
+            42
+        });
+        let (value, timing) = slow.wait(0);
+        assert_eq!(value, 0);
+        assert!(timing.timed_out);
+    }
+
+    #[test]
+    fn test_usage_percent() {
+        let row: Vec<String> = "/dev/sda2 1000 950 50 95% /nix".split_whitespace().map(String::from).collect();
+        assert_eq!(usage_percent(&row), Some(95));
+        let inodes: Vec<String> = "/dev/sda2 65536 - - - /nix".split_whitespace().map(String::from).collect();
+        assert_eq!(usage_percent(&inodes), None);
+    }
+
+    #[test]
+    fn test_mount_for() {
+        let mounts = "\
+/dev/sda2 / ext4 rw,relatime 0 0
+tmpfs /tmp tmpfs rw,nosuid,size=2097152k 0 0
+/dev/sda3 /nix ext4 rw,relatime 0 0
+";
+        let (mount, fstype) = mount_for(mounts, std::path::Path::new("/tmp/nix-build-1")).unwrap();
+        assert_eq!((mount.as_str(), fstype.as_str()), ("/tmp", "tmpfs"));
+
+        let (mount, fstype) = mount_for(mounts, std::path::Path::new("/var/tmp")).unwrap();
+        assert_eq!((mount.as_str(), fstype.as_str()), ("/", "ext4"));
+    }
 }
@@ -0,0 +1,154 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! `nixboost maintain`: periodic upkeep run as one pipeline.
+
+use crate::cache::CacheManager;
+use crate::core::config::{GenerationsConfig, MaintainConfig, MaintainStep};
+use crate::core::error::{Result, SystemError};
+use crate::system::disk::free_bytes;
+use crate::system::garbage_collector::{format_bytes, GarbageCollector, GcPlan};
+use crate::system::generations::GenerationManager;
+use crate::utils::command::Echo;
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+/// Disk state compared before and after the pipeline
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DiskSummary {
+    /// Free bytes on the filesystem holding `/nix/store`
+    pub store_free_bytes: Option<u64>,
+    /// Size of the NixBoost cache database
+    pub cache_bytes: u64,
+}
+
+impl DiskSummary {
+    pub fn collect(cache: Option<&CacheManager>) -> Self {
+        Self {
+            store_free_bytes: free_bytes(Path::new("/nix/store")),
+            cache_bytes: cache.map(|c| c.stats().disk_size_bytes).unwrap_or(0),
+        }
+    }
+}
+
+/// How one step went
+#[derive(Debug, Clone, Serialize)]
+pub struct StepOutcome {
+    pub step: MaintainStep,
+    pub success: bool,
+    pub detail: String,
+}
+
+impl StepOutcome {
+    pub fn new(step: MaintainStep, result: Result<String>) -> Self {
+        match result {
+            Ok(detail) => Self { step, success: true, detail },
+            Err(e) => Self { step, success: false, detail: e.to_string() },
+        }
+    }
+}
+
+/// Outcome of a `nixboost maintain` run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MaintainReport {
+    pub steps: Vec<StepOutcome>,
+    pub before: DiskSummary,
+    pub after: DiskSummary,
+}
+
+impl MaintainReport {
+    /// Change in free store space; positive when space was reclaimed
+    pub fn freed_bytes(&self) -> Option<i64> {
+        Some(self.after.store_free_bytes? as i64 - self.before.store_free_bytes? as i64)
+    }
+
+    /// Steps that failed, for the error returned after the report
+    pub fn failures(&self) -> Option<SystemError> {
+        let failed: Vec<String> = self.steps.iter().filter(|s| !s.success).map(|s| step_name(s.step)).collect();
+        (!failed.is_empty()).then(|| SystemError::MaintenanceFailed(failed.join(", ")))
+    }
+}
+
+/// Steps to run: the configured pipeline minus `skip`
+pub fn steps(config: &MaintainConfig, skip: &[MaintainStep]) -> Vec<MaintainStep> {
+    config.steps.iter().copied().filter(|step| !skip.contains(step)).collect()
+}
+
+/// The name a step has in the config file and on the command line
+pub fn step_name(step: MaintainStep) -> String {
+    clap::ValueEnum::to_possible_value(&step).map(|v| v.get_name().to_string()).unwrap_or_default()
+}
+
+/// Remove expired cache entries
+pub fn prune_cache(cache: Option<&CacheManager>) -> Result<String> {
+    match cache {
+        Some(cache) => Ok(format!("Pruned {} expired entries", cache.disk.prune()?)),
+        None => Ok("Cache disabled".to_string()),
+    }
+}
+
+/// Delete the generations the retention policy does not keep
+pub fn delete_generations(generations: &GenerationManager, policy: &GenerationsConfig) -> Result<String> {
+    let deleted = generations.delete_old(policy)?;
+    Ok(format!("Deleted {} generation(s)", deleted))
+}
+
+/// Collect unreferenced store paths, leaving generations alone
+pub fn collect_garbage() -> Result<String> {
+    let result = GarbageCollector::run_plan(&GcPlan::default())?;
+    Ok(format!("Freed {}", format_bytes(result.bytes_freed)))
+}
+
+/// Deduplicate identical files in the store
+pub fn optimise_store() -> Result<String> {
+    let output = Command::new("nix-store").arg("--optimise").echo().output()?;
+    if !output.status.success() {
+        return Err(SystemError::NixCommandFailed {
+            command: "nix-store --optimise".to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }.into());
+    }
+    Ok("Store optimised".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_and_report() {
+        let config = MaintainConfig::default();
+        assert_eq!(steps(&config, &[MaintainStep::Index, MaintainStep::Optimise]), vec![
+            MaintainStep::CachePrune,
+            MaintainStep::Generations,
+            MaintainStep::Gc,
+        ]);
+        assert_eq!(step_name(MaintainStep::CachePrune), "cache-prune");
+
+        let report = MaintainReport {
+            steps: vec![
+                StepOutcome::new(MaintainStep::Gc, Ok("Freed 1.0 GiB".to_string())),
+                StepOutcome::new(MaintainStep::Optimise, Err(SystemError::NixNotFound.into())),
+            ],
+            before: DiskSummary { store_free_bytes: Some(1000), cache_bytes: 0 },
+            after: DiskSummary { store_free_bytes: Some(4000), cache_bytes: 0 },
+        };
+        assert_eq!(report.freed_bytes(), Some(3000));
+        assert_eq!(report.failures().unwrap().to_string(), "Maintenance steps failed: optimise");
+    }
+}
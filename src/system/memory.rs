@@ -0,0 +1,183 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Out-of-memory guard for local builds.
+//!
+//! Each of `max-jobs` parallel builds runs up to `cores` compiler
+//! processes. Large C++ and compiler packages need far more memory per
+//! process than typical builds, so the peak is estimated from the
+//! derivations that will be built and compared with available memory.
+
+use crate::system::capabilities::nix_setting;
+use crate::system::garbage_collector::format_bytes;
+use std::fmt;
+
+/// Memory per compiler process for a typical build
+const TYPICAL_PROCESS_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Memory per compiler process for the packages in [`HEAVY`]
+const HEAVY_PROCESS_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Packages known for memory-hungry builds
+const HEAVY: &[&str] = &[
+    "chromium", "firefox", "thunderbird", "electron", "qtwebengine", "webkitgtk", "libreoffice",
+    "llvm", "clang", "gcc", "rustc", "ghc", "nodejs", "openjdk", "boost", "linux", "tensorflow",
+    "pytorch", "mongodb", "ceph", "julia", "swift",
+];
+
+/// `max-jobs` and `cores`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildParallelism {
+    pub max_jobs: usize,
+    pub cores: usize,
+}
+
+impl BuildParallelism {
+    /// The configured values, with `auto` and `0` meaning every CPU
+    pub fn detect() -> Self {
+        let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let max_jobs = match nix_setting("max-jobs").as_deref() {
+            Some("auto") => cpus,
+            Some(n) => n.parse().unwrap_or(1),
+            None => 1,
+        };
+        let cores = match nix_setting("cores").and_then(|n| n.parse().ok()) {
+            Some(0) | None => cpus,
+            Some(n) => n,
+        };
+        Self { max_jobs: max_jobs.max(1), cores: cores.max(1) }
+    }
+
+    /// Flags overriding the settings for one nix invocation
+    pub fn args(&self) -> Vec<String> {
+        vec!["--max-jobs".to_string(), self.max_jobs.to_string(), "--cores".to_string(), self.cores.to_string()]
+    }
+}
+
+impl fmt::Display for BuildParallelism {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "max-jobs {}, cores {}", self.max_jobs, self.cores)
+    }
+}
+
+/// Builds likely to exhaust memory, with settings that should fit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryWarning {
+    /// Estimated peak with the current settings
+    pub required: u64,
+    pub available: u64,
+    pub current: BuildParallelism,
+    pub suggested: BuildParallelism,
+}
+
+impl fmt::Display for MemoryWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "building with {} may need about {} of memory, but {} is available",
+            self.current,
+            format_bytes(self.required),
+            format_bytes(self.available)
+        )
+    }
+}
+
+/// `MemAvailable` from `/proc/meminfo`
+pub fn available_bytes() -> Option<u64> {
+    parse_meminfo(&std::fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    meminfo.lines().find_map(|line| {
+        let kb = line.strip_prefix("MemAvailable:")?.trim().strip_suffix("kB")?.trim();
+        kb.parse::<u64>().ok().map(|kb| kb * 1024)
+    })
+}
+
+/// Whether the derivation at `drv_path` is one of the [`HEAVY`] packages
+pub fn is_heavy(drv_path: &str) -> bool {
+    let file = drv_path.rsplit('/').next().unwrap_or(drv_path);
+    let name = file.split_once('-').map(|(_, name)| name).unwrap_or(file).trim_end_matches(".drv");
+    HEAVY.iter().any(|heavy| {
+        name.strip_prefix(heavy).is_some_and(|rest| {
+            rest.is_empty()
+                || rest.starts_with("-unwrapped")
+                || rest.strip_prefix('-').is_some_and(|v| v.starts_with(|c: char| c.is_ascii_digit()))
+        })
+    })
+}
+
+fn process_bytes(drv_path: &str) -> u64 {
+    if is_heavy(drv_path) { HEAVY_PROCESS_BYTES } else { TYPICAL_PROCESS_BYTES }
+}
+
+/// Peak memory of building `derivations`: the `max_jobs` most demanding
+/// ones at once, each running `cores` processes
+pub fn peak_bytes(derivations: &[String], parallelism: BuildParallelism) -> u64 {
+    let mut per_build: Vec<u64> = derivations.iter().map(|d| process_bytes(d) * parallelism.cores as u64).collect();
+    per_build.sort_unstable_by(|a, b| b.cmp(a));
+    per_build.iter().take(parallelism.max_jobs).sum()
+}
+
+/// A warning when building `derivations` with `parallelism` is likely to
+/// need more than `available` bytes
+pub fn check(derivations: &[String], parallelism: BuildParallelism, available: u64) -> Option<MemoryWarning> {
+    let required = peak_bytes(derivations, parallelism);
+    if derivations.is_empty() || required <= available {
+        return None;
+    }
+
+    // A heavy build gets the machine to itself; otherwise fit as many
+    // single-core jobs as memory allows
+    let heaviest = derivations.iter().map(|d| process_bytes(d)).max().unwrap_or(TYPICAL_PROCESS_BYTES);
+    let suggested = if heaviest == HEAVY_PROCESS_BYTES {
+        let cores = (available / HEAVY_PROCESS_BYTES) as usize;
+        BuildParallelism { max_jobs: 1, cores: cores.clamp(1, parallelism.cores) }
+    } else {
+        let jobs = (available / (TYPICAL_PROCESS_BYTES * parallelism.cores as u64)) as usize;
+        let cores = if jobs == 0 { (available / TYPICAL_PROCESS_BYTES) as usize } else { parallelism.cores };
+        BuildParallelism { max_jobs: jobs.clamp(1, parallelism.max_jobs), cores: cores.clamp(1, parallelism.cores) }
+    };
+
+    Some(MemoryWarning { required, available, current: parallelism, suggested })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    #[test]
+    fn test_memory_check() {
+        assert_eq!(parse_meminfo("MemTotal: 16 kB\nMemAvailable:    2048 kB\n"), Some(2048 * 1024));
+        assert!(is_heavy("/nix/store/abc-chromium-unwrapped-120.0.drv"));
+        assert!(is_heavy("/nix/store/abc-linux-6.6.1.drv"));
+        assert!(!is_heavy("/nix/store/abc-linux-pam-1.5.drv"));
+        assert!(!is_heavy("/nix/store/abc-hello-2.12.drv"));
+
+        let parallelism = BuildParallelism { max_jobs: 4, cores: 8 };
+        let small = vec!["/nix/store/abc-hello-2.12.drv".to_string()];
+        assert!(check(&small, parallelism, 8 * GIB).is_none());
+
+        let big = vec!["/nix/store/abc-llvm-17.0.6.drv".to_string(), "/nix/store/def-hello-2.12.drv".to_string()];
+        let warning = check(&big, parallelism, 8 * GIB).unwrap();
+        assert_eq!(warning.required, 16 * GIB + 4 * GIB);
+        assert_eq!(warning.suggested, BuildParallelism { max_jobs: 1, cores: 4 });
+        assert!(warning.suggested.args().contains(&"--cores".to_string()));
+    }
+}
@@ -19,7 +19,22 @@
 pub mod health;
 pub mod garbage_collector;
 pub mod generations;
+pub mod disk;
+pub mod daemon;
+pub mod triage;
+pub mod monitor;
+pub mod closure;
+pub mod duplicates;
+pub mod rebuild;
+pub mod watchdog;
+pub mod capabilities;
+pub mod maintain;
+pub mod memory;
+pub mod quarantine;
+pub mod builders;
 
 pub use health::HealthChecker;
 pub use garbage_collector::GarbageCollector;
 pub use generations::GenerationManager;
+pub use disk::DiskGuard;
+pub use daemon::NixDaemon;
@@ -0,0 +1,233 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Continuous health monitoring (`system health --watch`).
+
+use crate::core::error::Result;
+use crate::system::health::{HealthChecker, HealthMode, HealthReport};
+use console::style;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// State of one check at a point in time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckState {
+    pub name: &'static str,
+    pub ok: bool,
+    /// Passing, but with warnings (e.g. a disk filling up)
+    pub warning: bool,
+    pub detail: String,
+}
+
+/// A check whose state differs from the previous run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateChange {
+    pub name: &'static str,
+    pub ok: bool,
+    pub warning: bool,
+    pub detail: String,
+}
+
+impl StateChange {
+    /// Whether this change is a degradation
+    pub fn degraded(&self) -> bool {
+        !self.ok || self.warning
+    }
+}
+
+impl HealthReport {
+    /// Per-check states used to detect changes between runs
+    pub fn states(&self) -> Vec<CheckState> {
+        vec![
+            CheckState {
+                name: "systemd",
+                ok: self.systemd_ok,
+                warning: false,
+                detail: self.systemd_failed.join(", "),
+            },
+            CheckState {
+                name: "nix store",
+                ok: self.nix_store_ok,
+                warning: false,
+                detail: self.nix_store_issues.first().cloned().unwrap_or_default(),
+            },
+            CheckState {
+                name: "nix daemon",
+                ok: self.nix_daemon_ok,
+                warning: false,
+                detail: String::new(),
+            },
+            CheckState {
+                name: "disk",
+                ok: self.disk_space_ok,
+                warning: !self.disk_space_warnings.is_empty(),
+                detail: self.disk_space_warnings.join("; "),
+            },
+            CheckState {
+                name: "builders",
+                ok: self.builders_unreachable.is_empty(),
+                warning: false,
+                detail: self.builders_unreachable.join(", "),
+            },
+        ]
+    }
+}
+
+/// Loops the quick health checks and reports state changes
+pub struct HealthMonitor {
+    interval: Duration,
+    last: Option<Vec<CheckState>>,
+}
+
+impl HealthMonitor {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, last: None }
+    }
+
+    /// Run forever, checking every `interval`
+    pub fn watch(&mut self) -> Result<()> {
+        info!("Watching system health every {:?}", self.interval);
+
+        loop {
+            let report = HealthChecker::run_mode(HealthMode::Quick)?;
+            for change in self.update(report.states()) {
+                report_change(&change);
+            }
+            std::thread::sleep(self.interval);
+        }
+    }
+
+    /// Record new states, returning what changed since the previous run.
+    ///
+    /// The first run reports only failing checks and checks with warnings.
+    pub fn update(&mut self, current: Vec<CheckState>) -> Vec<StateChange> {
+        let changes = current
+            .iter()
+            .filter(|state| match &self.last {
+                Some(prev) => prev
+                    .iter()
+                    .find(|p| p.name == state.name)
+                    .map(|p| p.ok != state.ok || p.warning != state.warning || p.detail != state.detail)
+                    .unwrap_or(true),
+                None => !state.ok || state.warning,
+            })
+            .map(|state| StateChange {
+                name: state.name,
+                ok: state.ok,
+                warning: state.warning,
+                detail: state.detail.clone(),
+            })
+            .collect();
+
+        self.last = Some(current);
+        changes
+    }
+}
+
+/// Log a change and alert on degradation
+fn report_change(change: &StateChange) {
+    let detail = if change.detail.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", change.detail)
+    };
+
+    if change.degraded() {
+        warn!("Health degraded: {}{}", change.name, detail);
+        println!("{} {} degraded{}", style("✗").red(), change.name, detail);
+        #[cfg(feature = "notifications")]
+        notify(change, &detail);
+    } else {
+        info!("Health changed: {}{}", change.name, detail);
+        println!("{} {} ok{}", style("✓").green(), change.name, detail);
+    }
+}
+
+/// Send a desktop notification for a degraded check
+#[cfg(feature = "notifications")]
+fn notify(change: &StateChange, detail: &str) {
+    let result = notify_rust::Notification::new()
+        .summary(&format!("NixBoost: {} degraded", change.name))
+        .body(detail.trim_start_matches(": "))
+        .appname("nixboost")
+        .show();
+
+    if let Err(e) = result {
+        warn!("Failed to send notification: {}", e);
+    }
+}
+
+/// Parse an interval like `30s`, `5m`, `1h` (bare numbers are seconds)
+pub fn parse_interval(s: &str) -> std::result::Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let value: u64 = num.parse().map_err(|_| format!("invalid interval '{}'", s))?;
+
+    let secs = match unit {
+        "" | "s" => value,
+        "m" => value.checked_mul(60).ok_or_else(|| format!("interval '{}' is too long", s))?,
+        "h" => value.checked_mul(3600).ok_or_else(|| format!("interval '{}' is too long", s))?,
+        _ => return Err(format!("invalid interval unit '{}' (use s, m or h)", unit)),
+    };
+
+    if secs == 0 {
+        return Err("interval must be greater than zero".to_string());
+    }
+
+    Ok(Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(name: &'static str, ok: bool) -> CheckState {
+        CheckState { name, ok, warning: false, detail: String::new() }
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_interval("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_interval("1h"), Ok(Duration::from_secs(3600)));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("5d").is_err());
+        assert!(parse_interval("99999999999999999h").is_err());
+    }
+
+    #[test]
+    fn test_monitor_reports_changes() {
+        let mut monitor = HealthMonitor::new(Duration::from_secs(1));
+
+        let first = monitor.update(vec![state("systemd", true), state("nix daemon", false), state("disk", true)]);
+        assert_eq!(first.len(), 1);
+        assert!(first[0].degraded());
+
+        assert!(monitor.update(vec![state("systemd", true), state("nix daemon", false), state("disk", true)]).is_empty());
+
+        let recovered = monitor.update(vec![state("systemd", true), state("nix daemon", true), state("disk", true)]);
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].name, "nix daemon");
+        assert!(!recovered[0].degraded());
+
+        let mut filling = state("disk", true);
+        filling.warning = true;
+        let warned = monitor.update(vec![state("systemd", true), state("nix daemon", true), filling]);
+        assert_eq!(warned.len(), 1);
+        assert!(warned[0].degraded());
+    }
+}
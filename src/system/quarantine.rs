@@ -0,0 +1,156 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Deleted generations kept restorable for a while (`generation restore`).
+//!
+//! Deleting a generation only removes its profile link; until garbage
+//! collection removes the store path, the link can be recreated. The
+//! quarantine remembers what was deleted, in `quarantine.json` in the data
+//! directory, for `[generations] quarantine_days`.
+
+use crate::core::config::Config;
+use crate::core::error::{NixBoostError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A deleted generation that can still be restored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantinedGeneration {
+    /// Profile path the generation belonged to
+    pub profile: String,
+    pub number: u64,
+    /// Store path the generation link pointed to
+    pub store_path: String,
+    /// Unix timestamp the generation was created
+    pub created_at: u64,
+    /// Unix timestamp it was deleted
+    pub deleted_at: u64,
+}
+
+impl QuarantinedGeneration {
+    /// Whether garbage collection has removed the generation's store path
+    pub fn is_collected(&self) -> bool {
+        !Path::new(&self.store_path).exists()
+    }
+}
+
+/// Recently deleted generations
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quarantine {
+    #[serde(default)]
+    pub generations: Vec<QuarantinedGeneration>,
+}
+
+impl Quarantine {
+    pub fn path() -> PathBuf {
+        Config::data_dir().join("quarantine.json")
+    }
+
+    /// The quarantine from the default file; empty if it does not exist
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&content).map_err(|e| NixBoostError::Serialization(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| NixBoostError::Serialization(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Remember a deleted generation, replacing an older entry for the same one
+    pub fn add(&mut self, generation: QuarantinedGeneration) {
+        self.generations.retain(|g| !(g.profile == generation.profile && g.number == generation.number));
+        self.generations.push(generation);
+    }
+
+    /// Forget generations deleted more than `days` ago, returning how many
+    pub fn prune(&mut self, days: u64, now: u64) -> usize {
+        let before = self.generations.len();
+        self.generations.retain(|g| now.saturating_sub(g.deleted_at) < days * 86400);
+        before - self.generations.len()
+    }
+
+    /// Quarantined generations of `profile`, oldest first
+    pub fn for_profile(&self, profile: &str) -> Vec<&QuarantinedGeneration> {
+        let mut generations: Vec<&QuarantinedGeneration> =
+            self.generations.iter().filter(|g| g.profile == profile).collect();
+        generations.sort_by_key(|g| g.number);
+        generations
+    }
+
+    /// Remove and return generation `number` of `profile`
+    pub fn take(&mut self, profile: &str, number: u64) -> Option<QuarantinedGeneration> {
+        let index = self.generations.iter().position(|g| g.profile == profile && g.number == number)?;
+        Some(self.generations.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(number: u64, deleted_at: u64) -> QuarantinedGeneration {
+        QuarantinedGeneration {
+            profile: "/nix/var/nix/profiles/per-user/alice/profile".to_string(),
+            number,
+            store_path: "/nix/store/0123456789abcdfghijklmnpqrsvwxyz-user-environment".to_string(),
+            created_at: 0,
+            deleted_at,
+        }
+    }
+
+    #[test]
+    fn test_quarantine_roundtrip_and_prune() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("quarantine.json");
+        assert!(Quarantine::load_from(&path).unwrap().generations.is_empty());
+
+        let mut quarantine = Quarantine::default();
+        quarantine.add(entry(4, 100));
+        quarantine.add(entry(3, 100));
+        quarantine.add(entry(3, 200));
+        assert_eq!(quarantine.generations.len(), 2);
+        quarantine.save_to(&path).unwrap();
+
+        let mut loaded = Quarantine::load_from(&path).unwrap();
+        assert_eq!(loaded, quarantine);
+        let profile = "/nix/var/nix/profiles/per-user/alice/profile";
+        assert_eq!(loaded.for_profile(profile).iter().map(|g| g.number).collect::<Vec<_>>(), vec![3, 4]);
+
+        // Two days kept; entry 4 was deleted two days and a bit before `now`
+        assert_eq!(loaded.prune(2, 100 + 2 * 86400), 1);
+        assert!(loaded.take(profile, 4).is_none());
+        assert!(loaded.take(profile, 3).unwrap().is_collected());
+        assert!(loaded.generations.is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! NixOS system rebuilds with a closure diff before switching.
+
+use crate::core::error::{Result, SystemError};
+use crate::system::closure::{diff_closures, ClosureChange};
+use crate::system::daemon::privileged;
+use crate::system::generations::SYSTEM_PROFILE;
+use crate::utils::command::Echo;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+use tracing::info;
+
+/// Currently running system
+pub const CURRENT_SYSTEM: &str = "/run/current-system";
+
+/// A built, not yet activated, system configuration
+pub struct SystemBuild {
+    /// Store path of the built system
+    pub path: PathBuf,
+    /// Changes relative to the running system
+    pub changes: Vec<ClosureChange>,
+    /// Holds the `result` out-link that keeps `path` from being collected
+    _workdir: TempDir,
+}
+
+impl SystemBuild {
+    /// Build the configuration with `nixos-rebuild build`, updating channels
+    /// first when `upgrade` is set, and diff it against the running system
+    pub fn build(upgrade: bool) -> Result<Self> {
+        info!("Building system configuration (upgrade: {})", upgrade);
+
+        let workdir = tempfile::Builder::new().prefix("nixboost-rebuild").tempdir()?;
+
+        let mut cmd = if upgrade {
+            // Updating channels needs root
+            let mut cmd = privileged("nixos-rebuild")?;
+            cmd.arg("--upgrade");
+            cmd
+        } else {
+            Command::new("nixos-rebuild")
+        };
+
+        let status = cmd.arg("build").current_dir(workdir.path()).echo().status()?;
+        if !status.success() {
+            return Err(SystemError::NixCommandFailed {
+                command: "nixos-rebuild build".to_string(),
+                stderr: String::new(),
+            }.into());
+        }
+
+        let path = std::fs::canonicalize(workdir.path().join("result"))?;
+        let changes = diff_closures(Path::new(CURRENT_SYSTEM), &path)?;
+        Ok(Self { path, changes, _workdir: workdir })
+    }
+
+    /// Whether the build is the running system. A configuration-only change
+    /// has no package changes but is still a different system.
+    pub fn is_current(&self) -> bool {
+        std::fs::canonicalize(CURRENT_SYSTEM).is_ok_and(|current| current == self.path)
+    }
+
+    /// Changes that touch the kernel, bootloader or display drivers
+    pub fn critical_changes(&self) -> Vec<&ClosureChange> {
+        self.changes.iter().filter(|c| c.critical.is_some()).collect()
+    }
+
+    /// Activate exactly this build: make it the system profile's new
+    /// generation and run its `switch-to-configuration`. `nixos-rebuild
+    /// switch` would evaluate again and could activate something else.
+    pub fn switch(&self) -> Result<()> {
+        info!("Switching to {:?}", self.path);
+
+        let status = privileged("nix-env")?
+            .args(["-p", SYSTEM_PROFILE, "--set"])
+            .arg(&self.path)
+            .echo().status()?;
+        if !status.success() {
+            return Err(SystemError::NixCommandFailed {
+                command: "nix-env --set".to_string(),
+                stderr: String::new(),
+            }.into());
+        }
+
+        let activate = self.path.join("bin/switch-to-configuration");
+        let status = privileged(&activate.to_string_lossy())?.arg("switch").echo().status()?;
+        if !status.success() {
+            return Err(SystemError::NixCommandFailed {
+                command: "switch-to-configuration switch".to_string(),
+                stderr: String::new(),
+            }.into());
+        }
+
+        Ok(())
+    }
+}
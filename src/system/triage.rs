@@ -0,0 +1,227 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Classification and repair of Nix store verification issues.
+
+use crate::core::error::{Result, SystemError};
+use crate::system::daemon::{is_permission_error, privileged};
+use crate::utils::command::Echo;
+use std::fmt;
+use std::process::Command;
+use tracing::{debug, info};
+
+/// Category of a store verification issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    /// Path contents do not match the recorded hash
+    HashMismatch,
+    /// Path is registered as valid but missing on disk
+    MissingPath,
+    /// Path cannot be read due to ownership/permissions
+    Permissions,
+    /// Anything we cannot classify
+    Other,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueKind::HashMismatch => write!(f, "hash mismatch"),
+            IssueKind::MissingPath => write!(f, "missing path"),
+            IssueKind::Permissions => write!(f, "permissions"),
+            IssueKind::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// Repair action for an issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairAction {
+    /// `nix-store --repair-path` - fetch a good copy from a substituter
+    Resubstitute,
+    /// `nix-store --realise --repair` on the path's deriver
+    Rebuild,
+    /// `chown -R` on the affected store entry, to the owner of `/nix/store`
+    /// (root on multi-user installs, the user on single-user ones)
+    Chown,
+}
+
+impl fmt::Display for RepairAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepairAction::Resubstitute => write!(f, "re-substitute"),
+            RepairAction::Rebuild => write!(f, "rebuild"),
+            RepairAction::Chown => write!(f, "chown"),
+        }
+    }
+}
+
+/// A classified verification issue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreIssue {
+    pub kind: IssueKind,
+    /// Affected store path, when one could be extracted
+    pub path: Option<String>,
+    /// Original message from `nix-store --verify`
+    pub message: String,
+}
+
+impl StoreIssue {
+    /// Classify a single line of `nix-store --verify` output
+    pub fn classify(line: &str) -> Self {
+        let lower = line.to_lowercase();
+        let kind = if lower.contains("was modified") || lower.contains("hash mismatch") {
+            IssueKind::HashMismatch
+        } else if lower.contains("disappeared") || lower.contains("does not exist") || lower.contains("no such file") {
+            IssueKind::MissingPath
+        } else if lower.contains("permission denied") || lower.contains("not permitted") {
+            IssueKind::Permissions
+        } else {
+            IssueKind::Other
+        };
+
+        Self {
+            kind,
+            path: store_path(line),
+            message: line.trim().to_string(),
+        }
+    }
+
+    /// Suggested repair, if the issue can be fixed automatically
+    pub fn action(&self) -> Option<RepairAction> {
+        self.path.as_ref()?;
+        match self.kind {
+            IssueKind::HashMismatch => Some(RepairAction::Resubstitute),
+            IssueKind::MissingPath => Some(RepairAction::Rebuild),
+            IssueKind::Permissions => Some(RepairAction::Chown),
+            IssueKind::Other => None,
+        }
+    }
+
+    /// Run the suggested repair
+    pub fn repair(&self) -> Result<()> {
+        let (Some(action), Some(path)) = (self.action(), self.path.as_deref()) else {
+            return Err(SystemError::StoreVerificationFailed(
+                format!("no automatic repair for: {}", self.message)
+            ).into());
+        };
+
+        info!("Repairing {} ({})", path, action);
+
+        let output = match action {
+            RepairAction::Resubstitute => privileged("nix-store")?
+                .args(["--repair-path", path])
+                .echo().output()?,
+            RepairAction::Rebuild => {
+                let target = deriver(path).unwrap_or_else(|| path.to_string());
+                privileged("nix-store")?
+                    .args(["--realise", "--repair", &target])
+                    .echo().output()?
+            }
+            RepairAction::Chown => privileged("chown")?
+                .args(["-R", &store_owner()?, store_root(path)])
+                .echo().output()?,
+        };
+
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if is_permission_error(&stderr) {
+            return Err(SystemError::PermissionDenied(format!("cannot {} {}", action, path)).into());
+        }
+
+        Err(SystemError::NixCommandFailed {
+            command: format!("{} {}", action, path),
+            stderr,
+        }.into())
+    }
+}
+
+/// Classify every issue line reported by the store check
+pub fn triage(issues: &[String]) -> Vec<StoreIssue> {
+    issues.iter().map(|line| StoreIssue::classify(line)).collect()
+}
+
+/// Extract the first `/nix/store/...` path from a message
+fn store_path(line: &str) -> Option<String> {
+    let start = line.find("/nix/store/")?;
+    let path: String = line[start..]
+        .chars()
+        .take_while(|c| !c.is_whitespace() && !matches!(c, '\'' | '"' | '`' | ':' | ','))
+        .collect();
+    (path.len() > "/nix/store/".len()).then_some(path)
+}
+
+/// Top-level store entry containing `path`
+fn store_root(path: &str) -> &str {
+    let rest = &path["/nix/store/".len()..];
+    match rest.find('/') {
+        Some(idx) => &path[..("/nix/store/".len() + idx)],
+        None => path,
+    }
+}
+
+/// `uid:gid` owning the store directory
+fn store_owner() -> Result<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let meta = std::fs::metadata("/nix/store")?;
+    Ok(format!("{}:{}", meta.uid(), meta.gid()))
+}
+
+/// Derivation that produced a store path
+fn deriver(path: &str) -> Option<String> {
+    let output = Command::new("nix-store")
+        .args(["--query", "--deriver", path])
+        .echo().output()
+        .ok()?;
+
+    let drv = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    debug!("Deriver of {}: {}", path, drv);
+    (output.status.success() && drv.ends_with(".drv")).then_some(drv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_issues() {
+        let issues = triage(&[
+            "error: path '/nix/store/abc-hello-2.12' was modified! expected hash 'sha256:1', got 'sha256:2'".to_string(),
+            "warning: path '/nix/store/def-git-2.44' disappeared, but it still has valid referrers!".to_string(),
+            "error: opening file '/nix/store/ghi-curl/bin/curl': Permission denied".to_string(),
+            "error: some unexpected failure".to_string(),
+        ]);
+
+        assert_eq!(issues[0].kind, IssueKind::HashMismatch);
+        assert_eq!(issues[0].path.as_deref(), Some("/nix/store/abc-hello-2.12"));
+        assert_eq!(issues[0].action(), Some(RepairAction::Resubstitute));
+
+        assert_eq!(issues[1].kind, IssueKind::MissingPath);
+        assert_eq!(issues[1].action(), Some(RepairAction::Rebuild));
+
+        assert_eq!(issues[2].kind, IssueKind::Permissions);
+        assert_eq!(issues[2].path.as_deref(), Some("/nix/store/ghi-curl/bin/curl"));
+        assert_eq!(issues[2].action(), Some(RepairAction::Chown));
+        assert_eq!(store_root("/nix/store/ghi-curl/bin/curl"), "/nix/store/ghi-curl");
+
+        assert_eq!(issues[3].kind, IssueKind::Other);
+        assert_eq!(issues[3].action(), None);
+    }
+}
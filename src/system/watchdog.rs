@@ -0,0 +1,141 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Auto-rollback watchdog for system switches.
+//!
+//! The rollback is scheduled as a transient systemd timer so it still fires
+//! when the SSH session that started the switch is lost.
+
+use crate::core::error::{Result, SystemError};
+use crate::system::daemon::privileged;
+use crate::system::generations::SYSTEM_PROFILE;
+use crate::utils::command::Echo;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use tracing::info;
+
+/// Transient unit name for the pending rollback
+const ROLLBACK_UNIT: &str = "nixboost-rollback";
+
+/// A scheduled rollback to a previous system
+pub struct RollbackWatchdog {
+    previous: PathBuf,
+}
+
+impl RollbackWatchdog {
+    /// Remember the running system so it can be restored
+    pub fn capture(current_system: &Path) -> Result<Self> {
+        let previous = std::fs::canonicalize(current_system)?;
+        Ok(Self { previous })
+    }
+
+    /// Schedule the rollback to fire after `timeout`
+    pub fn arm(&self, timeout: Duration) -> Result<()> {
+        info!("Arming rollback to {:?} in {:?}", self.previous, timeout);
+
+        let output = privileged("systemd-run")?
+            .args([
+                "--unit",
+                ROLLBACK_UNIT,
+                "--description",
+                "NixBoost automatic rollback",
+                &format!("--on-active={}", timeout.as_secs()),
+                "/bin/sh",
+                "-c",
+                &rollback_script(&self.previous),
+            ])
+            .echo().output()?;
+
+        if !output.status.success() {
+            return Err(SystemError::RollbackFailed(format!(
+                "could not schedule rollback: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )).into());
+        }
+
+        Ok(())
+    }
+
+    /// Roll back right away instead of waiting for the timer. The timer is
+    /// only stopped once the rollback succeeded, so a failed attempt leaves
+    /// it armed as a fallback.
+    pub fn trigger(&self) -> Result<()> {
+        let status = privileged("sh")?
+            .args(["-c", &rollback_script(&self.previous)])
+            .echo().status()?;
+
+        if !status.success() {
+            return Err(SystemError::RollbackFailed(
+                "immediate rollback failed; the rollback timer is still armed".to_string()
+            ).into());
+        }
+
+        disarm()?;
+        Ok(())
+    }
+}
+
+/// Cancel a pending rollback. Returns `false` if none was scheduled.
+pub fn disarm() -> Result<bool> {
+    if !is_armed() {
+        return Ok(false);
+    }
+
+    let status = privileged("systemctl")?
+        .args(["stop", &format!("{}.timer", ROLLBACK_UNIT)])
+        .echo().status()?;
+
+    if !status.success() {
+        return Err(SystemError::RollbackFailed("could not cancel pending rollback".to_string()).into());
+    }
+    Ok(true)
+}
+
+/// Whether a rollback timer is pending
+pub fn is_armed() -> bool {
+    Command::new("systemctl")
+        .args(["is-active", "--quiet", &format!("{}.timer", ROLLBACK_UNIT)])
+        .echo().status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// Shell command restoring `previous` as the system profile and activating it
+fn rollback_script(previous: &Path) -> String {
+    let prev = previous.display();
+    format!(
+        "{prev}/sw/bin/nix-env -p {profile} --set {prev} && {prev}/bin/switch-to-configuration switch",
+        prev = prev,
+        profile = SYSTEM_PROFILE
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollback_script() {
+        let script = rollback_script(Path::new("/nix/store/abc-nixos-system-host-24.05"));
+        assert_eq!(
+            script,
+            "/nix/store/abc-nixos-system-host-24.05/sw/bin/nix-env -p /nix/var/nix/profiles/system \
+             --set /nix/store/abc-nixos-system-host-24.05 && \
+             /nix/store/abc-nixos-system-host-24.05/bin/switch-to-configuration switch"
+        );
+    }
+}
@@ -18,6 +18,10 @@
 
 pub mod progress;
 pub mod output;
+pub mod theme;
+pub mod porcelain;
+#[cfg(feature = "tui")]
+pub mod tui;
 
 pub use progress::ProgressManager;
 pub use output::Output;
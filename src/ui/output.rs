@@ -16,16 +16,48 @@
 
 //! Output formatting for NixBoost.
 
-use crate::cli::args::OutputFormat;
-use crate::core::types::{Package, SearchResult};
+use crate::cache::CacheVerifyReport;
+use crate::cli::args::{OutputFormat, PorcelainVersion, SearchColumn};
+use crate::core::history::OperationRecord;
+use crate::core::types::{GCPreview, Generation, OperationResult, OperationStatus, Package, PackageSource, SearchResult};
+use crate::nur::client::NurIndexStatus;
+use crate::package::buildlog::{self, LogLine};
+use crate::package::changelog::{ReleaseNotes, MAX_COMMITS};
+use crate::package::deps::DepNode;
+use crate::package::diagnose::{self, Diagnosis};
+use crate::package::journal::JournalEntry;
+use crate::package::manifest::ImportPlan;
+use crate::package::profile::{resolve_element, ElementMatch, ProfileElement};
+use crate::package::profiles::ProfileStatus;
+use crate::package::provides::Provider;
+use crate::package::size::InstallSize;
+use crate::package::upgrade::UpgradePreview;
+use crate::package::which::StorePathInfo;
+use crate::search::index::IndexStatus;
+use crate::ui::porcelain::{self, ChangeRecord, ElementRecord, PackageRecord, PorcelainRecord};
+use crate::package::pins::Pins;
+use crate::package::staging::Staging;
+use crate::system::builders::BuilderStatus;
+use crate::system::duplicates::{print_duplicates, Duplicate};
+use crate::system::maintain::{step_name, MaintainReport};
+use crate::system::garbage_collector::{format_bytes, GCImpact, GCResult, GarbageCollector, GcPlan};
+use crate::system::generations::{GenerationDiff, GenerationManager};
+use crate::system::quarantine::QuarantinedGeneration;
+use crate::ui::progress::ProgressManager;
+use crate::ui::theme::{DiffKind, DiffTheme};
+use crate::utils::time::{format_age, unix_now};
 use comfy_table::{Table, presets::UTF8_FULL, presets::ASCII_BORDERS_ONLY_CONDENSED};
-use console::style;
+use console::{style, Color};
 use serde::Serialize;
+use std::time::Duration;
 
 /// Output formatter
 pub struct Output {
     format: OutputFormat,
     colors: bool,
+    theme: DiffTheme,
+    porcelain: Option<PorcelainVersion>,
+    progress: ProgressManager,
 }
 
 impl Output {
@@ -34,6 +66,9 @@ impl Output {
         Self {
             format,
             colors: true,
+            theme: DiffTheme::default(),
+            porcelain: None,
+            progress: ProgressManager::new(),
         }
     }
 
@@ -41,31 +76,570 @@ impl Output {
     pub fn no_colors(mut self, disable: bool) -> Self {
         if disable {
             self.colors = false;
+            self.theme = self.theme.no_colors(true);
         }
         self
     }
 
+    /// Set the diff theme
+    pub fn theme(mut self, theme: DiffTheme) -> Self {
+        self.theme = theme.no_colors(!self.colors);
+        self
+    }
+
+    /// Use the versioned porcelain format for packages, profile elements and changes
+    pub fn porcelain(mut self, version: Option<PorcelainVersion>) -> Self {
+        self.porcelain = version;
+        if version.is_some() {
+            self.colors = false;
+        }
+        self
+    }
+
+    /// Share `progress` for spinners and bars; they stay hidden for
+    /// machine-readable output
+    pub fn with_progress(mut self, progress: ProgressManager) -> Self {
+        let machine = self.is_structured() || self.is_porcelain();
+        self.progress = progress.enabled(!machine);
+        self
+    }
+
+    /// Progress bars for long-running phases
+    pub fn progress(&self) -> &ProgressManager {
+        &self.progress
+    }
+
+    /// Whether output is machine-readable (JSON or NDJSON)
+    pub fn is_structured(&self) -> bool {
+        matches!(self.format, OutputFormat::Json | OutputFormat::Ndjson)
+    }
+
+    /// Whether porcelain output was requested
+    pub fn is_porcelain(&self) -> bool {
+        self.porcelain.is_some()
+    }
+
+    /// Print porcelain records; returns `false` when porcelain is off
+    fn print_porcelain<T: PorcelainRecord>(&self, items: &[T]) -> bool {
+        match self.porcelain {
+            Some(version) => {
+                print!("{}", porcelain::render(version, self.is_structured(), items));
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Print packages
     pub fn print_packages(&self, packages: &[Package]) {
+        let records: Vec<PackageRecord> = packages.iter().map(PackageRecord::from).collect();
+        if self.print_porcelain(&records) {
+            return;
+        }
+
         match self.format {
             OutputFormat::Human => self.print_packages_human(packages),
-            OutputFormat::Json => self.print_json(packages),
+            OutputFormat::Json | OutputFormat::Ndjson => self.print_records(packages),
             OutputFormat::Plain => self.print_packages_plain(packages),
         }
     }
 
+    /// Print NUR packages as a table of repository, name, version and
+    /// description, marking those that match an `installed` profile element
+    pub fn print_nur_packages(&self, packages: &[Package], installed: &[ProfileElement]) {
+        if self.format != OutputFormat::Human || self.porcelain.is_some() {
+            self.print_packages(packages);
+            return;
+        }
+
+        let rows: Vec<Vec<String>> = packages
+            .iter()
+            .map(|pkg| {
+                let repo = match &pkg.source {
+                    PackageSource::Nur { repo } if self.colors => style(repo).fg(owner_color(repo)).to_string(),
+                    PackageSource::Nur { repo } => repo.clone(),
+                    other => other.to_string(),
+                };
+                let mut name = pkg.name.clone();
+                if resolve_element(installed, &pkg.name) != ElementMatch::None {
+                    let badge = if self.colors { style(" [installed]").cyan().to_string() } else { " [installed]".to_string() };
+                    name.push_str(&badge);
+                }
+                vec![repo, name, pkg.version.clone(), truncate(&pkg.description, DESCRIPTION_WIDTH)]
+            })
+            .collect();
+        self.print_table(vec!["Repository", "Package", "Version", "Description"], rows);
+    }
+
     /// Print search results
     pub fn print_search_results(&self, results: &[SearchResult]) {
+        let records: Vec<PackageRecord> = results.iter().map(|r| PackageRecord::from(&r.package)).collect();
+        if self.print_porcelain(&records) {
+            return;
+        }
+
         match self.format {
             OutputFormat::Human => self.print_search_human(results),
-            OutputFormat::Json => {
+            OutputFormat::Json | OutputFormat::Ndjson => {
                 let packages: Vec<&Package> = results.iter().map(|r| &r.package).collect();
-                self.print_json(&packages);
+                self.print_records(&packages);
             }
             OutputFormat::Plain => self.print_search_plain(results),
         }
     }
 
+    /// Print search results as a table of the given columns
+    pub fn print_search_table(&self, results: &[SearchResult], columns: &[SearchColumn]) {
+        if self.is_structured() || self.is_porcelain() {
+            self.print_search_results(results);
+            return;
+        }
+
+        let columns = if columns.is_empty() { &SearchColumn::DEFAULT[..] } else { columns };
+        let rows: Vec<Vec<String>> = results
+            .iter()
+            .map(|r| columns.iter().map(|&c| search_cell(&r.package, c)).collect())
+            .collect();
+        self.print_table(columns.iter().map(|c| c.header()).collect(), rows);
+    }
+
+    /// Print a generation list
+    pub fn print_generations(&self, generations: &[Generation]) {
+        if self.is_structured() {
+            self.print_records(generations);
+        } else {
+            GenerationManager::print_list(generations);
+        }
+    }
+
+    /// Print the generations `generation restore` can bring back
+    pub fn print_quarantined(&self, generations: &[QuarantinedGeneration]) {
+        if self.is_structured() {
+            self.print_records(generations);
+            return;
+        }
+        if generations.is_empty() {
+            self.info("No deleted generations to restore");
+            return;
+        }
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Generation", "Deleted", "Path"]);
+        let now = unix_now();
+        for gen in generations {
+            let deleted = format!("{} ago", format_age(std::time::Duration::from_secs(now.saturating_sub(gen.deleted_at))));
+            let path = if gen.is_collected() {
+                style(format!("{} (collected)", gen.store_path)).dim().to_string()
+            } else {
+                gen.store_path.clone()
+            };
+            table.add_row(vec![gen.number.to_string(), deleted, path]);
+        }
+        println!("{}", table);
+    }
+
+    /// Print build log lines, paged when interactive
+    pub fn print_build_log(&self, lines: &[LogLine], use_pager: bool) -> crate::core::error::Result<()> {
+        if self.is_structured() {
+            self.print_records(lines);
+            return Ok(());
+        }
+        if lines.is_empty() {
+            self.info("No matching log lines");
+            return Ok(());
+        }
+
+        let text: Vec<String> = lines.iter().map(LogLine::highlighted).collect();
+        buildlog::page(&text.join("\n"), use_pager)
+    }
+
+    /// Print the packages providing a command or file
+    pub fn print_providers(&self, query: &str, providers: &[Provider]) {
+        if self.is_structured() {
+            self.print_records(providers);
+            return;
+        }
+        if providers.is_empty() {
+            self.info(&format!("No package provides '{}'", query));
+            return;
+        }
+
+        let width = providers.iter().map(|p| p.package.len()).max().unwrap_or(0);
+        for provider in providers {
+            println!("  {:<width$}  {}", style(&provider.package).bold(), style(&provider.path).dim(), width = width);
+        }
+    }
+
+    /// Print where a package lives in the store
+    pub fn print_which(&self, info: &StorePathInfo) {
+        if self.is_structured() {
+            self.print_record(info);
+            return;
+        }
+
+        println!("{}", style(&info.package).bold());
+        for path in &info.store_paths {
+            println!("  {}", path);
+        }
+        match &info.element {
+            Some(element) => println!("  Installed:  {} (profile element '{}')", style("yes").green(), element),
+            None => println!("  Installed:  no"),
+        }
+        let size = info.closure_bytes.map(format_bytes).unwrap_or_else(|| "unknown".to_string());
+        if info.in_store {
+            println!("  Size:       {} closure", size);
+        } else {
+            let download = info.download_bytes.map(format_bytes).unwrap_or_else(|| "unknown".to_string());
+            println!("  Size:       {} closure, {} to download (not in the store)", size, download);
+        }
+    }
+
+    /// Print what changed in each upgraded package
+    pub fn print_release_notes(&self, notes: &[ReleaseNotes]) {
+        for note in notes {
+            println!();
+            println!("{} {} → {}", style(&note.name).bold(), note.from, note.to);
+            if let Some(url) = &note.changelog {
+                println!("  Changelog: {}", style(url).cyan());
+            }
+            for commit in note.commits.iter().take(MAX_COMMITS) {
+                println!("  {} {}", style(&commit.sha).dim(), commit.summary);
+            }
+            let more = note.commits.len().saturating_sub(MAX_COMMITS);
+            match &note.history_url {
+                Some(url) if more > 0 => println!("  {} more: {}", more, style(url).cyan()),
+                Some(url) if note.commits.is_empty() => println!("  nixpkgs history: {}", style(url).cyan()),
+                _ if note.is_empty() => println!("  {}", style("No release notes found").dim()),
+                _ => {}
+            }
+        }
+        if !notes.is_empty() {
+            println!();
+        }
+    }
+
+    /// Print named profiles
+    pub fn print_profiles(&self, profiles: &[ProfileStatus]) {
+        if self.is_structured() {
+            self.print_records(profiles);
+            return;
+        }
+        if profiles.is_empty() {
+            self.info("No named profiles; create one with 'nixboost profile create <name>'");
+            return;
+        }
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Profile", "Status", "Generations", "Path"]);
+        for profile in profiles {
+            let status = if profile.active { style("(active)").green().to_string() } else { String::new() };
+            table.add_row(vec![
+                profile.name.clone(),
+                status,
+                profile.generations.to_string(),
+                profile.path.display().to_string(),
+            ]);
+        }
+        println!("{}", table);
+    }
+
+    /// Print remote builder probes and systems left without a reachable builder
+    pub fn print_builders(&self, statuses: &[BuilderStatus], unreachable: &[String]) {
+        if self.is_structured() {
+            self.print_records(statuses);
+            return;
+        }
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        table.set_header(vec!["Builder", "Systems", "Jobs", "Status", "Load", "Notes"]);
+        for status in statuses {
+            let state = if status.reachable {
+                style(format!("✓ {}ms", status.latency_ms.unwrap_or(0))).green().to_string()
+            } else {
+                style("✗ unreachable").red().to_string()
+            };
+            let unsupported = status.unsupported_systems();
+            let notes = match &status.error {
+                Some(error) => error.clone(),
+                None if !unsupported.is_empty() => format!("remote Nix lacks {}", unsupported.join(", ")),
+                None => String::new(),
+            };
+            table.add_row(vec![
+                status.builder.uri.clone(),
+                status.builder.systems.join(", "),
+                status.builder.max_jobs.to_string(),
+                state,
+                status.load.map(|l| format!("{:.2}", l)).unwrap_or_else(|| "-".into()),
+                notes,
+            ]);
+        }
+        println!("{}", table);
+
+        if !unreachable.is_empty() {
+            self.error(&format!("No reachable builder for: {}", unreachable.join(", ")));
+        }
+    }
+
+    /// Print a generation diff
+    pub fn print_generation_diff(&self, diff: &GenerationDiff) {
+        if self.is_structured() {
+            self.print_record(diff);
+        } else {
+            diff.print(&self.theme);
+        }
+    }
+
+    /// Print a garbage collection result
+    pub fn print_gc_result(&self, result: &GCResult) {
+        if self.is_structured() {
+            self.print_record(result);
+        } else {
+            GarbageCollector::print_result(result);
+        }
+    }
+
+    /// Print the outcome of `cache verify`
+    pub fn print_cache_verify(&self, report: &CacheVerifyReport) {
+        if self.is_structured() {
+            self.print_record(report);
+            return;
+        }
+
+        for error in &report.integrity_errors {
+            self.warn(&format!("Integrity check: {}", error));
+        }
+        for key in &report.corrupted {
+            self.warn(&format!("Corrupted entry: {}", key));
+        }
+        if report.is_ok() {
+            self.success(&format!("Cache OK ({} entries checked)", report.checked));
+        } else {
+            self.info(&format!(
+                "{} entries checked, {} corrupted, {} removed",
+                report.checked,
+                report.corrupted.len(),
+                report.removed
+            ));
+        }
+        if !report.integrity_errors.is_empty() {
+            self.info("The database itself is damaged; run 'nixboost cache clear' to start over");
+        }
+    }
+
+    /// Print the steps of `nixboost maintain` and the disk space they freed
+    pub fn print_maintain_report(&self, report: &MaintainReport) {
+        if self.is_structured() {
+            self.print_record(report);
+            return;
+        }
+
+        for outcome in &report.steps {
+            let mark = if outcome.success { style("✓").green() } else { style("✗").red() };
+            println!("{} {:<12} {}", mark, step_name(outcome.step), outcome.detail);
+        }
+
+        if let (Some(before), Some(after)) = (report.before.store_free_bytes, report.after.store_free_bytes) {
+            let freed = report.freed_bytes().unwrap_or(0);
+            let sign = if freed < 0 { "-" } else { "+" };
+            self.info(&format!(
+                "Free space on /nix/store: {} -> {} ({}{})",
+                format_bytes(before),
+                format_bytes(after),
+                sign,
+                format_bytes(freed.unsigned_abs())
+            ));
+        }
+        self.info(&format!(
+            "Cache: {} -> {}",
+            format_bytes(report.before.cache_bytes),
+            format_bytes(report.after.cache_bytes)
+        ));
+    }
+
+    /// Print the generations a garbage collection deletes and what it frees
+    pub fn print_gc_plan(&self, plan: &GcPlan) {
+        if self.is_structured() {
+            self.print_record(plan);
+            return;
+        }
+
+        if plan.generations.is_empty() {
+            self.info("No generations to delete");
+        } else {
+            let numbers: Vec<String> = plan.generations.iter().map(|n| n.to_string()).collect();
+            self.info(&format!("Would delete generation(s) {}", numbers.join(", ")));
+        }
+        self.info(&format!(
+            "Would free about {} across {} store paths ({} already unreferenced)",
+            format_bytes(plan.estimated_bytes),
+            plan.paths.len(),
+            format_bytes(plan.dead_bytes)
+        ));
+        if let Some(target) = plan.max_freed {
+            if plan.estimated_bytes < target {
+                self.warn(&format!(
+                    "Only about {} can be freed without deleting the current generation; target was {}",
+                    format_bytes(plan.estimated_bytes),
+                    format_bytes(target)
+                ));
+            }
+        }
+    }
+
+    /// Print packages installed both in the profile and the system
+    pub fn print_duplicates(&self, duplicates: &[Duplicate]) {
+        if self.is_structured() {
+            self.print_records(duplicates);
+        } else {
+            print_duplicates(duplicates);
+        }
+    }
+
+    /// Print the space a package removal would reclaim
+    pub fn print_gc_impact(&self, impact: &GCImpact) {
+        if self.is_structured() {
+            self.print_record(impact);
+        } else {
+            impact.print();
+        }
+    }
+
+    /// Print an upgrade preview
+    pub fn print_upgrade_preview(&self, preview: &UpgradePreview) {
+        let records: Vec<ChangeRecord> = preview.changes.iter().map(ChangeRecord::from).collect();
+        if self.print_porcelain(&records) {
+            return;
+        }
+
+        if self.is_structured() {
+            self.print_record(preview);
+        } else {
+            preview.print();
+        }
+    }
+
+    /// Print the NUR index status
+    pub fn print_nur_status(&self, status: &NurIndexStatus) {
+        if self.is_structured() {
+            self.print_record(status);
+        } else {
+            status.print();
+        }
+    }
+
+    /// Print a dependency tree
+    pub fn print_dep_tree(&self, tree: &DepNode, depth: Option<usize>) {
+        if self.is_structured() {
+            self.print_record(tree);
+        } else {
+            tree.print(depth);
+        }
+    }
+
+    /// Print the local package index status
+    pub fn print_index_status(&self, status: &IndexStatus) {
+        if self.is_structured() {
+            self.print_record(status);
+        } else {
+            status.print();
+        }
+    }
+
+    /// Print journaled installs, one row per package
+    pub fn print_journal(&self, entries: &[JournalEntry]) {
+        if self.is_structured() {
+            self.print_records(entries);
+            return;
+        }
+
+        let now = crate::utils::time::unix_now();
+        let mut rows = Vec::new();
+        for entry in entries {
+            let age = std::time::Duration::from_secs(now.saturating_sub(entry.timestamp));
+            for pkg in &entry.packages {
+                let revision = pkg.revision.as_deref().unwrap_or("-");
+                rows.push(vec![
+                    format!("{} ago", crate::utils::time::format_age(age)),
+                    pkg.installable.clone(),
+                    revision.chars().take(12).collect(),
+                    pkg.reproducible_ref().to_string(),
+                ]);
+            }
+        }
+        self.print_table(vec!["When", "Installable", "Revision", "Reproduce with"], rows);
+    }
+
+    /// Print a garbage collection preview
+    pub fn print_gc_preview(&self, preview: &GCPreview) {
+        if self.is_structured() {
+            self.print_record(preview);
+        } else {
+            let delta = match (preview.delta_bytes, preview.previous_at) {
+                (Some(delta), Some(at)) => {
+                    let age = Duration::from_secs(unix_now().saturating_sub(at));
+                    let sign = if delta < 0 { "-" } else { "+" };
+                    format!(" ({}{} since the preview {} ago)", sign, format_bytes(delta.unsigned_abs()), format_age(age))
+                }
+                _ => String::new(),
+            };
+            self.info(&format!(
+                "Would delete {} paths, freeing {}{}",
+                preview.paths.len(),
+                preview.size_human(),
+                delta
+            ));
+            for (path, size) in &preview.largest {
+                println!("   {:>10}  {}", format_bytes(*size), path);
+            }
+        }
+    }
+
+    /// Print search results grouped by attribute namespace.
+    ///
+    /// Namespaced groups are collapsed to `collapse_at` entries unless
+    /// `expand` is set; top-level packages are always shown in full.
+    pub fn print_grouped_results(&self, results: &[SearchResult], expand: bool, collapse_at: usize) {
+        if self.format != OutputFormat::Human || self.is_porcelain() {
+            self.print_search_results(results);
+            return;
+        }
+
+        for (namespace, group) in crate::search::engine::group_by_namespace(results) {
+            if namespace.is_empty() {
+                let group: Vec<SearchResult> = group.into_iter().cloned().collect();
+                self.print_search_human(&group);
+                continue;
+            }
+
+            let header = format!("{} ({} result{})", namespace, group.len(), if group.len() == 1 { "" } else { "s" });
+            if self.colors {
+                println!("\n{} {}", style("::").bold().cyan(), style(header).bold());
+            } else {
+                println!("\n:: {}", header);
+            }
+
+            let shown = if expand { group.len() } else { group.len().min(collapse_at) };
+            let visible: Vec<SearchResult> = group[..shown].iter().map(|r| (*r).clone()).collect();
+            self.print_search_human(&visible);
+
+            if shown < group.len() {
+                let hint = format!(
+                    "... {} more (use --scope {} to expand)",
+                    group.len() - shown,
+                    namespace
+                );
+                if self.colors {
+                    println!("    {}", style(hint).dim());
+                } else {
+                    println!("    {}", hint);
+                }
+            }
+        }
+    }
+
     /// Print packages in human-readable format
     fn print_packages_human(&self, packages: &[Package]) {
         for pkg in packages {
@@ -111,6 +685,19 @@ impl Output {
                     pkg.source, pkg.name, pkg.version, pkg.description
                 );
             }
+
+            if !result.alternatives.is_empty() {
+                let sources = result.alternatives
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if self.colors {
+                    println!("    {} {}", style("also in:").dim(), style(sources).dim());
+                } else {
+                    println!("    also in: {}", sources);
+                }
+            }
         }
     }
 
@@ -129,6 +716,28 @@ impl Output {
         }
     }
 
+    /// Print a single record: pretty JSON, or one compact line for NDJSON
+    fn print_record<T: Serialize + ?Sized>(&self, data: &T) {
+        if self.format == OutputFormat::Ndjson {
+            if let Ok(json) = serde_json::to_string(data) {
+                println!("{}", json);
+            }
+        } else {
+            self.print_json(data);
+        }
+    }
+
+    /// Print a list: a JSON array, or one line per item for NDJSON
+    fn print_records<T: Serialize>(&self, items: &[T]) {
+        if self.format == OutputFormat::Ndjson {
+            for item in items {
+                self.print_record(item);
+            }
+        } else {
+            self.print_json(items);
+        }
+    }
+
     /// Print a table
     pub fn print_table(&self, headers: Vec<&str>, rows: Vec<Vec<String>>) {
         match self.format {
@@ -145,7 +754,7 @@ impl Output {
                 }
                 println!("{}", table);
             }
-            OutputFormat::Json => {
+            OutputFormat::Json | OutputFormat::Ndjson => {
                 // Convert to JSON array of objects
                 let objects: Vec<_> = rows
                     .iter()
@@ -157,7 +766,7 @@ impl Output {
                             .collect::<std::collections::HashMap<_, _>>()
                     })
                     .collect();
-                self.print_json(&objects);
+                self.print_records(&objects);
             }
         }
     }
@@ -180,24 +789,235 @@ impl Output {
         }
     }
 
-    /// Print an info message
+    /// Print an info message (to stderr for structured formats)
     pub fn info(&self, message: &str) {
-        if self.colors {
+        if self.is_structured() || self.is_porcelain() {
+            eprintln!(":: {}", message);
+        } else if self.colors {
             println!("{} {}", style("::").bold().cyan(), message);
         } else {
             println!(":: {}", message);
         }
     }
 
-    /// Print a success message
+    /// Print a success message (to stderr for structured formats)
     pub fn success(&self, message: &str) {
-        if self.colors {
+        if self.is_structured() || self.is_porcelain() {
+            eprintln!("+ {}", message);
+        } else if self.colors {
             println!("{} {}", style("✓").green().bold(), message);
         } else {
             println!("+ {}", message);
         }
     }
 
+    /// Print profile elements (porcelain), or just their names
+    pub fn print_profile_elements(&self, elements: &[ProfileElement]) {
+        let records: Vec<ElementRecord> = elements.iter().map(ElementRecord::from).collect();
+        if !self.print_porcelain(&records) {
+            let mut names: Vec<String> = elements.iter().map(|el| el.name.clone()).collect();
+            names.sort();
+            self.print_installed(&names);
+        }
+    }
+
+    /// Print recorded operations, newest first
+    pub fn print_operations(&self, records: &[OperationRecord]) {
+        if self.is_structured() {
+            self.print_records(records);
+            return;
+        }
+        let rows: Vec<Vec<String>> = records
+            .iter()
+            .map(|r| {
+                let age = Duration::from_secs(unix_now().saturating_sub(r.timestamp));
+                vec![
+                    format!("{} ago", format_age(age)),
+                    r.result.operation.to_string(),
+                    r.result.packages.join(", "),
+                    r.result.status.to_string(),
+                    format!("{:.1}s", r.result.duration_ms as f64 / 1000.0),
+                    r.result.error.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        self.print_table(vec!["When", "Operation", "Packages", "Status", "Took", "Error"], rows);
+    }
+
+    /// Print the closing summary of an operation
+    pub fn print_operation_result(&self, result: &OperationResult) {
+        if self.is_structured() {
+            self.print_record(result);
+            return;
+        }
+        if self.is_porcelain() {
+            return;
+        }
+
+        let took = format!("{:.1}s", result.duration_ms as f64 / 1000.0);
+        let subject = if result.packages.is_empty() {
+            result.operation.to_string()
+        } else {
+            format!("{} {}", result.operation, result.packages.join(", "))
+        };
+        let line = match result.status {
+            OperationStatus::Success => format!("{} finished in {}", subject, took),
+            OperationStatus::Skipped => format!(
+                "{} skipped ({}) in {}",
+                subject,
+                result.message.as_deref().unwrap_or("nothing to do"),
+                took
+            ),
+            _ => format!("{} {} after {}", subject, result.status, took),
+        };
+        if self.colors {
+            println!("{} {}", style("::").bold().cyan(), style(line).dim());
+        } else {
+            println!(":: {}", line);
+        }
+    }
+
+    /// Print pinned packages
+    pub fn print_pins(&self, pins: &Pins) {
+        if self.is_structured() {
+            self.print_record(pins);
+            return;
+        }
+        if pins.pins.is_empty() {
+            println!("No pinned packages");
+            return;
+        }
+        let rows: Vec<Vec<String>> = pins
+            .pins
+            .iter()
+            .map(|(name, pin)| vec![name.clone(), pin.rev.clone(), pin.flake_ref.clone()])
+            .collect();
+        self.print_table(vec!["Package", "Revision", "Installed from"], rows);
+    }
+
+    /// Print staged changes
+    pub fn print_staged(&self, staging: &Staging) {
+        if self.is_structured() {
+            self.print_record(staging);
+            return;
+        }
+        if staging.is_empty() {
+            println!("Nothing staged");
+            return;
+        }
+        for name in &staging.remove {
+            println!("  {} {}", style("-").red(), name);
+        }
+        for name in &staging.install {
+            println!("  {} {}", style("+").green(), name);
+        }
+    }
+
+    /// Print the package list of a `nixboost declare` file
+    pub fn print_declared(&self, path: &std::path::Path, packages: &[String]) {
+        if self.is_structured() {
+            self.print_records(packages);
+            return;
+        }
+        println!("{} {}", style(":: declared in").bold(), style(path.display()).bold());
+        for pkg in packages {
+            println!("   {}", pkg);
+        }
+    }
+
+    /// Print post-install setup hints (to stderr for structured formats)
+    pub fn print_hints(&self, hints: &[(&str, &[String])]) {
+        for (package, lines) in hints {
+            self.info(&format!("{} may need some setup:", package));
+            for line in lines.iter() {
+                if self.is_structured() || self.is_porcelain() {
+                    eprintln!("   {}", line);
+                } else {
+                    println!("   {} {}", style("→").cyan(), line);
+                }
+            }
+        }
+    }
+
+    /// Print the download and on-disk size of each package and their total
+    pub fn print_install_sizes(&self, sizes: &[(String, Option<InstallSize>)]) {
+        #[derive(Serialize)]
+        struct SizeRecord<'a> {
+            package: &'a str,
+            #[serde(flatten)]
+            size: Option<InstallSize>,
+        }
+
+        if self.is_structured() {
+            let records: Vec<SizeRecord> = sizes
+                .iter()
+                .map(|(package, size)| SizeRecord { package, size: *size })
+                .collect();
+            self.print_records(&records);
+            return;
+        }
+
+        let download = |size: &InstallSize| size.download_bytes.map(format_bytes).unwrap_or_else(|| "?".into());
+        let width = sizes.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        println!("{}", style("Install size:").bold());
+        for (name, size) in sizes {
+            match size {
+                Some(size) => println!(
+                    "  {:<width$}  {:>10} download  {:>10} on disk",
+                    name,
+                    download(size),
+                    format_bytes(size.closure_bytes)
+                ),
+                None => println!("  {:<width$}  {}", name, style("size unknown").dim()),
+            }
+        }
+
+        let known: Vec<InstallSize> = sizes.iter().filter_map(|(_, size)| *size).collect();
+        if sizes.len() > 1 && !known.is_empty() {
+            let total = InstallSize::total(&known);
+            println!(
+                "  {}  {:>10} download  {:>10} on disk",
+                style(format!("{:<width$}", "Total")).bold(),
+                download(&total),
+                format_bytes(total.closure_bytes)
+            );
+        }
+    }
+
+    /// Print what `import` would change
+    pub fn print_import_plan(&self, plan: &ImportPlan) {
+        if self.is_structured() {
+            self.print_record(plan);
+            return;
+        }
+
+        if plan.install.is_empty() {
+            self.success(&format!("All {} package(s) in the manifest are installed", plan.present.len()));
+        } else {
+            println!("{}", self.theme.header(DiffKind::Added));
+            for entry in &plan.install {
+                println!("{}", self.theme.line(DiffKind::Added, &format!("{} ({})", entry.name, entry.install_ref())));
+            }
+        }
+        if !plan.extra.is_empty() {
+            self.info(&format!("Installed but not in the manifest (kept): {}", plan.extra.join(", ")));
+        }
+    }
+
+    /// Print likely causes of `package` failing to build
+    pub fn print_diagnoses(&self, package: &str, diagnoses: &[Diagnosis]) {
+        for diagnosis in diagnoses {
+            self.warn(&format!("{} (build log line {})", diagnosis.failure, diagnosis.line));
+            if self.is_structured() || self.is_porcelain() {
+                eprintln!("   {}", diagnosis.suggestion);
+            } else {
+                println!("   {} {}", style("→").cyan(), diagnosis.suggestion);
+            }
+        }
+        self.info(&format!("Full log: nixboost logs {}", package));
+        self.info(&format!("Known issues: {}", diagnose::issues_url(package)));
+    }
+
     /// Print installed packages list
     pub fn print_installed(&self, packages: &[String]) {
         match self.format {
@@ -207,7 +1027,7 @@ impl Output {
                     println!("   {}", pkg);
                 }
             }
-            OutputFormat::Json => self.print_json(packages),
+            OutputFormat::Json | OutputFormat::Ndjson => self.print_records(packages),
             OutputFormat::Plain => {
                 for pkg in packages {
                     println!("{}", pkg);
@@ -240,6 +1060,35 @@ pub fn print_success(msg: &str) {
     println!("{}", style(format!("✓ {}", msg)).green());
 }
 
+/// Descriptions are cut to this many characters in tables
+const DESCRIPTION_WIDTH: usize = 60;
+
+fn truncate(text: &str, width: usize) -> String {
+    let mut truncated: String = text.chars().take(width).collect();
+    if text.chars().count() > width {
+        truncated.push('…');
+    }
+    truncated
+}
+
+/// Stable color for a NUR repository owner, so one owner's packages stand out
+fn owner_color(owner: &str) -> Color {
+    const PALETTE: [Color; 6] = [Color::Cyan, Color::Magenta, Color::Yellow, Color::Blue, Color::Green, Color::Red];
+    let hash = owner.bytes().fold(0usize, |acc, b| acc.wrapping_mul(31).wrapping_add(b as usize));
+    PALETTE[hash % PALETTE.len()]
+}
+
+fn search_cell(pkg: &Package, column: SearchColumn) -> String {
+    match column {
+        SearchColumn::Name => pkg.name.clone(),
+        SearchColumn::Version => pkg.version.clone(),
+        SearchColumn::Source => pkg.source.to_string(),
+        SearchColumn::Description => truncate(&pkg.description, DESCRIPTION_WIDTH),
+        SearchColumn::License => pkg.license.clone().unwrap_or_default(),
+        SearchColumn::Homepage => pkg.homepage.clone().unwrap_or_default(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -254,4 +1103,31 @@ mod tests {
         ];
         output.print_packages(&packages);
     }
+
+    #[test]
+    fn test_structured_formats() {
+        assert!(Output::new(OutputFormat::Json).is_structured());
+        assert!(Output::new(OutputFormat::Ndjson).is_structured());
+        assert!(!Output::new(OutputFormat::Plain).is_structured());
+
+        let output = Output::new(OutputFormat::Ndjson);
+        output.print_packages(&[Package::new("test", "1.0", "A test package")]);
+        output.print_gc_preview(&GCPreview::default());
+    }
+
+    #[test]
+    fn test_search_cells() {
+        let mut pkg = Package::new("ripgrep", "14.1.0", "x".repeat(80));
+        pkg.license = Some("MIT".to_string());
+        assert_eq!(search_cell(&pkg, SearchColumn::License), "MIT");
+        assert_eq!(search_cell(&pkg, SearchColumn::Homepage), "");
+        assert_eq!(search_cell(&pkg, SearchColumn::Description).chars().count(), DESCRIPTION_WIDTH + 1);
+        assert_eq!(truncate("short", DESCRIPTION_WIDTH), "short");
+    }
+
+    #[test]
+    fn test_owner_color() {
+        assert_eq!(owner_color("mic92"), owner_color("mic92"));
+        Output::new(OutputFormat::Human).no_colors(true).print_nur_packages(&[Package::new("hello", "1.0", "")], &[]);
+    }
 }
@@ -0,0 +1,220 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Versioned machine-readable output (`--porcelain v1`).
+//!
+//! The v1 contract is one record per line with tab-separated fields in a
+//! fixed order; `\t`, `\n` and `\` inside fields are backslash-escaped and
+//! missing values are empty. With `--output json` the same records are
+//! printed as `{"porcelain":"v1","kind":...,"items":[...]}`.
+//!
+//! | kind      | fields                                                  |
+//! |-----------|---------------------------------------------------------|
+//! | `package` | name, version, source, license, homepage, description   |
+//! | `element` | name, version, attr_path, original_url                  |
+//! | `change`  | name, kind, from, to                                    |
+//!
+//! Fields may be appended in a later minor release but are never reordered
+//! or removed within v1.
+
+use crate::cli::args::PorcelainVersion;
+use crate::core::types::Package;
+use crate::package::profile::ProfileElement;
+use crate::package::upgrade::{parse_store_name, VersionChange};
+use serde::Serialize;
+
+/// A record with a stable field order
+pub trait PorcelainRecord: Serialize {
+    const KIND: &'static str;
+
+    fn fields(&self) -> Vec<String>;
+}
+
+/// `package` record (search, info)
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageRecord {
+    pub name: String,
+    pub version: String,
+    pub source: String,
+    pub license: Option<String>,
+    pub homepage: Option<String>,
+    pub description: String,
+}
+
+impl From<&Package> for PackageRecord {
+    fn from(pkg: &Package) -> Self {
+        Self {
+            name: pkg.name.clone(),
+            version: pkg.version.clone(),
+            source: pkg.source.to_string(),
+            license: pkg.license.clone(),
+            homepage: pkg.homepage.clone(),
+            description: pkg.description.clone(),
+        }
+    }
+}
+
+impl PorcelainRecord for PackageRecord {
+    const KIND: &'static str = "package";
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.version.clone(),
+            self.source.clone(),
+            self.license.clone().unwrap_or_default(),
+            self.homepage.clone().unwrap_or_default(),
+            self.description.clone(),
+        ]
+    }
+}
+
+/// `element` record (list)
+#[derive(Debug, Clone, Serialize)]
+pub struct ElementRecord {
+    pub name: String,
+    pub version: String,
+    pub attr_path: Option<String>,
+    pub original_url: Option<String>,
+}
+
+impl From<&ProfileElement> for ElementRecord {
+    fn from(el: &ProfileElement) -> Self {
+        Self {
+            name: el.name.clone(),
+            version: el.store_paths.first().map(|p| parse_store_name(p).1).unwrap_or_default(),
+            attr_path: el.attr_path.clone(),
+            original_url: el.original_url.clone(),
+        }
+    }
+}
+
+impl PorcelainRecord for ElementRecord {
+    const KIND: &'static str = "element";
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.version.clone(),
+            self.attr_path.clone().unwrap_or_default(),
+            self.original_url.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+/// `change` record (outdated)
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeRecord {
+    pub name: String,
+    pub kind: String,
+    pub from: String,
+    pub to: Option<String>,
+}
+
+impl From<&VersionChange> for ChangeRecord {
+    fn from(change: &VersionChange) -> Self {
+        let kind = serde_json::to_value(change.kind)
+            .ok()
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_default();
+        Self {
+            name: change.name.clone(),
+            kind,
+            from: change.from.clone(),
+            to: change.to.clone(),
+        }
+    }
+}
+
+impl PorcelainRecord for ChangeRecord {
+    const KIND: &'static str = "change";
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.kind.clone(),
+            self.from.clone(),
+            self.to.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    porcelain: &'static str,
+    kind: &'static str,
+    items: &'a [T],
+}
+
+impl PorcelainVersion {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PorcelainVersion::V1 => "v1",
+        }
+    }
+}
+
+/// Render records as TSV lines, or as a single-line JSON envelope
+pub fn render<T: PorcelainRecord>(version: PorcelainVersion, json: bool, items: &[T]) -> String {
+    if json {
+        let envelope = Envelope { porcelain: version.as_str(), kind: T::KIND, items };
+        return serde_json::to_string(&envelope).unwrap_or_default() + "\n";
+    }
+
+    items
+        .iter()
+        .map(|item| {
+            let fields: Vec<String> = item.fields().iter().map(|f| escape(f)).collect();
+            fields.join("\t") + "\n"
+        })
+        .collect()
+}
+
+fn escape(field: &str) -> String {
+    field.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::upgrade::ChangeKind;
+
+    #[test]
+    fn test_package_tsv() {
+        let mut pkg = Package::from_nixpkgs("hello", "2.12.1", "Prints\t\"Hello\"\nworld");
+        pkg.license = Some("GPL-3.0-or-later".to_string());
+
+        let out = render(PorcelainVersion::V1, false, &[PackageRecord::from(&pkg)]);
+        assert_eq!(out, "hello\t2.12.1\tnixpkgs\tGPL-3.0-or-later\t\tPrints\\t\"Hello\"\\nworld\n");
+    }
+
+    #[test]
+    fn test_change_json_envelope() {
+        let change = VersionChange {
+            name: "firefox".to_string(),
+            kind: ChangeKind::Upgrade,
+            from: "121.0".to_string(),
+            to: Some("122.0".to_string()),
+            renamed_to: None,
+        };
+
+        let out = render(PorcelainVersion::V1, true, &[ChangeRecord::from(&change)]);
+        assert_eq!(
+            out,
+            "{\"porcelain\":\"v1\",\"kind\":\"change\",\"items\":[{\"name\":\"firefox\",\"kind\":\"upgrade\",\"from\":\"121.0\",\"to\":\"122.0\"}]}\n"
+        );
+    }
+}
@@ -16,12 +16,18 @@
 
 //! Progress bar management for NixBoost.
 
+use crate::core::config::UiConfig;
 use indicatif::{ProgressBar, ProgressStyle, MultiProgress};
 use std::time::Duration;
 
-/// Progress bar manager
+/// Progress bar manager. Clones share the same bars.
+#[derive(Clone)]
 pub struct ProgressManager {
     multi: MultiProgress,
+    /// When off, every bar is hidden
+    enabled: bool,
+    /// Tick interval overriding each bar's default
+    refresh: Option<Duration>,
 }
 
 impl ProgressManager {
@@ -29,25 +35,52 @@ impl ProgressManager {
     pub fn new() -> Self {
         Self {
             multi: MultiProgress::new(),
+            enabled: true,
+            refresh: None,
         }
     }
 
+    /// Progress manager following `[ui] progress` and `progress_refresh_ms`
+    pub fn from_config(ui: &UiConfig) -> Self {
+        let mut progress = Self::new().enabled(ui.progress);
+        progress.refresh = (ui.progress_refresh_ms > 0).then(|| Duration::from_millis(ui.progress_refresh_ms));
+        progress
+    }
+
+    /// Show bars at all
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = self.enabled && enabled;
+        self
+    }
+
+    fn add(&self, pb: ProgressBar) -> ProgressBar {
+        if self.enabled {
+            self.multi.add(pb)
+        } else {
+            ProgressBar::hidden()
+        }
+    }
+
+    fn tick(&self, default_ms: u64) -> Duration {
+        self.refresh.unwrap_or(Duration::from_millis(default_ms))
+    }
+
     /// Create a spinner for indeterminate operations
     pub fn spinner(&self, message: &str) -> ProgressBar {
-        let pb = self.multi.add(ProgressBar::new_spinner());
+        let pb = self.add(ProgressBar::new_spinner());
         pb.set_style(
             ProgressStyle::with_template("{prefix:.bold.dim} {spinner} {msg}")
                 .unwrap()
                 .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ")
         );
         pb.set_message(message.to_string());
-        pb.enable_steady_tick(Duration::from_millis(80));
+        pb.enable_steady_tick(self.tick(80));
         pb
     }
 
     /// Create a progress bar for determinate operations
     pub fn bar(&self, total: u64, message: &str) -> ProgressBar {
-        let pb = self.multi.add(ProgressBar::new(total));
+        let pb = self.add(ProgressBar::new(total));
         pb.set_style(
             ProgressStyle::with_template(
                 "{prefix:.bold.dim} [{bar:40.cyan/blue}] {pos}/{len} {msg}"
@@ -61,7 +94,7 @@ impl ProgressManager {
 
     /// Create a download progress bar
     pub fn download(&self, total: u64, filename: &str) -> ProgressBar {
-        let pb = self.multi.add(ProgressBar::new(total));
+        let pb = self.add(ProgressBar::new(total));
         pb.set_style(
             ProgressStyle::with_template(
                 "{prefix:.bold.dim} {spinner} [{bar:30.green/dim}] {bytes}/{total_bytes} ({eta}) {msg}"
@@ -72,20 +105,20 @@ impl ProgressManager {
         );
         pb.set_prefix("↓");
         pb.set_message(filename.to_string());
-        pb.enable_steady_tick(Duration::from_millis(100));
+        pb.enable_steady_tick(self.tick(100));
         pb
     }
 
     /// Create a simple status spinner
     pub fn status(&self, message: &str) -> ProgressBar {
-        let pb = self.multi.add(ProgressBar::new_spinner());
+        let pb = self.add(ProgressBar::new_spinner());
         pb.set_style(
             ProgressStyle::with_template("{spinner:.cyan} {msg}")
                 .unwrap()
                 .tick_chars("⣾⣽⣻⢿⡿⣟⣯⣷")
         );
         pb.set_message(message.to_string());
-        pb.enable_steady_tick(Duration::from_millis(100));
+        pb.enable_steady_tick(self.tick(100));
         pb
     }
 
@@ -101,32 +134,6 @@ impl Default for ProgressManager {
     }
 }
 
-/// Create a simple spinner (standalone)
-pub fn spinner(message: &str) -> ProgressBar {
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::with_template("{prefix:.bold.dim} {spinner} {msg}")
-            .unwrap()
-            .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ ")
-    );
-    pb.set_message(message.to_string());
-    pb.enable_steady_tick(Duration::from_millis(80));
-    pb
-}
-
-/// Create a simple progress bar (standalone)
-pub fn bar(total: u64) -> ProgressBar {
-    let pb = ProgressBar::new(total);
-    pb.set_style(
-        ProgressStyle::with_template(
-            "{prefix:.bold.dim} [{bar:40.cyan/blue}] {pos}/{len} {msg}"
-        )
-        .unwrap()
-        .progress_chars("█▓▒░")
-    );
-    pb
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,5 +143,8 @@ mod tests {
         let pm = ProgressManager::new();
         let spinner = pm.spinner("test");
         spinner.finish_and_clear();
+
+        let ui = UiConfig { progress: false, ..Default::default() };
+        assert!(ProgressManager::from_config(&ui).bar(10, "test").is_hidden());
     }
 }
@@ -0,0 +1,164 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Diff palettes and symbols.
+
+use crate::core::config::ThemeConfig;
+use console::{style, Color};
+use tracing::warn;
+
+/// Color palette for diffs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Palette {
+    /// Green for additions, red for removals
+    #[default]
+    Default,
+    /// Blue/orange, distinguishable with red-green color blindness
+    ColorBlind,
+    /// No colors; symbols and weight only
+    Monochrome,
+}
+
+impl Palette {
+    /// Parse a palette name from config, falling back to the default
+    pub fn parse(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "default" => Palette::Default,
+            "colorblind" | "color-blind" | "cb" => Palette::ColorBlind,
+            "monochrome" | "mono" | "none" => Palette::Monochrome,
+            other => {
+                warn!("Unknown palette '{}', using default", other);
+                Palette::Default
+            }
+        }
+    }
+
+    fn color(self, kind: DiffKind) -> Option<Color> {
+        match (self, kind) {
+            (Palette::Default, DiffKind::Added) => Some(Color::Green),
+            (Palette::Default, DiffKind::Removed) => Some(Color::Red),
+            (Palette::Default, DiffKind::Changed) => Some(Color::Yellow),
+            (Palette::ColorBlind, DiffKind::Added) => Some(Color::Color256(33)),
+            (Palette::ColorBlind, DiffKind::Removed) => Some(Color::Color256(208)),
+            (Palette::ColorBlind, DiffKind::Changed) => Some(Color::Magenta),
+            (Palette::Monochrome, _) => None,
+        }
+    }
+}
+
+/// Kind of diff entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl DiffKind {
+    fn label(self) -> &'static str {
+        match self {
+            DiffKind::Added => "Added",
+            DiffKind::Removed => "Removed",
+            DiffKind::Changed => "Changed",
+        }
+    }
+}
+
+/// Palette and symbols used when rendering diffs
+#[derive(Debug, Clone)]
+pub struct DiffTheme {
+    palette: Palette,
+    added: String,
+    removed: String,
+    changed: String,
+}
+
+impl DiffTheme {
+    /// Build a theme from the `[ui.theme]` config section
+    pub fn from_config(config: &ThemeConfig) -> Self {
+        Self {
+            palette: Palette::parse(&config.palette),
+            added: config.added_symbol.clone(),
+            removed: config.removed_symbol.clone(),
+            changed: config.changed_symbol.clone(),
+        }
+    }
+
+    /// Drop colors, keeping the configured symbols
+    pub fn no_colors(mut self, disable: bool) -> Self {
+        if disable {
+            self.palette = Palette::Monochrome;
+        }
+        self
+    }
+
+    /// Prefix symbol for an entry kind
+    pub fn symbol(&self, kind: DiffKind) -> &str {
+        match kind {
+            DiffKind::Added => &self.added,
+            DiffKind::Removed => &self.removed,
+            DiffKind::Changed => &self.changed,
+        }
+    }
+
+    /// Section header, e.g. `Added:`
+    pub fn header(&self, kind: DiffKind) -> String {
+        let label = format!("{}:", kind.label());
+        match self.palette.color(kind) {
+            Some(color) => style(label).fg(color).bold().to_string(),
+            None => style(label).bold().to_string(),
+        }
+    }
+
+    /// A single `  <symbol> <text>` line
+    pub fn line(&self, kind: DiffKind, text: &str) -> String {
+        let line = format!("{} {}", self.symbol(kind), text);
+        match self.palette.color(kind) {
+            Some(color) => format!("  {}", style(line).fg(color)),
+            None => format!("  {}", line),
+        }
+    }
+}
+
+impl Default for DiffTheme {
+    fn default() -> Self {
+        Self::from_config(&ThemeConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_parse() {
+        assert_eq!(Palette::parse("colorblind"), Palette::ColorBlind);
+        assert_eq!(Palette::parse("Mono"), Palette::Monochrome);
+        assert_eq!(Palette::parse("neon"), Palette::Default);
+    }
+
+    #[test]
+    fn test_monochrome_uses_symbols_only() {
+        let config = ThemeConfig {
+            palette: "monochrome".to_string(),
+            added_symbol: "[add]".to_string(),
+            ..ThemeConfig::default()
+        };
+        let theme = DiffTheme::from_config(&config);
+        assert_eq!(theme.line(DiffKind::Added, "ripgrep"), "  [add] ripgrep");
+        assert_eq!(theme.line(DiffKind::Removed, "htop"), "  - htop");
+    }
+}
@@ -0,0 +1,217 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Interactive browser state and key handling.
+
+use crate::core::types::{Package, SearchResult};
+use crate::search::SearchEngine;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashSet};
+
+/// What a marked package will have done to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Install,
+    Remove,
+}
+
+/// Result of handling a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+    Quit,
+    Apply,
+}
+
+/// Marked changes, applied together once the terminal is restored
+#[derive(Debug, Clone, Default)]
+pub struct Transaction {
+    pub install: Vec<Package>,
+    pub remove: Vec<String>,
+}
+
+/// State of the package browser
+pub struct App {
+    packages: Vec<Package>,
+    installed: HashSet<String>,
+    engine: SearchEngine,
+    pub query: String,
+    pub results: Vec<SearchResult>,
+    pub selected: usize,
+    /// Marks keyed by `Package::display_name`, which is unique across sources
+    pub marks: BTreeMap<String, (Mark, Package)>,
+    pub show_details: bool,
+    pub status: Option<String>,
+}
+
+impl App {
+    pub fn new(packages: Vec<Package>, installed: HashSet<String>) -> Self {
+        Self {
+            packages,
+            installed,
+            engine: SearchEngine::new(),
+            query: String::new(),
+            results: Vec::new(),
+            selected: 0,
+            marks: BTreeMap::new(),
+            show_details: false,
+            status: None,
+        }
+    }
+
+    /// Set the maximum number of results shown
+    pub fn max_results(mut self, max: usize) -> Self {
+        self.engine = self.engine.max_results(max);
+        self
+    }
+
+    pub fn package_count(&self) -> usize {
+        self.packages.len()
+    }
+
+    pub fn is_installed(&self, pkg: &Package) -> bool {
+        self.installed.contains(&pkg.name)
+    }
+
+    pub fn mark_of(&self, pkg: &Package) -> Option<Mark> {
+        self.marks.get(&pkg.display_name()).map(|(mark, _)| *mark)
+    }
+
+    pub fn selected_package(&self) -> Option<&Package> {
+        self.results.get(self.selected).map(|r| &r.package)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        self.status = None;
+
+        match key.code {
+            KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('c') if ctrl => return Action::Quit,
+            KeyCode::Char('a') if ctrl => {
+                if !self.marks.is_empty() {
+                    return Action::Apply;
+                }
+                self.status = Some("Nothing marked".to_string());
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.results.len() => self.selected += 1,
+            KeyCode::Tab => self.toggle_mark(),
+            KeyCode::Enter => self.show_details = !self.show_details,
+            KeyCode::Backspace if self.query.pop().is_some() => self.refresh(),
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.refresh();
+            }
+            _ => {}
+        }
+
+        Action::Continue
+    }
+
+    /// Mark the selected package for install, or for removal if it is installed.
+    /// Marking it again clears the mark.
+    fn toggle_mark(&mut self) {
+        let Some(pkg) = self.selected_package().cloned() else {
+            return;
+        };
+
+        let key = pkg.display_name();
+        if self.marks.remove(&key).is_none() {
+            let mark = if self.is_installed(&pkg) { Mark::Remove } else { Mark::Install };
+            self.marks.insert(key, (mark, pkg));
+        }
+    }
+
+    /// Re-run the search for the current query
+    fn refresh(&mut self) {
+        self.results = if self.query.trim().is_empty() {
+            Vec::new()
+        } else {
+            self.engine.search(self.query.trim(), &self.packages).unwrap_or_default()
+        };
+        self.selected = 0;
+    }
+
+    /// Collect marks into a transaction
+    pub fn transaction(&self) -> Transaction {
+        let mut tx = Transaction::default();
+        for (mark, pkg) in self.marks.values() {
+            match mark {
+                Mark::Install => tx.install.push(pkg.clone()),
+                Mark::Remove => tx.remove.push(pkg.name.clone()),
+            }
+        }
+        tx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn app() -> App {
+        let packages = vec![
+            Package::from_nixpkgs("ripgrep", "14.1.0", "Fast grep"),
+            Package::from_nixpkgs("ripgrep-all", "0.10.6", "ripgrep for PDFs and archives"),
+            Package::from_nixpkgs("fd", "10.1.0", "Simple find alternative"),
+        ];
+        App::new(packages, HashSet::from(["fd".to_string()]))
+    }
+
+    #[test]
+    fn test_typing_searches() {
+        let mut app = app();
+        for c in "rip".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(app.selected_package().unwrap().name, "ripgrep");
+
+        app.handle_key(key(KeyCode::Down));
+        assert_eq!(app.selected_package().unwrap().name, "ripgrep-all");
+
+        for _ in 0..3 {
+            app.handle_key(key(KeyCode::Backspace));
+        }
+        assert!(app.results.is_empty());
+    }
+
+    #[test]
+    fn test_marks_make_one_transaction() {
+        let mut app = app();
+        assert_eq!(app.handle_key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL)), Action::Continue);
+
+        app.handle_key(key(KeyCode::Char('r')));
+        app.handle_key(key(KeyCode::Tab));
+        app.handle_key(key(KeyCode::Backspace));
+        app.handle_key(key(KeyCode::Char('f')));
+        app.handle_key(key(KeyCode::Char('d')));
+        app.handle_key(key(KeyCode::Tab));
+
+        assert_eq!(app.handle_key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL)), Action::Apply);
+        let tx = app.transaction();
+        assert_eq!(tx.install.len(), 1);
+        assert_eq!(tx.remove, vec!["fd".to_string()]);
+
+        // Tab again clears the mark
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.transaction().remove.len(), 0);
+    }
+}
@@ -0,0 +1,58 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Interactive package browser (`nixboost tui`).
+//!
+//! Search results update as the query is typed; packages marked for install
+//! or removal are returned as one [`Transaction`] after the terminal has been
+//! restored, so installs run with normal output.
+
+pub mod app;
+pub mod view;
+
+pub use app::{App, Transaction};
+
+use crate::core::error::Result;
+use app::Action;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+
+/// Run the browser until the user quits (`None`) or applies their marks
+pub fn run(app: &mut App) -> Result<Option<Transaction>> {
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, app);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> Result<Option<Transaction>> {
+    loop {
+        terminal.draw(|frame| view::draw(frame, app))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match app.handle_key(key) {
+            Action::Continue => {}
+            Action::Quit => return Ok(None),
+            Action::Apply => return Ok(Some(app.transaction())),
+        }
+    }
+}
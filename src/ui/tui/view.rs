@@ -0,0 +1,123 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rendering of the package browser.
+
+use super::app::{App, Mark};
+use crate::core::types::{Package, PackageSource};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+
+const HELP: &str = "Tab mark · Enter details · Ctrl-a apply · Esc quit";
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [input, body, status] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let title = format!(" Search {} packages ", app.package_count());
+    frame.render_widget(
+        Paragraph::new(app.query.as_str()).block(Block::default().borders(Borders::ALL).title(title)),
+        input,
+    );
+    frame.set_cursor_position((input.x + 1 + app.query.chars().count() as u16, input.y + 1));
+
+    let (list_area, details_area) = if app.show_details {
+        let [list, details] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(body);
+        (list, Some(details))
+    } else {
+        (body, None)
+    };
+
+    let items: Vec<ListItem> = app.results.iter().map(|r| result_line(app, &r.package)).collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} results ", app.results.len())))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected((!app.results.is_empty()).then_some(app.selected));
+    frame.render_stateful_widget(list, list_area, &mut state);
+
+    if let Some(area) = details_area {
+        let text = app.selected_package().map(details).unwrap_or_default();
+        frame.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title(" Details ")),
+            area,
+        );
+    }
+
+    let installs = app.marks.values().filter(|(m, _)| *m == Mark::Install).count();
+    let removes = app.marks.len() - installs;
+    let status_text = match &app.status {
+        Some(message) => message.clone(),
+        None => format!("+{} -{} marked │ {}", installs, removes, HELP),
+    };
+    frame.render_widget(Paragraph::new(status_text).style(Style::default().fg(Color::DarkGray)), status);
+}
+
+fn result_line<'a>(app: &App, pkg: &'a Package) -> ListItem<'a> {
+    let mark = match app.mark_of(pkg) {
+        Some(Mark::Install) => Span::styled("[+] ", Style::default().fg(Color::Green)),
+        Some(Mark::Remove) => Span::styled("[-] ", Style::default().fg(Color::Red)),
+        None => Span::raw("    "),
+    };
+
+    let mut spans = vec![
+        mark,
+        Span::styled(pkg.name.as_str(), Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(" "),
+        Span::styled(pkg.version.as_str(), Style::default().fg(Color::Green)),
+    ];
+    if pkg.source != PackageSource::Nixpkgs {
+        spans.push(Span::styled(format!(" ({})", pkg.source), Style::default().fg(Color::Magenta)));
+    }
+    if app.is_installed(pkg) {
+        spans.push(Span::styled(" [installed]", Style::default().fg(Color::Cyan)));
+    }
+    ListItem::new(Line::from(spans))
+}
+
+fn details(pkg: &Package) -> Vec<Line<'static>> {
+    let field = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{:<12}", label), Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(value),
+        ])
+    };
+
+    let mut lines = vec![
+        field("Name", pkg.name.clone()),
+        field("Version", pkg.version.clone()),
+        field("Source", pkg.source.to_string()),
+    ];
+    if let Some(license) = &pkg.license {
+        lines.push(field("License", license.clone()));
+    }
+    if let Some(homepage) = &pkg.homepage {
+        lines.push(field("Homepage", homepage.clone()));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::raw(pkg.description.clone()));
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(pkg.install_command(), Style::default().fg(Color::DarkGray)));
+    lines
+}
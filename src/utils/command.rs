@@ -0,0 +1,167 @@
+// NixBoost - High-performance NixOS package manager frontend
+// Copyright (C) 2025 nacreousdawn596, compiledkernel-idk and NixBoost contributors
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Echoing of the external commands NixBoost runs (`--show-commands`),
+//! and forwarding of `--option` settings to the Nix ones.
+
+use console::style;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use tracing::debug;
+
+static SHOW_COMMANDS: AtomicBool = AtomicBool::new(false);
+
+/// `NIX_CONFIG` carrying `--option` and `[nix.options]` settings
+static NIX_CONFIG: OnceLock<String> = OnceLock::new();
+
+/// Programs that read `NIX_CONFIG`
+const NIX_PROGRAMS: &[&str] = &[
+    "nix", "nix-env", "nix-store", "nix-build", "nix-shell", "nix-instantiate",
+    "nix-collect-garbage", "nix-channel", "nixos-rebuild",
+];
+
+/// Print every command to stderr before it runs
+pub fn set_show_commands(show: bool) {
+    SHOW_COMMANDS.store(show, Ordering::Relaxed);
+}
+
+/// Pass `options` to every Nix command, as `NIX_CONFIG` lines after any the
+/// environment already has
+pub fn set_nix_options(options: &BTreeMap<String, String>) {
+    if options.is_empty() {
+        return;
+    }
+    let _ = NIX_CONFIG.set(nix_config(std::env::var("NIX_CONFIG").ok(), options));
+}
+
+/// `NIX_CONFIG` for Nix commands, when options were set
+pub fn nix_config_env() -> Option<&'static str> {
+    NIX_CONFIG.get().map(String::as_str)
+}
+
+/// Whether `program` is a Nix command that takes settings from `NIX_CONFIG`
+pub fn is_nix_program(program: &OsStr) -> bool {
+    Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| NIX_PROGRAMS.contains(&name))
+}
+
+fn nix_config(existing: Option<String>, options: &BTreeMap<String, String>) -> String {
+    existing
+        .into_iter()
+        .filter(|config| !config.trim().is_empty())
+        .chain(options.iter().map(|(key, value)| format!("{} = {}", key, value)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Prepare a command right before it is executed: Nix commands get the
+/// `--option` settings, and the command is announced at debug level and,
+/// with `--show-commands`, on stderr
+pub trait Echo {
+    fn echo(&mut self) -> &mut Self;
+}
+
+impl Echo for Command {
+    fn echo(&mut self) -> &mut Self {
+        if let Some(config) = nix_config_env().filter(|_| is_nix_program(self.get_program())) {
+            self.env("NIX_CONFIG", config);
+        }
+        announce(self);
+        self
+    }
+}
+
+impl Echo for tokio::process::Command {
+    fn echo(&mut self) -> &mut Self {
+        if let Some(config) = nix_config_env().filter(|_| is_nix_program(self.as_std().get_program())) {
+            self.env("NIX_CONFIG", config);
+        }
+        announce(self.as_std());
+        self
+    }
+}
+
+fn announce(cmd: &Command) {
+    let line = format_command(cmd);
+    debug!("Running: {}", line);
+    if SHOW_COMMANDS.load(Ordering::Relaxed) {
+        eprintln!("{} {}", style("$").dim(), line);
+    }
+}
+
+/// The command as a line that can be pasted into a shell, including the
+/// working directory and environment it was given
+pub fn format_command(cmd: &Command) -> String {
+    let mut words = Vec::new();
+    if let Some(dir) = cmd.get_current_dir() {
+        words.push(format!("cd {} &&", shell_quote(&dir.to_string_lossy())));
+    }
+    for (key, value) in cmd.get_envs() {
+        if let Some(value) = value {
+            words.push(format!("{}={}", key.to_string_lossy(), shell_quote(&value.to_string_lossy())));
+        }
+    }
+    words.extend(std::iter::once(cmd.get_program()).chain(cmd.get_args()).map(|w| shell_quote(&w.to_string_lossy())));
+    words.join(" ")
+}
+
+/// Quote `word` for a POSIX shell unless it only has safe characters
+fn shell_quote(word: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=@%+,#".contains(c);
+    if !word.is_empty() && !word.starts_with('#') && word.chars().all(safe) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_command() {
+        let mut cmd = Command::new("nix");
+        cmd.args(["profile", "install", "nixpkgs#hello", "--log-format", "bar with logs"]);
+        assert_eq!(format_command(&cmd), "nix profile install nixpkgs#hello --log-format 'bar with logs'");
+
+        let mut cmd = Command::new("nixos-rebuild");
+        cmd.arg("build").current_dir("/tmp/nixboost rebuild").env("NIX_PATH", "nixpkgs=/x");
+        assert_eq!(format_command(&cmd), "cd '/tmp/nixboost rebuild' && NIX_PATH=nixpkgs=/x nixos-rebuild build");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_nix_config() {
+        let options = BTreeMap::from([
+            ("max-jobs".to_string(), "2".to_string()),
+            ("sandbox".to_string(), "false".to_string()),
+        ]);
+        assert_eq!(nix_config(None, &options), "max-jobs = 2\nsandbox = false");
+        assert_eq!(
+            nix_config(Some("experimental-features = flakes".to_string()), &options),
+            "experimental-features = flakes\nmax-jobs = 2\nsandbox = false"
+        );
+        assert!(is_nix_program(OsStr::new("/run/current-system/sw/bin/nix-store")));
+        assert!(!is_nix_program(OsStr::new("sudo")));
+    }
+}
@@ -16,8 +16,10 @@
 
 //! Utility module for NixBoost.
 
+pub mod command;
 pub mod updater;
 pub mod news;
+pub mod time;
 
 pub use updater::{check_for_updates, perform_update};
 pub use news::fetch_nixos_news;
@@ -16,23 +16,18 @@
 
 //! NixOS news fetcher for NixBoost.
 
+use crate::network::HttpClient;
+use crate::ui::progress::ProgressManager;
 use anyhow::Result;
 use comfy_table::{Table, presets::UTF8_FULL};
-use console::style;
+
+const NEWS_FEED_URL: &str = "https://nixos.org/blog/feed.xml";
 
 /// Fetch and display NixOS news
-pub async fn fetch_nixos_news() -> Result<()> {
-    println!("{}", style(":: fetching nixos news...").bold());
-
-    let client = reqwest::Client::new();
-    let res = client
-        .get("https://nixos.org/blog/feed.xml")
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    let channel = rss::Channel::read_from(res.as_bytes())
+pub async fn fetch_nixos_news(http: &HttpClient, progress: &ProgressManager) -> Result<()> {
+    let res = http.get_with_progress(NEWS_FEED_URL, progress, "NixOS news").await?;
+
+    let channel = rss::Channel::read_from(res.as_slice())
         .map_err(|e| anyhow::anyhow!("failed to parse rss: {}", e))?;
 
     let mut table = Table::new();
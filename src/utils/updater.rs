@@ -16,14 +16,24 @@
 
 //! Self-updater for NixBoost.
 
+use crate::cache::invalidation::TTL;
+use crate::core::config::Config;
+use crate::network::HttpClient;
+use crate::utils::time::unix_now;
+use crate::utils::command::Echo;
 use anyhow::Result;
 use console::style;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::process::Command;
-use std::time::Duration;
 use tracing::{debug, info};
 
+const RELEASES_URL: &str = "https://api.github.com/repos/NacreousDawn596/nixboost/releases/latest";
+
+/// Latest release seen by the last check, cached for [`TTL::UPDATE_CHECK`]
+const UPDATE_CHECK_FILE: &str = "update-check.json";
+
 /// Update information
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateInfo {
     pub version: String,
     pub download_url: Option<String>,
@@ -43,44 +53,55 @@ struct GithubRelease {
     assets: Vec<GithubAsset>,
 }
 
-/// Check for updates
-pub fn check_for_updates(current_version: &str) -> Option<UpdateInfo> {
-    let url = "https://api.github.com/repos/NacreousDawn596/nixboost/releases/latest";
-
-    debug!("Checking for updates from {}", url);
-
-    let response = ureq::get(url)
-        .set("User-Agent", "nixboost-updater")
-        .timeout(Duration::from_secs(2))
-        .call();
-
-    match response {
-        Ok(res) => {
-            if let Ok(release) = res.into_json::<GithubRelease>() {
-                let latest = release.tag_name.trim_start_matches('v');
-                
-                if is_newer_version(latest, current_version) {
-                    debug!("New version available: {} -> {}", current_version, latest);
-                    
-                    let download_url = release.assets
-                        .iter()
-                        .find(|a| a.name == "nixboost")
-                        .map(|a| a.browser_download_url.clone());
-
-                    return Some(UpdateInfo {
-                        version: latest.to_string(),
-                        download_url,
-                        release_notes: release.body,
-                    });
+#[derive(Serialize, Deserialize)]
+struct CachedCheck {
+    checked_at: u64,
+    latest: UpdateInfo,
+}
+
+/// Check for a release newer than `current_version`. The latest release is
+/// cached for a day so most runs make no request at all.
+pub async fn check_for_updates(http: &HttpClient, current_version: &str) -> Option<UpdateInfo> {
+    let cache_file = Config::cache_dir().join(UPDATE_CHECK_FILE);
+    let cached: Option<CachedCheck> = std::fs::read_to_string(&cache_file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .filter(|c: &CachedCheck| unix_now().saturating_sub(c.checked_at) < TTL::UPDATE_CHECK);
+
+    let latest = match cached {
+        Some(check) => check.latest,
+        None => {
+            debug!("Checking for updates from {}", RELEASES_URL);
+            let release = match http.get_json::<GithubRelease>(RELEASES_URL).await {
+                Ok(release) => release,
+                Err(e) => {
+                    debug!("Failed to check for updates: {}", e);
+                    return None;
                 }
+            };
+
+            let latest = UpdateInfo {
+                version: release.tag_name.trim_start_matches('v').to_string(),
+                download_url: release.assets
+                    .iter()
+                    .find(|a| a.name == "nixboost")
+                    .map(|a| a.browser_download_url.clone()),
+                release_notes: release.body,
+            };
+            let check = CachedCheck { checked_at: unix_now(), latest: latest.clone() };
+            if let Ok(json) = serde_json::to_string(&check) {
+                let _ = std::fs::create_dir_all(Config::cache_dir());
+                let _ = std::fs::write(&cache_file, json);
             }
+            latest
         }
-        Err(e) => {
-            debug!("Failed to check for updates: {}", e);
-        }
-    }
+    };
 
-    None
+    if !is_newer_version(&latest.version, current_version) {
+        return None;
+    }
+    debug!("New version available: {} -> {}", current_version, latest.version);
+    Some(latest)
 }
 
 /// Perform update via nix
@@ -90,7 +111,7 @@ pub fn perform_update(_info: UpdateInfo) -> Result<()> {
 
     let status = Command::new("nix")
         .args(["profile", "install", "github:NacreousDawn596/nixboost"])
-        .status()?;
+        .echo().status()?;
 
     if !status.success() {
         anyhow::bail!("nix profile install failed");